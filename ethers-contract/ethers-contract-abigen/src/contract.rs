@@ -1,5 +1,6 @@
 #![deny(missing_docs)]
 mod common;
+mod errors;
 mod events;
 mod methods;
 mod structs;
@@ -36,6 +37,8 @@ pub struct ExpandedContract {
     pub events: TokenStream,
     /// All contract call struct related types
     pub call_structs: TokenStream,
+    /// All custom error types of the contract and the enum over them
+    pub errors: TokenStream,
     /// The contract's internal structs
     pub abi_structs: TokenStream,
 }
@@ -43,8 +46,15 @@ pub struct ExpandedContract {
 impl ExpandedContract {
     /// Merges everything into a single module
     pub fn into_tokens(self) -> TokenStream {
-        let ExpandedContract { module, imports, contract, events, call_structs, abi_structs } =
-            self;
+        let ExpandedContract {
+            module,
+            imports,
+            contract,
+            events,
+            call_structs,
+            errors,
+            abi_structs,
+        } = self;
         quote! {
            // export all the created data types
             pub use #module::*;
@@ -55,6 +65,7 @@ impl ExpandedContract {
                 #contract
                 #events
                 #call_structs
+                #errors
                 #abi_structs
             }
         }
@@ -126,6 +137,9 @@ impl Context {
         // 6. Declare the structs parsed from the human readable abi
         let abi_structs_decl = self.abi_structs()?;
 
+        // 7. Declare the custom error types and the enum over them
+        let errors = self.errors()?;
+
         let ethers_core = ethers_core_crate();
         let ethers_contract = ethers_contract_crate();
         let ethers_providers = ethers_providers_crate();
@@ -161,6 +175,7 @@ impl Context {
             contract,
             events: events_decl,
             call_structs,
+            errors,
             abi_structs: abi_structs_decl,
         })
     }
@@ -0,0 +1,266 @@
+//! derive error bindings
+
+use super::{methods, util, Context};
+use ethers_core::{
+    abi::{ethabi::AbiError, Function, FunctionExt, StateMutability},
+    macros::{ethers_contract_crate, ethers_core_crate},
+};
+use eyre::{Context as _, Result};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::Ident;
+
+impl Context {
+    /// Returns all error declarations
+    pub(crate) fn errors(&self) -> Result<TokenStream> {
+        let mut struct_defs = Vec::new();
+        let mut struct_names = Vec::new();
+        // error structs must not collide with the structs of the ABI
+        let mut taken = self.struct_names();
+        for errors in self.abi.errors.values() {
+            for (idx, error) in errors.iter().enumerate() {
+                let error = error_as_function(error);
+                let overload = if errors.len() > 1 { Some(idx) } else { None };
+                let struct_name = expand_error_struct_name(&error, overload);
+                let struct_name =
+                    util::ident(&unique_name(struct_name.to_string(), "Error", &taken));
+                taken.insert(struct_name.to_string());
+                struct_defs.push(
+                    self.expand_error_struct(&error, &struct_name)
+                        .with_context(|| format!("error expanding error '{}'", error.name))?,
+                );
+                struct_names.push(struct_name);
+            }
+        }
+
+        if struct_defs.is_empty() {
+            return Ok(TokenStream::new())
+        }
+
+        let errors_enum = self.expand_errors_enum(&struct_names);
+
+        Ok(quote! {
+            #( #struct_defs )*
+
+            #errors_enum
+        })
+    }
+
+    /// Expands to the corresponding struct type of the given error
+    fn expand_error_struct(&self, error: &Function, error_name: &Ident) -> Result<TokenStream> {
+        let fields = self.expand_input_params(error)?;
+        // expand as a tuple if all fields are anonymous
+        let all_anonymous_fields = error.inputs.iter().all(|input| input.name.is_empty());
+        let error_type_definition = if all_anonymous_fields {
+            // expand to a tuple struct
+            methods::expand_data_tuple(error_name, &fields)
+        } else {
+            // expand to a struct
+            methods::expand_data_struct(error_name, &fields)
+        };
+        let name = &error.name;
+        let abi_signature = error.abi_signature();
+        let doc = format!(
+            "Custom Error type `{}` with signature `{}` and selector `{:?}`",
+            error.name,
+            abi_signature,
+            error.selector()
+        );
+        let abi_signature_doc = util::expand_doc(&doc);
+        let ethers_contract = ethers_contract_crate();
+        // use the same derives as for events
+        let derives = util::expand_derives(&self.event_derives);
+
+        Ok(quote! {
+            #abi_signature_doc
+            #[derive(Clone, Debug, Default, Eq, PartialEq, #ethers_contract::EthError, #ethers_contract::EthDisplay, #derives)]
+            #[etherror( name = #name, abi = #abi_signature )]
+            pub #error_type_definition
+        })
+    }
+
+    /// Returns the names of all the structs generated for the ABI
+    fn struct_names(&self) -> HashSet<String> {
+        if self.human_readable {
+            self.abi_parser.structs.keys().cloned().collect()
+        } else {
+            self.internal_structs.rust_type_names.values().cloned().collect()
+        }
+    }
+
+    /// Expands to the enum over all errors of the contract, including the builtin
+    /// `Error(string)` and `Panic(uint256)` errors
+    ///
+    /// The variants of the builtin errors are renamed if the contract declares errors of the same
+    /// name.
+    fn expand_errors_enum(&self, struct_names: &[Ident]) -> TokenStream {
+        let ethers_core = ethers_core_crate();
+        let ethers_contract = ethers_contract_crate();
+
+        let taken = struct_names.iter().map(ToString::to_string).collect::<HashSet<_>>();
+        let revert_string =
+            util::ident(&unique_name("RevertString".to_string(), "Builtin", &taken));
+        let panic = util::ident(&unique_name("Panic".to_string(), "Builtin", &taken));
+
+        // use the same derives as for events
+        let derives = util::expand_derives(&self.event_derives);
+        let enum_name = self.expand_errors_enum_name();
+
+        quote! {
+            /// Container type for all of the contract's custom errors, as well as the builtin
+            /// `Error(string)` and `Panic(uint256)` errors
+            #[derive(Debug, Clone, PartialEq, Eq, #derives)]
+            pub enum #enum_name {
+                #( #struct_names(#struct_names), )*
+                /// The builtin `Error(string)` raised by `revert` and `require`
+                #revert_string(::std::string::String),
+                /// The builtin `Panic(uint256)` raised by failing assertions, arithmetic
+                /// overflows, etc.
+                #panic(#ethers_contract::Panic),
+            }
+
+            impl #ethers_core::abi::AbiDecode for #enum_name {
+                fn decode(data: impl AsRef<[u8]>) -> Result<Self, #ethers_core::abi::AbiError> {
+                    let data = data.as_ref();
+                    #(
+                        if let Ok(decoded) = <#struct_names as #ethers_core::abi::AbiDecode>::decode(data) {
+                            return Ok(#enum_name::#struct_names(decoded))
+                        }
+                    )*
+                    if let Some(decoded) = <::std::string::String as #ethers_contract::EthError>::decode_with_selector(data) {
+                        return Ok(#enum_name::#revert_string(decoded))
+                    }
+                    if let Some(decoded) = <#ethers_contract::Panic as #ethers_contract::EthError>::decode_with_selector(data) {
+                        return Ok(#enum_name::#panic(decoded))
+                    }
+                    Err(#ethers_core::abi::Error::InvalidData.into())
+                }
+            }
+
+            impl #ethers_core::abi::AbiEncode for #enum_name {
+                fn encode(self) -> ::std::vec::Vec<u8> {
+                    match self {
+                        #(
+                            #enum_name::#struct_names(element) => element.encode(),
+                        )*
+                        #enum_name::#revert_string(reason) => {
+                            <::std::string::String as #ethers_contract::EthError>::selector()
+                                .iter()
+                                .copied()
+                                .chain(reason.encode())
+                                .collect()
+                        }
+                        #enum_name::#panic(element) => element.encode(),
+                    }
+                }
+            }
+
+            impl #ethers_contract::ContractRevert for #enum_name {
+                fn decode_with_selector(data: &[u8]) -> Option<Self> {
+                    <Self as #ethers_core::abi::AbiDecode>::decode(data).ok()
+                }
+            }
+
+            impl ::std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        #(
+                            #enum_name::#struct_names(element) => element.fmt(f),
+                        )*
+                        #enum_name::#revert_string(reason) => reason.fmt(f),
+                        #enum_name::#panic(element) => element.fmt(f),
+                    }
+                }
+            }
+
+            #(
+                impl ::std::convert::From<#struct_names> for #enum_name {
+                    fn from(var: #struct_names) -> Self {
+                        #enum_name::#struct_names(var)
+                    }
+                }
+            )*
+        }
+    }
+
+    /// The name ident of the errors enum
+    fn expand_errors_enum_name(&self) -> Ident {
+        util::ident(&format!("{}Errors", self.contract_ident))
+    }
+}
+
+/// Errors have the same representation as functions without outputs, which lets them share the
+/// parameter expansion of call structs
+fn error_as_function(error: &AbiError) -> Function {
+    #[allow(deprecated)]
+    Function {
+        name: error.name.clone(),
+        inputs: error.inputs.clone(),
+        outputs: vec![],
+        constant: None,
+        state_mutability: StateMutability::NonPayable,
+    }
+}
+
+/// Appends `suffix` to `name` until it's not `taken`
+fn unique_name(mut name: String, suffix: &str, taken: &HashSet<String>) -> String {
+    while taken.contains(&name) {
+        name.push_str(suffix);
+    }
+    name
+}
+
+/// Expands to the name of the error struct, overloaded errors are suffixed with their index
+fn expand_error_struct_name(error: &Function, overload: Option<usize>) -> Ident {
+    let name = util::safe_pascal_case(&error.name);
+    match overload {
+        Some(idx) => util::ident(&format!("{}{}", name, idx)),
+        None => util::safe_ident(&name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Abigen;
+
+    #[test]
+    fn expands_errors_enum() {
+        let abi = r#"[{"inputs":[{"internalType":"uint256","name":"available","type":"uint256"},{"internalType":"uint256","name":"required","type":"uint256"}],"name":"InsufficientBalance","type":"error"},{"inputs":[],"name":"Unauthorized","type":"error"}]"#;
+        let abigen = Abigen::new("Vault", abi).unwrap();
+        let context = Context::from_abigen(abigen).unwrap();
+        assert_eq!(context.abi.errors.len(), 2);
+
+        let tokens = context.errors().unwrap().to_string();
+        assert!(tokens.contains("pub struct InsufficientBalance"));
+        assert!(tokens.contains("pub struct Unauthorized"));
+        assert!(tokens.contains("pub enum VaultErrors"));
+        assert!(tokens.contains("RevertString"));
+    }
+
+    #[test]
+    fn expands_overloaded_error_names() {
+        let error = error_as_function(&AbiError { name: "my_error".to_string(), inputs: vec![] });
+        assert_eq!(expand_error_struct_name(&error, None), "MyError");
+        assert_eq!(expand_error_struct_name(&error, Some(1)), "MyError1");
+    }
+
+    #[test]
+    fn renames_colliding_errors() {
+        let abi = r#"[{"inputs":[{"internalType":"uint256","name":"code","type":"uint256"}],"name":"Panic","type":"error"},{"inputs":[],"name":"RevertString","type":"error"}]"#;
+        let abigen = Abigen::new("Vault", abi).unwrap();
+        let context = Context::from_abigen(abigen).unwrap();
+        let tokens = context.errors().unwrap().to_string();
+        assert!(tokens.contains("pub struct Panic"));
+        assert!(tokens.contains("Panic (Panic)"));
+        assert!(tokens.contains("PanicBuiltin"));
+        assert!(tokens.contains("RevertStringBuiltin"));
+
+        let abi = r#"[{"inputs":[{"components":[{"internalType":"uint256","name":"amount","type":"uint256"}],"internalType":"struct Vault.Position","name":"position","type":"tuple"}],"name":"Position","type":"error"}]"#;
+        let abigen = Abigen::new("Vault", abi).unwrap();
+        let context = Context::from_abigen(abigen).unwrap();
+        let tokens = context.errors().unwrap().to_string();
+        assert!(tokens.contains("pub struct PositionError"));
+    }
+}
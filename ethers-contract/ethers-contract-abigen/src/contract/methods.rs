@@ -299,7 +299,10 @@ impl Context {
     }
 
     /// Expands to the `name : type` pairs of the function's inputs
-    fn expand_input_params(&self, fun: &Function) -> Result<Vec<(TokenStream, TokenStream)>> {
+    pub(super) fn expand_input_params(
+        &self,
+        fun: &Function,
+    ) -> Result<Vec<(TokenStream, TokenStream)>> {
         self.expand_params(fun, &fun.inputs)
     }
 
@@ -719,7 +722,10 @@ fn expand_call_struct_variant_name(function: &Function, alias: Option<&MethodAli
 }
 
 /// Expands to the tuple struct definition
-fn expand_data_tuple(name: &Ident, params: &[(TokenStream, TokenStream)]) -> TokenStream {
+pub(super) fn expand_data_tuple(
+    name: &Ident,
+    params: &[(TokenStream, TokenStream)],
+) -> TokenStream {
     let fields = params
         .iter()
         .map(|(_, ty)| {
@@ -736,7 +742,10 @@ fn expand_data_tuple(name: &Ident, params: &[(TokenStream, TokenStream)]) -> Tok
 }
 
 /// Expands to the struct definition of a call struct
-fn expand_data_struct(name: &Ident, params: &[(TokenStream, TokenStream)]) -> TokenStream {
+pub(super) fn expand_data_struct(
+    name: &Ident,
+    params: &[(TokenStream, TokenStream)],
+) -> TokenStream {
     let fields = params
         .iter()
         .map(|(name, ty)| {
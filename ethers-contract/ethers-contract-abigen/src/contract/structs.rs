@@ -246,10 +246,11 @@ impl InternalStructs {
         let mut function_params = HashMap::new();
         let mut outputs = HashMap::new();
         let mut structs = HashMap::new();
-        for item in abi
-            .into_iter()
-            .filter(|item| item.type_field == "constructor" || item.type_field == "function")
-        {
+        for item in abi.into_iter().filter(|item| {
+            item.type_field == "constructor" ||
+                item.type_field == "function" ||
+                item.type_field == "error"
+        }) {
             if let Some(name) = item.name {
                 for input in item.inputs {
                     if let Some(ty) = input
@@ -274,7 +275,9 @@ impl InternalStructs {
                         top_level_internal_types.insert(ty.to_string(), output);
                     }
                 }
-                outputs.insert(name, output_structs);
+                if item.type_field != "error" {
+                    outputs.insert(name, output_structs);
+                }
             }
         }
 
@@ -11,7 +11,7 @@ use syn::{parse::Error, spanned::Spanned as _, AttrStyle, DeriveInput, Lit, Meta
 
 /// Generates the `ethcall` trait support
 pub(crate) fn derive_eth_call_impl(input: DeriveInput) -> TokenStream {
-    let attributes = match parse_call_attributes(&input) {
+    let attributes = match parse_call_attributes(&input, "ethcall") {
         Ok(attributes) => attributes,
         Err(errors) => return errors,
     };
@@ -170,34 +170,38 @@ fn derive_trait_impls_with_abi_type(
     Ok(derive_trait_impls(input, function_call_name, abi_signature, None, decode_impl))
 }
 
-/// All the attributes the `EthCall` macro supports
+/// All the attributes the `EthCall` and `EthError` macros support
 #[derive(Default)]
-struct EthCallAttributes {
-    name: Option<(String, Span)>,
-    abi: Option<(String, Span)>,
+pub(crate) struct EthCallAttributes {
+    pub(crate) name: Option<(String, Span)>,
+    pub(crate) abi: Option<(String, Span)>,
 }
 
-/// extracts the attributes from the struct annotated with `EthCall`
-fn parse_call_attributes(input: &DeriveInput) -> Result<EthCallAttributes, TokenStream> {
+/// extracts the `#[<attr_name>(..)]` attributes from the struct annotated with `EthCall` or
+/// `EthError`
+pub(crate) fn parse_call_attributes(
+    input: &DeriveInput,
+    attr_name: &str,
+) -> Result<EthCallAttributes, TokenStream> {
     let mut result = EthCallAttributes::default();
     for a in input.attrs.iter() {
         if let AttrStyle::Outer = a.style {
             if let Ok(Meta::List(meta)) = a.parse_meta() {
-                if meta.path.is_ident("ethcall") {
+                if meta.path.is_ident(attr_name) {
                     for n in meta.nested.iter() {
                         if let NestedMeta::Meta(meta) = n {
                             match meta {
                                 Meta::Path(path) => {
                                     return Err(Error::new(
                                         path.span(),
-                                        "unrecognized ethcall parameter",
+                                        format!("unrecognized {} parameter", attr_name),
                                     )
                                     .to_compile_error())
                                 }
                                 Meta::List(meta) => {
                                     return Err(Error::new(
                                         meta.path.span(),
-                                        "unrecognized ethcall parameter",
+                                        format!("unrecognized {} parameter", attr_name),
                                     )
                                     .to_compile_error())
                                 }
@@ -243,7 +247,7 @@ fn parse_call_attributes(input: &DeriveInput) -> Result<EthCallAttributes, Token
                                     } else {
                                         return Err(Error::new(
                                             meta.span(),
-                                            "unrecognized ethcall parameter",
+                                            format!("unrecognized {} parameter", attr_name),
                                         )
                                         .to_compile_error())
                                     }
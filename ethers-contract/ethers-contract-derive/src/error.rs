@@ -0,0 +1,161 @@
+//! Helper functions for deriving `EthError`
+
+use crate::{abi_ty, call::parse_call_attributes, utils};
+use ethers_core::{
+    abi::{FunctionExt, HumanReadableParser, Param},
+    macros::{ethers_contract_crate, ethers_core_crate},
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::Error, DeriveInput};
+
+/// Generates the `EthError` trait support
+pub(crate) fn derive_eth_error_impl(input: DeriveInput) -> TokenStream {
+    let attributes = match parse_call_attributes(&input, "etherror") {
+        Ok(attributes) => attributes,
+        Err(errors) => return errors,
+    };
+
+    let error_name = attributes.name.map(|(s, _)| s).unwrap_or_else(|| input.ident.to_string());
+
+    let mut error = if let Some((src, span)) = attributes.abi {
+        let raw_error_sig = src.trim_start_matches("error ").trim_start();
+        // an error has the same human readable representation as a function without outputs
+        if let Ok(fun) = HumanReadableParser::parse_function(raw_error_sig) {
+            fun
+        } else {
+            // try to determine the abi by using its fields at runtime
+            return match derive_trait_impls_with_abi_type(&input, &error_name, Some(raw_error_sig))
+            {
+                Ok(derived) => derived,
+                Err(err) => {
+                    Error::new(span, format!("Unable to determine ABI for `{}` : {}", src, err))
+                        .to_compile_error()
+                }
+            }
+        }
+    } else {
+        // try to determine the abi by using its fields at runtime
+        return match derive_trait_impls_with_abi_type(&input, &error_name, None) {
+            Ok(derived) => derived,
+            Err(err) => err.to_compile_error(),
+        }
+    };
+    error.name = error_name.clone();
+    let abi = error.abi_signature();
+    let selector = utils::selector(error.selector());
+    let decode_impl = derive_decode_impl_from_params(&error.inputs);
+
+    derive_trait_impls(&input, &error_name, quote! {#abi.into()}, Some(selector), decode_impl)
+}
+
+/// Generates the EthError implementation
+fn derive_trait_impls(
+    input: &DeriveInput,
+    error_name: &str,
+    abi_signature: TokenStream,
+    selector: Option<TokenStream>,
+    decode_impl: TokenStream,
+) -> TokenStream {
+    // the ethers crates to use
+    let core_crate = ethers_core_crate();
+    let contract_crate = ethers_contract_crate();
+    let struct_name = &input.ident;
+
+    let selector = selector.unwrap_or_else(|| {
+        quote! {
+             #core_crate::utils::id(Self::abi_signature())
+        }
+    });
+
+    let etherror_impl = quote! {
+        impl #contract_crate::EthError for #struct_name {
+
+            fn error_name() -> ::std::borrow::Cow<'static, str> {
+                #error_name.into()
+            }
+
+            fn selector() -> #core_crate::types::Selector {
+                #selector
+            }
+
+            fn abi_signature() -> ::std::borrow::Cow<'static, str> {
+                #abi_signature
+            }
+        }
+
+        impl  #core_crate::abi::AbiDecode for #struct_name {
+            fn decode(bytes: impl AsRef<[u8]>) -> Result<Self, #core_crate::abi::AbiError> {
+                #decode_impl
+            }
+        }
+
+        impl #core_crate::abi::AbiEncode for #struct_name {
+            fn encode(self) -> ::std::vec::Vec<u8> {
+                let tokens =  #core_crate::abi::Tokenize::into_tokens(self);
+                let selector = <Self as #contract_crate::EthError>::selector();
+                let encoded = #core_crate::abi::encode(&tokens);
+                selector
+                    .iter()
+                    .copied()
+                    .chain(encoded.into_iter())
+                    .collect()
+            }
+        }
+
+    };
+    let tokenize_impl = abi_ty::derive_tokenizeable_impl(input);
+
+    quote! {
+        #tokenize_impl
+        #etherror_impl
+    }
+}
+
+/// Generates the decode implementation based on the error's input types
+fn derive_decode_impl_from_params(params: &[Param]) -> TokenStream {
+    let datatypes = params.iter().map(|input| utils::param_type_quote(&input.kind));
+    let datatypes_array = quote! {[#( #datatypes ),*]};
+    derive_decode_impl(datatypes_array)
+}
+
+/// Generates the decode implementation based on the error's runtime `AbiType` impl
+fn derive_decode_impl_with_abi_type(input: &DeriveInput) -> Result<TokenStream, Error> {
+    let datatypes_array = utils::derive_abi_parameters_array(input, "EthError")?;
+    Ok(derive_decode_impl(datatypes_array))
+}
+
+fn derive_decode_impl(datatypes_array: TokenStream) -> TokenStream {
+    let core_crate = ethers_core_crate();
+    let contract_crate = ethers_contract_crate();
+    let data_types_init = quote! {let data_types = #datatypes_array;};
+
+    quote! {
+        let bytes = bytes.as_ref();
+        if bytes.len() < 4 || bytes[..4] != <Self as #contract_crate::EthError>::selector() {
+            return Err(#contract_crate::AbiError::WrongSelector);
+        }
+        #data_types_init
+        let data_tokens = #core_crate::abi::decode(&data_types, &bytes[4..])?;
+        Ok(<Self as #core_crate::abi::Tokenizable>::from_token( #core_crate::abi::Token::Tuple(data_tokens))?)
+    }
+}
+
+/// Use the `AbiType` trait to determine the correct `ParamType` and signature at runtime
+fn derive_trait_impls_with_abi_type(
+    input: &DeriveInput,
+    error_name: &str,
+    abi_signature: Option<&str>,
+) -> Result<TokenStream, Error> {
+    let abi_signature = if let Some(abi) = abi_signature {
+        quote! {#abi}
+    } else {
+        utils::derive_abi_signature_with_abi_type(input, error_name, "EthError")?
+    };
+
+    let abi_signature = quote! {
+         #abi_signature.into()
+    };
+    let decode_impl = derive_decode_impl_with_abi_type(input)?;
+    Ok(derive_trait_impls(input, error_name, abi_signature, None, decode_impl))
+}
@@ -13,6 +13,7 @@ mod abigen;
 mod call;
 mod codec;
 mod display;
+mod error;
 mod event;
 mod spanned;
 pub(crate) mod utils;
@@ -281,3 +282,37 @@ pub fn derive_abi_call(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     TokenStream::from(call::derive_eth_call_impl(input))
 }
+
+/// Derives the `EthError` and `Tokenizeable` trait for the labeled type.
+///
+/// Additional arguments can be specified using the `#[etherror(...)]`
+/// attribute:
+///
+/// For the struct:
+///
+/// - `name`, `name = "..."`: Overrides the generated `EthError` name, default is the
+///  struct's name.
+/// - `abi`, `abi = "..."`: The ABI signature of the solidity custom error this struct's data
+///   corresponds to.
+///
+/// # Example
+///
+/// ```ignore
+/// use ethers_contract::EthError;
+///
+/// #[derive(Debug, Clone, EthError)]
+/// #[etherror(name = "InsufficientBalance", abi = "InsufficientBalance(uint256,uint256)")]
+/// struct InsufficientBalance {
+///     available: U256,
+///     required: U256,
+/// }
+/// assert_eq!(
+///     InsufficientBalance::abi_signature().as_ref(),
+///     "InsufficientBalance(uint256,uint256)"
+/// );
+/// ```
+#[proc_macro_derive(EthError, attributes(etherror))]
+pub fn derive_abi_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    TokenStream::from(error::derive_eth_error_impl(input))
+}
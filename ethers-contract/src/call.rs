@@ -1,6 +1,10 @@
 #![allow(clippy::return_self_not_must_use)]
 
-use super::base::{decode_function_data, AbiError};
+use super::{
+    base::{decode_function_data, AbiError},
+    error::ContractRevert,
+    multicall::MulticallError,
+};
 use ethers_core::{
     abi::{AbiDecode, AbiEncode, Detokenize, Function, InvalidOutputType, Tokenizable},
     types::{
@@ -64,6 +68,69 @@ pub enum ContractError<M: Middleware> {
     /// receipt
     #[error("Contract was not deployed")]
     ContractNotDeployed,
+
+    /// Thrown if a call reverted, contains the revert data
+    #[error("Contract call reverted with data: {0}")]
+    Revert(Bytes),
+
+    /// Thrown if the calls of a [`Multicall`](crate::Multicall) need a feature its version
+    /// doesn't support
+    #[error(transparent)]
+    MulticallError(#[from] MulticallError),
+}
+
+impl<M: Middleware> ContractError<M> {
+    /// Returns the revert data of the failed call, if there is any.
+    ///
+    /// Besides the [`ContractError::Revert`] variant this extracts the revert data from the
+    /// JSON-RPC error response of the node. For a [`ContractError::MiddlewareError`] this is only
+    /// possible if the middleware error is a [`ProviderError`] or has one in its chain of
+    /// [`source`](std::error::Error::source)s, like the errors of the `ethers-middleware`
+    /// middlewares.
+    pub fn as_revert(&self) -> Option<Bytes>
+    where
+        M::Error: 'static,
+    {
+        match self {
+            ContractError::Revert(data) => Some(data.clone()),
+            ContractError::ProviderError(err) => err.as_error_response()?.as_revert_data(),
+            ContractError::MiddlewareError(err) => {
+                let mut err: Option<&(dyn std::error::Error + 'static)> = Some(err);
+                while let Some(current) = err {
+                    if let Some(err) = current.downcast_ref::<ProviderError>() {
+                        return err.as_error_response()?.as_revert_data()
+                    }
+                    err = current.source();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the revert data of the failed call into a custom error, or one of the
+    /// `<Contract>Errors` enums generated by `abigen`.
+    ///
+    /// Returns `None` if the call did not revert or the revert data does not match the error type.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// match contract.withdraw(amount).call().await {
+    ///     Err(err) => match err.decode_revert::<MyContractErrors>() {
+    ///         Some(MyContractErrors::InsufficientBalance(e)) => { /* .. */ }
+    ///         Some(MyContractErrors::RevertString(reason)) => { /* .. */ }
+    ///         _ => { /* .. */ }
+    ///     },
+    ///     Ok(_) => {}
+    /// }
+    /// ```
+    pub fn decode_revert<E: ContractRevert>(&self) -> Option<E>
+    where
+        M::Error: 'static,
+    {
+        E::decode_with_selector(self.as_revert()?.as_ref())
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,123 @@
+use ethers_core::{
+    abi::{AbiDecode, AbiEncode, AbiError, InvalidOutputType, Token, Tokenizable},
+    types::{Selector, U256},
+    utils::id,
+};
+use std::{borrow::Cow, fmt};
+
+/// A helper trait for types that represent a custom error of a contract, declared in solidity as
+/// `error <name>(<args>)`
+pub trait EthError: Tokenizable + AbiDecode + AbiEncode + Send + Sync {
+    /// The name of the error
+    fn error_name() -> Cow<'static, str>;
+
+    /// Retrieves the ABI signature for the error
+    fn abi_signature() -> Cow<'static, str>;
+
+    /// The selector of the error
+    fn selector() -> Selector {
+        id(Self::abi_signature())
+    }
+
+    /// Decodes the revert data if it starts with the selector of this error
+    fn decode_with_selector(data: &[u8]) -> Option<Self> {
+        // the `AbiDecode` impls of errors check the selector
+        <Self as AbiDecode>::decode(data).ok()
+    }
+}
+
+/// The builtin `Error(string)` that is raised by `revert("reason")` and failing `require`
+/// statements
+impl EthError for String {
+    fn error_name() -> Cow<'static, str> {
+        "Error".into()
+    }
+
+    fn abi_signature() -> Cow<'static, str> {
+        "Error(string)".into()
+    }
+
+    fn selector() -> Selector {
+        [0x08, 0xc3, 0x79, 0xa0]
+    }
+
+    fn decode_with_selector(data: &[u8]) -> Option<Self> {
+        if data.len() < 4 || data[..4] != Self::selector() {
+            return None
+        }
+        <String as AbiDecode>::decode(&data[4..]).ok()
+    }
+}
+
+/// The builtin `Panic(uint256)` that is raised by failing `assert`s, arithmetic overflows,
+/// out-of-bounds array accesses and the like. Contains the panic code, see
+/// <https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Panic(pub U256);
+
+impl Tokenizable for Panic {
+    fn from_token(token: Token) -> Result<Self, InvalidOutputType> {
+        match token {
+            Token::Tuple(mut tokens) if tokens.len() == 1 => {
+                Ok(Panic(U256::from_token(tokens.remove(0))?))
+            }
+            other => Err(InvalidOutputType(format!("Expected `Panic`, got {:?}", other))),
+        }
+    }
+
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![self.0.into_token()])
+    }
+}
+
+impl AbiDecode for Panic {
+    fn decode(bytes: impl AsRef<[u8]>) -> Result<Self, AbiError> {
+        let bytes = bytes.as_ref();
+        if bytes.len() < 4 || bytes[..4] != <Self as EthError>::selector() {
+            return Err(AbiError::WrongSelector)
+        }
+        Ok(Panic(U256::decode(&bytes[4..])?))
+    }
+}
+
+impl AbiEncode for Panic {
+    fn encode(self) -> Vec<u8> {
+        <Self as EthError>::selector().iter().copied().chain(self.0.encode()).collect()
+    }
+}
+
+impl EthError for Panic {
+    fn error_name() -> Cow<'static, str> {
+        "Panic".into()
+    }
+
+    fn abi_signature() -> Cow<'static, str> {
+        "Panic(uint256)".into()
+    }
+
+    fn selector() -> Selector {
+        [0x4e, 0x48, 0x7b, 0x71]
+    }
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Panic({:#x})", self.0)
+    }
+}
+
+/// A type that the revert data of a failed contract call can be decoded into.
+///
+/// This is implemented for all [`EthError`]s and for the `<Contract>Errors` enums that `abigen`
+/// generates for contracts with custom errors.
+pub trait ContractRevert: Sized {
+    /// Decodes the revert data, returns `None` if the data does not match any of the errors
+    /// represented by this type
+    fn decode_with_selector(data: &[u8]) -> Option<Self>;
+}
+
+impl<T: EthError> ContractRevert for T {
+    fn decode_with_selector(data: &[u8]) -> Option<Self> {
+        <T as EthError>::decode_with_selector(data)
+    }
+}
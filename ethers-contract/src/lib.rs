@@ -11,6 +11,9 @@ pub use base::{decode_function_data, encode_function_data, AbiError, BaseContrac
 mod call;
 pub use call::{ContractError, EthCall};
 
+mod error;
+pub use error::{ContractRevert, EthError, Panic};
+
 mod factory;
 pub use factory::{ContractDeployer, ContractFactory};
 
@@ -23,7 +26,9 @@ pub use log::{decode_logs, EthLogDecode, LogMeta};
 pub mod stream;
 
 mod multicall;
-pub use multicall::Multicall;
+pub use multicall::{
+    Multicall, MulticallError, MulticallVersion, MULTICALL_ADDRESS, MULTICALL_SUPPORTED_CHAINS,
+};
 
 /// This module exposes low lever builder structures which are only consumed by the
 /// type-safe ABI bindings generators.
@@ -42,7 +47,9 @@ pub use ethers_contract_abigen::{Abigen, MultiAbigen};
 
 #[cfg(any(test, feature = "abigen"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "abigen")))]
-pub use ethers_contract_derive::{abigen, EthAbiCodec, EthAbiType, EthCall, EthDisplay, EthEvent};
+pub use ethers_contract_derive::{
+    abigen, EthAbiCodec, EthAbiType, EthCall, EthDisplay, EthError, EthEvent,
+};
 
 // Hide the Lazy re-export, it's just for convenience
 #[doc(hidden)]
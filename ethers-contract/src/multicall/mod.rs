@@ -1,10 +1,11 @@
 use ethers_core::{
     abi::{Detokenize, Function, Token},
-    types::{Address, BlockNumber, Bytes, Chain, NameOrAddress, TxHash, U256},
+    types::{Address, BlockNumber, Bytes, Chain, NameOrAddress, TxHash, H160, U256},
 };
 use ethers_providers::Middleware;
 
 use std::{collections::HashMap, str::FromStr, sync::Arc};
+use thiserror::Error as ThisError;
 
 use crate::{
     call::{ContractCall, ContractError},
@@ -14,8 +15,49 @@ use crate::{
 mod multicall_contract;
 use multicall_contract::MulticallContract;
 
+/// The address of the [Multicall3](https://github.com/mds1/multicall) contract, which is the same
+/// on every chain it is deployed to: `0xcA11bde05977b3631167028862bE2a173976CA11`
+pub const MULTICALL_ADDRESS: Address = H160([
+    0xca, 0x11, 0xbd, 0xe0, 0x59, 0x77, 0xb3, 0x63, 0x11, 0x67, 0x02, 0x88, 0x62, 0xbe, 0x2a, 0x17,
+    0x39, 0x76, 0xca, 0x11,
+]);
+
+/// The chains the Multicall3 contract is deployed to at [`MULTICALL_ADDRESS`]
+pub const MULTICALL_SUPPORTED_CHAINS: &[Chain] = &[
+    Chain::Mainnet,
+    Chain::Ropsten,
+    Chain::Rinkeby,
+    Chain::Goerli,
+    Chain::Kovan,
+    Chain::Sepolia,
+    Chain::Optimism,
+    Chain::OptimismKovan,
+    Chain::Arbitrum,
+    Chain::ArbitrumTestnet,
+    Chain::Polygon,
+    Chain::PolygonMumbai,
+    Chain::XDai,
+    Chain::Avalanche,
+    Chain::AvalancheFuji,
+    Chain::Fantom,
+    Chain::FantomTestnet,
+    Chain::BinanceSmartChain,
+    Chain::BinanceSmartChainTestnet,
+    Chain::Moonbeam,
+    Chain::Moonbase,
+    Chain::Moonriver,
+    Chain::Cronos,
+    Chain::Evmos,
+    Chain::EvmosTestnet,
+    Chain::Emerald,
+    Chain::EmeraldTestnet,
+];
+
 /// A lazily computed hash map with the Ethereum network IDs as keys and the corresponding
-/// Multicall smart contract addresses as values
+/// Multicall smart contract addresses as values.
+///
+/// These are deployments of the original Multicall contract, which are only used on chains that
+/// are not in [`MULTICALL_SUPPORTED_CHAINS`].
 pub static ADDRESS_BOOK: Lazy<HashMap<U256, Address>> = Lazy::new(|| {
     fn decode_address(input: &str) -> Address {
         Address::from_str(input).expect("Decoding failed")
@@ -35,15 +77,44 @@ pub static ADDRESS_BOOK: Lazy<HashMap<U256, Address>> = Lazy::new(|| {
     .into()
 });
 
+/// The version of the Multicall contract a [`Multicall`] talks to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MulticallVersion {
+    /// The original Multicall contract, which reverts if any call fails (`aggregate`)
+    Multicall = 1,
+    /// Multicall2, which can tolerate failing calls (`tryAggregate`)
+    Multicall2 = 2,
+    /// Multicall3, which supports per-call failure tolerance and sending value (`aggregate3` and
+    /// `aggregate3Value`)
+    Multicall3 = 3,
+}
+
+/// Thrown if a call of a [`Multicall`] needs a feature its [`MulticallVersion`] doesn't support
+#[derive(ThisError, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulticallError {
+    /// Thrown if a call sends value, which requires Multicall3
+    #[error("{0:?} can't send value with a call, which requires Multicall3")]
+    ValueTx(MulticallVersion),
+
+    /// Thrown if a call is allowed to fail, which requires Multicall2 or Multicall3
+    #[error("{0:?} can't allow calls to fail, which requires Multicall2 or Multicall3")]
+    AllowFailure(MulticallVersion),
+}
+
 /// A Multicall is an abstraction for sending batched calls/transactions to the Ethereum blockchain.
-/// It stores an instance of the [`Multicall` smart contract](https://etherscan.io/address/0xeefba1e63905ef1d7acba5a8513c70307c1ce441#code)
+/// It stores an instance of the [`Multicall3` smart contract](https://etherscan.io/address/0xcA11bde05977b3631167028862bE2a173976CA11#code)
 /// and the user provided list of transactions to be made.
 ///
 /// `Multicall` can instantiate the Multicall contract instance from the chain ID of the client
-/// supplied to [`new`]. It supports the Ethereum mainnet, as well as testnets
-/// [Rinkeby](https://rinkeby.etherscan.io/address/0x42ad527de7d4e9d9d011ac45b31d8551f8fe9821#code),
-/// [Goerli](https://goerli.etherscan.io/address/0x77dca2c955b15e9de4dbbcf1246b4b85b651e50e) and
-/// [Kovan](https://kovan.etherscan.io/address/0x2cc8688c5f75e365aaeeb4ea8d6a480405a48d2a#code).
+/// supplied to [`new`]. On all [supported chains](MULTICALL_SUPPORTED_CHAINS) it uses the
+/// Multicall3 contract at [`MULTICALL_ADDRESS`], otherwise it falls back to the original Multicall
+/// contracts of its address book. A custom address is assumed to be the original Multicall
+/// contract, the version of the contract at that address can be set with [`version`].
+///
+/// Calls added with [`add_call_allowing_failure`] may revert without failing the whole batch, their
+/// results can be retrieved with [`try_call`] and [`try_call_raw`]. This requires Multicall2 or
+/// Multicall3.
 ///
 /// Additionally, the `block` number can be provided for the call by using the [`block`] method.
 /// Build on the `Multicall` instance by adding calls using the [`add_call`] method.
@@ -120,11 +191,16 @@ pub static ADDRESS_BOOK: Lazy<HashMap<U256, Address>> = Lazy::new(|| {
 /// [`new`]: method@crate::Multicall::new
 /// [`block`]: method@crate::Multicall::block
 /// [`add_call`]: method@crate::Multicall::add_call
+/// [`version`]: method@crate::Multicall::version
+/// [`add_call_allowing_failure`]: method@crate::Multicall::add_call_allowing_failure
+/// [`try_call`]: method@crate::Multicall::try_call
+/// [`try_call_raw`]: method@crate::Multicall::try_call_raw
 pub struct Multicall<M> {
     calls: Vec<Call>,
     block: Option<BlockNumber>,
     contract: MulticallContract<M>,
     legacy: bool,
+    version: MulticallVersion,
 }
 
 impl<M> Clone for Multicall<M> {
@@ -134,6 +210,7 @@ impl<M> Clone for Multicall<M> {
             block: self.block,
             contract: self.contract.clone(),
             legacy: self.legacy,
+            version: self.version,
         }
     }
 }
@@ -144,17 +221,22 @@ impl<M> Clone for Multicall<M> {
 pub struct Call {
     target: Address,
     data: Bytes,
+    value: U256,
+    allow_failure: bool,
     function: Function,
 }
 
 impl<M: Middleware> Multicall<M> {
     /// Creates a new Multicall instance from the provided client. If provided with an `address`,
-    /// it instantiates the Multicall contract with that address. Otherwise it fetches the address
-    /// from the address book.
+    /// it instantiates the Multicall contract with that address, assuming it is the original
+    /// Multicall contract; use [`version`](Self::version) for a Multicall2 or Multicall3
+    /// deployment. Otherwise it uses the Multicall3 contract at [`MULTICALL_ADDRESS`] if the
+    /// client's chain is one of the [`MULTICALL_SUPPORTED_CHAINS`], or fetches the address of the
+    /// original Multicall contract from the address book.
     ///
     /// # Panics
     /// If a `None` address is provided, and the provided client also does not belong to one of
-    /// the supported network IDs (mainnet, kovan, rinkeby and goerli)
+    /// the supported network IDs
     pub async fn new<C: Into<Arc<M>>>(
         client: C,
         address: Option<Address>,
@@ -164,16 +246,20 @@ impl<M: Middleware> Multicall<M> {
         // Fetch chain id and the corresponding address of Multicall contract
         // preference is given to Multicall contract's address if provided
         // otherwise check the address book for the client's chain ID
-        let address: Address = match address {
-            Some(addr) => addr,
+        let (address, version) = match address {
+            Some(addr) => (addr, MulticallVersion::Multicall),
             None => {
                 let chain_id =
                     client.get_chainid().await.map_err(ContractError::MiddlewareError)?;
-                match ADDRESS_BOOK.get(&chain_id) {
-                    Some(addr) => *addr,
-                    None => panic!(
-                        "Must either be a supported Network ID or provide Multicall contract address"
-                    ),
+                if MULTICALL_SUPPORTED_CHAINS.iter().any(|chain| U256::from(*chain) == chain_id) {
+                    (MULTICALL_ADDRESS, MulticallVersion::Multicall3)
+                } else {
+                    match ADDRESS_BOOK.get(&chain_id) {
+                        Some(addr) => (*addr, MulticallVersion::Multicall),
+                        None => panic!(
+                            "Must either be a supported Network ID or provide Multicall contract address"
+                        ),
+                    }
                 }
             }
         };
@@ -181,7 +267,15 @@ impl<M: Middleware> Multicall<M> {
         // Instantiate the multicall contract
         let contract = MulticallContract::new(address, client);
 
-        Ok(Self { calls: vec![], block: None, contract, legacy: false })
+        Ok(Self { calls: vec![], block: None, contract, legacy: false, version })
+    }
+
+    /// Sets the version of the Multicall contract, which determines the function used to
+    /// aggregate the calls
+    #[must_use]
+    pub fn version(mut self, version: MulticallVersion) -> Self {
+        self.version = version;
+        self
     }
 
     /// Makes a legacy transaction instead of an EIP-1559 one
@@ -200,9 +294,35 @@ impl<M: Middleware> Multicall<M> {
 
     /// Appends a `call` to the list of calls for the Multicall instance
     pub fn add_call<D: Detokenize>(&mut self, call: ContractCall<M, D>) -> &mut Self {
+        self.push_call(call, false)
+    }
+
+    /// Appends a `call` that is allowed to revert without reverting the whole batch.
+    ///
+    /// Its result is `Err` with the revert data in [`try_call`](Self::try_call) and
+    /// [`try_call_raw`](Self::try_call_raw). This requires Multicall2 or Multicall3, with the
+    /// original Multicall contract the batch fails with [`MulticallError::AllowFailure`].
+    pub fn add_call_allowing_failure<D: Detokenize>(
+        &mut self,
+        call: ContractCall<M, D>,
+    ) -> &mut Self {
+        self.push_call(call, true)
+    }
+
+    fn push_call<D: Detokenize>(
+        &mut self,
+        call: ContractCall<M, D>,
+        allow_failure: bool,
+    ) -> &mut Self {
         match (call.tx.to(), call.tx.data()) {
             (Some(NameOrAddress::Address(target)), Some(data)) => {
-                let call = Call { target: *target, data: data.clone(), function: call.function };
+                let call = Call {
+                    target: *target,
+                    data: data.clone(),
+                    value: call.tx.value().copied().unwrap_or_default(),
+                    allow_failure,
+                    function: call.function,
+                };
                 self.calls.push(call);
                 self
             }
@@ -308,7 +428,8 @@ impl<M: Middleware> Multicall<M> {
     /// Queries the Ethereum blockchain via an `eth_call`, but via the Multicall contract and
     /// without detokenization.
     ///
-    /// It returns a [`ContractError<M>`] if there is any error in the RPC call.
+    /// It returns a [`ContractError<M>`] if there is any error in the RPC call, or
+    /// [`ContractError::Revert`] if any call failed.
     ///
     /// ```no_run
     /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
@@ -330,29 +451,98 @@ impl<M: Middleware> Multicall<M> {
     /// Note: this method _does not_ send a transaction from your account
     ///
     /// [`ContractError<M>`]: crate::ContractError<M>
-
     pub async fn call_raw(&self) -> Result<Vec<Token>, ContractError<M>> {
-        let contract_call = self.as_contract_call();
+        self.try_call_raw()
+            .await?
+            .into_iter()
+            .map(|result| result.map_err(ContractError::Revert))
+            .collect()
+    }
+
+    /// Queries the Ethereum blockchain via an `eth_call`, but via the Multicall contract, and
+    /// decodes the output of every call into `D`.
+    ///
+    /// The result of a call added with
+    /// [`add_call_allowing_failure`](Self::add_call_allowing_failure) is `Err` with the revert
+    /// data if the call reverted.
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use ethers_core::types::U256;
+    /// # use ethers_providers::{Provider, Http};
+    /// # use ethers_contract::Multicall;
+    /// # use std::convert::TryFrom;
+    /// #
+    /// # let client = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// #
+    /// # let multicall = Multicall::new(client, None).await?;
+    /// // all calls return a `uint256`
+    /// let results = multicall.try_call::<U256>().await?;
+    /// for result in results {
+    ///     match result {
+    ///         Ok(value) => println!("value: {}", value),
+    ///         Err(revert_data) => println!("reverted: {}", revert_data),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Note: this method _does not_ send a transaction from your account
+    pub async fn try_call<D: Detokenize>(&self) -> Result<Vec<Result<D, Bytes>>, ContractError<M>> {
+        self.try_call_raw()
+            .await?
+            .into_iter()
+            .map(|result| match result {
+                Ok(token) => Ok(Ok(D::from_tokens(vec![token])?)),
+                Err(data) => Ok(Err(data)),
+            })
+            .collect()
+    }
+
+    /// Queries the Ethereum blockchain via an `eth_call`, but via the Multicall contract and
+    /// without detokenization, returning the revert data of every call that failed.
+    ///
+    /// It returns [`ContractError::Revert`] if a call that is not allowed to fail reverted, and a
+    /// [`MulticallError`] if a call needs a feature the [`MulticallVersion`] doesn't support.
+    ///
+    /// Note: this method _does not_ send a transaction from your account
+    pub async fn try_call_raw(&self) -> Result<Vec<Result<Token, Bytes>>, ContractError<M>> {
         // Fetch response from the Multicall contract
-        let (_block_number, return_data) = contract_call.call().await?;
-        let tokens = self
-            .calls
+        let results = match self.version {
+            MulticallVersion::Multicall => {
+                let (_block_number, return_data) = self.as_aggregate_call()?.call().await?;
+                return_data.into_iter().map(|data| (true, data)).collect()
+            }
+            MulticallVersion::Multicall2 => self.as_try_aggregate_call()?.call().await?,
+            MulticallVersion::Multicall3 => self.as_aggregate_3_call().call().await?,
+        };
+
+        self.calls
             .iter()
-            .zip(&return_data)
-            .map(|(call, bytes)| {
-                let mut tokens: Vec<Token> = call.function.decode_output(bytes.as_ref())?;
-                Ok(match tokens.len() {
+            .zip(results)
+            .map(|(call, (success, data))| {
+                if !success {
+                    if call.allow_failure {
+                        return Ok(Err(data))
+                    }
+                    return Err(ContractError::Revert(data))
+                }
+                let mut tokens: Vec<Token> = call.function.decode_output(data.as_ref())?;
+                Ok(Ok(match tokens.len() {
                     0 => Token::Tuple(vec![]),
                     1 => tokens.remove(0),
                     _ => Token::Tuple(tokens),
-                })
+                }))
             })
-            .collect::<Result<Vec<Token>, ContractError<M>>>()?;
-        Ok(tokens)
+            .collect()
     }
 
     /// Signs and broadcasts a batch of transactions by using the Multicall contract as proxy.
     ///
+    /// With Multicall3, the value of all calls is sent along with the transaction. Older versions
+    /// can't send value and return [`MulticallError::ValueTx`] if a call has one.
+    ///
     /// ```no_run
     /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
     /// # use ethers_providers::{Provider, Http};
@@ -368,23 +558,77 @@ impl<M: Middleware> Multicall<M> {
     /// Note: this method sends a transaction from your account, and will return an error
     /// if you do not have sufficient funds to pay for gas
     pub async fn send(&self) -> Result<TxHash, ContractError<M>> {
-        let contract_call = self.as_contract_call();
-
         // Broadcast transaction and return the transaction hash
         // TODO: Can we make this return a PendingTransaction directly instead?
         // Seems hard due to `returns a value referencing data owned by the current function`
-        let tx_hash = *contract_call.send().await?;
+        let tx_hash = match self.version {
+            MulticallVersion::Multicall => *self.as_aggregate_call()?.send().await?,
+            MulticallVersion::Multicall2 => *self.as_try_aggregate_call()?.send().await?,
+            MulticallVersion::Multicall3 => *self.as_aggregate_3_call().send().await?,
+        };
 
         Ok(tx_hash)
     }
 
-    fn as_contract_call(&self) -> ContractCall<M, (U256, Vec<Bytes>)> {
+    /// The `aggregate` call of the original Multicall contract
+    ///
+    /// Returns an error if a call sends value or is allowed to fail.
+    fn as_aggregate_call(&self) -> Result<ContractCall<M, (U256, Vec<Bytes>)>, MulticallError> {
+        self.ensure_no_value()?;
+        if self.calls.iter().any(|call| call.allow_failure) {
+            return Err(MulticallError::AllowFailure(self.version))
+        }
+
         // Map the Multicall struct into appropriate types for `aggregate` function
         let calls: Vec<(Address, Bytes)> =
             self.calls.iter().map(|call| (call.target, call.data.clone())).collect();
 
         // Construct the ContractCall for `aggregate` function to broadcast the transaction
-        let mut contract_call = self.contract.aggregate(calls);
+        Ok(self.prepare(self.contract.aggregate(calls)))
+    }
+
+    /// The `tryAggregate` call of Multicall2, which only requires success if no call is allowed
+    /// to fail
+    ///
+    /// Returns an error if a call sends value.
+    fn as_try_aggregate_call(&self) -> Result<ContractCall<M, Vec<(bool, Bytes)>>, MulticallError> {
+        self.ensure_no_value()?;
+        let require_success = self.calls.iter().all(|call| !call.allow_failure);
+        let calls: Vec<(Address, Bytes)> =
+            self.calls.iter().map(|call| (call.target, call.data.clone())).collect();
+
+        Ok(self.prepare(self.contract.try_aggregate(require_success, calls)))
+    }
+
+    /// Returns an error if a call sends value, which only Multicall3 supports
+    fn ensure_no_value(&self) -> Result<(), MulticallError> {
+        if self.calls.iter().any(|call| !call.value.is_zero()) {
+            return Err(MulticallError::ValueTx(self.version))
+        }
+        Ok(())
+    }
+
+    /// The `aggregate3` call of Multicall3, or `aggregate3Value` if any call sends value
+    fn as_aggregate_3_call(&self) -> ContractCall<M, Vec<(bool, Bytes)>> {
+        let total_value = self.calls.iter().fold(U256::zero(), |total, call| total + call.value);
+        if total_value.is_zero() {
+            let calls: Vec<(Address, bool, Bytes)> = self
+                .calls
+                .iter()
+                .map(|call| (call.target, call.allow_failure, call.data.clone()))
+                .collect();
+            self.prepare(self.contract.aggregate_3(calls))
+        } else {
+            let calls: Vec<(Address, bool, U256, Bytes)> = self
+                .calls
+                .iter()
+                .map(|call| (call.target, call.allow_failure, call.value, call.data.clone()))
+                .collect();
+            self.prepare(self.contract.aggregate_3_value(calls)).value(total_value)
+        }
+    }
+
+    fn prepare<D: Detokenize>(&self, mut contract_call: ContractCall<M, D>) -> ContractCall<M, D> {
         if let Some(block) = self.block {
             contract_call = contract_call.block(block)
         };
@@ -396,3 +640,41 @@ impl<M: Middleware> Multicall<M> {
         contract_call
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{MockProvider, Provider};
+
+    async fn multicall(version: MulticallVersion) -> Multicall<Provider<MockProvider>> {
+        let (provider, _mock) = Provider::mocked();
+        Multicall::new(provider, Some(Address::random())).await.unwrap().version(version)
+    }
+
+    #[tokio::test]
+    async fn rejects_calls_the_version_cannot_honour() {
+        let target = Address::random();
+
+        for version in [MulticallVersion::Multicall, MulticallVersion::Multicall2] {
+            let mut multicall = multicall(version).await;
+            let call = multicall.contract.get_eth_balance(target).value(1u64);
+            multicall.add_call(call);
+            assert!(matches!(
+                multicall.try_call_raw().await.unwrap_err(),
+                ContractError::MulticallError(MulticallError::ValueTx(v)) if v == version
+            ));
+            assert!(matches!(
+                multicall.send().await.unwrap_err(),
+                ContractError::MulticallError(MulticallError::ValueTx(_))
+            ));
+        }
+
+        let mut multicall = multicall(MulticallVersion::Multicall).await;
+        let call = multicall.contract.get_eth_balance(target);
+        multicall.add_call_allowing_failure(call);
+        assert!(matches!(
+            multicall.try_call_raw().await.unwrap_err(),
+            ContractError::MulticallError(MulticallError::AllowFailure(MulticallVersion::Multicall))
+        ));
+    }
+}
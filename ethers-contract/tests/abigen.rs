@@ -1,14 +1,14 @@
 #![cfg(feature = "abigen")]
 #![allow(unused)]
 //! Test cases to validate the `abigen!` macro
-use ethers_contract::{abigen, Abigen, EthCall, EthEvent};
+use ethers_contract::{abigen, Abigen, ContractError, EthCall, EthError, EthEvent, Panic};
 use ethers_core::{
     abi::{AbiDecode, AbiEncode, Address, Tokenizable},
     types::{transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, U256},
     utils::Anvil,
 };
-use ethers_middleware::SignerMiddleware;
-use ethers_providers::{MockProvider, Provider};
+use ethers_middleware::{signer::SignerMiddlewareError, SignerMiddleware};
+use ethers_providers::{HttpClientError, JsonRpcError, MockProvider, Provider, ProviderError};
 use ethers_signers::{LocalWallet, Signer};
 use ethers_solc::Solc;
 use std::{convert::TryFrom, sync::Arc};
@@ -654,3 +654,102 @@ fn can_generate_to_string_overload() {
         ToStringCalls::ToString5(_) => {}
     };
 }
+
+#[test]
+fn can_gen_custom_errors() {
+    abigen!(
+        Vault,
+        r#"[
+            error InsufficientBalance(uint256 available, uint256 required)
+            error Unauthorized()
+            function withdraw(uint256 amount)
+    ]"#
+    );
+
+    assert_eq!(InsufficientBalance::abi_signature(), "InsufficientBalance(uint256,uint256)");
+    assert_eq!(Unauthorized::abi_signature(), "Unauthorized()");
+
+    let error = InsufficientBalance { available: 1u64.into(), required: 2u64.into() };
+    let encoded = error.clone().encode();
+    assert_eq!(
+        VaultErrors::decode(&encoded).unwrap(),
+        VaultErrors::InsufficientBalance(error.clone())
+    );
+
+    let revert = VaultErrors::RevertString("not the owner".to_string()).encode();
+    assert_eq!(&revert[..4], &[0x08, 0xc3, 0x79, 0xa0]);
+    assert_eq!(
+        VaultErrors::decode(&revert).unwrap(),
+        VaultErrors::RevertString("not the owner".to_string())
+    );
+
+    let panic = VaultErrors::Panic(Panic(0x11u64.into())).encode();
+    assert_eq!(VaultErrors::decode(&panic).unwrap(), VaultErrors::Panic(Panic(0x11u64.into())));
+    assert_eq!(VaultErrors::Panic(Panic(0x11u64.into())).to_string(), "Panic(0x11)");
+
+    // decode the revert data of a failed call from the node's error response
+    let response = JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: Some(format!("0x{}", hex::encode(&encoded)).into()),
+    };
+    let err = ContractError::<Provider<MockProvider>>::ProviderError(ProviderError::from(
+        HttpClientError::from(response),
+    ));
+    assert_eq!(err.as_revert().unwrap().as_ref(), encoded.as_slice());
+    assert_eq!(
+        err.decode_revert::<VaultErrors>(),
+        Some(VaultErrors::InsufficientBalance(error.clone()))
+    );
+    assert_eq!(err.decode_revert::<InsufficientBalance>(), Some(error.clone()));
+    assert_eq!(err.decode_revert::<Unauthorized>(), None);
+
+    let err = ContractError::<Provider<MockProvider>>::MiddlewareError(ProviderError::from(
+        HttpClientError::from(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(format!("0x{}", hex::encode(&revert)).into()),
+        }),
+    ));
+    assert_eq!(err.decode_revert::<String>(), Some("not the owner".to_string()));
+
+    // the revert data is found through the error of the middleware stack
+    type Client = SignerMiddleware<Provider<MockProvider>, LocalWallet>;
+    let err = ContractError::<Client>::MiddlewareError(SignerMiddlewareError::MiddlewareError(
+        ProviderError::from(HttpClientError::from(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(format!("0x{}", hex::encode(&encoded)).into()),
+        })),
+    ));
+    assert_eq!(
+        err.decode_revert::<VaultErrors>(),
+        Some(VaultErrors::InsufficientBalance(error.clone()))
+    );
+    assert_eq!(err.decode_revert::<InsufficientBalance>(), Some(error));
+}
+
+#[test]
+fn can_gen_errors_named_like_builtin_errors() {
+    abigen!(
+        Vault,
+        r#"[
+            error Panic(uint256 code)
+            error RevertString()
+            function withdraw(uint256 amount)
+    ]"#
+    );
+
+    let error = Panic { code: 0x11u64.into() };
+    let encoded = error.clone().encode();
+    assert_eq!(VaultErrors::decode(&encoded).unwrap(), VaultErrors::Panic(error.clone()));
+
+    // the builtin errors are renamed
+    let revert = VaultErrors::RevertStringBuiltin("not the owner".to_string()).encode();
+    assert_eq!(
+        VaultErrors::decode(&revert).unwrap(),
+        VaultErrors::RevertStringBuiltin("not the owner".to_string())
+    );
+    let panic = VaultErrors::PanicBuiltin(ethers_contract::Panic(0x11u64.into())).encode();
+    assert_eq!(panic, encoded);
+}
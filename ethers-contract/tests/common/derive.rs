@@ -1,5 +1,5 @@
 use ethers_contract::{
-    abigen, EthAbiCodec, EthAbiType, EthCall, EthDisplay, EthEvent, EthLogDecode,
+    abigen, EthAbiCodec, EthAbiType, EthCall, EthDisplay, EthError, EthEvent, EthLogDecode,
 };
 use ethers_core::{
     abi::{AbiDecode, AbiEncode, RawLog, Tokenizable},
@@ -8,6 +8,7 @@ use ethers_core::{
 
 fn assert_tokenizeable<T: Tokenizable>() {}
 fn assert_ethcall<T: EthCall>() {}
+fn assert_etherror<T: EthError>() {}
 
 #[derive(Debug, Clone, PartialEq, Eq, EthAbiType)]
 struct ValueChanged {
@@ -466,6 +467,37 @@ fn can_derive_ethcall_with_nested_structs() {
     assert_ethcall::<FooCall>();
 }
 
+#[test]
+fn can_derive_etherror() {
+    #[derive(Debug, Clone, PartialEq, Eq, EthError, EthDisplay)]
+    #[etherror(name = "InsufficientBalance", abi = "InsufficientBalance(uint256,uint256)")]
+    struct InsufficientBalance {
+        available: U256,
+        required: U256,
+    }
+
+    assert_tokenizeable::<InsufficientBalance>();
+    assert_etherror::<InsufficientBalance>();
+    assert_eq!(
+        InsufficientBalance::abi_signature().as_ref(),
+        "InsufficientBalance(uint256,uint256)"
+    );
+    assert_eq!(
+        InsufficientBalance::selector(),
+        ethers_core::utils::id("InsufficientBalance(uint256,uint256)")
+    );
+
+    let error = InsufficientBalance { available: 1u64.into(), required: 2u64.into() };
+    let encoded = error.clone().encode();
+    assert_eq!(&encoded[..4], &InsufficientBalance::selector());
+    assert_eq!(InsufficientBalance::decode(&encoded).unwrap(), error);
+    assert_eq!(InsufficientBalance::decode_with_selector(&encoded), Some(error));
+
+    // the selector of a different error is rejected
+    assert!(InsufficientBalance::decode_with_selector(&encoded[1..]).is_none());
+    assert!(String::decode_with_selector(&encoded).is_none());
+}
+
 #[test]
 fn can_derive_for_enum() {
     #[derive(Debug, Clone, PartialEq, Eq, EthAbiType)]
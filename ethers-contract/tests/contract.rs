@@ -7,7 +7,7 @@ use ethers_core::types::{Filter, ValueOrArray, H256};
 #[cfg(not(feature = "celo"))]
 mod eth_tests {
     use super::*;
-    use ethers_contract::{LogMeta, Multicall};
+    use ethers_contract::{LogMeta, Multicall, MulticallVersion};
     use ethers_core::{
         abi::{Detokenize, Token, Tokenizable},
         types::{transaction::eip712::Eip712, Address, BlockId, Bytes, I256, U256},
@@ -534,6 +534,44 @@ mod eth_tests {
         assert_eq!(results, ["many"; 17]);
     }
 
+    #[tokio::test]
+    async fn multicall_aggregate_3_allow_failure() {
+        let (provider, mock) = Provider::mocked();
+        let client = Arc::new(provider);
+
+        let abi =
+            ethers_core::abi::parse_abi(&["function getValue() view returns (string)"]).unwrap();
+        let contract =
+            ethers_contract::Contract::<Provider<_>>::new(Address::zero(), abi, client.clone());
+
+        let mut multicall = Multicall::new(client, Some(Address::repeat_byte(1)))
+            .await
+            .unwrap()
+            .version(MulticallVersion::Multicall3);
+        multicall
+            .add_call(contract.method::<_, String>("getValue", ()).unwrap())
+            .add_call_allowing_failure(contract.method::<_, String>("getValue", ()).unwrap());
+
+        // the second call reverts, `aggregate3` returns `(bool success, bytes returnData)[]`
+        let revert_data = Bytes::from(vec![1, 2, 3]);
+        let output = ethers_core::abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![
+                Token::Bool(true),
+                Token::Bytes(ethers_core::abi::encode(&[Token::String("hi".to_string())])),
+            ]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(revert_data.to_vec())]),
+        ])]);
+        mock.push::<Bytes, Bytes>(output.clone().into()).unwrap();
+
+        let results = multicall.try_call::<String>().await.unwrap();
+        assert_eq!(results, vec![Ok("hi".to_string()), Err(revert_data.clone())]);
+
+        // `call_raw` fails the whole batch if any call failed
+        mock.push::<Bytes, Bytes>(output.into()).unwrap();
+        let err = multicall.call_raw().await.unwrap_err();
+        assert!(matches!(err, ethers_contract::ContractError::Revert(data) if data == revert_data));
+    }
+
     #[tokio::test]
     async fn test_derive_eip712() {
         // Generate Contract ABI Bindings
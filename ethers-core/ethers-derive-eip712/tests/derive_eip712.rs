@@ -61,10 +61,10 @@ fn test_struct_hash() {
     }
 
     let domain = Domain {
-        name: "Radicle".to_string(),
-        version: "1".to_string(),
-        chain_id: U256::from(1),
-        verifying_contract: H160::from(&[0; 20]),
+        name: Some("Radicle".to_string()),
+        version: Some("1".to_string()),
+        chain_id: Some(U256::from(1)),
+        verifying_contract: Some(H160::from(&[0; 20])),
         salt: None,
     };
 
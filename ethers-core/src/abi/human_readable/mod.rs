@@ -82,6 +82,9 @@ impl AbiParser {
             if line.starts_with("event") {
                 let event = self.parse_event(line)?;
                 abi.events.entry(event.name.clone()).or_default().push(event);
+            } else if let Some(error) = line.strip_prefix("error ") {
+                let error = self.parse_error(error)?;
+                abi.errors.entry(error.name.clone()).or_default().push(error);
             } else if line.starts_with("constructor") {
                 let inputs = self
                     .constructor_inputs(line)?
@@ -162,6 +165,16 @@ impl AbiParser {
         }
     }
 
+    /// Parses a solidity custom error declaration from `<name>(args*)`, without the leading
+    /// `error` keyword
+    fn parse_error(&mut self, s: &str) -> Result<ethabi::AbiError> {
+        let function = self.parse_function(s)?;
+        if !function.outputs.is_empty() {
+            bail!("Illegal abi `error {}`, errors can't have outputs", s)
+        }
+        Ok(ethabi::AbiError { name: function.name, inputs: function.inputs })
+    }
+
     /// Parses a solidity event declaration from `event <name> (args*) anonymous?`
     pub fn parse_event(&self, s: &str) -> Result<Event> {
         let mut event = s.trim();
@@ -563,6 +576,23 @@ mod tests {
         assert!(parsed.outputs.is_empty());
     }
 
+    #[test]
+    fn parses_custom_errors() {
+        let abi = AbiParser::default()
+            .parse(&[
+                "error InsufficientBalance(uint256 available, uint256 required)",
+                "error Unauthorized()",
+                "function withdraw(uint256 amount)",
+            ])
+            .unwrap();
+        assert_eq!(abi.errors.len(), 2);
+        let error = &abi.errors["InsufficientBalance"][0];
+        assert_eq!(error.inputs[0].name, "available");
+        assert_eq!(error.inputs[1].kind, ParamType::Uint(256));
+        assert!(abi.errors["Unauthorized"][0].inputs.is_empty());
+        assert_eq!(abi.functions.len(), 1);
+    }
+
     #[test]
     fn parses_function_payable() {
         let fn_str = "function foo() public payable";
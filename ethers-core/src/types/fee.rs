@@ -20,7 +20,7 @@ pub struct FeeHistory {
     pub reward: Vec<Vec<U256>>,
 }
 
-fn from_int_or_hex<'de, D>(deserializer: D) -> Result<U256, D::Error>
+pub(crate) fn from_int_or_hex<'de, D>(deserializer: D) -> Result<U256, D::Error>
 where
     D: Deserializer<'de>,
 {
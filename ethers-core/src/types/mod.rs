@@ -64,5 +64,8 @@ pub use proof::*;
 mod fee;
 pub use fee::*;
 
+mod user_operation;
+pub use user_operation::*;
+
 mod other;
 pub use other::OtherFields;
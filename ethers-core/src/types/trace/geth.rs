@@ -0,0 +1,521 @@
+//! Types for Geth's `debug` tracing API
+//!
+//! <https://geth.ethereum.org/docs/rpc/ns-debug>
+use crate::types::{Address, Bytes, H256, U256};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// The result of a `debug_trace*` call, its shape depends on the tracer that was used
+///
+/// Deserializing picks the first variant the output fits, so e.g. the `{}` a JS tracer returns is
+/// decoded as an empty prestate. Use [`GethTrace::from_tracer_output`] if the tracing options are
+/// known.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum GethTrace {
+    /// Output of the default struct logger
+    Default(DefaultFrame),
+    /// Output of the built-in `callTracer`
+    CallTracer(CallFrame),
+    /// Output of the built-in `prestateTracer`
+    PreStateTracer(PreStateFrame),
+    /// Output of a custom JS tracer or any other tracer
+    Unknown(serde_json::Value),
+}
+
+impl GethTrace {
+    /// Decodes the output of a `debug_trace*` call as the output of the tracer requested by the
+    /// given options
+    pub fn from_tracer_output(
+        output: serde_json::Value,
+        opts: &GethDebugTracingOptions,
+    ) -> Result<Self, serde_json::Error> {
+        use GethDebugBuiltInTracerType::*;
+
+        Ok(match opts.tracer {
+            None => GethTrace::Default(serde_json::from_value(output)?),
+            Some(GethDebugTracerType::BuiltInTracer(CallTracer)) => {
+                GethTrace::CallTracer(serde_json::from_value(output)?)
+            }
+            Some(GethDebugTracerType::BuiltInTracer(PreStateTracer)) => {
+                let diff_mode = matches!(
+                    opts.tracer_config,
+                    Some(GethDebugTracerConfig::PreStateTracer(PreStateConfig {
+                        diff_mode: Some(true)
+                    }))
+                );
+                let frame = if diff_mode {
+                    PreStateFrame::Diff(serde_json::from_value(output)?)
+                } else {
+                    PreStateFrame::Default(serde_json::from_value(output)?)
+                };
+                GethTrace::PreStateTracer(frame)
+            }
+            Some(GethDebugTracerType::JsTracer(_)) => GethTrace::Unknown(output),
+        })
+    }
+}
+
+/// Output of the default struct logger
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DefaultFrame {
+    /// Whether the execution failed
+    pub failed: bool,
+    /// The total gas used
+    pub gas: u64,
+    /// The returned data
+    #[serde(rename = "returnValue")]
+    pub return_value: Bytes,
+    /// All executed opcodes
+    #[serde(rename = "structLogs")]
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A single executed opcode of the struct logger
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StructLog {
+    /// The call depth
+    pub depth: u64,
+    /// The error, if the opcode failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The remaining gas
+    pub gas: u64,
+    /// The gas cost of the opcode
+    #[serde(rename = "gasCost")]
+    pub gas_cost: u64,
+    /// The memory, unless disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// The name of the opcode
+    pub op: String,
+    /// The program counter
+    pub pc: u64,
+    /// The stack, unless disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// The storage of the current contract, unless disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Output of the built-in `callTracer`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CallFrame {
+    /// The type of the call, e.g. `CALL` or `CREATE`
+    #[serde(rename = "type")]
+    pub typ: String,
+    /// The caller
+    pub from: Address,
+    /// The callee, not set for failed contract creations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// The transferred value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// The gas provided to the call
+    pub gas: U256,
+    /// The gas used by the call
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// The calldata
+    pub input: Bytes,
+    /// The returned data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// The error, if the call failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The decoded revert reason, if the call reverted with one
+    #[serde(default, rename = "revertReason", skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// The subcalls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calls: Option<Vec<CallFrame>>,
+    /// The logs emitted by the call, if `withLog` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<Vec<CallLogFrame>>,
+}
+
+/// A log emitted during a call, as reported by the `callTracer` with `withLog` enabled
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CallLogFrame {
+    /// The address of the contract that emitted the log
+    pub address: Address,
+    /// The topics of the log
+    #[serde(default)]
+    pub topics: Vec<H256>,
+    /// The data of the log
+    #[serde(default)]
+    pub data: Bytes,
+}
+
+/// Output of the built-in `prestateTracer`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PreStateFrame {
+    /// The state of all touched accounts before the execution
+    Default(BTreeMap<Address, AccountState>),
+    /// The state before and after the execution, if `diffMode` is enabled
+    Diff(DiffMode),
+}
+
+/// Output of the `prestateTracer` in `diffMode`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DiffMode {
+    /// The state of all modified accounts before the execution
+    pub pre: BTreeMap<Address, AccountState>,
+    /// The state of all modified accounts after the execution
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+/// The state of an account as reported by the `prestateTracer`
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct AccountState {
+    /// The balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// The code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// The touched storage slots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// The trace of a single transaction as returned by `debug_traceBlockByNumber`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GethTraceResult {
+    /// The trace, unless tracing failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GethTrace>,
+    /// The error, if tracing failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl GethTraceResult {
+    /// Decodes a trace of `debug_traceBlockByNumber`, see [`GethTrace::from_tracer_output`]
+    pub fn from_tracer_output(
+        output: serde_json::Value,
+        opts: &GethDebugTracingOptions,
+    ) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct RawTraceResult {
+            #[serde(default)]
+            result: Option<serde_json::Value>,
+            #[serde(default)]
+            error: Option<String>,
+        }
+
+        let RawTraceResult { result, error } = serde_json::from_value(output)?;
+        let result =
+            result.map(|result| GethTrace::from_tracer_output(result, opts)).transpose()?;
+        Ok(GethTraceResult { result, error })
+    }
+}
+
+/// The tracers built into Geth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GethDebugBuiltInTracerType {
+    /// Traces all call frames of the execution
+    #[serde(rename = "callTracer")]
+    CallTracer,
+    /// Returns the state of all accounts the execution touched
+    #[serde(rename = "prestateTracer")]
+    PreStateTracer,
+}
+
+/// The tracer to use instead of the default struct logger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GethDebugTracerType {
+    /// One of the tracers built into Geth
+    BuiltInTracer(GethDebugBuiltInTracerType),
+    /// A custom tracer, as JavaScript source
+    JsTracer(String),
+}
+
+impl From<GethDebugBuiltInTracerType> for GethDebugTracerType {
+    fn from(tracer: GethDebugBuiltInTracerType) -> Self {
+        GethDebugTracerType::BuiltInTracer(tracer)
+    }
+}
+
+impl Serialize for GethDebugTracerType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            GethDebugTracerType::BuiltInTracer(tracer) => tracer.serialize(serializer),
+            GethDebugTracerType::JsTracer(code) => serializer.serialize_str(code),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GethDebugTracerType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tracer = String::deserialize(deserializer)?;
+        let builtin = serde_json::from_value(serde_json::Value::String(tracer.clone()));
+        Ok(match builtin {
+            Ok(builtin) => GethDebugTracerType::BuiltInTracer(builtin),
+            Err(_) if tracer.is_empty() => return Err(de::Error::custom("empty tracer")),
+            Err(_) => GethDebugTracerType::JsTracer(tracer),
+        })
+    }
+}
+
+/// Configuration of the built-in `callTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CallConfig {
+    /// Only trace the top level call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_top_call: Option<bool>,
+    /// Include the logs emitted by the calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_log: Option<bool>,
+}
+
+/// Configuration of the built-in `prestateTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PreStateConfig {
+    /// Return the state before and after the execution instead of only the prestate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_mode: Option<bool>,
+}
+
+/// Configuration of the selected tracer
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum GethDebugTracerConfig {
+    /// Configuration of the `callTracer`
+    CallTracer(CallConfig),
+    /// Configuration of the `prestateTracer`
+    PreStateTracer(PreStateConfig),
+    /// Configuration of a custom JS tracer, or any configuration the other variants reject
+    JsTracer(serde_json::Value),
+}
+
+/// Options for `debug_traceTransaction`, `debug_traceCall` and `debug_traceBlockByNumber`
+///
+/// <https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracetransaction>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDebugTracingOptions {
+    /// Do not capture storage, only applies to the struct logger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_storage: Option<bool>,
+    /// Do not capture the stack, only applies to the struct logger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_stack: Option<bool>,
+    /// Capture the memory, only applies to the struct logger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_memory: Option<bool>,
+    /// Capture the return data, only applies to the struct logger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_return_data: Option<bool>,
+    /// The tracer to use instead of the struct logger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<GethDebugTracerType>,
+    /// Configuration of the selected tracer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer_config: Option<GethDebugTracerConfig>,
+    /// Overrides the default timeout of 5 seconds for JS tracers, e.g. `"10s"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+}
+
+impl GethDebugTracingOptions {
+    /// Sets the tracer to use instead of the struct logger
+    #[must_use]
+    pub fn tracer(mut self, tracer: impl Into<GethDebugTracerType>) -> Self {
+        self.tracer = Some(tracer.into());
+        self
+    }
+
+    /// Sets the configuration of the selected tracer
+    #[must_use]
+    pub fn tracer_config(mut self, config: GethDebugTracerConfig) -> Self {
+        self.tracer_config = Some(config);
+        self
+    }
+
+    /// Sets the `disableStorage` flag
+    #[must_use]
+    pub fn disable_storage(mut self, disable: bool) -> Self {
+        self.disable_storage = Some(disable);
+        self
+    }
+
+    /// Sets the `disableStack` flag
+    #[must_use]
+    pub fn disable_stack(mut self, disable: bool) -> Self {
+        self.disable_stack = Some(disable);
+        self
+    }
+
+    /// Sets the `enableMemory` flag
+    #[must_use]
+    pub fn enable_memory(mut self, enable: bool) -> Self {
+        self.enable_memory = Some(enable);
+        self
+    }
+
+    /// Sets the `enableReturnData` flag
+    #[must_use]
+    pub fn enable_return_data(mut self, enable: bool) -> Self {
+        self.enable_return_data = Some(enable);
+        self
+    }
+
+    /// Sets the timeout for JS tracers
+    #[must_use]
+    pub fn timeout(mut self, timeout: impl Into<String>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ser_tracing_options() {
+        let opts = GethDebugTracingOptions::default().disable_storage(true).disable_stack(true);
+        assert_eq!(
+            serde_json::to_string(&opts).unwrap(),
+            r#"{"disableStorage":true,"disableStack":true}"#
+        );
+
+        let opts = GethDebugTracingOptions::default()
+            .tracer(GethDebugBuiltInTracerType::CallTracer)
+            .tracer_config(GethDebugTracerConfig::CallTracer(CallConfig {
+                only_top_call: Some(true),
+                with_log: None,
+            }));
+        assert_eq!(
+            serde_json::to_string(&opts).unwrap(),
+            r#"{"tracer":"callTracer","tracerConfig":{"onlyTopCall":true}}"#
+        );
+
+        let js = "{data: [], fault: function(log) {}, step: function(log) {}, result: function() { return this.data; }}";
+        let opts = GethDebugTracingOptions::default()
+            .tracer(GethDebugTracerType::JsTracer(js.to_string()))
+            .timeout("10s");
+        let ser = serde_json::to_string(&opts).unwrap();
+        let de: GethDebugTracingOptions = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, opts);
+    }
+
+    #[test]
+    fn deser_struct_logs() {
+        let s = r#"{"failed":false,"gas":21000,"returnValue":"","structLogs":[{"pc":0,"op":"PUSH1","gas":78694,"gasCost":3,"depth":1,"stack":[],"storage":{}},{"pc":2,"op":"SSTORE","gas":78691,"gasCost":20000,"depth":1,"stack":["0x1","0x0"],"storage":{"0x0000000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000001"}}]}"#;
+        let trace: GethTrace = serde_json::from_str(s).unwrap();
+        match trace {
+            GethTrace::Default(frame) => {
+                assert_eq!(frame.gas, 21000);
+                assert_eq!(frame.struct_logs.len(), 2);
+                assert_eq!(frame.struct_logs[1].op, "SSTORE");
+                assert_eq!(frame.struct_logs[1].stack.as_ref().unwrap()[0], 1u64.into());
+            }
+            _ => panic!("expected struct logs"),
+        }
+    }
+
+    #[test]
+    fn deser_call_tracer() {
+        let s = r#"{"type":"CALL","from":"0x25f5ad2e8b3f0b3bd6a3e5c0a8a7a5a3e5f3b7e1","to":"0x6b175474e89094c44da98b954eedeac495271d0f","value":"0x0","gas":"0x1a4d8","gasUsed":"0x6f0a","input":"0xa9059cbb","output":"0x0000000000000000000000000000000000000000000000000000000000000001","calls":[{"type":"STATICCALL","from":"0x6b175474e89094c44da98b954eedeac495271d0f","to":"0x0000000000000000000000000000000000000001","gas":"0x1000","gasUsed":"0xbb8","input":"0x"}]}"#;
+        let trace: GethTrace = serde_json::from_str(s).unwrap();
+        match trace {
+            GethTrace::CallTracer(frame) => {
+                assert_eq!(frame.typ, "CALL");
+                assert_eq!(frame.calls.unwrap()[0].typ, "STATICCALL");
+            }
+            _ => panic!("expected call frame"),
+        }
+
+        let s = r#"{"0x6b175474e89094c44da98b954eedeac495271d0f":{"balance":"0x0","nonce":1,"code":"0x6080"}}"#;
+        let trace: GethTrace = serde_json::from_str(s).unwrap();
+        assert!(matches!(trace, GethTrace::PreStateTracer(PreStateFrame::Default(_))));
+    }
+
+    #[test]
+    fn deser_call_tracer_with_logs() {
+        let s = r#"{"from":"0x4f7a67464b5976d7547c860109e4432d50afb38e","gas":"0x11d0e","gasUsed":"0xa1c6","to":"0xdac17f958d2ee523a2206206994597c13d831ec7","input":"0xa9059cbb000000000000000000000000c2b7e8e5a0d2d3d0d5d2c5bd1a6e3c0f3e1c9e4a0000000000000000000000000000000000000000000000000000000005f5e100","logs":[{"address":"0xdac17f958d2ee523a2206206994597c13d831ec7","topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000004f7a67464b5976d7547c860109e4432d50afb38e","0x000000000000000000000000c2b7e8e5a0d2d3d0d5d2c5bd1a6e3c0f3e1c9e4a"],"data":"0x0000000000000000000000000000000000000000000000000000000005f5e100"}],"value":"0x0","type":"CALL"}"#;
+        let frame: CallFrame = serde_json::from_str(s).unwrap();
+        let logs = frame.logs.as_ref().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].address,
+            "0xdac17f958d2ee523a2206206994597c13d831ec7".parse::<Address>().unwrap()
+        );
+        assert_eq!(logs[0].topics.len(), 3);
+        assert_eq!(U256::from_big_endian(&logs[0].data), U256::from(100_000_000u64));
+        let ser = serde_json::to_string(&frame).unwrap();
+        assert_eq!(serde_json::from_str::<CallFrame>(&ser).unwrap(), frame);
+
+        let s = r#"{"from":"0x4f7a67464b5976d7547c860109e4432d50afb38e","gas":"0x5208","gasUsed":"0x5208","to":"0xdac17f958d2ee523a2206206994597c13d831ec7","input":"0x","output":"0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000046e6f706500000000000000000000000000000000000000000000000000000000","error":"execution reverted","revertReason":"nope","value":"0x0","type":"CALL"}"#;
+        let frame: CallFrame = serde_json::from_str(s).unwrap();
+        assert_eq!(frame.revert_reason.as_deref(), Some("nope"));
+        assert!(frame.logs.is_none());
+    }
+
+    #[test]
+    fn deser_tracer_config() {
+        let config: GethDebugTracerConfig = serde_json::from_str(r#"{"diffMode":true}"#).unwrap();
+        assert_eq!(
+            config,
+            GethDebugTracerConfig::PreStateTracer(PreStateConfig { diff_mode: Some(true) })
+        );
+
+        let config: GethDebugTracerConfig =
+            serde_json::from_str(r#"{"onlyTopCall":true}"#).unwrap();
+        assert!(matches!(config, GethDebugTracerConfig::CallTracer(_)));
+
+        // empty configs are the same for all tracers
+        let config: GethDebugTracerConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(serde_json::to_string(&config).unwrap(), "{}");
+
+        let config: GethDebugTracerConfig = serde_json::from_str(r#"{"depth":2}"#).unwrap();
+        assert!(matches!(config, GethDebugTracerConfig::JsTracer(_)));
+    }
+
+    #[test]
+    fn deser_tracer_output() {
+        let opts = GethDebugTracingOptions::default()
+            .tracer(GethDebugTracerType::JsTracer("{}".to_string()));
+        let trace = GethTrace::from_tracer_output(serde_json::json!({}), &opts).unwrap();
+        assert_eq!(trace, GethTrace::Unknown(serde_json::json!({})));
+
+        let opts =
+            GethDebugTracingOptions::default().tracer(GethDebugBuiltInTracerType::PreStateTracer);
+        let trace = GethTrace::from_tracer_output(serde_json::json!({}), &opts).unwrap();
+        assert_eq!(trace, GethTrace::PreStateTracer(PreStateFrame::Default(Default::default())));
+
+        let opts = opts.tracer_config(GethDebugTracerConfig::PreStateTracer(PreStateConfig {
+            diff_mode: Some(true),
+        }));
+        let output = serde_json::json!({ "pre": {}, "post": {} });
+        let trace = GethTrace::from_tracer_output(output, &opts).unwrap();
+        assert!(matches!(trace, GethTrace::PreStateTracer(PreStateFrame::Diff(_))));
+
+        let opts = GethDebugTracingOptions::default()
+            .tracer(GethDebugTracerType::JsTracer("{}".to_string()));
+        let output = serde_json::json!({ "result": {} });
+        let result = GethTraceResult::from_tracer_output(output, &opts).unwrap();
+        assert_eq!(result.result, Some(GethTrace::Unknown(serde_json::json!({}))));
+        let output = serde_json::json!({ "error": "execution timeout" });
+        let result = GethTraceResult::from_tracer_output(output, &opts).unwrap();
+        assert_eq!(result.error.as_deref(), Some("execution timeout"));
+    }
+}
@@ -6,10 +6,14 @@ use syn::{
     GenericArgument, Lit, NestedMeta, PathArguments, Type,
 };
 
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
 use crate::{
     abi,
     abi::{ParamType, Token},
-    types::{Address, H160, U256},
+    types::{Address, Bytes, H256, I256, U256},
     utils::keccak256,
 };
 
@@ -46,6 +50,8 @@ pub enum Eip712Error {
     NestedEip712StructNotImplemented,
     #[error("Error from Eip712 struct: {0:?}")]
     Inner(String),
+    #[error("{0}")]
+    Message(String),
 }
 
 /// The Eip712 trait provides helper methods for computing
@@ -102,24 +108,34 @@ pub trait Eip712 {
 
 /// Eip712 Domain attributes used in determining the domain separator;
 /// Unused fields are left out of the struct type.
-#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EIP712Domain {
     ///  The user readable name of signing domain, i.e. the name of the DApp or the protocol.
-    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 
     /// The current major version of the signing domain. Signatures from different versions are not
     /// compatible.
-    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 
     /// The EIP-155 chain id. The user-agent should refuse signing if it does not match the
     /// currently active chain.
-    pub chain_id: U256,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_opt_numeric"
+    )]
+    pub chain_id: Option<U256>,
 
     /// The address of the contract that will verify the signature.
-    pub verifying_contract: Address,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifying_contract: Option<Address>,
 
     /// A disambiguating salt for the protocol. This can be used as a domain separator of last
     /// resort.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "salt")]
     pub salt: Option<[u8; 32]>,
 }
 
@@ -127,29 +143,91 @@ impl EIP712Domain {
     // Compute the domain separator;
     // See: https://github.com/gakonst/ethers-rs/blob/master/examples/permit_hash.rs#L41
     pub fn separator(&self) -> [u8; 32] {
-        let domain_type_hash = if self.salt.is_some() {
-            EIP712_DOMAIN_TYPE_HASH_WITH_SALT
-        } else {
-            EIP712_DOMAIN_TYPE_HASH
-        };
-
-        let mut tokens = vec![
-            Token::Uint(U256::from(domain_type_hash)),
-            Token::Uint(U256::from(keccak256(&self.name))),
-            Token::Uint(U256::from(keccak256(&self.version))),
-            Token::Uint(self.chain_id),
-            Token::Address(self.verifying_contract),
-        ];
+        // only the fields that are set are part of the domain type
+        let mut ty = "EIP712Domain(".to_string();
+        let mut tokens = Vec::new();
+        let mut needs_comma = false;
+
+        if let Some(name) = &self.name {
+            ty += "string name";
+            tokens.push(Token::Uint(U256::from(keccak256(name))));
+            needs_comma = true;
+        }
+
+        if let Some(version) = &self.version {
+            if needs_comma {
+                ty.push(',');
+            }
+            ty += "string version";
+            tokens.push(Token::Uint(U256::from(keccak256(version))));
+            needs_comma = true;
+        }
+
+        if let Some(chain_id) = self.chain_id {
+            if needs_comma {
+                ty.push(',');
+            }
+            ty += "uint256 chainId";
+            tokens.push(Token::Uint(chain_id));
+            needs_comma = true;
+        }
+
+        if let Some(verifying_contract) = self.verifying_contract {
+            if needs_comma {
+                ty.push(',');
+            }
+            ty += "address verifyingContract";
+            tokens.push(Token::Address(verifying_contract));
+            needs_comma = true;
+        }
 
         // Add the salt to the struct to be hashed if it exists;
         if let Some(salt) = &self.salt {
+            if needs_comma {
+                ty.push(',');
+            }
+            ty += "bytes32 salt";
             tokens.push(Token::Uint(U256::from(salt)));
         }
 
+        ty.push(')');
+
+        tokens.insert(0, Token::Uint(U256::from(keccak256(ty))));
+
         keccak256(abi::encode(&tokens))
     }
 }
 
+/// (De)serializes the domain salt as a hex string
+mod salt {
+    use super::*;
+
+    pub(super) fn serialize<S>(salt: &Option<[u8; 32]>, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        salt.map(H256::from).serialize(s)
+    }
+
+    pub(super) fn deserialize<'de, D>(d: D) -> Result<Option<[u8; 32]>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Option::<H256>::deserialize(d)?.map(|salt| salt.0))
+    }
+}
+
+/// Deserializes a number that's either a JSON number, a decimal string or a hex string, as they
+/// are all used by dapps for the `chainId` of the domain and `uint` values
+fn deserialize_opt_numeric<'de, D>(d: D) -> Result<Option<U256>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<Value>::deserialize(d)?
+        .map(|value| parse_uint(&value).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 #[derive(Debug, Clone)]
 pub struct EIP712WithDomain<T>
 where
@@ -191,6 +269,292 @@ impl<T: Eip712 + Clone> Eip712 for EIP712WithDomain<T> {
     }
 }
 
+/// The types of a [`TypedData`] object, a mapping of struct names to their fields
+pub type Types = BTreeMap<String, Vec<Eip712DomainType>>;
+
+/// A single field of a struct in the `types` of a [`TypedData`] object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip712DomainType {
+    /// The name of the field
+    pub name: String,
+    /// The solidity type of the field, either an atomic type like `uint256`, another struct of
+    /// the `types` or an array of either
+    #[serde(rename = "type")]
+    pub r#type: String,
+}
+
+/// Represents the [EIP-712](https://eips.ethereum.org/EIPS/eip-712) typed data object that is
+/// signed with `eth_signTypedData_v4`.
+///
+/// Unlike the `Eip712` derive, which requires a rust struct at compile time, this can represent
+/// arbitrary structs, including nested structs and arrays, so it can be used to sign typed data
+/// that is only known at runtime. The JSON object can also be deserialized from a string
+/// containing the JSON object, which is how it's sent to `eth_signTypedData_v4`.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::transaction::eip712::{Eip712, TypedData};
+///
+/// let json = r#"{
+///   "types": {
+///     "EIP712Domain": [
+///       { "name": "name", "type": "string" },
+///       { "name": "chainId", "type": "uint256" }
+///     ],
+///     "Person": [
+///       { "name": "name", "type": "string" },
+///       { "name": "wallets", "type": "address[]" }
+///     ]
+///   },
+///   "primaryType": "Person",
+///   "domain": { "name": "Ether Mail", "chainId": 1 },
+///   "message": {
+///     "name": "Cow",
+///     "wallets": ["0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"]
+///   }
+/// }"#;
+///
+/// let typed_data: TypedData = serde_json::from_str(json).unwrap();
+/// let digest = typed_data.encode_eip712().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    /// Signing domain metadata. The signing domain is the intended context for the signature
+    /// (e.g. the dapp, protocol, etc. that it's intended for). This data is used to construct
+    /// the domain seperator of the message.
+    pub domain: EIP712Domain,
+    /// The custom types used by this message.
+    pub types: Types,
+    /// The type of the message.
+    pub primary_type: String,
+    /// The message to be signed.
+    pub message: BTreeMap<String, Value>,
+}
+
+// deserializes either the JSON object or a string that contains the JSON object
+impl<'de> Deserialize<'de> for TypedData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct TypedDataHelper {
+            domain: EIP712Domain,
+            types: Types,
+            primary_type: String,
+            message: BTreeMap<String, Value>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Type {
+            Val(TypedDataHelper),
+            String(String),
+        }
+
+        let TypedDataHelper { domain, types, primary_type, message } =
+            match Type::deserialize(deserializer)? {
+                Type::Val(v) => v,
+                Type::String(s) => serde_json::from_str(&s).map_err(serde::de::Error::custom)?,
+            };
+        Ok(TypedData { domain, types, primary_type, message })
+    }
+}
+
+impl TypedData {
+    /// Returns the `encodeType` of the primary type, which is the primary type followed by all
+    /// struct types it references in alphabetical order, e.g.
+    /// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`
+    pub fn encode_type(&self) -> Result<String, Eip712Error> {
+        encode_type(&self.primary_type, &self.types)
+    }
+}
+
+impl Eip712 for TypedData {
+    type Error = Eip712Error;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.clone())
+    }
+
+    /// The types of a `TypedData` object are only known at runtime, use
+    /// [`TypedData::encode_type`] instead
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Err(Eip712Error::Message("dynamic type".to_string()))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        let message = Value::Object(self.message.clone().into_iter().collect());
+        hash_struct(&self.primary_type, &message, &self.types)
+    }
+}
+
+/// Returns the `encodeType` of the given struct type
+fn encode_type(primary_type: &str, types: &Types) -> Result<String, Eip712Error> {
+    let mut deps = BTreeSet::new();
+    find_type_dependencies(primary_type, types, &mut deps)?;
+    deps.remove(primary_type);
+
+    let mut encoded = String::new();
+    for ty in std::iter::once(primary_type).chain(deps.iter().map(String::as_str)) {
+        let fields = types[ty].iter().map(|field| format!("{} {}", field.r#type, field.name));
+        encoded += &format!("{}({})", ty, fields.collect::<Vec<_>>().join(","));
+    }
+    Ok(encoded)
+}
+
+/// Collects all struct types that the given struct type references, including itself
+fn find_type_dependencies(
+    ty: &str,
+    types: &Types,
+    deps: &mut BTreeSet<String>,
+) -> Result<(), Eip712Error> {
+    let fields = types
+        .get(ty)
+        .ok_or_else(|| Eip712Error::Message(format!("missing struct type `{}`", ty)))?;
+    if !deps.insert(ty.to_string()) {
+        return Ok(())
+    }
+    for field in fields {
+        let field_ty = strip_array_suffixes(&field.r#type);
+        if types.contains_key(field_ty) {
+            find_type_dependencies(field_ty, types, deps)?;
+        }
+    }
+    Ok(())
+}
+
+/// Strips all array suffixes from a type, `Person[][2]` becomes `Person`
+fn strip_array_suffixes(ty: &str) -> &str {
+    ty.find('[').map(|idx| &ty[..idx]).unwrap_or(ty)
+}
+
+/// `hashStruct(s) = keccak256(typeHash ‖ encodeData(s))`
+fn hash_struct(ty: &str, value: &Value, types: &Types) -> Result<[u8; 32], Eip712Error> {
+    let fields = value.as_object().ok_or_else(|| {
+        Eip712Error::Message(format!("expected an object for struct `{}`, got {}", ty, value))
+    })?;
+
+    let mut tokens = vec![Token::Uint(U256::from(keccak256(encode_type(ty, types)?)))];
+    for field in &types[ty] {
+        let value = fields.get(&field.name).unwrap_or(&Value::Null);
+        tokens.push(encode_field(&field.r#type, value, types)?);
+    }
+    Ok(keccak256(abi::encode(&tokens)))
+}
+
+/// Encodes a single value of the given type as a 32 byte word
+fn encode_field(ty: &str, value: &Value, types: &Types) -> Result<Token, Eip712Error> {
+    // arrays are encoded as the hash of their concatenated encoded elements
+    if let Some(inner) = ty.strip_suffix(']') {
+        let (elem_ty, len) = inner
+            .rsplit_once('[')
+            .ok_or_else(|| Eip712Error::Message(format!("invalid array type `{}`", ty)))?;
+        let values = value.as_array().ok_or_else(|| {
+            Eip712Error::Message(format!("expected an array for type `{}`, got {}", ty, value))
+        })?;
+        if !len.is_empty() && len.parse::<usize>().ok() != Some(values.len()) {
+            return Err(Eip712Error::Message(format!(
+                "expected {} elements for type `{}`, got {}",
+                len,
+                ty,
+                values.len()
+            )))
+        }
+        let tokens = values
+            .iter()
+            .map(|value| encode_field(elem_ty, value, types))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Token::Uint(U256::from(keccak256(abi::encode(&tokens)))))
+    }
+
+    if types.contains_key(ty) {
+        return Ok(Token::Uint(U256::from(hash_struct(ty, value, types)?)))
+    }
+
+    let invalid = || Eip712Error::Message(format!("invalid value for type `{}`: {}", ty, value));
+    let token = match ty {
+        "string" => Token::Uint(U256::from(keccak256(value.as_str().ok_or_else(invalid)?))),
+        "bytes" => {
+            let bytes: Bytes =
+                value.as_str().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+            Token::Uint(U256::from(keccak256(bytes)))
+        }
+        "bool" => Token::Bool(match value {
+            Value::Bool(b) => *b,
+            Value::String(s) => s.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }),
+        "address" => {
+            Token::Address(value.as_str().ok_or_else(invalid)?.parse().map_err(|_| invalid())?)
+        }
+        _ => {
+            if let Some(size) = ty.strip_prefix("bytes") {
+                let size: usize = size.parse().map_err(|_| invalid())?;
+                let bytes: Bytes =
+                    value.as_str().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+                if size == 0 || size > 32 || bytes.len() > size {
+                    return Err(invalid())
+                }
+                Token::FixedBytes(bytes.to_vec())
+            } else if ty.starts_with("uint") {
+                Token::Uint(parse_uint(value).map_err(|_| invalid())?)
+            } else if ty.starts_with("int") {
+                Token::Int(parse_int(value).map_err(|_| invalid())?.into_raw())
+            } else {
+                return Err(Eip712Error::Message(format!("unknown type `{}`", ty)))
+            }
+        }
+    };
+    Ok(token)
+}
+
+/// Parses an unsigned integer from a JSON number, a decimal string or a hex string
+fn parse_uint(value: &Value) -> Result<U256, String> {
+    let parsed = match value {
+        // numbers above `u64::MAX` are floats unless serde_json's `arbitrary_precision` is enabled
+        Value::Number(num) => U256::from_dec_str(&num.to_string()).ok().or_else(|| {
+            num.as_f64()
+                .filter(|num| num.is_finite() && *num >= 0.0 && num.fract() == 0.0)
+                .and_then(|num| U256::from_dec_str(&format!("{:.0}", num)).ok())
+        }),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok(),
+            None => U256::from_dec_str(s).ok(),
+        },
+        _ => None,
+    };
+    parsed.ok_or_else(|| format!("invalid unsigned integer {}", value))
+}
+
+/// Parses a signed integer from a JSON number, a decimal string or a hex string
+fn parse_int(value: &Value) -> Result<I256, String> {
+    let parsed = match value {
+        // numbers outside the `i64` range are floats unless serde_json's `arbitrary_precision` is
+        // enabled
+        Value::Number(num) => I256::from_dec_str(&num.to_string()).ok().or_else(|| {
+            num.as_f64()
+                .filter(|num| num.is_finite() && num.fract() == 0.0)
+                .and_then(|num| I256::from_dec_str(&format!("{:.0}", num)).ok())
+        }),
+        Value::String(s) => {
+            let (negative, abs) = match s.strip_prefix('-') {
+                Some(abs) => (true, abs),
+                None => (false, s.as_str()),
+            };
+            let abs = match abs.strip_prefix("0x") {
+                Some(hex) => I256::from_hex_str(hex).ok(),
+                None => I256::from_dec_str(abs).ok(),
+            };
+            abs.map(|abs| if negative { -abs } else { abs })
+        }
+        _ => None,
+    };
+    parsed.ok_or_else(|| format!("invalid integer {}", value))
+}
 // Parse the AST of the struct to determine the domain attributes
 impl TryFrom<&syn::DeriveInput> for EIP712Domain {
     type Error = TokenStream;
@@ -219,7 +583,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                         match ident.to_string().as_ref() {
                                             "name" => match meta.lit {
                                                 syn::Lit::Str(ref lit_str) => {
-                                                    if domain.name != String::default() {
+                                                    if domain.name.is_some() {
                                                         return Err(Error::new(
                                                             meta.path.span(),
                                                             "domain name already specified",
@@ -227,7 +591,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                                         .to_compile_error())
                                                     }
 
-                                                    domain.name = lit_str.value();
+                                                    domain.name = Some(lit_str.value());
                                                 }
                                                 _ => {
                                                     return Err(Error::new(
@@ -239,7 +603,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                             },
                                             "version" => match meta.lit {
                                                 syn::Lit::Str(ref lit_str) => {
-                                                    if domain.version != String::default() {
+                                                    if domain.version.is_some() {
                                                         return Err(Error::new(
                                                             meta.path.span(),
                                                             "domain version already specified",
@@ -247,7 +611,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                                         .to_compile_error())
                                                     }
 
-                                                    domain.version = lit_str.value();
+                                                    domain.version = Some(lit_str.value());
                                                 }
                                                 _ => {
                                                     return Err(Error::new(
@@ -259,7 +623,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                             },
                                             "chain_id" => match meta.lit {
                                                 syn::Lit::Int(ref lit_int) => {
-                                                    if domain.chain_id != U256::default() {
+                                                    if domain.chain_id.is_some() {
                                                         return Err(Error::new(
                                                             meta.path.span(),
                                                             "domain chain_id already specified",
@@ -267,7 +631,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                                         .to_compile_error())
                                                     }
 
-                                                    domain.chain_id = U256::from(
+                                                    domain.chain_id = Some(U256::from(
                                                         lit_int.base10_parse::<u64>().map_err(
                                                             |_| {
                                                                 Error::new(
@@ -277,7 +641,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                                                 .to_compile_error()
                                                             },
                                                         )?,
-                                                    );
+                                                    ));
                                                 }
                                                 _ => {
                                                     return Err(Error::new(
@@ -289,8 +653,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                             },
                                             "verifying_contract" => match meta.lit {
                                                 syn::Lit::Str(ref lit_str) => {
-                                                    if domain.verifying_contract != H160::default()
-                                                    {
+                                                    if domain.verifying_contract.is_some() {
                                                         return Err(Error::new(
                                                             meta.path.span(),
                                                             "domain verifying_contract already specified",
@@ -298,13 +661,13 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                                         .to_compile_error());
                                                     }
 
-                                                    domain.verifying_contract = lit_str.value().parse().map_err(|_| {
+                                                    domain.verifying_contract = Some(lit_str.value().parse().map_err(|_| {
                                                             Error::new(
                                                                 meta.path.span(),
                                                                 "failed to parse verifying contract into Address",
                                                             )
                                                             .to_compile_error()
-                                                        })?;
+                                                        })?);
                                                 }
                                                 _ => {
                                                     return Err(Error::new(
@@ -316,7 +679,7 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                                             },
                                             "salt" => match meta.lit {
                                                 syn::Lit::Str(ref lit_str) => {
-                                                    if domain.salt != Option::None {
+                                                    if domain.salt.is_some() {
                                                         return Err(Error::new(
                                                             meta.path.span(),
                                                             "domain salt already specified",
@@ -365,28 +728,28 @@ impl TryFrom<&syn::DeriveInput> for EIP712Domain {
                             }
                         }
 
-                        if domain.name == String::default() {
+                        if domain.name.is_none() {
                             return Err(Error::new(
                                 meta.path.span(),
                                 "missing required domain attribute: 'name'".to_string(),
                             )
                             .to_compile_error())
                         }
-                        if domain.version == String::default() {
+                        if domain.version.is_none() {
                             return Err(Error::new(
                                 meta.path.span(),
                                 "missing required domain attribute: 'version'".to_string(),
                             )
                             .to_compile_error())
                         }
-                        if domain.chain_id == U256::default() {
+                        if domain.chain_id.is_none() {
                             return Err(Error::new(
                                 meta.path.span(),
                                 "missing required domain attribute: 'chain_id'".to_string(),
                             )
                             .to_compile_error())
                         }
-                        if domain.verifying_contract == H160::default() {
+                        if domain.verifying_contract.is_none() {
                             return Err(Error::new(
                                 meta.path.span(),
                                 "missing required domain attribute: 'verifying_contract'"
@@ -580,3 +943,187 @@ pub fn encode_eip712_type(token: Token) -> Token {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_typed_data() {
+        // https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js
+        let json = serde_json::json!({
+          "types": {
+            "EIP712Domain": [
+              { "name": "name", "type": "string" },
+              { "name": "version", "type": "string" },
+              { "name": "chainId", "type": "uint256" },
+              { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+              { "name": "name", "type": "string" },
+              { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+              { "name": "from", "type": "Person" },
+              { "name": "to", "type": "Person" },
+              { "name": "contents", "type": "string" }
+            ]
+          },
+          "primaryType": "Mail",
+          "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+          },
+          "message": {
+            "from": {
+              "name": "Cow",
+              "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+            },
+            "to": {
+              "name": "Bob",
+              "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+            },
+            "contents": "Hello, Bob!"
+          }
+        });
+
+        let typed_data: TypedData = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            typed_data.encode_type().unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed_data.struct_hash().unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(typed_data.encode_eip712().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn test_hash_typed_data_with_arrays() {
+        // https://github.com/MetaMask/eth-sig-util/blob/main/src/sign-typed-data.test.ts
+        let json = r#"{
+          "types": {
+            "EIP712Domain": [
+              { "name": "name", "type": "string" },
+              { "name": "version", "type": "string" },
+              { "name": "chainId", "type": "uint256" },
+              { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+              { "name": "name", "type": "string" },
+              { "name": "wallets", "type": "address[]" }
+            ],
+            "Mail": [
+              { "name": "from", "type": "Person" },
+              { "name": "to", "type": "Person[]" },
+              { "name": "contents", "type": "string" }
+            ],
+            "Group": [
+              { "name": "name", "type": "string" },
+              { "name": "members", "type": "Person[]" }
+            ]
+          },
+          "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": "0x1",
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+          },
+          "primaryType": "Mail",
+          "message": {
+            "from": {
+              "name": "Cow",
+              "wallets": [
+                "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"
+              ]
+            },
+            "to": [
+              {
+                "name": "Bob",
+                "wallets": [
+                  "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                  "0xB0BdaBea57B0BDABeA57b0bdABEA57b0BDabEa57",
+                  "0xB0B0b0b0b0b0B000000000000000000000000000"
+                ]
+              }
+            ],
+            "contents": "Hello, Bob!"
+          }
+        }"#;
+
+        // `eth_signTypedData_v4` sends the typed data as a JSON string
+        let typed_data: TypedData = serde_json::from_value(json.into()).unwrap();
+        assert_eq!(typed_data, serde_json::from_str(json).unwrap());
+        assert_eq!(
+            typed_data.encode_type().unwrap(),
+            "Mail(Person from,Person[] to,string contents)Person(string name,address[] wallets)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.encode_eip712().unwrap()),
+            "a85c2e2b118698e88db68a8105b794a8cc7cec074e89ef991cb4f5f533819cc2"
+        );
+    }
+
+    #[test]
+    fn test_domain_separator_with_partial_domain() {
+        let domain: EIP712Domain =
+            serde_json::from_str(r#"{"name":"Permit2","chainId":"1","verifyingContract":"0x000000000022d473030f116ddee9f6b43ac78ba3"}"#)
+                .unwrap();
+        assert_eq!(domain.version, None);
+
+        let type_hash =
+            keccak256("EIP712Domain(string name,uint256 chainId,address verifyingContract)");
+        let expected = keccak256(abi::encode(&[
+            Token::Uint(U256::from(type_hash)),
+            Token::Uint(U256::from(keccak256("Permit2"))),
+            Token::Uint(1u64.into()),
+            Token::Address(domain.verifying_contract.unwrap()),
+        ]));
+        assert_eq!(domain.separator(), expected);
+    }
+
+    #[test]
+    fn test_parse_uint_numbers() {
+        assert_eq!(parse_uint(&serde_json::json!(1)).unwrap(), 1u64.into());
+        assert_eq!(parse_uint(&serde_json::json!(1.0)).unwrap(), 1u64.into());
+
+        // too large for a `u64`, so serde_json parses it as a float
+        let value: Value = serde_json::from_str("100000000000000000000").unwrap();
+        assert_eq!(parse_uint(&value).unwrap(), U256::exp10(20));
+        let value: Value = serde_json::from_str("1e20").unwrap();
+        assert_eq!(parse_uint(&value).unwrap(), U256::exp10(20));
+
+        assert!(parse_uint(&serde_json::json!(1.5)).is_err());
+        assert!(parse_uint(&serde_json::json!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_int_numbers() {
+        assert_eq!(parse_int(&serde_json::json!(-1)).unwrap(), I256::from(-1));
+        assert_eq!(parse_int(&serde_json::json!(-1.0)).unwrap(), I256::from(-1));
+        assert_eq!(parse_int(&serde_json::json!(u64::MAX)).unwrap(), I256::from(u64::MAX));
+
+        // too large for an `i64`, so serde_json parses it as a float
+        let value: Value = serde_json::from_str("-100000000000000000000").unwrap();
+        assert_eq!(parse_int(&value).unwrap(), -I256::exp10(20));
+        let value: Value = serde_json::from_str("1e20").unwrap();
+        assert_eq!(parse_int(&value).unwrap(), I256::exp10(20));
+        let value: Value = serde_json::from_str("-1e20").unwrap();
+        assert_eq!(parse_int(&value).unwrap(), -I256::exp10(20));
+
+        assert!(parse_int(&serde_json::json!(-1.5)).is_err());
+        let value: Value = serde_json::from_str("1e80").unwrap();
+        assert!(parse_int(&value).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+//! Types for [EIP-4337](https://eips.ethereum.org/EIPS/eip-4337) account abstraction
+use crate::{
+    abi::{encode, Token},
+    types::{fee::from_int_or_hex, Address, Bytes, Log, TransactionReceipt, H160, H256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// The address of the v0.6 EntryPoint contract, which is deployed at the same address on every
+/// chain
+pub const ENTRY_POINT_V0_6: Address = H160([
+    0x5f, 0xf1, 0x37, 0xd4, 0xb0, 0xfd, 0xcd, 0x49, 0xdc, 0xa3, 0x0c, 0x7c, 0xf5, 0x7e, 0x57, 0x8a,
+    0x02, 0x6d, 0x27, 0x89,
+]);
+
+/// The address of the v0.7 EntryPoint contract, which is deployed at the same address on every
+/// chain
+pub const ENTRY_POINT_V0_7: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x71, 0x72, 0x7d, 0xe2, 0x2e, 0x5e, 0x9d, 0x8b, 0xaf, 0x0e, 0xda, 0xc6,
+    0xf3, 0x7d, 0xa0, 0x32,
+]);
+
+/// The version of the EntryPoint contract, which determines how user operations are hashed and
+/// how they are sent to the bundler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntryPointVersion {
+    /// EntryPoint v0.6
+    V0_6,
+    /// EntryPoint v0.7, which packs the gas limits and fees of the user operation
+    V0_7,
+}
+
+/// An EntryPoint contract to which user operations are submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntryPoint {
+    /// The address of the contract
+    pub address: Address,
+    /// The version of the contract
+    pub version: EntryPointVersion,
+}
+
+impl EntryPoint {
+    /// An EntryPoint of the `version` deployed at `address`
+    pub fn new(address: Address, version: EntryPointVersion) -> Self {
+        Self { address, version }
+    }
+
+    /// The canonical v0.6 EntryPoint
+    pub fn v0_6() -> Self {
+        Self::new(ENTRY_POINT_V0_6, EntryPointVersion::V0_6)
+    }
+
+    /// The canonical v0.7 EntryPoint
+    pub fn v0_7() -> Self {
+        Self::new(ENTRY_POINT_V0_7, EntryPointVersion::V0_7)
+    }
+}
+
+impl Default for EntryPoint {
+    fn default() -> Self {
+        Self::v0_6()
+    }
+}
+
+/// Error thrown when a user operation can not be sent to a v0.7 EntryPoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum UserOperationError {
+    /// The init code is neither empty nor starts with the factory address
+    #[error("the init code must be empty or at least 20 bytes long, got {0} bytes")]
+    InvalidInitCode(usize),
+    /// The paymaster and data is neither empty nor starts with the paymaster address and its gas
+    /// limits
+    #[error("the paymaster and data must be empty or at least 52 bytes long, got {0} bytes")]
+    InvalidPaymasterAndData(usize),
+}
+
+/// A user operation, the transaction of a smart contract account that is sent to a bundler.
+///
+/// The fields follow the v0.6 layout. For v0.7 the `init_code` is the factory address followed by
+/// the factory data and the `paymaster_and_data` is the paymaster address followed by its
+/// verification gas limit and post-op gas limit (16 bytes each) and the paymaster data, like in
+/// the packed user operation of the EntryPoint contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    /// The account making the operation
+    pub sender: Address,
+    /// Anti-replay nonce, the key in the upper 192 bits and the sequence in the lower 64 bits
+    pub nonce: U256,
+    /// The factory address and the calldata to deploy the account, empty if it is deployed
+    pub init_code: Bytes,
+    /// The calldata of the call to the account
+    pub call_data: Bytes,
+    /// The gas limit of the call to the account
+    pub call_gas_limit: U256,
+    /// The gas limit of the verification of the operation
+    pub verification_gas_limit: U256,
+    /// The gas paid to the bundler for the overhead of the operation
+    pub pre_verification_gas: U256,
+    /// Maximum fee per gas, as in EIP-1559
+    pub max_fee_per_gas: U256,
+    /// Maximum priority fee per gas, as in EIP-1559
+    pub max_priority_fee_per_gas: U256,
+    /// The paymaster address and its data, empty if the account pays for itself
+    pub paymaster_and_data: Bytes,
+    /// The signature of the operation, verified by the account
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Creates an empty operation of the `sender` account
+    pub fn new<T: Into<Address>>(sender: T) -> Self {
+        Self { sender: sender.into(), ..Default::default() }
+    }
+
+    /// Sets the `nonce` field in the operation
+    #[must_use]
+    pub fn nonce<T: Into<U256>>(mut self, nonce: T) -> Self {
+        self.nonce = nonce.into();
+        self
+    }
+
+    /// Sets the `init_code` field in the operation
+    #[must_use]
+    pub fn init_code<T: Into<Bytes>>(mut self, init_code: T) -> Self {
+        self.init_code = init_code.into();
+        self
+    }
+
+    /// Sets the `call_data` field in the operation
+    #[must_use]
+    pub fn call_data<T: Into<Bytes>>(mut self, call_data: T) -> Self {
+        self.call_data = call_data.into();
+        self
+    }
+
+    /// Sets the `call_gas_limit` field in the operation
+    #[must_use]
+    pub fn call_gas_limit<T: Into<U256>>(mut self, gas: T) -> Self {
+        self.call_gas_limit = gas.into();
+        self
+    }
+
+    /// Sets the `verification_gas_limit` field in the operation
+    #[must_use]
+    pub fn verification_gas_limit<T: Into<U256>>(mut self, gas: T) -> Self {
+        self.verification_gas_limit = gas.into();
+        self
+    }
+
+    /// Sets the `pre_verification_gas` field in the operation
+    #[must_use]
+    pub fn pre_verification_gas<T: Into<U256>>(mut self, gas: T) -> Self {
+        self.pre_verification_gas = gas.into();
+        self
+    }
+
+    /// Sets the `max_fee_per_gas` field in the operation
+    #[must_use]
+    pub fn max_fee_per_gas<T: Into<U256>>(mut self, max_fee_per_gas: T) -> Self {
+        self.max_fee_per_gas = max_fee_per_gas.into();
+        self
+    }
+
+    /// Sets the `max_priority_fee_per_gas` field in the operation
+    #[must_use]
+    pub fn max_priority_fee_per_gas<T: Into<U256>>(mut self, max_priority_fee_per_gas: T) -> Self {
+        self.max_priority_fee_per_gas = max_priority_fee_per_gas.into();
+        self
+    }
+
+    /// Sets the `paymaster_and_data` field in the operation
+    #[must_use]
+    pub fn paymaster_and_data<T: Into<Bytes>>(mut self, paymaster_and_data: T) -> Self {
+        self.paymaster_and_data = paymaster_and_data.into();
+        self
+    }
+
+    /// Sets the `signature` field in the operation
+    #[must_use]
+    pub fn signature<T: Into<Bytes>>(mut self, signature: T) -> Self {
+        self.signature = signature.into();
+        self
+    }
+
+    /// Sets the gas limits of the operation to the estimate of the bundler
+    #[must_use]
+    pub fn gas(mut self, estimate: &UserOperationGasEstimate) -> Self {
+        self.call_gas_limit = estimate.call_gas_limit;
+        self.verification_gas_limit = estimate.verification_gas_limit;
+        self.pre_verification_gas = estimate.pre_verification_gas;
+        self
+    }
+
+    /// Hashes the operation like `EntryPoint.getUserOpHash`, which is the hash the account
+    /// verifies the signature against.
+    ///
+    /// The hash covers every field but the signature, as well as the EntryPoint and the chain id.
+    pub fn hash(&self, entry_point: &EntryPoint, chain_id: u64) -> H256 {
+        let packed = keccak256(encode(&self.packed_tokens(entry_point.version)));
+        keccak256(encode(&[
+            Token::FixedBytes(packed.to_vec()),
+            Token::Address(entry_point.address),
+            Token::Uint(chain_id.into()),
+        ]))
+        .into()
+    }
+
+    /// The ABI tokens of the operation without its signature, with the dynamic fields hashed
+    fn packed_tokens(&self, version: EntryPointVersion) -> Vec<Token> {
+        let hashed = |bytes: &Bytes| Token::FixedBytes(keccak256(bytes).to_vec());
+        match version {
+            EntryPointVersion::V0_6 => vec![
+                Token::Address(self.sender),
+                Token::Uint(self.nonce),
+                hashed(&self.init_code),
+                hashed(&self.call_data),
+                Token::Uint(self.call_gas_limit),
+                Token::Uint(self.verification_gas_limit),
+                Token::Uint(self.pre_verification_gas),
+                Token::Uint(self.max_fee_per_gas),
+                Token::Uint(self.max_priority_fee_per_gas),
+                hashed(&self.paymaster_and_data),
+            ],
+            EntryPointVersion::V0_7 => vec![
+                Token::Address(self.sender),
+                Token::Uint(self.nonce),
+                hashed(&self.init_code),
+                hashed(&self.call_data),
+                pack_u128s(self.verification_gas_limit, self.call_gas_limit),
+                Token::Uint(self.pre_verification_gas),
+                pack_u128s(self.max_priority_fee_per_gas, self.max_fee_per_gas),
+                hashed(&self.paymaster_and_data),
+            ],
+        }
+    }
+
+    /// Returns the operation in the format the bundler expects for the `version` of the
+    /// EntryPoint.
+    ///
+    /// v0.6 operations are sent as-is, v0.7 operations are sent with the factory and the paymaster
+    /// fields unpacked. Fails for v0.7 if the `init_code` or the `paymaster_and_data` are too
+    /// short to be unpacked.
+    pub fn to_rpc(&self, version: EntryPointVersion) -> Result<Value, UserOperationError> {
+        Ok(match version {
+            EntryPointVersion::V0_6 => {
+                serde_json::to_value(self).expect("user operations are serializable")
+            }
+            EntryPointVersion::V0_7 => {
+                let mut op = json!({
+                    "sender": self.sender,
+                    "nonce": self.nonce,
+                    "callData": self.call_data,
+                    "callGasLimit": self.call_gas_limit,
+                    "verificationGasLimit": self.verification_gas_limit,
+                    "preVerificationGas": self.pre_verification_gas,
+                    "maxFeePerGas": self.max_fee_per_gas,
+                    "maxPriorityFeePerGas": self.max_priority_fee_per_gas,
+                    "signature": self.signature,
+                });
+                match self.init_code.len() {
+                    0 => {}
+                    len if len < 20 => return Err(UserOperationError::InvalidInitCode(len)),
+                    _ => {
+                        let (factory, data) = self.init_code.split_at(20);
+                        op["factory"] = json!(Address::from_slice(factory));
+                        op["factoryData"] = json!(Bytes::from(data.to_vec()));
+                    }
+                }
+                let len = self.paymaster_and_data.len();
+                if len > 0 && len < 52 {
+                    return Err(UserOperationError::InvalidPaymasterAndData(len))
+                }
+                if len > 0 {
+                    let (paymaster, rest) = self.paymaster_and_data.split_at(20);
+                    let (gas, data) = rest.split_at(32);
+                    op["paymaster"] = json!(Address::from_slice(paymaster));
+                    op["paymasterVerificationGasLimit"] = json!(U256::from_big_endian(&gas[..16]));
+                    op["paymasterPostOpGasLimit"] = json!(U256::from_big_endian(&gas[16..]));
+                    op["paymasterData"] = json!(Bytes::from(data.to_vec()));
+                }
+                op
+            }
+        })
+    }
+}
+
+/// Packs two 128 bit values in a `bytes32`, `high` in the upper half
+fn pack_u128s(high: U256, low: U256) -> Token {
+    let mut word = [0u8; 32];
+    (high << 128 | low & U256::from(u128::MAX)).to_big_endian(&mut word);
+    Token::FixedBytes(word.to_vec())
+}
+
+/// The gas limits of a user operation as estimated by `eth_estimateUserOperationGas`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasEstimate {
+    /// The gas paid to the bundler for the overhead of the operation
+    #[serde(deserialize_with = "from_int_or_hex")]
+    pub pre_verification_gas: U256,
+    /// The gas limit of the verification of the operation
+    #[serde(alias = "verificationGas", deserialize_with = "from_int_or_hex")]
+    pub verification_gas_limit: U256,
+    /// The gas limit of the call to the account
+    #[serde(deserialize_with = "from_int_or_hex")]
+    pub call_gas_limit: U256,
+}
+
+/// The receipt of a user operation as returned by `eth_getUserOperationReceipt`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    /// The hash of the operation
+    pub user_op_hash: H256,
+    /// The EntryPoint that executed the operation
+    pub entry_point: Address,
+    /// The account that made the operation
+    pub sender: Address,
+    /// The nonce of the operation
+    pub nonce: U256,
+    /// The paymaster that paid for the operation, the zero address if the account paid for itself
+    #[serde(default)]
+    pub paymaster: Address,
+    /// The gas cost of the operation paid by the account or the paymaster
+    pub actual_gas_cost: U256,
+    /// The gas used by the operation
+    pub actual_gas_used: U256,
+    /// Whether the call to the account succeeded
+    pub success: bool,
+    /// The revert reason if the call to the account failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The logs emitted by the operation
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    /// The receipt of the transaction of the bundle that included the operation
+    pub receipt: TransactionReceipt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op() -> UserOperation {
+        UserOperation::new(Address::repeat_byte(0x11))
+            .nonce(1u64)
+            .call_data(vec![0xb6, 0x1d, 0x27, 0xf6])
+            .call_gas_limit(100_000u64)
+            .verification_gas_limit(200_000u64)
+            .pre_verification_gas(50_000u64)
+            .max_fee_per_gas(30_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+    }
+
+    #[test]
+    fn entry_point_addresses() {
+        assert_eq!(
+            ENTRY_POINT_V0_6,
+            "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            ENTRY_POINT_V0_7,
+            "0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn hash_covers_everything_but_the_signature() {
+        let op = op();
+        let entry_point = EntryPoint::v0_6();
+        let hash = op.hash(&entry_point, 1);
+
+        assert_eq!(op.clone().signature(vec![1, 2, 3]).hash(&entry_point, 1), hash);
+        assert_ne!(op.clone().nonce(2u64).hash(&entry_point, 1), hash);
+        assert_ne!(op.hash(&entry_point, 5), hash);
+        assert_ne!(op.hash(&EntryPoint::v0_7(), 1), hash);
+        assert_ne!(op.hash(&EntryPoint::new(ENTRY_POINT_V0_7, EntryPointVersion::V0_6), 1), hash);
+    }
+
+    #[test]
+    fn hash_known_answers() {
+        // as returned by `getUserOpHash` of a v0.6 EntryPoint deployed at this address on a
+        // devnet with chain id 1337
+        let entry_point = EntryPoint::new(
+            "0x66a15edcc3b50a663e72f1457ffd49b9ae284ddc".parse().unwrap(),
+            EntryPointVersion::V0_6,
+        );
+        assert_eq!(
+            UserOperation::default().hash(&entry_point, 1337),
+            "0xdca97c3b49558ab360659f6ead939773be8bf26631e61bb17045bb70dc983b2d".parse().unwrap()
+        );
+
+        // computed independently from the `UserOperationLib` encoding of each EntryPoint version,
+        // with every field set
+        let mut paymaster_and_data = Address::repeat_byte(0x33).as_bytes().to_vec();
+        paymaster_and_data.extend_from_slice(&60_000u128.to_be_bytes());
+        paymaster_and_data.extend_from_slice(&10_000u128.to_be_bytes());
+        paymaster_and_data.extend_from_slice(&[0xab, 0xcd]);
+        let op = op()
+            .init_code([Address::repeat_byte(0x22).as_bytes(), &[0x5f, 0xbf, 0xb9, 0xcf]].concat())
+            .paymaster_and_data(paymaster_and_data);
+        assert_eq!(
+            op.hash(&EntryPoint::v0_6(), 1),
+            "0xd2a91a794ef4a47af11f42cd84e363057d131aea11f9b989be6968758990a140".parse().unwrap()
+        );
+        assert_eq!(
+            op.hash(&EntryPoint::v0_7(), 1),
+            "0x3d8313eda2c2353acfa3b935fadeea4044c32fab572f099d6c13484f92877835".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn v0_7_packs_gas_fields() {
+        let op = op();
+        let tokens = op.packed_tokens(EntryPointVersion::V0_7);
+        assert_eq!(tokens.len(), 8);
+
+        let mut account_gas_limits = [0u8; 32];
+        account_gas_limits[13..16].copy_from_slice(&[0x03, 0x0d, 0x40]);
+        account_gas_limits[29..32].copy_from_slice(&[0x01, 0x86, 0xa0]);
+        assert_eq!(tokens[4], Token::FixedBytes(account_gas_limits.to_vec()));
+    }
+
+    #[test]
+    fn v0_7_unpacks_rpc_fields() {
+        let factory = Address::repeat_byte(0x22);
+        let paymaster = Address::repeat_byte(0x33);
+        let mut paymaster_and_data = paymaster.as_bytes().to_vec();
+        paymaster_and_data.extend_from_slice(&[0u8; 15]);
+        paymaster_and_data.push(7);
+        paymaster_and_data.extend_from_slice(&[0u8; 15]);
+        paymaster_and_data.push(9);
+        paymaster_and_data.extend_from_slice(&[0xab]);
+
+        let op = op()
+            .init_code([factory.as_bytes(), &[0xcd]].concat())
+            .paymaster_and_data(paymaster_and_data);
+
+        let v6 = op.to_rpc(EntryPointVersion::V0_6).unwrap();
+        assert_eq!(serde_json::from_value::<UserOperation>(v6).unwrap(), op);
+
+        let v7 = op.to_rpc(EntryPointVersion::V0_7).unwrap();
+        assert!(v7.get("initCode").is_none());
+        assert_eq!(v7["factory"], json!(factory));
+        assert_eq!(v7["factoryData"], "0xcd");
+        assert_eq!(v7["paymaster"], json!(paymaster));
+        assert_eq!(v7["paymasterVerificationGasLimit"], "0x7");
+        assert_eq!(v7["paymasterPostOpGasLimit"], "0x9");
+        assert_eq!(v7["paymasterData"], "0xab");
+
+        let op = op.paymaster_and_data(paymaster.as_bytes().to_vec());
+        assert_eq!(
+            op.to_rpc(EntryPointVersion::V0_7).unwrap_err(),
+            UserOperationError::InvalidPaymasterAndData(20)
+        );
+        let op = op.paymaster_and_data(Bytes::default()).init_code(vec![0x22]);
+        assert_eq!(
+            op.to_rpc(EntryPointVersion::V0_7).unwrap_err(),
+            UserOperationError::InvalidInitCode(1)
+        );
+        assert!(op.to_rpc(EntryPointVersion::V0_6).is_ok());
+    }
+
+    #[test]
+    fn deserialize_gas_estimate() {
+        let estimate: UserOperationGasEstimate = serde_json::from_str(
+            r#"{"preVerificationGas":"0xc350","verificationGas":200000,"callGasLimit":"0x186a0"}"#,
+        )
+        .unwrap();
+        assert_eq!(estimate.pre_verification_gas, 50_000u64.into());
+        assert_eq!(estimate.verification_gas_limit, 200_000u64.into());
+        assert_eq!(estimate.call_gas_limit, 100_000u64.into());
+
+        let op = UserOperation::default().gas(&estimate);
+        assert_eq!(op.verification_gas_limit, 200_000u64.into());
+    }
+}
@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockNumber, H256, U64};
+use ethers_providers::{FromErr, Middleware};
+use ethers_signers::Signer;
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+mod pending;
+pub use pending::PendingBundle;
+
+mod relay;
+pub use relay::{Relay, RelayError, FLASHBOTS_SIGNATURE_HEADER};
+
+mod request;
+pub use request::{
+    BundleRequest, BundleStats, BundleTransaction, SimulatedBundle, SimulatedTransaction,
+};
+
+/// The URL of the Flashbots relay on mainnet
+pub const FLASHBOTS_RELAY_URL: &str = "https://relay.flashbots.net";
+
+#[derive(Debug)]
+/// Middleware used for submitting bundles of signed transactions privately to a relay, e.g. the
+/// Flashbots relay, instead of broadcasting them to the public mempool.
+///
+/// All other requests are sent to the inner middleware, which is also used to sign the
+/// transactions of bundles and to watch for the inclusion of submitted bundles.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::{
+///     bundle::{BundleMiddleware, BundleRequest, FLASHBOTS_RELAY_URL},
+///     SignerMiddleware,
+/// };
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let wallet: LocalWallet =
+///     "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse()?;
+/// let client = SignerMiddleware::new(provider, wallet);
+///
+/// // the relay signer only identifies the searcher
+/// let relay_signer: LocalWallet =
+///     "cd8c407233c0560f6de24bb2dc60a8b02335c959a1a17f749ce6c1ccf63d74a7".parse()?;
+/// let client = BundleMiddleware::new(client, Url::parse(FLASHBOTS_RELAY_URL)?, relay_signer);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100);
+/// let block = client.get_block_number().await?;
+/// let bundle = BundleRequest::new()
+///     .transaction(client.sign_bundle_transaction(tx).await?)
+///     .block(block + 1);
+///
+/// let simulated = client.simulate_bundle(&bundle).await?;
+/// assert!(simulated.results.iter().all(|tx| !tx.is_failure()));
+///
+/// let included = client.send_bundle(&bundle).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BundleMiddleware<M, S> {
+    inner: M,
+    relay: Relay<S>,
+}
+
+#[derive(Error, Debug)]
+/// Thrown when the bundle middleware fails
+pub enum BundleMiddlewareError<M: Middleware, S: Signer>
+where
+    M::Error: 'static,
+{
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
+
+    /// Thrown when the request to the relay fails
+    #[error(transparent)]
+    RelayError(#[from] RelayError<S>),
+
+    /// Thrown if the bundle has no target block
+    #[error("the bundle has no target block")]
+    MissingTargetBlock,
+
+    /// Thrown if the transaction has no sender and the middleware has no default sender
+    #[error("the transaction has no sender")]
+    MissingSender,
+
+    /// Thrown if the relay returned no result
+    #[error("the relay returned no result")]
+    EmptyResponse,
+}
+
+impl<M: Middleware, S: Signer> FromErr<M::Error> for BundleMiddlewareError<M, S> {
+    fn from(src: M::Error) -> Self {
+        BundleMiddlewareError::MiddlewareError(src)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendBundleResponse {
+    bundle_hash: H256,
+}
+
+impl<M, S> BundleMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    /// Instantiates the middleware, which submits bundles to the relay at `relay_url` and
+    /// authenticates with the `relay_signer`
+    pub fn new(inner: M, relay_url: impl Into<Url>, relay_signer: S) -> Self {
+        Self { inner, relay: Relay::new(relay_url, relay_signer) }
+    }
+
+    /// Returns the client of the relay
+    pub fn relay(&self) -> &Relay<S> {
+        &self.relay
+    }
+
+    /// Fills the missing fields of the transaction and signs it with the inner middleware, so it
+    /// can be added to a [`BundleRequest`].
+    ///
+    /// Like for any other transaction the nonce defaults to the pending transaction count of the
+    /// sender, so the nonces of multiple transactions of the same sender in one bundle must be
+    /// set explicitly.
+    pub async fn sign_bundle_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+    ) -> Result<BundleTransaction, BundleMiddlewareError<M, S>> {
+        let mut tx = tx.into();
+        self.inner
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(BundleMiddlewareError::MiddlewareError)?;
+        if tx.nonce().is_none() {
+            let from = *tx.from().ok_or(BundleMiddlewareError::MissingSender)?;
+            let nonce = self
+                .inner
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(BundleMiddlewareError::MiddlewareError)?;
+            tx.set_nonce(nonce);
+        }
+
+        let from = *tx.from().ok_or(BundleMiddlewareError::MissingSender)?;
+        let signature = self
+            .inner
+            .sign_transaction(&tx, from)
+            .await
+            .map_err(BundleMiddlewareError::MiddlewareError)?;
+        Ok((tx, signature).into())
+    }
+
+    /// Simulates the bundle on top of the state of its simulation block with `eth_callBundle`
+    pub async fn simulate_bundle(
+        &self,
+        bundle: &BundleRequest,
+    ) -> Result<SimulatedBundle, BundleMiddlewareError<M, S>> {
+        let block = bundle.target_block().ok_or(BundleMiddlewareError::MissingTargetBlock)?;
+        self.relay
+            .request("eth_callBundle", [bundle.call_params(block)])
+            .await?
+            .ok_or(BundleMiddlewareError::EmptyResponse)
+    }
+
+    /// Submits the bundle to the relay with `eth_sendBundle`. The returned [`PendingBundle`]
+    /// resolves once the target block is mined.
+    pub async fn send_bundle(
+        &self,
+        bundle: &BundleRequest,
+    ) -> Result<PendingBundle<'_, M::Provider>, BundleMiddlewareError<M, S>> {
+        let block = bundle.target_block().ok_or(BundleMiddlewareError::MissingTargetBlock)?;
+        let res: Option<SendBundleResponse> =
+            self.relay.request("eth_sendBundle", [bundle]).await?;
+        let bundle_hash = res.map(|res| res.bundle_hash);
+        tracing::debug!(?bundle_hash, ?block, "sent bundle");
+
+        Ok(PendingBundle::new(bundle_hash, block, bundle.transaction_hashes(), self.provider()))
+    }
+
+    /// Returns the status of a submitted bundle with `flashbots_getBundleStats`
+    pub async fn get_bundle_stats(
+        &self,
+        bundle_hash: H256,
+        block: U64,
+    ) -> Result<BundleStats, BundleMiddlewareError<M, S>> {
+        self.relay
+            .request(
+                "flashbots_getBundleStats",
+                [serde_json::json!({ "bundleHash": bundle_hash, "blockNumber": block })],
+            )
+            .await?
+            .ok_or(BundleMiddlewareError::EmptyResponse)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for BundleMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    type Error = BundleMiddlewareError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+}
@@ -0,0 +1,135 @@
+use ethers_core::types::{Block, H256, U64};
+use ethers_providers::{interval, JsonRpcClient, Middleware, Provider, ProviderError};
+use futures_util::{stream::Stream, StreamExt};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+type GetBlockFut<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<Block<H256>>, ProviderError>> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+type GetBlockFut<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<Block<H256>>, ProviderError>> + 'a>>;
+
+enum PendingBundleState<'a> {
+    /// Waiting for interval to elapse before calling API again
+    PausedGettingBlock,
+    /// Polling the target block
+    GettingBlock(GetBlockFut<'a>),
+    /// Future has completed and should panic if polled again
+    Completed,
+}
+
+/// A bundle that was submitted to the relay for a target block.
+///
+/// `await`'ing it polls the target block until it is mined and resolves to the hash of the
+/// target block if all transactions of the bundle were included in it, or `None` if the bundle
+/// was not included.
+pub struct PendingBundle<'a, P> {
+    bundle_hash: Option<H256>,
+    block: U64,
+    transactions: Vec<H256>,
+    provider: &'a Provider<P>,
+    state: PendingBundleState<'a>,
+    interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
+}
+
+impl<'a, P: JsonRpcClient> PendingBundle<'a, P> {
+    /// Creates a new pending bundle for the `block` the bundle targets, consisting of the
+    /// `transactions`
+    pub fn new(
+        bundle_hash: Option<H256>,
+        block: U64,
+        transactions: Vec<H256>,
+        provider: &'a Provider<P>,
+    ) -> Self {
+        Self {
+            bundle_hash,
+            block,
+            transactions,
+            provider,
+            state: PendingBundleState::PausedGettingBlock,
+            interval: Box::new(interval(provider.get_interval())),
+        }
+    }
+
+    /// Sets the polling interval
+    #[must_use]
+    pub fn interval<T: Into<Duration>>(mut self, duration: T) -> Self {
+        self.interval = Box::new(interval(duration.into()));
+        self
+    }
+
+    /// Returns the hash of the bundle, if the relay returned one
+    pub fn bundle_hash(&self) -> Option<H256> {
+        self.bundle_hash
+    }
+
+    /// Returns the block the bundle targets
+    pub fn block(&self) -> U64 {
+        self.block
+    }
+
+    /// Returns the hashes of the transactions of the bundle
+    pub fn transactions(&self) -> &[H256] {
+        &self.transactions
+    }
+}
+
+impl<'a, P: JsonRpcClient> Future for PendingBundle<'a, P> {
+    type Output = Result<Option<H256>, ProviderError>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match &mut this.state {
+                PendingBundleState::PausedGettingBlock => {
+                    // Wait the polling period so that we do not spam the chain when no
+                    // new block has been mined
+                    let _ready = futures_util::ready!(this.interval.poll_next_unpin(ctx));
+                    let fut = Box::pin(this.provider.get_block(this.block));
+                    this.state = PendingBundleState::GettingBlock(fut);
+                }
+                PendingBundleState::GettingBlock(fut) => {
+                    let block = match futures_util::ready!(fut.as_mut().poll(ctx)) {
+                        Ok(Some(block)) => block,
+                        // the target block was not mined yet
+                        Ok(None) => {
+                            this.state = PendingBundleState::PausedGettingBlock;
+                            continue
+                        }
+                        Err(err) => {
+                            this.state = PendingBundleState::Completed;
+                            return Poll::Ready(Err(err))
+                        }
+                    };
+
+                    let included =
+                        this.transactions.iter().all(|tx| block.transactions.contains(tx));
+                    tracing::debug!(block = ?this.block, bundle_hash = ?this.bundle_hash, included, "target block mined");
+                    this.state = PendingBundleState::Completed;
+                    return Poll::Ready(Ok(block.hash.filter(|_| included)))
+                }
+                PendingBundleState::Completed => {
+                    panic!("polled pending bundle future after completion")
+                }
+            }
+        }
+    }
+}
+
+impl<'a, P> fmt::Debug for PendingBundle<'a, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingBundle")
+            .field("bundle_hash", &self.bundle_hash)
+            .field("block", &self.block)
+            .field("transactions", &self.transactions)
+            .finish()
+    }
+}
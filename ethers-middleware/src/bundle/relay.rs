@@ -0,0 +1,116 @@
+use ethers_core::{types::H256, utils::keccak256};
+use ethers_providers::JsonRpcError;
+use ethers_signers::Signer;
+use reqwest::{header::HeaderValue, Client};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+use url::Url;
+
+/// The header that authenticates the searcher at the relay
+pub const FLASHBOTS_SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+/// A JSON-RPC client for a private bundle relay, e.g. the Flashbots relay.
+///
+/// Every request is authenticated with the `X-Flashbots-Signature` header, which carries the
+/// signature of the hash of the request body by the relay signer. The relay signer only
+/// identifies the searcher and should not be the key that signs the transactions.
+#[derive(Debug)]
+pub struct Relay<S> {
+    id: AtomicU64,
+    url: Url,
+    client: Client,
+    signer: S,
+}
+
+#[derive(Error, Debug)]
+/// Thrown when a request to the relay fails
+pub enum RelayError<S: Signer> {
+    /// Thrown if the HTTP request failed
+    #[error(transparent)]
+    ClientError(#[from] reqwest::Error),
+
+    /// Thrown if the relay returned a JSON-RPC error
+    #[error(transparent)]
+    JsonRpcError(#[from] JsonRpcError),
+
+    /// Thrown if the request could not be serialized
+    #[error(transparent)]
+    RequestSerdeJson(serde_json::Error),
+
+    /// Thrown if the response could not be deserialized
+    #[error("Deserialization Error: {err}. Response: {text}")]
+    ResponseSerdeJson { err: serde_json::Error, text: String },
+
+    /// Thrown if the relay signer failed to sign the request
+    #[error("{0}")]
+    SignerError(S::Error),
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    id: u64,
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+struct Response<R> {
+    #[serde(default = "Option::default")]
+    result: Option<R>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+impl<S: Signer> Relay<S> {
+    /// Creates a new relay client for the relay at `url`, which signs its requests with `signer`
+    pub fn new(url: impl Into<Url>, signer: S) -> Self {
+        Self { id: AtomicU64::new(1), url: url.into(), client: Client::new(), signer }
+    }
+
+    /// Returns the URL of the relay
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the signer that authenticates the requests
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Sends a signed JSON-RPC request to the relay. A `null` result is returned as `None`.
+    pub async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<Option<R>, RelayError<S>> {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let body = serde_json::to_string(&Request { id, jsonrpc: "2.0", method, params })
+            .map_err(RelayError::RequestSerdeJson)?;
+
+        let signature = self
+            .signer
+            .sign_message(format!("{:?}", H256::from(keccak256(body.as_bytes()))))
+            .await
+            .map_err(RelayError::SignerError)?;
+        let header = format!("{:?}:0x{}", self.signer.address(), signature);
+
+        let res = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .header(FLASHBOTS_SIGNATURE_HEADER, header)
+            .body(body)
+            .send()
+            .await?;
+        let text = res.text().await?;
+
+        let res: Response<R> = serde_json::from_str(&text)
+            .map_err(|err| RelayError::ResponseSerdeJson { err, text })?;
+        match res.error {
+            Some(err) => Err(err.into()),
+            None => Ok(res.result),
+        }
+    }
+}
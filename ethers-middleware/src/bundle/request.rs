@@ -0,0 +1,357 @@
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, Signature, H256, U256,
+        U64,
+    },
+    utils::keccak256,
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// A transaction of a bundle, either signed by the caller or already RLP encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleTransaction {
+    /// A transaction with its signature
+    Signed(Box<TypedTransaction>, Signature),
+    /// A raw signed transaction
+    Raw(Bytes),
+}
+
+impl BundleTransaction {
+    /// Returns the RLP encoding of the signed transaction
+    pub fn rlp(&self) -> Bytes {
+        match self {
+            BundleTransaction::Signed(tx, signature) => tx.rlp_signed(signature),
+            BundleTransaction::Raw(raw) => raw.clone(),
+        }
+    }
+}
+
+impl From<(TypedTransaction, Signature)> for BundleTransaction {
+    fn from((tx, signature): (TypedTransaction, Signature)) -> Self {
+        BundleTransaction::Signed(Box::new(tx), signature)
+    }
+}
+
+impl From<Bytes> for BundleTransaction {
+    fn from(raw: Bytes) -> Self {
+        BundleTransaction::Raw(raw)
+    }
+}
+
+/// A bundle of signed transactions that is included atomically, in order, in the target block or
+/// not at all.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::Bytes;
+/// use ethers_middleware::bundle::BundleRequest;
+///
+/// let bundle = BundleRequest::new()
+///     .transaction(Bytes::from(vec![0x01]))
+///     .revertible_transaction(Bytes::from(vec![0x02]))
+///     .block(15_000_000u64);
+/// assert_eq!(bundle.transactions().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRequest {
+    #[serde(rename = "txs")]
+    transactions: Vec<Bytes>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reverting_tx_hashes: Vec<H256>,
+    #[serde(rename = "blockNumber", skip_serializing_if = "Option::is_none")]
+    target_block: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_timestamp: Option<u64>,
+    #[serde(skip)]
+    simulation_block: Option<U64>,
+    #[serde(skip)]
+    simulation_timestamp: Option<u64>,
+}
+
+impl BundleRequest {
+    /// Creates an empty bundle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a transaction to the bundle, the whole bundle is dropped if it reverts
+    #[must_use]
+    pub fn transaction<T: Into<BundleTransaction>>(mut self, tx: T) -> Self {
+        self.transactions.push(tx.into().rlp());
+        self
+    }
+
+    /// Appends a transaction to the bundle that is allowed to revert
+    #[must_use]
+    pub fn revertible_transaction<T: Into<BundleTransaction>>(mut self, tx: T) -> Self {
+        let tx = tx.into().rlp();
+        self.reverting_tx_hashes.push(keccak256(&tx).into());
+        self.transactions.push(tx);
+        self
+    }
+
+    /// Sets the block the bundle should be included in
+    #[must_use]
+    pub fn block<T: Into<U64>>(mut self, block: T) -> Self {
+        self.target_block = Some(block.into());
+        self
+    }
+
+    /// Sets the minimum timestamp of the target block
+    #[must_use]
+    pub fn min_timestamp(mut self, timestamp: u64) -> Self {
+        self.min_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the maximum timestamp of the target block
+    #[must_use]
+    pub fn max_timestamp(mut self, timestamp: u64) -> Self {
+        self.max_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the block whose state the bundle is simulated on, defaults to the latest block
+    #[must_use]
+    pub fn simulation_block<T: Into<U64>>(mut self, block: T) -> Self {
+        self.simulation_block = Some(block.into());
+        self
+    }
+
+    /// Sets the timestamp of the simulated block, defaults to the timestamp of the block after the
+    /// simulation block
+    #[must_use]
+    pub fn simulation_timestamp(mut self, timestamp: u64) -> Self {
+        self.simulation_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Returns the RLP encoded signed transactions of the bundle
+    pub fn transactions(&self) -> &[Bytes] {
+        &self.transactions
+    }
+
+    /// Returns the hashes of the transactions of the bundle
+    pub fn transaction_hashes(&self) -> Vec<H256> {
+        self.transactions.iter().map(|tx| keccak256(tx).into()).collect()
+    }
+
+    /// Returns the hashes of the transactions that are allowed to revert
+    pub fn reverting_tx_hashes(&self) -> &[H256] {
+        &self.reverting_tx_hashes
+    }
+
+    /// Returns the block the bundle should be included in
+    pub fn target_block(&self) -> Option<U64> {
+        self.target_block
+    }
+
+    /// Returns the parameters of `eth_callBundle`
+    pub(crate) fn call_params(&self, block: U64) -> CallBundleParams<'_> {
+        CallBundleParams {
+            transactions: &self.transactions,
+            block,
+            state_block: self
+                .simulation_block
+                .map(BlockNumber::Number)
+                .unwrap_or(BlockNumber::Latest),
+            timestamp: self.simulation_timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CallBundleParams<'a> {
+    #[serde(rename = "txs")]
+    transactions: &'a [Bytes],
+    #[serde(rename = "blockNumber")]
+    block: U64,
+    #[serde(rename = "stateBlockNumber")]
+    state_block: BlockNumber,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+}
+
+/// The result of simulating a bundle with `eth_callBundle`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBundle {
+    /// The hash of the bundle
+    pub bundle_hash: H256,
+    /// The gas price of the bundle, i.e. the fees and coinbase payments divided by the gas used
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub bundle_gas_price: U256,
+    /// The balance change of the coinbase
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub coinbase_diff: U256,
+    /// The value sent directly to the coinbase
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub eth_sent_to_coinbase: U256,
+    /// The fees paid for the gas used
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub gas_fees: U256,
+    /// The block whose state the bundle was simulated on
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub state_block_number: U256,
+    /// The gas used by the whole bundle
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub total_gas_used: U256,
+    /// The results of the simulated transactions
+    pub results: Vec<SimulatedTransaction>,
+}
+
+/// The result of a single transaction of a simulated bundle
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTransaction {
+    /// The hash of the transaction
+    pub tx_hash: H256,
+    /// The sender of the transaction
+    pub from_address: Address,
+    /// The receiver of the transaction, `None` for contract deployments
+    pub to_address: Option<Address>,
+    /// The gas used by the transaction
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub gas_used: U256,
+    /// The gas price of the transaction
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub gas_price: U256,
+    /// The fees paid for the gas used
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub gas_fees: U256,
+    /// The balance change of the coinbase
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub coinbase_diff: U256,
+    /// The value sent directly to the coinbase
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub eth_sent_to_coinbase: U256,
+    /// The output of the transaction
+    #[serde(default)]
+    pub value: Option<Bytes>,
+    /// The error, if the transaction failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// The revert reason, if the transaction reverted
+    #[serde(default)]
+    pub revert: Option<String>,
+}
+
+impl SimulatedTransaction {
+    /// Returns `true` if the transaction failed
+    pub fn is_failure(&self) -> bool {
+        self.error.is_some() || self.revert.is_some()
+    }
+}
+
+/// The status of a submitted bundle as reported by `flashbots_getBundleStats`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStats {
+    /// Whether the bundle was simulated by the relay
+    pub is_simulated: bool,
+    /// Whether the bundle was sent to the miners
+    pub is_sent_to_miners: bool,
+    /// Whether the bundle was treated with high priority
+    #[serde(default)]
+    pub is_high_priority: bool,
+    /// When the bundle was simulated, as RFC 3339 timestamp
+    #[serde(default)]
+    pub simulated_at: Option<String>,
+    /// When the bundle was submitted, as RFC 3339 timestamp
+    #[serde(default)]
+    pub submitted_at: Option<String>,
+    /// When the bundle was sent to the miners, as RFC 3339 timestamp
+    #[serde(default)]
+    pub sent_to_miners_at: Option<String>,
+}
+
+/// The relay returns amounts as decimal strings, gas as numbers and block numbers as either
+fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Numeric {
+        String(String),
+        Number(u64),
+    }
+
+    match Numeric::deserialize(deserializer)? {
+        Numeric::Number(num) => Ok(num.into()),
+        Numeric::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map_err(de::Error::custom),
+            None => U256::from_dec_str(&s).map_err(de::Error::custom),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_send_bundle_params() {
+        let bundle = BundleRequest::new()
+            .transaction(Bytes::from(vec![0x01]))
+            .revertible_transaction(Bytes::from(vec![0x02]))
+            .block(10u64)
+            .min_timestamp(1000)
+            .simulation_block(9u64);
+
+        assert_eq!(
+            serde_json::to_value(&bundle).unwrap(),
+            serde_json::json!({
+                "txs": ["0x01", "0x02"],
+                "revertingTxHashes": [H256::from(keccak256([0x02]))],
+                "blockNumber": "0xa",
+                "minTimestamp": 1000,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(bundle.call_params(10u64.into())).unwrap(),
+            serde_json::json!({
+                "txs": ["0x01", "0x02"],
+                "blockNumber": "0xa",
+                "stateBlockNumber": "0x9",
+            })
+        );
+    }
+
+    #[test]
+    fn deserializes_simulated_bundle() {
+        let simulated: SimulatedBundle = serde_json::from_str(
+            r#"{
+                "bundleGasPrice": "476190476193",
+                "bundleHash": "0x73b1e258c7a42fd0230b2fd05529c5d4b6fcb66c227783f8bece8aeacdd1db2e",
+                "coinbaseDiff": "20000000000126000",
+                "ethSentToCoinbase": "20000000000000000",
+                "gasFees": "126000",
+                "results": [{
+                    "coinbaseDiff": "10000000000063000",
+                    "ethSentToCoinbase": "10000000000000000",
+                    "fromAddress": "0x02A727155aeF8609c9f7F2179b2a1f560B39F5A0",
+                    "gasFees": "63000",
+                    "gasPrice": "476190476193",
+                    "gasUsed": 21000,
+                    "toAddress": "0x73625f59CAdc5009Cb458B751b3E7b6b48C06f2C",
+                    "txHash": "0x669b4704a7d993a946cdd6e2f95233f308ce0c4649d2e04944e8299efcaa098a",
+                    "value": "0x"
+                }],
+                "stateBlockNumber": 5221585,
+                "totalGasUsed": 42000
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(simulated.bundle_gas_price, 476190476193u64.into());
+        assert_eq!(simulated.state_block_number, 5221585u64.into());
+        assert_eq!(simulated.results[0].gas_used, 21000u64.into());
+        assert!(!simulated.results[0].is_failure());
+    }
+}
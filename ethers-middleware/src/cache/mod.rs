@@ -0,0 +1,411 @@
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Block, BlockId, BlockNumber, Bytes, NameOrAddress,
+    Transaction, TransactionReceipt, TxHash, H256, U256, U64,
+};
+use ethers_providers::{FromErr, Middleware};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::DiskCache;
+pub use store::{LruCache, ResponseCache, DEFAULT_LRU_CAPACITY};
+
+/// The default number of blocks after which a block is considered final
+pub const DEFAULT_FINALITY_DEPTH: u64 = 64;
+
+#[derive(Debug)]
+/// Middleware used for caching the responses to queries whose result can no longer change.
+///
+/// Queries for a block by its hash, as well as state queries at a block hash, are always cached.
+/// Queries at a block number are only cached once the block is at least `finality_depth` blocks
+/// behind the chain tip, and so are transactions and receipts once they were mined in such a
+/// block. Queries at the `latest` or `pending` block are never cached.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::cache::{CacheMiddleware, DiskCache};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+///
+/// // keeps the responses in memory
+/// let client = CacheMiddleware::new(provider.clone());
+///
+/// // or persists them on disk
+/// let client = CacheMiddleware::new_with_cache(provider, DiskCache::new("./cache/mainnet"))
+///     .finality_depth(12);
+///
+/// let block = client.get_block(1_000_000u64).await?;
+/// // served from the cache
+/// let block = client.get_block(1_000_000u64).await?;
+/// assert_eq!(client.stats().hits, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CacheMiddleware<M, C = LruCache> {
+    inner: M,
+    cache: C,
+    finality_depth: u64,
+    /// The highest block number seen so far
+    head: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The number of cache hits and misses of a [`CacheMiddleware`]
+pub struct CacheStats {
+    /// The number of queries that were served from the cache
+    pub hits: u64,
+    /// The number of cacheable queries that were sent to the inner middleware
+    pub misses: u64,
+}
+
+#[derive(Error, Debug)]
+/// Thrown when the cache middleware fails
+pub enum CacheMiddlewareError<M: Middleware>
+where
+    M::Error: 'static,
+{
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for CacheMiddlewareError<M> {
+    fn from(src: M::Error) -> Self {
+        CacheMiddlewareError::MiddlewareError(src)
+    }
+}
+
+/// Whether a response can be cached
+#[derive(Debug, Clone, Copy)]
+enum Cacheable {
+    /// The response can no longer change
+    Always,
+    /// The response can no longer change once the block is final
+    IfFinal(U64),
+    /// The response may still change
+    Never,
+}
+
+impl Cacheable {
+    /// Returns whether a query at the `block` can be cached
+    fn at(block: Option<BlockId>) -> Self {
+        match block {
+            Some(BlockId::Hash(_)) => Cacheable::Always,
+            Some(BlockId::Number(BlockNumber::Number(number))) => Cacheable::IfFinal(number),
+            Some(BlockId::Number(BlockNumber::Earliest)) => Cacheable::Always,
+            Some(BlockId::Number(BlockNumber::Latest | BlockNumber::Pending)) | None => {
+                Cacheable::Never
+            }
+        }
+    }
+
+    /// Returns whether a query for state of the `target` at the `block` can be cached, queries
+    /// for ENS names are never cached since the name may resolve to a different address
+    fn at_address(target: &NameOrAddress, block: Option<BlockId>) -> Self {
+        match target {
+            NameOrAddress::Address(_) => Cacheable::at(block),
+            NameOrAddress::Name(_) => Cacheable::Never,
+        }
+    }
+
+    /// Returns whether a response that was mined in the `block` can be cached
+    fn mined_in(block: Option<U64>) -> Self {
+        block.map(Cacheable::IfFinal).unwrap_or(Cacheable::Never)
+    }
+}
+
+/// Returns the key of the query for the `method` with the `params`
+fn cache_key(method: &str, params: impl Serialize) -> String {
+    format!("{}:{}", method, serde_json::to_string(&params).unwrap_or_default())
+}
+
+impl<M> CacheMiddleware<M, LruCache>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Instantiates the middleware with an in-memory [`LruCache`] that keeps up to
+    /// [`DEFAULT_LRU_CAPACITY`] responses
+    pub fn new(inner: M) -> Self {
+        Self::new_with_cache(inner, LruCache::default())
+    }
+}
+
+impl<M, C> CacheMiddleware<M, C>
+where
+    M: Middleware,
+    C: ResponseCache,
+    M::Error: 'static,
+{
+    /// Instantiates the middleware with the given cache backend
+    pub fn new_with_cache(inner: M, cache: C) -> Self {
+        Self {
+            inner,
+            cache,
+            finality_depth: DEFAULT_FINALITY_DEPTH,
+            head: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the number of blocks after which a block is considered final and queries at it are
+    /// cached
+    #[must_use]
+    pub fn finality_depth(mut self, finality_depth: u64) -> Self {
+        self.finality_depth = finality_depth;
+        self
+    }
+
+    /// Returns the cache backend
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// Returns the number of cache hits and misses so far
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the cached response for the `key`, a response that can not be deserialized is
+    /// treated as a miss
+    async fn lookup<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.cache.get(key).await.and_then(|value| serde_json::from_str(&value).ok());
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        tracing::trace!(key, hit = value.is_some(), "cache lookup");
+        value
+    }
+
+    /// Caches the `value` for the `key` if it can no longer change
+    async fn store<T: Serialize + Sync>(
+        &self,
+        key: String,
+        value: &T,
+        cacheable: Cacheable,
+    ) -> Result<(), CacheMiddlewareError<M>> {
+        let cacheable = match cacheable {
+            Cacheable::Always => true,
+            Cacheable::IfFinal(number) => self.is_final(number).await?,
+            Cacheable::Never => false,
+        };
+        if cacheable {
+            if let Ok(value) = serde_json::to_string(value) {
+                self.cache.insert(key, value).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the block is final, the chain tip is only fetched if the block is not
+    /// final according to the highest block seen so far
+    async fn is_final(&self, number: U64) -> Result<bool, CacheMiddlewareError<M>> {
+        let number = number.as_u64();
+        let is_final = |head: u64| number.saturating_add(self.finality_depth) <= head;
+        if is_final(self.head.load(Ordering::Relaxed)) {
+            return Ok(true)
+        }
+
+        let head = self
+            .inner
+            .get_block_number()
+            .await
+            .map_err(CacheMiddlewareError::MiddlewareError)?
+            .as_u64();
+        let head = self.head.fetch_max(head, Ordering::Relaxed).max(head);
+        Ok(is_final(head))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, C> Middleware for CacheMiddleware<M, C>
+where
+    M: Middleware,
+    C: ResponseCache,
+    M::Error: 'static,
+{
+    type Error = CacheMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn get_block<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<TxHash>>, Self::Error> {
+        let id = block_hash_or_number.into();
+        let cacheable = Cacheable::at(Some(id));
+        if let Cacheable::Never = cacheable {
+            return self.inner.get_block(id).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_getBlock", id);
+        if let Some(block) = self.lookup(&key).await {
+            return Ok(Some(block))
+        }
+        let block = self.inner.get_block(id).await.map_err(FromErr::from)?;
+        // the block may not exist yet
+        if let Some(ref block) = block {
+            self.store(key, block, cacheable).await?;
+        }
+        Ok(block)
+    }
+
+    async fn get_block_with_txs<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<Transaction>>, Self::Error> {
+        let id = block_hash_or_number.into();
+        let cacheable = Cacheable::at(Some(id));
+        if let Cacheable::Never = cacheable {
+            return self.inner.get_block_with_txs(id).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_getBlockWithTxs", id);
+        if let Some(block) = self.lookup(&key).await {
+            return Ok(Some(block))
+        }
+        let block = self.inner.get_block_with_txs(id).await.map_err(FromErr::from)?;
+        if let Some(ref block) = block {
+            self.store(key, block, cacheable).await?;
+        }
+        Ok(block)
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = cache_key("eth_getTransactionByHash", hash);
+        if let Some(tx) = self.lookup(&key).await {
+            return Ok(Some(tx))
+        }
+        let tx = self.inner.get_transaction(hash).await.map_err(FromErr::from)?;
+        // pending transactions may still be replaced or dropped
+        if let Some(ref tx) = tx {
+            self.store(key, tx, Cacheable::mined_in(tx.block_number)).await?;
+        }
+        Ok(tx)
+    }
+
+    async fn get_transaction_receipt<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<TransactionReceipt>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = cache_key("eth_getTransactionReceipt", hash);
+        if let Some(receipt) = self.lookup(&key).await {
+            return Ok(Some(receipt))
+        }
+        let receipt = self.inner.get_transaction_receipt(hash).await.map_err(FromErr::from)?;
+        if let Some(ref receipt) = receipt {
+            self.store(key, receipt, Cacheable::mined_in(receipt.block_number)).await?;
+        }
+        Ok(receipt)
+    }
+
+    async fn get_balance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let from = from.into();
+        let cacheable = Cacheable::at_address(&from, block);
+        if let Cacheable::Never = cacheable {
+            return self.inner.get_balance(from, block).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_getBalance", (&from, block));
+        if let Some(balance) = self.lookup(&key).await {
+            return Ok(balance)
+        }
+        let balance = self.inner.get_balance(from, block).await.map_err(FromErr::from)?;
+        self.store(key, &balance, cacheable).await?;
+        Ok(balance)
+    }
+
+    async fn get_code<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        at: T,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let at = at.into();
+        let cacheable = Cacheable::at_address(&at, block);
+        if let Cacheable::Never = cacheable {
+            return self.inner.get_code(at, block).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_getCode", (&at, block));
+        if let Some(code) = self.lookup(&key).await {
+            return Ok(code)
+        }
+        let code = self.inner.get_code(at, block).await.map_err(FromErr::from)?;
+        self.store(key, &code, cacheable).await?;
+        Ok(code)
+    }
+
+    async fn get_storage_at<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, Self::Error> {
+        let from = from.into();
+        let cacheable = Cacheable::at_address(&from, block);
+        if let Cacheable::Never = cacheable {
+            return self.inner.get_storage_at(from, location, block).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_getStorageAt", (&from, location, block));
+        if let Some(value) = self.lookup(&key).await {
+            return Ok(value)
+        }
+        let value =
+            self.inner.get_storage_at(from, location, block).await.map_err(FromErr::from)?;
+        self.store(key, &value, cacheable).await?;
+        Ok(value)
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        // the target of the call may be an ENS name
+        let cacheable = match tx.to() {
+            Some(NameOrAddress::Name(_)) => Cacheable::Never,
+            _ => Cacheable::at(block),
+        };
+        if let Cacheable::Never = cacheable {
+            return self.inner.call(tx, block).await.map_err(FromErr::from)
+        }
+
+        let key = cache_key("eth_call", (tx, block));
+        if let Some(output) = self.lookup(&key).await {
+            return Ok(output)
+        }
+        let output = self.inner.call(tx, block).await.map_err(FromErr::from)?;
+        self.store(key, &output, cacheable).await?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,255 @@
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use ethers_core::utils;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// A key value store for serialized responses.
+///
+/// Caching is best effort, so backends handle their own errors and report a failed lookup as a
+/// miss.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ResponseCache: Send + Sync + fmt::Debug {
+    /// Returns the response stored for the `key`
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores the response for the `key`
+    async fn insert(&self, key: String, value: String);
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: ResponseCache + ?Sized> ResponseCache for Arc<C> {
+    async fn get(&self, key: &str) -> Option<String> {
+        (**self).get(key).await
+    }
+
+    async fn insert(&self, key: String, value: String) {
+        (**self).insert(key, value).await
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: ResponseCache + ?Sized> ResponseCache for Box<C> {
+    async fn get(&self, key: &str) -> Option<String> {
+        (**self).get(key).await
+    }
+
+    async fn insert(&self, key: String, value: String) {
+        (**self).insert(key, value).await
+    }
+}
+
+/// The default number of responses kept by the [`LruCache`]
+pub const DEFAULT_LRU_CAPACITY: usize = 10_000;
+
+/// A [`ResponseCache`] that keeps up to `capacity` responses in memory and evicts the least
+/// recently used response first
+#[derive(Debug)]
+pub struct LruCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    /// The responses with the tick of their last use
+    entries: HashMap<String, (u64, String)>,
+    /// The keys by the tick of their last use
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &str) -> Option<&str> {
+        let tick = self.tick + 1;
+        let (last_used, value) = self.entries.get_mut(key)?;
+        let key = self.order.remove(last_used).expect("tracked key");
+        *last_used = tick;
+        self.order.insert(tick, key);
+        self.tick = tick;
+        Some(value.as_str())
+    }
+}
+
+impl LruCache {
+    /// Creates a cache that keeps up to `capacity` responses
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), state: Default::default() }
+    }
+
+    /// Returns the number of cached responses
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Returns `true` if no responses are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for LruCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_LRU_CAPACITY)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ResponseCache for LruCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.state.lock().unwrap().touch(key).map(str::to_string)
+    }
+
+    async fn insert(&self, key: String, value: String) {
+        let mut state = self.state.lock().unwrap();
+        if state.touch(&key).is_some() {
+            if let Some(entry) = state.entries.get_mut(&key) {
+                entry.1 = value;
+            }
+            return
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.order.insert(tick, key.clone());
+        state.entries.insert(key, (tick, value));
+
+        while state.entries.len() > self.capacity {
+            let oldest = match state.order.keys().next().copied() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            if let Some(key) = state.order.remove(&oldest) {
+                state.entries.remove(&key);
+            }
+        }
+    }
+}
+
+/// A [`ResponseCache`] that persists every response in a file of a directory, so that it
+/// survives restarts.
+///
+/// Responses are only unique per chain, so a separate directory should be used for every chain.
+///
+/// The files are accessed on the blocking thread pool of tokio, so the cache must be used within a
+/// tokio runtime.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DiskCache {
+    /// Creates a cache that stores the responses in `dir`, the directory is created on first use
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory the responses are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = utils::keccak256(key.as_bytes());
+        self.dir.join(format!("{}.json", utils::hex::encode(hash)))
+    }
+
+    fn write(dir: &Path, path: &Path, value: &str) -> std::io::Result<()> {
+        /// Distinguishes the temporary files of concurrent writes within the process
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+
+        std::fs::create_dir_all(dir)?;
+        // write to a temporary file first so that readers never see a partial response
+        let write = WRITES.fetch_add(1, Ordering::Relaxed);
+        let tmp = path.with_extension(format!("{}-{}.tmp", std::process::id(), write));
+        std::fs::write(&tmp, value)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl ResponseCache for DiskCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let path = self.path(key);
+        tokio::task::spawn_blocking(move || std::fs::read_to_string(path).ok()).await.ok().flatten()
+    }
+
+    async fn insert(&self, key: String, value: String) {
+        let (dir, path) = (self.dir.clone(), self.path(&key));
+        let res = tokio::task::spawn_blocking(move || Self::write(&dir, &path, &value)).await;
+        if let Ok(Err(err)) = res {
+            tracing::debug!(?err, dir = ?self.dir, "failed to write cached response");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lru_evicts_least_recently_used() {
+        let cache = LruCache::new(2);
+        cache.insert("a".to_string(), "1".to_string()).await;
+        cache.insert("b".to_string(), "2".to_string()).await;
+
+        // `a` is used, so `b` is evicted
+        assert_eq!(cache.get("a").await, Some("1".to_string()));
+        cache.insert("c".to_string(), "3".to_string()).await;
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b").await, None);
+        assert_eq!(cache.get("a").await, Some("1".to_string()));
+        assert_eq!(cache.get("c").await, Some("3".to_string()));
+
+        // updating a response does not grow the cache
+        cache.insert("c".to_string(), "4".to_string()).await;
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("c").await, Some("4".to_string()));
+    }
+
+    #[tokio::test]
+    async fn disk_cache_persists_responses() {
+        let dir = std::env::temp_dir().join(format!("response-cache-{}", rand::random::<u64>()));
+        let cache = DiskCache::new(&dir);
+        assert_eq!(cache.get("a").await, None);
+        cache.insert("a".to_string(), "1".to_string()).await;
+
+        // a new instance sees the response
+        assert_eq!(DiskCache::new(&dir).get("a").await, Some("1".to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn disk_cache_handles_concurrent_writes() {
+        let dir = std::env::temp_dir().join(format!("response-cache-{}", rand::random::<u64>()));
+        let cache = DiskCache::new(&dir);
+        let writes = (0..8).map(|_| {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.insert("a".to_string(), "1".to_string()).await })
+        });
+        for write in writes.collect::<Vec<_>>() {
+            write.await.unwrap();
+        }
+
+        // every write used its own temporary file, so none failed or was left behind
+        assert_eq!(cache.get("a").await, Some("1".to_string()));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
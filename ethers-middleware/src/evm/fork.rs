@@ -0,0 +1,307 @@
+//! A lazily populated fork of the upstream chain state
+
+use ethers_core::types::{Address, Block, BlockId, BlockNumber, H256, U256, U64};
+use ethers_providers::{call_raw::spoof, Middleware};
+use revm::{
+    primitives::{
+        AccountInfo, Address as EvmAddress, BlockEnv, Bytecode, Bytes as EvmBytes, CfgEnv,
+        EVMError, Env, ExecutionResult, SpecId, TransactTo, TxEnv, B256, U256 as EvmU256,
+    },
+    DatabaseRef, Evm,
+};
+use std::{cell::RefCell, collections::HashMap, convert::Infallible, fmt, sync::RwLock};
+
+/// State that is not yet cached and must be fetched from the upstream provider before the
+/// execution can be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MissingState {
+    Account(Address),
+    Storage(Address, H256),
+    BlockHash(u64),
+}
+
+impl fmt::Display for MissingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissingState::Account(address) => write!(f, "missing account {:?}", address),
+            MissingState::Storage(address, slot) => {
+                write!(f, "missing storage slot {:?} of {:?}", slot, address)
+            }
+            MissingState::BlockHash(number) => write!(f, "missing hash of block {}", number),
+        }
+    }
+}
+
+/// The state of the upstream chain at a fixed block, fetched on demand
+#[derive(Debug)]
+pub(crate) struct Fork {
+    pub(crate) number: U64,
+    pub(crate) hash: Option<H256>,
+    cfg: CfgEnv,
+    block: BlockEnv,
+    cache: RwLock<ForkCache>,
+}
+
+/// Everything that was already fetched from the upstream provider
+#[derive(Debug, Default)]
+struct ForkCache {
+    accounts: HashMap<EvmAddress, AccountInfo>,
+    storage: HashMap<(EvmAddress, EvmU256), EvmU256>,
+    code: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl Fork {
+    /// Creates a new, empty fork of the given block
+    pub(crate) fn new(number: U64, block: &Block<H256>, chain_id: u64) -> Self {
+        let mut cfg = CfgEnv::default();
+        cfg.chain_id = chain_id;
+
+        let mut env = BlockEnv {
+            number: EvmU256::from(number.as_u64()),
+            coinbase: to_evm_address(block.author.unwrap_or_default()),
+            timestamp: to_evm_u256(block.timestamp),
+            gas_limit: to_evm_u256(block.gas_limit),
+            basefee: to_evm_u256(block.base_fee_per_gas.unwrap_or_default()),
+            ..Default::default()
+        };
+        #[cfg(not(feature = "celo"))]
+        {
+            env.difficulty = to_evm_u256(block.difficulty);
+            env.prevrandao = Some(B256::from(block.mix_hash.unwrap_or_default().0));
+        }
+        #[cfg(feature = "celo")]
+        {
+            env.prevrandao = Some(B256::ZERO);
+        }
+        env.set_blob_excess_gas_and_price(0);
+
+        Self { number, hash: block.hash, cfg, block: env, cache: Default::default() }
+    }
+
+    /// The gas limit of the forked block
+    pub(crate) fn gas_limit(&self) -> u64 {
+        self.block.gas_limit.saturating_to()
+    }
+
+    /// Executes the transaction against the cached state without committing any changes.
+    ///
+    /// State that is not cached yet reads as empty so that the execution can continue and discover
+    /// as much of the missing state as possible. If any state was missing, the result is discarded
+    /// and all missing state is returned instead, so it can be fetched at once before retrying.
+    pub(crate) fn transact(
+        &self,
+        spec_id: SpecId,
+        tx: &TxEnv,
+        overrides: Option<&spoof::State>,
+    ) -> Result<Result<ExecutionResult, EVMError<Infallible>>, Vec<MissingState>> {
+        let cache = self.cache.read().unwrap();
+        let db = ForkDb { cache: &cache, overrides, missing: Default::default() };
+
+        // the sender, the target and the access list are needed anyway, touching them up front
+        // fetches them in the first round even if the execution stops early without them
+        let _ = db.basic_ref(tx.caller);
+        if let TransactTo::Call(to) = tx.transact_to {
+            let _ = db.basic_ref(to);
+        }
+        for (address, keys) in &tx.access_list {
+            let _ = db.basic_ref(*address);
+            for key in keys {
+                let _ = db.storage_ref(*address, *key);
+            }
+        }
+
+        let mut env = Box::<Env>::default();
+        env.cfg = self.cfg.clone();
+        env.block = self.block.clone();
+        env.tx = tx.clone();
+        // like `eth_call`, calls without a gas price are not checked against the base fee
+        env.cfg.disable_base_fee = env.tx.gas_price == EvmU256::ZERO;
+
+        let res = Evm::builder()
+            .with_ref_db(&db)
+            .with_env(env)
+            .with_spec_id(spec_id)
+            .build()
+            .transact()
+            .map(|res| res.result);
+
+        let missing = db.missing.into_inner();
+        if missing.is_empty() {
+            Ok(res)
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Fetches all missing state concurrently and adds it to the cache
+    pub(crate) async fn fetch_all<M: Middleware>(
+        &self,
+        client: &M,
+        missing: Vec<MissingState>,
+    ) -> Result<(), M::Error> {
+        futures_util::future::try_join_all(missing.into_iter().map(|missing| {
+            tracing::trace!(%missing, "fetching state");
+            self.fetch(client, missing)
+        }))
+        .await?;
+        Ok(())
+    }
+
+    /// Fetches the missing state at the forked block and adds it to the cache
+    pub(crate) async fn fetch<M: Middleware>(
+        &self,
+        client: &M,
+        missing: MissingState,
+    ) -> Result<(), M::Error> {
+        let block = Some(BlockId::Number(BlockNumber::Number(self.number)));
+        match missing {
+            MissingState::Account(address) => {
+                let (balance, nonce, code) = futures_util::try_join!(
+                    client.get_balance(address, block),
+                    client.get_transaction_count(address, block),
+                    client.get_code(address, block)
+                )?;
+                let code = Bytecode::new_raw(EvmBytes::from(code.to_vec()));
+                let info = AccountInfo::new(
+                    to_evm_u256(balance),
+                    nonce.as_u64(),
+                    code.hash_slow(),
+                    code.clone(),
+                );
+
+                let mut cache = self.cache.write().unwrap();
+                cache.code.insert(info.code_hash, code);
+                cache.accounts.insert(to_evm_address(address), info);
+            }
+            MissingState::Storage(address, slot) => {
+                let value = client.get_storage_at(address, slot, block).await?;
+                self.cache.write().unwrap().storage.insert(
+                    (to_evm_address(address), EvmU256::from_be_bytes(slot.0)),
+                    EvmU256::from_be_bytes(value.0),
+                );
+            }
+            MissingState::BlockHash(number) => {
+                let hash = client
+                    .get_block(number)
+                    .await?
+                    .and_then(|block| block.hash)
+                    .unwrap_or_default();
+                self.cache.write().unwrap().block_hashes.insert(number, B256::from(hash.0));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A read-only view of the cached state with the state overrides of a call applied on top.
+///
+/// Reads of uncached state are recorded in `missing` and return empty state.
+struct ForkDb<'a> {
+    cache: &'a ForkCache,
+    overrides: Option<&'a spoof::State>,
+    missing: RefCell<Vec<MissingState>>,
+}
+
+impl<'a> ForkDb<'a> {
+    fn account_override(&self, address: &Address) -> Option<&'a spoof::Account> {
+        self.overrides.and_then(|state| state.get(address))
+    }
+
+    fn record(&self, missing: MissingState) {
+        let mut all = self.missing.borrow_mut();
+        if !all.contains(&missing) {
+            all.push(missing);
+        }
+    }
+}
+
+impl<'a> DatabaseRef for ForkDb<'a> {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        let addr = from_evm_address(address);
+        let account = self.account_override(&addr);
+
+        let mut info = match (self.cache.accounts.get(&address), account) {
+            (Some(info), _) => info.clone(),
+            // no need to fetch an account whose state is completely overridden
+            (
+                None,
+                Some(spoof::Account { nonce: Some(_), balance: Some(_), code: Some(_), .. }),
+            ) => AccountInfo::default(),
+            (None, _) => {
+                self.record(MissingState::Account(addr));
+                AccountInfo::default()
+            }
+        };
+
+        if let Some(account) = account {
+            if let Some(nonce) = account.nonce {
+                info.nonce = nonce.as_u64();
+            }
+            if let Some(balance) = account.balance {
+                info.balance = to_evm_u256(balance);
+            }
+            if let Some(ref code) = account.code {
+                let code = Bytecode::new_raw(EvmBytes::from(code.to_vec()));
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+        }
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // accounts are always returned with their code, so this is only reached for unknown code
+        Ok(self.cache.code.get(&code_hash).cloned().unwrap_or_default())
+    }
+
+    fn storage_ref(&self, address: EvmAddress, index: EvmU256) -> Result<EvmU256, Self::Error> {
+        let addr = from_evm_address(address);
+        let slot = H256(index.to_be_bytes());
+
+        if let Some(storage) = self.account_override(&addr).and_then(|acc| acc.storage.as_ref()) {
+            match storage {
+                spoof::Storage::Replace(storage) => {
+                    return Ok(storage
+                        .get(&slot)
+                        .map(|value| EvmU256::from_be_bytes(value.0))
+                        .unwrap_or_default())
+                }
+                spoof::Storage::Diff(storage) => {
+                    if let Some(value) = storage.get(&slot) {
+                        return Ok(EvmU256::from_be_bytes(value.0))
+                    }
+                }
+            }
+        }
+
+        Ok(self.cache.storage.get(&(address, index)).copied().unwrap_or_else(|| {
+            self.record(MissingState::Storage(addr, slot));
+            EvmU256::ZERO
+        }))
+    }
+
+    fn block_hash_ref(&self, number: EvmU256) -> Result<B256, Self::Error> {
+        let number = number.saturating_to();
+        Ok(self.cache.block_hashes.get(&number).copied().unwrap_or_else(|| {
+            self.record(MissingState::BlockHash(number));
+            B256::ZERO
+        }))
+    }
+}
+
+pub(crate) fn to_evm_address(address: Address) -> EvmAddress {
+    EvmAddress::from(address.0)
+}
+
+pub(crate) fn from_evm_address(address: EvmAddress) -> Address {
+    Address::from_slice(address.as_slice())
+}
+
+pub(crate) fn to_evm_u256(value: U256) -> EvmU256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    EvmU256::from_be_bytes(bytes)
+}
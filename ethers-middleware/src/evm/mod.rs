@@ -0,0 +1,480 @@
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, BlockNumber, Bytes, NameOrAddress, U256, U64,
+};
+use ethers_providers::{
+    call_raw::{spoof, RawCall},
+    FromErr, Middleware,
+};
+use revm::primitives::{Bytes as EvmBytes, ExecutionResult, TransactTo, TxEnv, U256 as EvmU256};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+use thiserror::Error;
+
+mod fork;
+use fork::{to_evm_address, to_evm_u256, Fork};
+
+pub use revm::primitives::SpecId;
+
+#[derive(Error, Debug)]
+/// Thrown when an error happens in the [`EvmMiddleware`]
+pub enum EvmMiddlewareError<M: Middleware>
+where
+    M::Error: 'static,
+{
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
+    /// Thrown when the execution reverted, contains the revert data
+    #[error("execution reverted: {0}")]
+    Revert(Bytes),
+    /// Thrown when the execution halted exceptionally, e.g. by running out of gas
+    #[error("execution halted: {0}")]
+    Halt(String),
+    /// Thrown when the transaction could not be executed, e.g. due to an insufficient balance
+    #[error("{0}")]
+    Evm(String),
+    /// Thrown when the block to fork could not be found
+    #[error("block {0:?} not found")]
+    BlockNotFound(BlockId),
+    /// Thrown when a call with state overrides targets a block other than the forked block
+    #[error("state overrides are only supported for the forked block, got {0:?}")]
+    UnsupportedBlock(BlockId),
+}
+
+impl<M: Middleware> FromErr<M::Error> for EvmMiddlewareError<M> {
+    fn from(src: M::Error) -> Self {
+        EvmMiddlewareError::MiddlewareError(src)
+    }
+}
+
+/// Middleware that executes `eth_call` and `eth_estimateGas` in an in-process EVM instead of
+/// sending them to the node.
+///
+/// The EVM runs on a fork of the upstream chain at a fixed block. The fork starts out empty and
+/// the accounts and storage slots that are touched by an execution are fetched lazily via
+/// `eth_getBalance`, `eth_getTransactionCount`, `eth_getCode` and `eth_getStorageAt`, then cached
+/// for all subsequent executions. Repeatedly simulating transactions against the same contracts
+/// therefore only hits the node once for every piece of state. Uncached state reads as empty
+/// until it is fetched, so a single execution discovers most of the state it needs and fetches it
+/// concurrently before it is retried.
+///
+/// By default the fork follows the latest block: before an execution on the latest block, the
+/// chain head is checked and the latest block is forked again, with an empty cache, if the head
+/// moved. A block set with [`EvmMiddleware::with_fork_block`] stays forked. Call
+/// [`EvmMiddleware::reset_fork`] to drop the cached state and move the fork to a new block. Calls
+/// that target another block are forwarded to the inner middleware.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::evm::EvmMiddleware;
+/// use ethers_providers::{call_raw::{spoof, RawCall}, Middleware, Provider, Http};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = EvmMiddleware::new(provider);
+///
+/// let weth: Address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse()?;
+/// // `totalSupply()`
+/// let tx = TransactionRequest::new().to(weth).data(vec![0x18, 0x16, 0x0d, 0xdd]).into();
+///
+/// // executed locally, fetches the code and storage of WETH from the node
+/// let supply = client.call(&tx, None).await?;
+/// // served from the cached state
+/// let supply = client.call(&tx, None).await?;
+///
+/// // state overrides apply to the local execution as well
+/// let state = spoof::storage(weth, Default::default(), Default::default());
+/// let res = client.call_raw(&tx).state(&state).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EvmMiddleware<M> {
+    inner: M,
+    spec_id: SpecId,
+    state: RwLock<ForkState>,
+}
+
+#[derive(Debug, Default)]
+struct ForkState {
+    /// The block to fork, `None` for the latest block
+    block: Option<U64>,
+    /// The current fork, created on first use
+    fork: Option<Arc<Fork>>,
+}
+
+impl<M> EvmMiddleware<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Creates a new EVM middleware which forks the latest block of the `inner` middleware
+    pub fn new(inner: M) -> Self {
+        Self { inner, spec_id: SpecId::LATEST, state: Default::default() }
+    }
+
+    /// Sets the block to fork instead of the latest block
+    #[must_use]
+    pub fn with_fork_block<T: Into<U64>>(mut self, block: T) -> Self {
+        self.state.get_mut().unwrap().block = Some(block.into());
+        self
+    }
+
+    /// Sets the hardfork rules to execute transactions with, defaults to the latest hardfork
+    #[must_use]
+    pub fn with_spec_id(mut self, spec_id: SpecId) -> Self {
+        self.spec_id = spec_id;
+        self
+    }
+
+    /// Returns the number of the forked block, `None` if the fork was not created yet
+    pub fn fork_block_number(&self) -> Option<U64> {
+        self.state.read().unwrap().fork.as_ref().map(|fork| fork.number)
+    }
+
+    /// Drops all cached state. The next execution forks the given block again, or the latest
+    /// block if `block` is `None`
+    pub fn reset_fork(&self, block: Option<U64>) {
+        let mut state = self.state.write().unwrap();
+        state.block = block;
+        state.fork = None;
+    }
+
+    /// Returns a builder for a local `eth_call` whose parameters, like the
+    /// [state override set](ethers_providers::call_raw::spoof), can be overridden via the
+    /// [`RawCall`] methods.
+    pub fn call_raw<'a>(&'a self, tx: &'a TypedTransaction) -> EvmCallBuilder<'a, M> {
+        EvmCallBuilder::new(self, tx)
+    }
+
+    /// Returns the current fork or creates it, forking the latest block again if the chain head
+    /// moved
+    async fn fork(&self) -> Result<Arc<Fork>, EvmMiddlewareError<M>> {
+        let (block, fork) = {
+            let state = self.state.read().unwrap();
+            (state.block, state.fork.clone())
+        };
+        if let Some(fork) = fork {
+            // the fork of a fixed block never changes
+            if block.is_some() {
+                return Ok(fork)
+            }
+            let head = self.inner.get_block_number().await.map_err(FromErr::from)?;
+            if head <= fork.number {
+                return Ok(fork)
+            }
+            tracing::trace!(fork = %fork.number, %head, "chain head moved, forking again");
+        }
+
+        let id: BlockId = block.map(BlockNumber::Number).unwrap_or(BlockNumber::Latest).into();
+        let (forked, chain_id) =
+            futures_util::try_join!(self.inner.get_block(id), self.inner.get_chainid())
+                .map_err(FromErr::from)?;
+        let forked = forked.ok_or(EvmMiddlewareError::BlockNotFound(id))?;
+        let number = forked.number.ok_or(EvmMiddlewareError::BlockNotFound(id))?;
+        let fork = Arc::new(Fork::new(number, &forked, chain_id.as_u64()));
+
+        let mut state = self.state.write().unwrap();
+        if state.block != block {
+            // the fork was reset concurrently
+            return Ok(fork)
+        }
+        match state.fork {
+            // a newer fork could have been created concurrently
+            Some(ref current) if current.number >= fork.number => Ok(current.clone()),
+            _ => Ok(state.fork.insert(fork).clone()),
+        }
+    }
+
+    /// Converts the transaction to the EVM's representation, the gas limit is set on execution
+    async fn tx_env(&self, tx: &TypedTransaction) -> Result<TxEnv, EvmMiddlewareError<M>> {
+        let transact_to = match tx.to() {
+            Some(NameOrAddress::Address(addr)) => TransactTo::call(to_evm_address(*addr)),
+            Some(NameOrAddress::Name(ens_name)) => {
+                let addr = self.inner.resolve_name(ens_name).await.map_err(FromErr::from)?;
+                TransactTo::call(to_evm_address(addr))
+            }
+            None => TransactTo::create(),
+        };
+
+        let mut env = TxEnv {
+            caller: to_evm_address(tx.from().copied().unwrap_or_default()),
+            gas_price: to_evm_u256(tx.gas_price().unwrap_or_default()),
+            transact_to,
+            value: to_evm_u256(tx.value().copied().unwrap_or_default()),
+            data: EvmBytes::from(tx.data().map(|data| data.to_vec()).unwrap_or_default()),
+            // like `eth_call`, the nonce of the sender is not checked
+            nonce: None,
+            ..Default::default()
+        };
+        if let TypedTransaction::Eip1559(ref inner) = tx {
+            env.gas_priority_fee = inner.max_priority_fee_per_gas.map(to_evm_u256);
+        }
+        if let Some(access_list) = tx.access_list() {
+            env.access_list = access_list
+                .0
+                .iter()
+                .map(|item| {
+                    let keys =
+                        item.storage_keys.iter().map(|key| EvmU256::from_be_bytes(key.0)).collect();
+                    (to_evm_address(item.address), keys)
+                })
+                .collect();
+        }
+        Ok(env)
+    }
+
+    /// Executes the transaction on the fork, fetching any missing state from the inner middleware
+    /// until the execution completes
+    async fn transact(
+        &self,
+        fork: &Fork,
+        tx: &TxEnv,
+        overrides: Option<&spoof::State>,
+    ) -> Result<ExecutionResult, EvmMiddlewareError<M>> {
+        loop {
+            match fork.transact(self.spec_id, tx, overrides) {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(err)) => return Err(EvmMiddlewareError::Evm(err.to_string())),
+                Err(missing) => {
+                    fork.fetch_all(&self.inner, missing).await.map_err(FromErr::from)?
+                }
+            }
+        }
+    }
+
+    /// Executes an `eth_call` on the fork
+    async fn call_with_state(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+        overrides: Option<&spoof::State>,
+    ) -> Result<Bytes, EvmMiddlewareError<M>> {
+        let fork = self.fork().await?;
+        if !is_fork_block(&fork, block) {
+            let block = block.expect("the latest block is forked");
+            if overrides.is_some() {
+                return Err(EvmMiddlewareError::UnsupportedBlock(block))
+            }
+            return self.inner.call(tx, Some(block)).await.map_err(FromErr::from)
+        }
+
+        let mut env = self.tx_env(tx).await?;
+        env.gas_limit = gas_limit(tx, &fork);
+
+        match self.transact(&fork, &env, overrides).await? {
+            ExecutionResult::Success { output, .. } => Ok(output.into_data().to_vec().into()),
+            res => Err(execution_error(res)),
+        }
+    }
+
+    /// Estimates the gas of the transaction on the fork by searching for the lowest gas limit
+    /// that the transaction succeeds with, like `eth_estimateGas`
+    async fn estimate_gas_with_fork(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<U256, EvmMiddlewareError<M>> {
+        let fork = self.fork().await?;
+        let mut env = self.tx_env(tx).await?;
+        let cap = gas_limit(tx, &fork);
+
+        // the transaction must succeed with the highest allowed gas limit
+        env.gas_limit = cap;
+        let gas_used = match self.transact(&fork, &env, None).await? {
+            ExecutionResult::Success { gas_used, .. } => gas_used,
+            res => return Err(execution_error(res)),
+        };
+
+        // the gas limit must at least cover the used gas but can be higher due to refunds and the
+        // gas that is withheld from nested calls
+        let (mut lo, mut hi) = (gas_used.saturating_sub(1), cap);
+        while lo + 1 < hi {
+            let mid = lo + (hi - lo) / 2;
+            env.gas_limit = mid;
+            match self.transact(&fork, &env, None).await {
+                Ok(res) if res.is_success() => hi = mid,
+                Ok(_) | Err(EvmMiddlewareError::Evm(_)) => lo = mid,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(hi.into())
+    }
+}
+
+/// Returns whether the block refers to the forked block
+fn is_fork_block(fork: &Fork, block: Option<BlockId>) -> bool {
+    match block {
+        None | Some(BlockId::Number(BlockNumber::Latest)) => true,
+        Some(BlockId::Number(BlockNumber::Number(number))) => fork.number == number,
+        Some(BlockId::Hash(hash)) => fork.hash == Some(hash),
+        _ => false,
+    }
+}
+
+/// The gas limit of the transaction, saturated to `u64`, or the gas limit of the forked block
+fn gas_limit(tx: &TypedTransaction, fork: &Fork) -> u64 {
+    tx.gas().map(|gas| to_evm_u256(*gas).saturating_to()).unwrap_or_else(|| fork.gas_limit())
+}
+
+/// Converts a failed execution to the corresponding error
+fn execution_error<M: Middleware>(res: ExecutionResult) -> EvmMiddlewareError<M> {
+    match res {
+        ExecutionResult::Revert { output, .. } => {
+            EvmMiddlewareError::Revert(output.to_vec().into())
+        }
+        ExecutionResult::Halt { reason, .. } => EvmMiddlewareError::Halt(format!("{:?}", reason)),
+        ExecutionResult::Success { .. } => unreachable!("execution succeeded"),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for EvmMiddleware<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    type Error = EvmMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Executes the call in the local EVM if it targets the forked block
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        self.call_with_state(tx, block, None).await
+    }
+
+    /// Estimates the gas in the local EVM
+    async fn estimate_gas(&self, tx: &TypedTransaction) -> Result<U256, Self::Error> {
+        self.estimate_gas_with_fork(tx).await
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+type EvmCallFut<'a, M> = Pin<Box<dyn Future<Output = Result<Bytes, EvmMiddlewareError<M>>> + 'a>>;
+#[cfg(not(target_arch = "wasm32"))]
+type EvmCallFut<'a, M> =
+    Pin<Box<dyn Future<Output = Result<Bytes, EvmMiddlewareError<M>>> + Send + 'a>>;
+
+/// A builder which implements [`RawCall`] methods for overriding the parameters of a local
+/// `eth_call`.
+///
+/// `.await`ing an `EvmCallBuilder` resolves to the result of executing the call on the fork of the
+/// [`EvmMiddleware`].
+#[must_use = "EvmCallBuilder does nothing unless you `.await` or poll it"]
+pub enum EvmCallBuilder<'a, M: Middleware>
+where
+    M::Error: 'static,
+{
+    /// The primary builder which exposes [`RawCall`] methods.
+    Build(EvmCaller<'a, M>),
+    /// Used by the [`std::future::Future`] implementation.
+    Wait(EvmCallFut<'a, M>),
+}
+
+impl<M: Middleware> fmt::Debug for EvmCallBuilder<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Build(call) => f.debug_tuple("Build").field(call).finish(),
+            Self::Wait(_) => f.debug_tuple("Wait").field(&"< Future >").finish(),
+        }
+    }
+}
+
+impl<'a, M: Middleware> EvmCallBuilder<'a, M> {
+    pub fn new(evm: &'a EvmMiddleware<M>, tx: &'a TypedTransaction) -> Self {
+        Self::Build(EvmCaller { evm, tx, block: None, state: None })
+    }
+
+    /// Applies a closure `f` to a `EvmCallBuilder::Build`. Does nothing for
+    /// `EvmCallBuilder::Wait`.
+    pub fn map_input<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut EvmCaller<'a, M>),
+    {
+        match self {
+            Self::Build(mut call) => {
+                f(&mut call);
+                Self::Build(call)
+            }
+            wait => wait,
+        }
+    }
+}
+
+impl<'a, M: Middleware> RawCall<'a> for EvmCallBuilder<'a, M> {
+    /// Sets the block number to execute against
+    fn block(self, id: BlockId) -> Self {
+        self.map_input(|call| call.block = Some(id))
+    }
+
+    /// Sets the [state override set](https://geth.ethereum.org/docs/rpc/ns-eth#3-object---state-override-set)
+    /// that is applied on top of the forked state.
+    fn state(self, state: &'a spoof::State) -> Self {
+        self.map_input(|call| call.state = Some(state))
+    }
+}
+
+impl<'a, M: Middleware + 'a> Future for EvmCallBuilder<'a, M>
+where
+    M::Error: 'static,
+{
+    type Output = Result<Bytes, EvmMiddlewareError<M>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let pin = self.get_mut();
+        loop {
+            match pin {
+                EvmCallBuilder::Build(ref call) => {
+                    let EvmCaller { evm, tx, block, state } = *call;
+                    let fut = Box::pin(evm.call_with_state(tx, block, state));
+                    *pin = EvmCallBuilder::Wait(fut);
+                }
+                EvmCallBuilder::Wait(ref mut fut) => return fut.as_mut().poll(cx),
+            }
+        }
+    }
+}
+
+/// Holds the inputs of a local `eth_call` along with the [`EvmMiddleware`].
+/// This type is constructed by [`EvmCallBuilder::new`].
+pub struct EvmCaller<'a, M> {
+    evm: &'a EvmMiddleware<M>,
+    tx: &'a TypedTransaction,
+    block: Option<BlockId>,
+    state: Option<&'a spoof::State>,
+}
+
+impl<M> Clone for EvmCaller<'_, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for EvmCaller<'_, M> {}
+
+impl<M> fmt::Debug for EvmCaller<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvmCaller")
+            .field("tx", &self.tx)
+            .field("block", &self.block)
+            .field("state", &self.state)
+            .finish()
+    }
+}
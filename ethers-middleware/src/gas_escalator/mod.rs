@@ -89,6 +89,7 @@ impl<M, E> Middleware for GasEscalatorMiddleware<M, E>
 where
     M: Middleware,
     E: GasEscalator,
+    M::Error: 'static,
 {
     type Error = GasEscalatorError<M>;
     type Provider = M::Provider;
@@ -129,6 +130,7 @@ impl<M, E> GasEscalatorMiddleware<M, E>
 where
     M: Middleware,
     E: GasEscalator,
+    M::Error: 'static,
 {
     /// Initializes the middleware with the provided gas escalator and the chosen
     /// escalation frequency (per block or per second)
@@ -238,7 +240,10 @@ where
 }
 
 // Boilerplate
-impl<M: Middleware> FromErr<M::Error> for GasEscalatorError<M> {
+impl<M: Middleware> FromErr<M::Error> for GasEscalatorError<M>
+where
+    M::Error: 'static,
+{
     fn from(src: M::Error) -> GasEscalatorError<M> {
         GasEscalatorError::MiddlewareError(src)
     }
@@ -246,10 +251,13 @@ impl<M: Middleware> FromErr<M::Error> for GasEscalatorError<M> {
 
 #[derive(Error, Debug)]
 /// Error thrown when the GasEscalator interacts with the blockchain
-pub enum GasEscalatorError<M: Middleware> {
+pub enum GasEscalatorError<M: Middleware>
+where
+    M::Error: 'static,
+{
     #[error("{0}")]
     /// Thrown when an internal middleware errors
-    MiddlewareError(M::Error),
+    MiddlewareError(#[source] M::Error),
 
     #[error("Gas escalation is only supported for EIP2930 or Legacy transactions")]
     UnsupportedTxType,
@@ -22,18 +22,24 @@ where
 }
 
 #[derive(Error, Debug)]
-pub enum MiddlewareError<M: Middleware> {
+pub enum MiddlewareError<M: Middleware>
+where
+    M::Error: 'static,
+{
     #[error(transparent)]
     GasOracleError(#[from] GasOracleError),
 
     #[error("{0}")]
-    MiddlewareError(M::Error),
+    MiddlewareError(#[source] M::Error),
 
     #[error("This gas price oracle only works with Legacy and EIP2930 transactions.")]
     UnsupportedTxType,
 }
 
-impl<M: Middleware> FromErr<M::Error> for MiddlewareError<M> {
+impl<M: Middleware> FromErr<M::Error> for MiddlewareError<M>
+where
+    M::Error: 'static,
+{
     fn from(src: M::Error) -> MiddlewareError<M> {
         MiddlewareError::MiddlewareError(src)
     }
@@ -45,6 +51,7 @@ impl<M, G> Middleware for GasOracleMiddleware<M, G>
 where
     M: Middleware,
     G: GasOracle,
+    M::Error: 'static,
 {
     type Error = MiddlewareError<M>;
     type Provider = M::Provider;
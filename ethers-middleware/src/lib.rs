@@ -1,4 +1,5 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(unsafe_code)]
 #![deny(rustdoc::broken_intra_doc_links)]
 
@@ -27,6 +28,11 @@ pub mod transformer;
 pub mod signer;
 pub use signer::SignerMiddleware;
 
+/// The [MultiSigner](crate::MultiSignerMiddleware) is used to locally sign transactions and
+/// messages with one of several signers, picked by the `from` address of the transaction
+pub mod multi_signer;
+pub use multi_signer::MultiSignerMiddleware;
+
 /// The [Policy](crate::PolicyMiddleware) is used to ensure transactions comply with the rules
 /// configured in the `PolicyMiddleware` before sending them.
 pub mod policy;
@@ -36,3 +42,31 @@ pub use policy::PolicyMiddleware;
 /// before the chain tip
 pub mod timelag;
 pub use timelag::TimeLag;
+
+/// The [Bundle middleware](crate::bundle::BundleMiddleware) is used to submit bundles of signed
+/// transactions privately to a relay, e.g. Flashbots
+pub mod bundle;
+pub use bundle::BundleMiddleware;
+
+/// The [Cache middleware](crate::cache::CacheMiddleware) is used to cache the responses to queries
+/// whose result can no longer change, e.g. blocks by hash or state at final blocks
+pub mod cache;
+pub use cache::CacheMiddleware;
+
+/// The [Metrics middleware](crate::MetricsMiddleware) records the latency, the outcome and the
+/// number of in-flight calls of every method of the inner middleware
+pub mod metrics;
+pub use metrics::MetricsMiddleware;
+
+/// The [UserOperation middleware](crate::UserOperationMiddleware) sends transactions as EIP-4337
+/// user operations of a smart contract account to a bundler
+pub mod user_operation;
+pub use user_operation::UserOperationMiddleware;
+
+/// The [EVM middleware](crate::evm::EvmMiddleware) executes `eth_call` and `eth_estimateGas` in a
+/// local EVM on a lazily fetched fork of the chain instead of sending them to the node
+#[cfg(feature = "evm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "evm")))]
+pub mod evm;
+#[cfg(feature = "evm")]
+pub use evm::EvmMiddleware;
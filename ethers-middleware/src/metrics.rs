@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Block, BlockId, Bytes, Filter, Log, NameOrAddress,
+    Transaction, TransactionReceipt, TxHash, H256, U256, U64,
+};
+use ethers_providers::{
+    metrics::{record_request, MetricsRecorder, RequestMetrics},
+    FromErr, Middleware, PendingTransaction,
+};
+use thiserror::Error;
+
+#[derive(Debug)]
+/// Middleware used for recording the latency, the outcome and the number of in-flight requests of
+/// the calls to the inner middleware with a [`MetricsRecorder`], and for sending every call in a
+/// `tracing` span.
+///
+/// The calls are recorded under the name of the `Middleware` method, e.g. `send_transaction`,
+/// which includes the requests made by the inner middlewares on the way. JSON-RPC error codes are
+/// only found if the inner middleware returns them as its error source, so to record the requests
+/// that are actually sent to the node wrap the transport in a
+/// [`MetricsClient`](ethers_providers::MetricsClient) instead.
+///
+/// Only the most common methods are recorded, all other calls are forwarded as-is.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::MetricsMiddleware;
+/// use ethers_providers::{metrics::RequestMetrics, Http, Middleware, Provider};
+/// use std::{convert::TryFrom, sync::Arc};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let metrics = Arc::new(RequestMetrics::default());
+/// let client = MetricsMiddleware::new_with_recorder(provider, metrics.clone());
+///
+/// client.get_block_number().await?;
+/// println!("{}", metrics.to_prometheus("ethers"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct MetricsMiddleware<M, R = RequestMetrics> {
+    inner: M,
+    recorder: R,
+}
+
+#[derive(Error, Debug)]
+/// Thrown when the metrics middleware fails
+pub enum MetricsMiddlewareError<M: Middleware>
+where
+    M::Error: 'static,
+{
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for MetricsMiddlewareError<M> {
+    fn from(src: M::Error) -> Self {
+        MetricsMiddlewareError::MiddlewareError(src)
+    }
+}
+
+impl<M> MetricsMiddleware<M, RequestMetrics>
+where
+    M: Middleware,
+{
+    /// Instantiates the middleware, which aggregates the metrics with a [`RequestMetrics`]
+    pub fn new(inner: M) -> Self {
+        Self::new_with_recorder(inner, RequestMetrics::default())
+    }
+}
+
+impl<M, R> MetricsMiddleware<M, R>
+where
+    M: Middleware,
+    R: MetricsRecorder,
+{
+    /// Instantiates the middleware, which sends the metrics to the `recorder`
+    pub fn new_with_recorder(inner: M, recorder: R) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Returns the recorder of the metrics
+    pub fn recorder(&self) -> &R {
+        &self.recorder
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, R> Middleware for MetricsMiddleware<M, R>
+where
+    M: Middleware,
+    M::Error: 'static,
+    R: MetricsRecorder,
+{
+    type Error = MetricsMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn get_block_number(&self) -> Result<U64, Self::Error> {
+        record_request(&self.recorder, "get_block_number", self.inner.get_block_number())
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        record_request(&self.recorder, "send_transaction", self.inner.send_transaction(tx, block))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn send_raw_transaction<'a>(
+        &'a self,
+        tx: Bytes,
+    ) -> Result<PendingTransaction<'a, Self::Provider>, Self::Error> {
+        record_request(&self.recorder, "send_raw_transaction", self.inner.send_raw_transaction(tx))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn estimate_gas(&self, tx: &TypedTransaction) -> Result<U256, Self::Error> {
+        record_request(&self.recorder, "estimate_gas", self.inner.estimate_gas(tx))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        record_request(&self.recorder, "call", self.inner.call(tx, block))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_chainid(&self) -> Result<U256, Self::Error> {
+        record_request(&self.recorder, "get_chainid", self.inner.get_chainid())
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_gas_price(&self) -> Result<U256, Self::Error> {
+        record_request(&self.recorder, "get_gas_price", self.inner.get_gas_price())
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn estimate_eip1559_fees(
+        &self,
+        estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
+    ) -> Result<(U256, U256), Self::Error> {
+        record_request(
+            &self.recorder,
+            "estimate_eip1559_fees",
+            self.inner.estimate_eip1559_fees(estimator),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        record_request(&self.recorder, "get_logs", self.inner.get_logs(filter))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_transaction_count<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        record_request(
+            &self.recorder,
+            "get_transaction_count",
+            self.inner.get_transaction_count(from, block),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+
+    async fn get_balance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        record_request(&self.recorder, "get_balance", self.inner.get_balance(from, block))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_code<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        at: T,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        record_request(&self.recorder, "get_code", self.inner.get_code(at, block))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_storage_at<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, Self::Error> {
+        record_request(
+            &self.recorder,
+            "get_storage_at",
+            self.inner.get_storage_at(from, location, block),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+
+    async fn get_block<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<TxHash>>, Self::Error> {
+        record_request(&self.recorder, "get_block", self.inner.get_block(block_hash_or_number))
+            .await
+            .map_err(FromErr::from)
+    }
+
+    async fn get_block_with_txs<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<Transaction>>, Self::Error> {
+        record_request(
+            &self.recorder,
+            "get_block_with_txs",
+            self.inner.get_block_with_txs(block_hash_or_number),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        record_request(
+            &self.recorder,
+            "get_transaction",
+            self.inner.get_transaction(transaction_hash),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+
+    async fn get_transaction_receipt<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<TransactionReceipt>, Self::Error> {
+        record_request(
+            &self.recorder,
+            "get_transaction_receipt",
+            self.inner.get_transaction_receipt(transaction_hash),
+        )
+        .await
+        .map_err(FromErr::from)
+    }
+}
@@ -0,0 +1,332 @@
+use ethers_core::types::{
+    transaction::{eip2718::TypedTransaction, eip712::Eip712},
+    Address, BlockId, BlockNumber, Bytes, Signature,
+};
+use ethers_providers::{maybe, FromErr, Middleware, PendingTransaction};
+use ethers_signers::Signer;
+
+use async_trait::async_trait;
+use futures_util::future::try_join_all;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use thiserror::Error;
+
+/// How the [`MultiSignerMiddleware`] picks the signer of a transaction without a `from` address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignerSelection {
+    /// Uses the signers in turn, in the order they were added
+    #[default]
+    RoundRobin,
+    /// Uses the signer with the fewest transactions in the mempool, i.e. the smallest difference
+    /// between its pending and its latest nonce. Ties go to the signer added first.
+    LeastPending,
+}
+
+#[derive(Debug)]
+/// Middleware used for locally signing transactions with one of several signers, e.g. to send
+/// transactions from a set of hot wallets through a single client.
+///
+/// Transactions, messages and typed data are signed by the signer of their `from` address.
+/// Transactions without a `from` address are sent from a signer picked according to the
+/// [`SignerSelection`], transactions from any other address are delegated to the inner
+/// middleware.
+///
+/// Signers of different types, e.g. a Ledger and local wallets, can be combined by boxing them
+/// as [`DynSigner`](ethers_signers::DynSigner)s.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::TransactionRequest;
+/// use ethers_middleware::{multi_signer::SignerSelection, MultiSignerMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let wallets = [
+///     "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse::<LocalWallet>()?,
+///     "cd8c407233c0560f6de24bb2dc60a8b02335c959a1a17f749ce6c1ccf63d74a7".parse::<LocalWallet>()?,
+/// ];
+/// let client =
+///     MultiSignerMiddleware::new(provider, wallets).selection(SignerSelection::LeastPending);
+///
+/// // sent from the wallet with the fewest pending transactions
+/// let tx = TransactionRequest::pay("vitalik.eth", 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiSignerMiddleware<M, S> {
+    inner: M,
+    signers: HashMap<Address, S>,
+    /// The addresses of the signers in the order they were added
+    addresses: Vec<Address>,
+    selection: SignerSelection,
+    next: AtomicUsize,
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the client interacts with the blockchain
+pub enum MultiSignerMiddlewareError<M: Middleware, S: Signer>
+where
+    M::Error: 'static,
+{
+    #[error("{0}")]
+    /// Thrown when the internal call to the signer fails
+    SignerError(S::Error),
+
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(#[source] M::Error),
+
+    /// Thrown if a signature is requested from an address without a signer
+    #[error("no signer for address {0:?}")]
+    UnknownSigner(Address),
+    /// Thrown if a signer has to be picked but the middleware has none
+    #[error("the middleware has no signers")]
+    NoSigners,
+    /// Thrown if the signer's chain_id is different than the chain_id of the transaction
+    #[error("specified chain_id is different than the signer's chain_id")]
+    DifferentChainID,
+}
+
+impl<M: Middleware, S: Signer> FromErr<M::Error> for MultiSignerMiddlewareError<M, S> {
+    fn from(src: M::Error) -> MultiSignerMiddlewareError<M, S> {
+        MultiSignerMiddlewareError::MiddlewareError(src)
+    }
+}
+
+impl<M, S> MultiSignerMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    /// Creates a new client from the provider and the signers, which are picked in a round-robin
+    /// fashion for transactions without a `from` address
+    pub fn new(inner: M, signers: impl IntoIterator<Item = S>) -> Self {
+        let mut this = Self {
+            inner,
+            signers: HashMap::new(),
+            addresses: Vec::new(),
+            selection: SignerSelection::default(),
+            next: AtomicUsize::new(0),
+        };
+        signers.into_iter().for_each(|signer| this.add_signer(signer));
+        this
+    }
+
+    /// Sets how the signer of transactions without a `from` address is picked
+    #[must_use]
+    pub fn selection(mut self, selection: SignerSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Adds the signer, replacing the signer with the same address if any
+    pub fn add_signer(&mut self, signer: S) {
+        let address = signer.address();
+        if self.signers.insert(address, signer).is_none() {
+            self.addresses.push(address);
+        }
+    }
+
+    /// Removes and returns the signer of the `address`
+    pub fn remove_signer(&mut self, address: &Address) -> Option<S> {
+        self.addresses.retain(|addr| addr != address);
+        self.signers.remove(address)
+    }
+
+    /// Returns the signer of the `address`
+    pub fn signer(&self, address: &Address) -> Option<&S> {
+        self.signers.get(address)
+    }
+
+    /// Returns the addresses of the signers in the order they were added
+    pub fn addresses(&self) -> &[Address] {
+        &self.addresses
+    }
+
+    /// Picks the signer of a transaction without a `from` address
+    pub async fn select_signer(&self) -> Result<Address, MultiSignerMiddlewareError<M, S>> {
+        if self.addresses.is_empty() {
+            return Err(MultiSignerMiddlewareError::NoSigners)
+        }
+        match self.selection {
+            SignerSelection::RoundRobin => {
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                Ok(self.addresses[next % self.addresses.len()])
+            }
+            SignerSelection::LeastPending => {
+                let pending = try_join_all(self.addresses.iter().map(|address| async move {
+                    let pending = self
+                        .inner
+                        .get_transaction_count(*address, Some(BlockNumber::Pending.into()))
+                        .await?;
+                    let latest = self
+                        .inner
+                        .get_transaction_count(*address, Some(BlockNumber::Latest.into()))
+                        .await?;
+                    Ok(pending.saturating_sub(latest))
+                }))
+                .await
+                .map_err(MultiSignerMiddlewareError::MiddlewareError)?;
+
+                let (idx, _) = pending
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(idx, pending)| (**pending, *idx))
+                    .expect("not empty");
+                Ok(self.addresses[idx])
+            }
+        }
+    }
+
+    /// Encodes and signs the typed data according EIP-712 with the signer of the `from` address
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+        from: &Address,
+    ) -> Result<Signature, MultiSignerMiddlewareError<M, S>> {
+        self.get_signer(from)?
+            .sign_typed_data(payload)
+            .await
+            .map_err(MultiSignerMiddlewareError::SignerError)
+    }
+
+    fn get_signer(&self, address: &Address) -> Result<&S, MultiSignerMiddlewareError<M, S>> {
+        self.signers.get(address).ok_or(MultiSignerMiddlewareError::UnknownSigner(*address))
+    }
+
+    /// Signs and returns the RLP encoding of the signed transaction with the signer of its `from`
+    /// address
+    async fn sign_transaction(
+        &self,
+        mut tx: TypedTransaction,
+        signer: &S,
+    ) -> Result<Bytes, MultiSignerMiddlewareError<M, S>> {
+        let chain_id = signer.chain_id();
+        match tx.chain_id() {
+            Some(id) if id.as_u64() != chain_id => {
+                return Err(MultiSignerMiddlewareError::DifferentChainID)
+            }
+            None => {
+                tx.set_chain_id(chain_id);
+            }
+            _ => {}
+        }
+
+        let signature =
+            signer.sign_transaction(&tx).await.map_err(MultiSignerMiddlewareError::SignerError)?;
+        Ok(tx.rlp_signed(&signature))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for MultiSignerMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    type Error = MultiSignerMiddlewareError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// `MultiSignerMiddleware` is instantiated with signers.
+    async fn is_signer(&self) -> bool {
+        !self.signers.is_empty()
+    }
+
+    /// Returns the addresses of all signers
+    async fn get_accounts(&self) -> Result<Vec<Address>, Self::Error> {
+        Ok(self.addresses.clone())
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+        from: Address,
+    ) -> Result<Signature, Self::Error> {
+        self.get_signer(&from)?
+            .sign_transaction(tx)
+            .await
+            .map_err(MultiSignerMiddlewareError::SignerError)
+    }
+
+    /// Picks a signer if the transaction has no `from` address and fills the nonce and the chain
+    /// id of the signer
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let from = match tx.from() {
+            Some(from) => *from,
+            None => self.select_signer().await?,
+        };
+        tx.set_from(from);
+
+        if let Some(signer) = self.signers.get(&from) {
+            if tx.chain_id().is_none() {
+                tx.set_chain_id(signer.chain_id());
+            }
+            let nonce = maybe(tx.nonce().cloned(), self.get_transaction_count(from, block)).await?;
+            tx.set_nonce(nonce);
+        }
+
+        self.inner()
+            .fill_transaction(tx, block)
+            .await
+            .map_err(MultiSignerMiddlewareError::MiddlewareError)
+    }
+
+    /// Signs the transaction with the signer of its `from` address and broadcasts it. Transactions
+    /// from other addresses are delegated to the inner middleware.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.fill_transaction(&mut tx, block).await?;
+
+        let signer = match tx.from().and_then(|from| self.signers.get(from)) {
+            Some(signer) => signer,
+            None => {
+                return self
+                    .inner
+                    .send_transaction(tx, block)
+                    .await
+                    .map_err(MultiSignerMiddlewareError::MiddlewareError)
+            }
+        };
+
+        let signed_tx = self.sign_transaction(tx, signer).await?;
+        self.inner
+            .send_raw_transaction(signed_tx)
+            .await
+            .map_err(MultiSignerMiddlewareError::MiddlewareError)
+    }
+
+    /// Signs a message with the signer of the `from` address
+    async fn sign<T: Into<Bytes> + Send + Sync>(
+        &self,
+        data: T,
+        from: &Address,
+    ) -> Result<Signature, Self::Error> {
+        self.get_signer(from)?
+            .sign_message(data.into())
+            .await
+            .map_err(MultiSignerMiddlewareError::SignerError)
+    }
+}
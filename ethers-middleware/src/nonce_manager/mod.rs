@@ -0,0 +1,272 @@
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, *};
+use ethers_providers::{is_nonce_too_low, FromErr, Middleware, PendingTransaction};
+use thiserror::Error;
+
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::FileNonceStore;
+pub use store::{InMemoryNonceStore, NonceStore, NonceStoreError};
+
+#[derive(Debug)]
+/// Middleware used for calculating nonces locally, useful for signing multiple
+/// consecutive transactions without waiting for them to hit the mempool
+///
+/// The nonces are kept in a [`NonceStore`] per sender, transactions without a `from` field are sent
+/// from the `address` the manager was created with. By default the nonces are only kept in memory,
+/// a [`FileNonceStore`] can be used to share them between multiple processes.
+///
+/// If a transaction is rejected because its nonce is too low, e.g. because another sender that is
+/// not coordinated via the store used the nonce, the nonce is resynced with the chain and the
+/// transaction is sent again with a fresh nonce. Nonces that were handed out but never reached the
+/// node, e.g. because the transaction was dropped, can be found with
+/// [`NonceManagerMiddleware::nonce_gaps`] and filled with [`NonceManagerMiddleware::fill_gaps`].
+pub struct NonceManagerMiddleware<M, S = InMemoryNonceStore> {
+    inner: M,
+    address: Address,
+    store: S,
+}
+
+impl<M> NonceManagerMiddleware<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Instantiates the nonce manager with an in-memory nonce store. The `address` should be the
+    /// address which you'll be sending transactions from
+    pub fn new(inner: M, address: Address) -> Self {
+        Self::new_with_store(inner, address, InMemoryNonceStore::default())
+    }
+}
+
+impl<M, S> NonceManagerMiddleware<M, S>
+where
+    M: Middleware,
+    S: NonceStore,
+    M::Error: 'static,
+{
+    /// Instantiates the nonce manager with the given nonce store. The `address` is used as sender
+    /// for transactions without a `from` field
+    pub fn new_with_store(inner: M, address: Address, store: S) -> Self {
+        Self { inner, address, store }
+    }
+
+    /// Returns the nonce store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the next nonce to be used for the manager's address. The nonce starts at 0 if it
+    /// was not initialized via [`NonceManagerMiddleware::initialize_nonce`].
+    pub async fn next(&self) -> Result<U256, NonceManagerError<M>> {
+        if let Some(nonce) = self.store.reserve(self.address).await? {
+            return Ok(nonce)
+        }
+        self.store.sync(self.address, U256::zero()).await?;
+        Ok(self.store.reserve(self.address).await?.unwrap_or_default())
+    }
+
+    /// Initializes the nonce of the manager's address with the transaction count at `block`, if
+    /// the store does not know the address yet. Returns the next nonce.
+    pub async fn initialize_nonce(
+        &self,
+        block: Option<BlockId>,
+    ) -> Result<U256, NonceManagerError<M>> {
+        if let Some(nonce) = self.store.get(self.address).await? {
+            return Ok(nonce)
+        }
+        let nonce = self
+            .inner
+            .get_transaction_count(self.address, block)
+            .await
+            .map_err(NonceManagerError::MiddlewareError)?;
+        Ok(self.store.sync(self.address, nonce).await?)
+    }
+
+    /// Raises the stored nonce of `address` to its pending transaction count, in case
+    /// transactions were sent without the manager. Returns the next nonce.
+    pub async fn resync(&self, address: Address) -> Result<U256, NonceManagerError<M>> {
+        let nonce = self
+            .inner
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(NonceManagerError::MiddlewareError)?;
+        tracing::debug!(?address, ?nonce, "resyncing nonce");
+        Ok(self.store.sync(address, nonce).await?)
+    }
+
+    /// Returns the nonces of `address` that were handed out by the manager but are not known to
+    /// the node, e.g. because the transaction was dropped from the mempool. Transactions with
+    /// higher nonces are stuck until the gaps are filled.
+    ///
+    /// Nonces that were reserved by another manager sharing the store but not yet broadcast are
+    /// reported as well, so this should only be called when all senders are idle or their
+    /// transactions are expected to have reached the node.
+    ///
+    /// If the node does not support `txpool_content`, only the first gap is reported because the
+    /// queued transactions are unknown.
+    pub async fn nonce_gaps(&self, address: Address) -> Result<Vec<U256>, NonceManagerError<M>> {
+        let next = match self.store.get(address).await? {
+            Some(next) => next,
+            None => return Ok(Vec::new()),
+        };
+        // the pending count is the first nonce that is neither mined nor pending
+        let pending = self
+            .inner
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(NonceManagerError::MiddlewareError)?;
+        if pending >= next {
+            return Ok(Vec::new())
+        }
+
+        let queued = match self.inner.txpool_content().await {
+            Ok(content) => content.queued.get(&address).cloned().unwrap_or_default(),
+            Err(err) => {
+                tracing::debug!(?err, "txpool_content unavailable, only the first gap is known");
+                return Ok(vec![pending])
+            }
+        };
+        let mut gaps = Vec::new();
+        let mut nonce = pending;
+        while nonce < next {
+            if !queued.contains_key(&nonce.to_string()) {
+                gaps.push(nonce);
+            }
+            nonce += U256::one();
+        }
+        Ok(gaps)
+    }
+
+    /// Fills the [gaps](NonceManagerMiddleware::nonce_gaps) of `address` by sending a transaction
+    /// without value from the address to itself for every missing nonce, which unblocks the
+    /// transactions with higher nonces.
+    pub async fn fill_gaps(
+        &self,
+        address: Address,
+    ) -> Result<Vec<PendingTransaction<'_, M::Provider>>, NonceManagerError<M>> {
+        let mut pending = Vec::new();
+        for nonce in self.nonce_gaps(address).await? {
+            tracing::debug!(?address, ?nonce, "filling nonce gap");
+            let tx = TransactionRequest::new().from(address).to(address).value(0u64).nonce(nonce);
+            pending.push(
+                self.inner
+                    .send_transaction(tx, None)
+                    .await
+                    .map_err(NonceManagerError::MiddlewareError)?,
+            );
+        }
+        Ok(pending)
+    }
+
+    /// Returns the sender of the transaction, defaults to the manager's address
+    fn sender(&self, tx: &TypedTransaction) -> Address {
+        tx.from().copied().unwrap_or(self.address)
+    }
+
+    async fn get_transaction_count_with_manager(
+        &self,
+        address: Address,
+        block: Option<BlockId>,
+    ) -> Result<U256, NonceManagerError<M>> {
+        if let Some(nonce) = self.store.reserve(address).await? {
+            return Ok(nonce)
+        }
+
+        // initialize the nonce the first time the sender is seen
+        let nonce = self
+            .inner
+            .get_transaction_count(address, block)
+            .await
+            .map_err(NonceManagerError::MiddlewareError)?;
+        self.store.sync(address, nonce).await?;
+        Ok(self.store.reserve(address).await?.unwrap_or(nonce))
+    }
+}
+
+#[derive(Error, Debug)]
+/// Thrown when an error happens at the Nonce Manager
+pub enum NonceManagerError<M: Middleware>
+where
+    M::Error: 'static,
+{
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
+    /// Thrown when the nonce store errors
+    #[error(transparent)]
+    StoreError(#[from] NonceStoreError),
+}
+
+impl<M: Middleware> FromErr<M::Error> for NonceManagerError<M>
+where
+    M::Error: 'static,
+{
+    fn from(src: M::Error) -> Self {
+        NonceManagerError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for NonceManagerMiddleware<M, S>
+where
+    M: Middleware,
+    S: NonceStore,
+    M::Error: 'static,
+{
+    type Error = NonceManagerError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        if tx.nonce().is_none() {
+            let sender = self.sender(tx);
+            tx.set_nonce(self.get_transaction_count_with_manager(sender, block).await?);
+        }
+
+        Ok(self
+            .inner()
+            .fill_transaction(tx, block)
+            .await
+            .map_err(NonceManagerError::MiddlewareError)?)
+    }
+
+    /// Signs and broadcasts the transaction. The optional parameter `block` can be passed so that
+    /// gas cost and nonce calculations take it into account. For simple transactions this can be
+    /// left to `None`.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        let sender = self.sender(&tx);
+
+        let managed = tx.nonce().is_none();
+        if managed {
+            tx.set_nonce(self.get_transaction_count_with_manager(sender, block).await?);
+        }
+
+        match self.inner.send_transaction(tx.clone(), block).await {
+            Ok(tx_hash) => Ok(tx_hash),
+            Err(err) if managed && is_nonce_too_low(&err) => {
+                // the nonce was used by a sender that does not share the store, resync the
+                // stored nonce with the chain and try re-submitting the transaction
+                self.resync(sender).await?;
+                tx.set_nonce(self.get_transaction_count_with_manager(sender, block).await?);
+                self.inner.send_transaction(tx, block).await.map_err(FromErr::from)
+            }
+            // propagate the error otherwise
+            Err(err) => Err(FromErr::from(err)),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use ethers_core::types::{Address, U256};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Error, Debug)]
+/// Error thrown by a [`NonceStore`]
+pub enum NonceStoreError {
+    /// An error reading or writing the persisted nonces
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// An error (de)serializing the persisted nonces
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    /// An error of a custom store implementation
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Keeps track of the next unused nonce of every sender.
+///
+/// All methods must be atomic, so that multiple
+/// [`NonceManagerMiddleware`](crate::NonceManagerMiddleware)s sharing the same store, possibly in
+/// different processes, never hand out the same nonce twice.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait NonceStore: Send + Sync + fmt::Debug {
+    /// Returns the next unused nonce of the `address` or `None` if the store does not know the
+    /// address yet
+    async fn get(&self, address: Address) -> Result<Option<U256>, NonceStoreError>;
+
+    /// Raises the next unused nonce of the `address` to `nonce`, if it is lower or not set yet.
+    /// Returns the next unused nonce.
+    async fn sync(&self, address: Address, nonce: U256) -> Result<U256, NonceStoreError>;
+
+    /// Returns the next unused nonce of the `address` and increments it, or `None` if the store
+    /// does not know the address yet
+    async fn reserve(&self, address: Address) -> Result<Option<U256>, NonceStoreError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: NonceStore + ?Sized> NonceStore for Arc<S> {
+    async fn get(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        (**self).get(address).await
+    }
+
+    async fn sync(&self, address: Address, nonce: U256) -> Result<U256, NonceStoreError> {
+        (**self).sync(address, nonce).await
+    }
+
+    async fn reserve(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        (**self).reserve(address).await
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: NonceStore + ?Sized> NonceStore for Box<S> {
+    async fn get(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        (**self).get(address).await
+    }
+
+    async fn sync(&self, address: Address, nonce: U256) -> Result<U256, NonceStoreError> {
+        (**self).sync(address, nonce).await
+    }
+
+    async fn reserve(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        (**self).reserve(address).await
+    }
+}
+
+/// A [`NonceStore`] that keeps the nonces in memory, only shared within the same process
+#[derive(Debug, Default)]
+pub struct InMemoryNonceStore {
+    nonces: Mutex<HashMap<Address, U256>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NonceStore for InMemoryNonceStore {
+    async fn get(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        Ok(self.nonces.lock().unwrap().get(&address).copied())
+    }
+
+    async fn sync(&self, address: Address, nonce: U256) -> Result<U256, NonceStoreError> {
+        let mut nonces = self.nonces.lock().unwrap();
+        let next = nonces.entry(address).or_insert(nonce);
+        *next = (*next).max(nonce);
+        Ok(*next)
+    }
+
+    async fn reserve(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        Ok(self.nonces.lock().unwrap().get_mut(&address).map(|next| {
+            let nonce = *next;
+            *next = nonce + 1;
+            nonce
+        }))
+    }
+}
+
+/// A [`NonceStore`] that persists the nonces in a JSON file which is exclusively locked for every
+/// access, so it can be shared by multiple processes on the same machine.
+///
+/// Nonces are only unique per chain, so a separate file should be used for every chain.
+///
+/// The file is accessed on the blocking thread pool of tokio, so the store must be used within a
+/// tokio runtime.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileNonceStore {
+    path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileNonceStore {
+    /// Creates a store that persists the nonces at `path`, the file is created on first use
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the file the nonces are stored in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens and locks the file on the blocking thread pool, then applies `f` to the stored
+    /// nonces and persists them if they were modified
+    async fn with_nonces<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut BTreeMap<Address, U256>) -> T + Send + 'static,
+    ) -> Result<T, NonceStoreError> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || Self::locked(&path, f))
+            .await
+            .map_err(|err| NonceStoreError::Other(Box::new(err)))?
+    }
+
+    fn locked<T>(
+        path: &Path,
+        f: impl FnOnce(&mut BTreeMap<Address, U256>) -> T,
+    ) -> Result<T, NonceStoreError> {
+        use fs2::FileExt;
+
+        let mut file =
+            OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        file.lock_exclusive()?;
+        let res = Self::update(&mut file, f);
+        file.unlock()?;
+        res
+    }
+
+    fn update<T>(
+        file: &mut File,
+        f: impl FnOnce(&mut BTreeMap<Address, U256>) -> T,
+    ) -> Result<T, NonceStoreError> {
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let mut nonces: BTreeMap<Address, U256> = if content.trim().is_empty() {
+            Default::default()
+        } else {
+            serde_json::from_str(&content)?
+        };
+
+        let before = nonces.clone();
+        let res = f(&mut nonces);
+        if nonces != before {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(serde_json::to_string_pretty(&nonces)?.as_bytes())?;
+            file.sync_all()?;
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl NonceStore for FileNonceStore {
+    async fn get(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        self.with_nonces(move |nonces| nonces.get(&address).copied()).await
+    }
+
+    async fn sync(&self, address: Address, nonce: U256) -> Result<U256, NonceStoreError> {
+        self.with_nonces(move |nonces| {
+            let next = nonces.entry(address).or_insert(nonce);
+            *next = (*next).max(nonce);
+            *next
+        })
+        .await
+    }
+
+    async fn reserve(&self, address: Address) -> Result<Option<U256>, NonceStoreError> {
+        self.with_nonces(move |nonces| {
+            nonces.get_mut(&address).map(|next| {
+                let nonce = *next;
+                *next = nonce + 1;
+                nonce
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn assert_store(store: &dyn NonceStore) {
+        let (a, b) = (Address::random(), Address::random());
+        assert_eq!(store.get(a).await.unwrap(), None);
+        assert_eq!(store.reserve(a).await.unwrap(), None);
+
+        assert_eq!(store.sync(a, 5u64.into()).await.unwrap(), 5u64.into());
+        assert_eq!(store.reserve(a).await.unwrap(), Some(5u64.into()));
+        assert_eq!(store.reserve(a).await.unwrap(), Some(6u64.into()));
+        assert_eq!(store.get(a).await.unwrap(), Some(7u64.into()));
+
+        // syncing never lowers the nonce
+        assert_eq!(store.sync(a, 3u64.into()).await.unwrap(), 7u64.into());
+        assert_eq!(store.sync(a, 10u64.into()).await.unwrap(), 10u64.into());
+
+        // senders are independent
+        assert_eq!(store.sync(b, 0u64.into()).await.unwrap(), 0u64.into());
+        assert_eq!(store.reserve(b).await.unwrap(), Some(0u64.into()));
+        assert_eq!(store.get(a).await.unwrap(), Some(10u64.into()));
+    }
+
+    #[tokio::test]
+    async fn in_memory_store() {
+        assert_store(&InMemoryNonceStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn file_store() {
+        let path = std::env::temp_dir().join(format!("nonces-{:?}.json", Address::random()));
+        let store = FileNonceStore::new(&path);
+        assert_store(&store).await;
+
+        // the nonces are persisted
+        let nonces: BTreeMap<Address, U256> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(nonces.len(), 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_store_is_shared_between_tasks() {
+        let path = std::env::temp_dir().join(format!("nonces-{:?}.json", Address::random()));
+        let address = Address::random();
+        FileNonceStore::new(&path).sync(address, 0u64.into()).await.unwrap();
+
+        let handles = (0..4)
+            .map(|_| {
+                let store = FileNonceStore::new(&path);
+                tokio::spawn(async move {
+                    let mut nonces = Vec::new();
+                    for _ in 0..25 {
+                        nonces.push(store.reserve(address).await.unwrap().unwrap());
+                    }
+                    nonces
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut nonces = Vec::new();
+        for handle in handles {
+            nonces.extend(handle.await.unwrap());
+        }
+        nonces.sort();
+        assert_eq!(nonces, (0u64..100).map(U256::from).collect::<Vec<_>>());
+        std::fs::remove_file(path).unwrap();
+    }
+}
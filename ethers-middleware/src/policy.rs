@@ -62,6 +62,7 @@ impl<M, P> PolicyMiddleware<M, P>
 where
     M: Middleware,
     P: Policy,
+    M::Error: 'static,
 {
     /// Creates a new client from the provider and policy.
     pub fn new(inner: M, policy: P) -> Self {
@@ -71,13 +72,16 @@ where
 
 #[derive(Error, Debug)]
 /// Error thrown when the client interacts with the policy middleware.
-pub enum PolicyMiddlewareError<M: Middleware, P: Policy> {
+pub enum PolicyMiddlewareError<M: Middleware, P: Policy>
+where
+    M::Error: 'static,
+{
     /// Thrown when the internal policy errors
     #[error("{0:?}")]
     PolicyError(P::Error),
     /// Thrown when an internal middleware errors
-    #[error(transparent)]
-    MiddlewareError(M::Error),
+    #[error("{0}")]
+    MiddlewareError(#[source] M::Error),
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -86,6 +90,7 @@ impl<M, P> Middleware for PolicyMiddleware<M, P>
 where
     M: Middleware,
     P: Policy,
+    M::Error: 'static,
 {
     type Error = PolicyMiddlewareError<M, P>;
     type Provider = M::Provider;
@@ -66,7 +66,10 @@ pub struct SignerMiddleware<M, S> {
     pub(crate) address: Address,
 }
 
-impl<M: Middleware, S: Signer> FromErr<M::Error> for SignerMiddlewareError<M, S> {
+impl<M: Middleware, S: Signer> FromErr<M::Error> for SignerMiddlewareError<M, S>
+where
+    M::Error: 'static,
+{
     fn from(src: M::Error) -> SignerMiddlewareError<M, S> {
         SignerMiddlewareError::MiddlewareError(src)
     }
@@ -74,14 +77,17 @@ impl<M: Middleware, S: Signer> FromErr<M::Error> for SignerMiddlewareError<M, S>
 
 #[derive(Error, Debug)]
 /// Error thrown when the client interacts with the blockchain
-pub enum SignerMiddlewareError<M: Middleware, S: Signer> {
+pub enum SignerMiddlewareError<M: Middleware, S: Signer>
+where
+    M::Error: 'static,
+{
     #[error("{0}")]
     /// Thrown when the internal call to the signer fails
     SignerError(S::Error),
 
     #[error("{0}")]
     /// Thrown when an internal middleware errors
-    MiddlewareError(M::Error),
+    MiddlewareError(#[source] M::Error),
 
     /// Thrown if the `nonce` field is missing
     #[error("no nonce was specified")]
@@ -105,6 +111,7 @@ impl<M, S> SignerMiddleware<M, S>
 where
     M: Middleware,
     S: Signer,
+    M::Error: 'static,
 {
     /// Creates a new client from the provider and signer.
     /// Sets the address of this middleware to the address of the signer.
@@ -204,6 +211,7 @@ impl<M, S> Middleware for SignerMiddleware<M, S>
 where
     M: Middleware,
     S: Signer,
+    M::Error: 'static,
 {
     type Error = SignerMiddlewareError<M, S>;
     type Provider = M::Provider;
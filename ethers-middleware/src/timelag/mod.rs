@@ -15,10 +15,11 @@ type TimeLagResult<T, M> = Result<T, TimeLagError<M>>;
 pub enum TimeLagError<M>
 where
     M: Middleware,
+    M::Error: 'static,
 {
     #[error("{0}")]
     /// Thrown when an internal middleware errors
-    MiddlewareError(M::Error),
+    MiddlewareError(#[source] M::Error),
 
     #[error("Unsupported RPC. Timelag provider does not support filters or subscriptions.")]
     Unsupported,
@@ -51,6 +52,7 @@ where
 impl<M> TimeLag<M>
 where
     M: Middleware,
+    M::Error: 'static,
 {
     async fn normalize_block_id(&self, id: Option<BlockId>) -> TimeLagResult<Option<BlockId>, M> {
         match id {
@@ -99,6 +101,7 @@ where
 impl<M> Middleware for TimeLag<M>
 where
     M: Middleware,
+    M::Error: 'static,
 {
     type Error = TimeLagError<M>;
 
@@ -16,6 +16,7 @@ impl<M, T> TransformerMiddleware<M, T>
 where
     M: Middleware,
     T: Transformer,
+    M::Error: 'static,
 {
     /// Creates a new TransformerMiddleware that intercepts transactions, modifying them to be sent
     /// through the Transformer.
@@ -25,12 +26,15 @@ where
 }
 
 #[derive(Error, Debug)]
-pub enum TransformerMiddlewareError<M: Middleware> {
+pub enum TransformerMiddlewareError<M: Middleware>
+where
+    M::Error: 'static,
+{
     #[error(transparent)]
     TransformerError(#[from] TransformerError),
 
     #[error("{0}")]
-    MiddlewareError(M::Error),
+    MiddlewareError(#[source] M::Error),
 }
 
 impl<M: Middleware> FromErr<M::Error> for TransformerMiddlewareError<M> {
@@ -45,6 +49,7 @@ impl<M, T> Middleware for TransformerMiddleware<M, T>
 where
     M: Middleware,
     T: Transformer,
+    M::Error: 'static,
 {
     type Error = TransformerMiddlewareError<M>;
     type Provider = M::Provider;
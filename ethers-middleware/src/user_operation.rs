@@ -0,0 +1,363 @@
+use async_trait::async_trait;
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, EntryPoint, NameOrAddress,
+        TransactionRequest, UserOperation, H256, U256,
+    },
+    utils::{hex, id},
+};
+use ethers_providers::{interval, FromErr, Middleware, PendingTransaction, StreamExt};
+use ethers_signers::Signer;
+use instant::Instant;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How long `send_transaction` waits for the user operation to be included by default
+const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A signature with the length of a real ECDSA signature, used to estimate the gas of the
+/// verification of the user operation before it is signed
+const DUMMY_SIGNATURE: &str = "fffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c";
+
+/// Encodes the call of a [`SimpleAccount`](https://github.com/eth-infinitism/account-abstraction/blob/develop/contracts/samples/SimpleAccount.sol)
+/// to `execute(address,uint256,bytes)`, which is implemented by most smart contract accounts
+pub fn simple_account_execute(to: Address, value: U256, data: Bytes) -> Bytes {
+    let mut call_data = id("execute(address,uint256,bytes)").to_vec();
+    call_data.extend(abi::encode(&[
+        Token::Address(to),
+        Token::Uint(value),
+        Token::Bytes(data.to_vec()),
+    ]));
+    call_data.into()
+}
+
+#[derive(Debug)]
+/// Middleware used for sending transactions as [EIP-4337](https://eips.ethereum.org/EIPS/eip-4337)
+/// user operations of a smart contract account, signed by the owner of the account.
+///
+/// Transactions sent through this middleware are turned into calls of the account, filled with
+/// the nonce of the account in the EntryPoint, the EIP-1559 fees and the gas limits estimated by
+/// the bundler, signed with [`Signer::sign_user_operation`] and sent to the bundler.
+/// `send_transaction` waits until the user operation is included and returns the transaction of
+/// the bundle that included it, or fails if the user operation is not included within the
+/// [`timeout`](Self::timeout).
+///
+/// The inner middleware must be connected to a bundler that also serves the standard `eth_`
+/// methods.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, EntryPoint, TransactionRequest};
+/// use ethers_middleware::UserOperationMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let bundler = Provider::<Http>::try_from("http://localhost:4337")?;
+/// let owner: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let account = "0x0000000000000000000000000000000000000001".parse::<Address>()?;
+/// let client = UserOperationMiddleware::new(bundler, owner, account, EntryPoint::v0_6());
+///
+/// let tx = TransactionRequest::pay("vitalik.eth", 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct UserOperationMiddleware<M, S> {
+    inner: M,
+    signer: S,
+    account: Address,
+    entry_point: EntryPoint,
+    init_code: Bytes,
+    encode_call: fn(Address, U256, Bytes) -> Bytes,
+    interval: Option<Duration>,
+    timeout: Duration,
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when sending user operations
+pub enum UserOperationMiddlewareError<M: Middleware, S: Signer>
+where
+    M::Error: 'static,
+{
+    #[error("{0}")]
+    /// Thrown when the internal call to the signer fails
+    SignerError(S::Error),
+
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(#[source] M::Error),
+
+    /// Thrown when the nonce returned by the EntryPoint can not be decoded
+    #[error(transparent)]
+    AbiError(#[from] abi::Error),
+
+    /// Thrown if the transaction has no recipient, accounts can not deploy contracts
+    #[error("the transaction has no recipient")]
+    RecipientMissing,
+
+    /// Thrown if the user operation was not included within the timeout
+    #[error("user operation {0:?} was not included within the timeout")]
+    Timeout(H256),
+
+    /// Thrown if the call of the account reverted
+    #[error("user operation {user_op_hash:?} reverted: {}", reason.as_deref().unwrap_or("no reason"))]
+    Reverted {
+        /// The hash of the user operation
+        user_op_hash: H256,
+        /// The revert reason, if provided by the bundler
+        reason: Option<String>,
+    },
+}
+
+impl<M: Middleware, S: Signer> FromErr<M::Error> for UserOperationMiddlewareError<M, S> {
+    fn from(src: M::Error) -> Self {
+        UserOperationMiddlewareError::MiddlewareError(src)
+    }
+}
+
+impl<M, S> UserOperationMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    /// Creates a new client that sends the transactions of the smart contract `account`, owned
+    /// by the `signer`, to the `entry_point`.
+    ///
+    /// The calls are encoded with [`simple_account_execute`].
+    pub fn new(inner: M, signer: S, account: Address, entry_point: EntryPoint) -> Self {
+        Self {
+            inner,
+            signer,
+            account,
+            entry_point,
+            init_code: Bytes::default(),
+            encode_call: simple_account_execute,
+            interval: None,
+            timeout: DEFAULT_RECEIPT_TIMEOUT,
+        }
+    }
+
+    /// Sets the function that encodes the `(to, value, data)` of a transaction as the calldata
+    /// of the account
+    #[must_use]
+    pub fn encode_call(mut self, encode_call: fn(Address, U256, Bytes) -> Bytes) -> Self {
+        self.encode_call = encode_call;
+        self
+    }
+
+    /// Sets the init code that deploys the account with its first user operation. It is only
+    /// included while the account has no code.
+    #[must_use]
+    pub fn init_code<T: Into<Bytes>>(mut self, init_code: T) -> Self {
+        self.init_code = init_code.into();
+        self
+    }
+
+    /// Sets the interval at which the receipts of the user operations are polled, defaults to
+    /// the interval of the provider
+    #[must_use]
+    pub fn interval<T: Into<Duration>>(mut self, interval: T) -> Self {
+        self.interval = Some(interval.into());
+        self
+    }
+
+    /// Sets how long `send_transaction` waits for the user operation to be included, defaults to
+    /// 5 minutes
+    #[must_use]
+    pub fn timeout<T: Into<Duration>>(mut self, timeout: T) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Returns the address of the smart contract account
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    /// Returns the EntryPoint the user operations are sent to
+    pub fn entry_point(&self) -> &EntryPoint {
+        &self.entry_point
+    }
+
+    /// Returns a reference to the owner of the account
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Gets the next nonce of the account with the key `0` from the EntryPoint
+    pub async fn get_nonce(&self) -> Result<U256, UserOperationMiddlewareError<M, S>> {
+        let mut data = id("getNonce(address,uint192)").to_vec();
+        data.extend(abi::encode(&[Token::Address(self.account), Token::Uint(U256::zero())]));
+        let tx = TransactionRequest::new().to(self.entry_point.address).data(data).into();
+
+        let res = self
+            .inner
+            .call(&tx, None)
+            .await
+            .map_err(UserOperationMiddlewareError::MiddlewareError)?;
+        let nonce = abi::decode(&[ParamType::Uint(192)], &res)?;
+        Ok(nonce.into_iter().next().and_then(Token::into_uint).unwrap_or_default())
+    }
+
+    /// Turns the transaction into an unsigned user operation of the account, with the nonce, the
+    /// fees and the gas limits filled in.
+    ///
+    /// The EIP-1559 fees and the gas limit of the transaction are used for the user operation if
+    /// they are set, the nonce of the transaction is ignored.
+    pub async fn fill_user_operation(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<UserOperation, UserOperationMiddlewareError<M, S>> {
+        let to = match tx.to() {
+            Some(NameOrAddress::Address(to)) => *to,
+            Some(NameOrAddress::Name(ens_name)) => self
+                .inner
+                .resolve_name(ens_name)
+                .await
+                .map_err(UserOperationMiddlewareError::MiddlewareError)?,
+            None => return Err(UserOperationMiddlewareError::RecipientMissing),
+        };
+        let value = tx.value().copied().unwrap_or_default();
+        let data = tx.data().cloned().unwrap_or_default();
+
+        let mut op =
+            UserOperation::new(self.account).call_data((self.encode_call)(to, value, data));
+
+        let (nonce, code) = futures_util::try_join!(self.get_nonce(), async {
+            if self.init_code.is_empty() {
+                return Ok(Bytes::default())
+            }
+            self.inner
+                .get_code(self.account, None)
+                .await
+                .map_err(UserOperationMiddlewareError::MiddlewareError)
+        })?;
+        op = op.nonce(nonce);
+        if !self.init_code.is_empty() && code.is_empty() {
+            op = op.init_code(self.init_code.clone());
+        }
+
+        let fees = match tx {
+            TypedTransaction::Eip1559(inner) => {
+                inner.max_fee_per_gas.zip(inner.max_priority_fee_per_gas)
+            }
+            _ => None,
+        };
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match fees {
+            Some(fees) => fees,
+            None => self
+                .inner
+                .estimate_eip1559_fees(None)
+                .await
+                .map_err(UserOperationMiddlewareError::MiddlewareError)?,
+        };
+        op = op.max_fee_per_gas(max_fee_per_gas).max_priority_fee_per_gas(max_priority_fee_per_gas);
+
+        let dummy = op.clone().signature(hex::decode(DUMMY_SIGNATURE).expect("valid hex"));
+        let estimate = self
+            .inner
+            .estimate_user_operation_gas(&dummy, &self.entry_point)
+            .await
+            .map_err(UserOperationMiddlewareError::MiddlewareError)?;
+        op = op.gas(&estimate);
+        if let Some(gas) = tx.gas() {
+            op = op.call_gas_limit(*gas);
+        }
+
+        Ok(op)
+    }
+
+    /// Signs the user operation with the owner of the account
+    pub async fn sign_user_operation(
+        &self,
+        op: UserOperation,
+    ) -> Result<UserOperation, UserOperationMiddlewareError<M, S>> {
+        let signature = self
+            .signer
+            .sign_user_operation(&op, &self.entry_point)
+            .await
+            .map_err(UserOperationMiddlewareError::SignerError)?;
+        Ok(op.signature(signature.to_vec()))
+    }
+
+    /// Fills, signs and sends the transaction as a user operation to the bundler and returns the
+    /// hash of the user operation
+    pub async fn send_user_operation_for<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+    ) -> Result<H256, UserOperationMiddlewareError<M, S>> {
+        let op = self.fill_user_operation(&tx.into()).await?;
+        let op = self.sign_user_operation(op).await?;
+        self.inner
+            .send_user_operation(&op, &self.entry_point)
+            .await
+            .map_err(UserOperationMiddlewareError::MiddlewareError)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for UserOperationMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+    M::Error: 'static,
+{
+    type Error = UserOperationMiddlewareError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Returns the address of the smart contract account
+    fn default_sender(&self) -> Option<Address> {
+        Some(self.account)
+    }
+
+    /// Sends the transaction as a user operation of the account and waits until it is included.
+    /// Returns the transaction of the bundle that included the user operation.
+    ///
+    /// Fails with [`UserOperationMiddlewareError::Timeout`] if the user operation is not included
+    /// within the timeout.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        _: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let user_op_hash = self.send_user_operation_for(tx).await?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut interval =
+            interval(self.interval.unwrap_or_else(|| self.provider().get_interval()));
+        let receipt = loop {
+            if let Some(receipt) = self
+                .inner
+                .get_user_operation_receipt(user_op_hash)
+                .await
+                .map_err(UserOperationMiddlewareError::MiddlewareError)?
+            {
+                break receipt
+            }
+            if Instant::now() >= deadline {
+                return Err(UserOperationMiddlewareError::Timeout(user_op_hash))
+            }
+            interval.next().await;
+        };
+        if !receipt.success {
+            return Err(UserOperationMiddlewareError::Reverted {
+                user_op_hash,
+                reason: receipt.reason,
+            })
+        }
+
+        Ok(PendingTransaction::new(receipt.receipt.transaction_hash, self.provider()))
+    }
+}
@@ -0,0 +1,231 @@
+#![cfg(not(target_arch = "wasm32"))]
+use ethers_core::{
+    types::{Address, Block, Bytes, Signature, TransactionRequest, H256, U64},
+    utils::keccak256,
+};
+use ethers_middleware::{
+    bundle::{BundleMiddleware, BundleMiddlewareError, BundleRequest, RelayError},
+    SignerMiddleware,
+};
+use ethers_providers::{MockProvider, Provider};
+use ethers_signers::{LocalWallet, Signer};
+use serde_json::{json, Value};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use url::Url;
+
+/// The requests received by the mock relay, with their signature header
+type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// Spawns a relay that answers every JSON-RPC request with the result for its method
+async fn spawn_relay(results: Value) -> (Url, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    let requests = Requests::default();
+
+    let received = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let (head, body) = loop {
+                let mut chunk = [0u8; 4096];
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                let request = String::from_utf8_lossy(&buf).to_string();
+                if let Some((head, body)) = request.split_once("\r\n\r\n") {
+                    let len = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or_default();
+                    if body.len() >= len {
+                        break (head.to_string(), body.to_string())
+                    }
+                }
+            };
+
+            let signature = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("x-flashbots-signature")
+                        .then(|| value.trim().to_string())
+                })
+                .unwrap_or_default();
+            verify_signature(&signature, &body);
+
+            let request: Value = serde_json::from_str(&body).unwrap();
+            let result = &results[request["method"].as_str().unwrap()];
+            let response = if result.get("code").is_some() {
+                json!({ "jsonrpc": "2.0", "id": request["id"], "error": result })
+            } else {
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+            }
+            .to_string();
+            received.lock().unwrap().push((signature, request));
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+/// Checks that the header is the signature of the hash of the body by the address in the header
+fn verify_signature(header: &str, body: &str) {
+    let (address, signature) = header.split_once(':').unwrap();
+    let address = Address::from_str(address).unwrap();
+    let signature = Signature::from_str(signature.trim_start_matches("0x")).unwrap();
+    let message = format!("{:?}", H256::from(keccak256(body.as_bytes())));
+    signature.verify(message, address).unwrap();
+}
+
+fn client(
+    relay: Url,
+) -> (
+    BundleMiddleware<SignerMiddleware<Provider<MockProvider>, LocalWallet>, LocalWallet>,
+    MockProvider,
+) {
+    let (provider, mock) = Provider::mocked();
+    let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1u64);
+    let relay_signer = LocalWallet::new(&mut rand::thread_rng());
+    (BundleMiddleware::new(SignerMiddleware::new(provider, wallet), relay, relay_signer), mock)
+}
+
+#[tokio::test]
+async fn sends_signed_bundle_and_watches_inclusion() {
+    let bundle_hash = H256::random();
+    let (url, requests) =
+        spawn_relay(json!({ "eth_sendBundle": { "bundleHash": bundle_hash } })).await;
+    let (client, mock) = client(url);
+
+    // all fields are set, so signing does not need the provider
+    let tx = TransactionRequest::pay(Address::random(), 100u64)
+        .nonce(0u64)
+        .gas(21_000u64)
+        .gas_price(100u64)
+        .chain_id(1u64);
+    let signed = client.sign_bundle_transaction(tx).await.unwrap();
+    let bundle = BundleRequest::new().transaction(signed.clone()).block(10u64);
+
+    let pending = client.send_bundle(&bundle).await.unwrap().interval(Duration::from_millis(1));
+    assert_eq!(pending.bundle_hash(), Some(bundle_hash));
+    assert_eq!(pending.transactions(), bundle.transaction_hashes());
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    let (signature, request) = &requests[0];
+    assert!(signature.starts_with(&format!("{:?}:", client.relay().signer().address())));
+    assert_eq!(request["method"], "eth_sendBundle");
+    assert_eq!(request["params"], json!([{ "txs": [signed.rlp()], "blockNumber": "0xa" }]));
+
+    // the target block is not mined at first, then it includes the bundle
+    let block_hash = H256::random();
+    let block = Block::<H256> {
+        hash: Some(block_hash),
+        number: Some(U64::from(10u64)),
+        transactions: bundle.transaction_hashes(),
+        ..Default::default()
+    };
+    mock.push(block).unwrap();
+    mock.push::<Option<Block<H256>>, _>(None).unwrap();
+    assert_eq!(pending.await.unwrap(), Some(block_hash));
+}
+
+#[tokio::test]
+async fn pending_bundle_resolves_to_none_if_not_included() {
+    let (url, _) = spawn_relay(json!({ "eth_sendBundle": { "bundleHash": H256::random() } })).await;
+    let (client, mock) = client(url);
+
+    let bundle = BundleRequest::new().transaction(Bytes::from(vec![1])).block(10u64);
+    let pending = client.send_bundle(&bundle).await.unwrap().interval(Duration::from_millis(1));
+
+    mock.push(Block::<H256> { hash: Some(H256::random()), ..Default::default() }).unwrap();
+    assert_eq!(pending.await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn simulates_bundle_and_gets_stats() {
+    let (url, requests) = spawn_relay(json!({
+        "eth_callBundle": {
+            "bundleGasPrice": "100",
+            "bundleHash": H256::zero(),
+            "coinbaseDiff": "2100000",
+            "ethSentToCoinbase": "0",
+            "gasFees": "2100000",
+            "results": [{
+                "coinbaseDiff": "2100000",
+                "ethSentToCoinbase": "0",
+                "fromAddress": Address::zero(),
+                "gasFees": "2100000",
+                "gasPrice": "100",
+                "gasUsed": 21000,
+                "toAddress": Address::zero(),
+                "txHash": H256::zero(),
+                "revert": "out of gas"
+            }],
+            "stateBlockNumber": 9,
+            "totalGasUsed": 21000
+        },
+        "flashbots_getBundleStats": {
+            "isSimulated": true,
+            "isSentToMiners": false,
+            "simulatedAt": "2022-08-06T21:36:06.317Z"
+        },
+    }))
+    .await;
+    let (client, _) = client(url);
+
+    let bundle =
+        BundleRequest::new().transaction(Bytes::from(vec![1])).block(10u64).simulation_block(9u64);
+    let simulated = client.simulate_bundle(&bundle).await.unwrap();
+    assert_eq!(simulated.total_gas_used, 21000u64.into());
+    assert!(simulated.results[0].is_failure());
+
+    let stats = client.get_bundle_stats(H256::zero(), 10u64.into()).await.unwrap();
+    assert!(stats.is_simulated);
+    assert!(!stats.is_sent_to_miners);
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(
+        requests[0].1["params"],
+        json!([{ "txs": ["0x01"], "blockNumber": "0xa", "stateBlockNumber": "0x9" }])
+    );
+    assert_eq!(requests[1].1["method"], "flashbots_getBundleStats");
+}
+
+#[tokio::test]
+async fn returns_relay_errors() {
+    let (url, _) = spawn_relay(json!({
+        "eth_sendBundle": { "code": -32000, "message": "bundle too large" }
+    }))
+    .await;
+    let (client, _) = client(url);
+
+    let bundle = BundleRequest::new().block(10u64);
+    let err = client.send_bundle(&bundle).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BundleMiddlewareError::RelayError(RelayError::JsonRpcError(ref err)) if err.message == "bundle too large"
+    ));
+
+    // a bundle needs a target block
+    let err = client.simulate_bundle(&BundleRequest::new()).await.unwrap_err();
+    assert!(matches!(err, BundleMiddlewareError::MissingTargetBlock));
+}
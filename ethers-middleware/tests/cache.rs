@@ -0,0 +1,132 @@
+#![cfg(not(target_arch = "wasm32"))]
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, Block, BlockNumber, Bytes, TransactionReceipt,
+    TransactionRequest, H256, U256, U64,
+};
+use ethers_middleware::cache::{CacheMiddleware, CacheStats, DiskCache};
+use ethers_providers::{Middleware, MockProvider, Provider};
+
+fn block(number: u64) -> Block<H256> {
+    Block { hash: Some(H256::random()), number: Some(number.into()), ..Default::default() }
+}
+
+fn client() -> (CacheMiddleware<Provider<MockProvider>>, MockProvider) {
+    let (provider, mock) = Provider::mocked();
+    (CacheMiddleware::new(provider).finality_depth(10), mock)
+}
+
+#[tokio::test]
+async fn caches_blocks_by_hash() {
+    let (client, mock) = client();
+    let block = block(1);
+    let hash = block.hash.unwrap();
+
+    mock.push(block.clone()).unwrap();
+    assert_eq!(client.get_block(hash).await.unwrap(), Some(block.clone()));
+    // served from the cache, the mock has no more responses
+    assert_eq!(client.get_block(hash).await.unwrap(), Some(block));
+    assert_eq!(client.stats(), CacheStats { hits: 1, misses: 1 });
+
+    // a block that does not exist yet is not cached
+    mock.push::<Option<Block<H256>>, _>(None).unwrap();
+    mock.push::<Option<Block<H256>>, _>(None).unwrap();
+    assert_eq!(client.get_block(H256::zero()).await.unwrap(), None);
+    assert_eq!(client.get_block(H256::zero()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn caches_blocks_by_number_once_final() {
+    let (client, mock) = client();
+    let (recent, old) = (block(95), block(80));
+
+    // the block is not final yet, so the tip is fetched every time
+    for _ in 0..2 {
+        mock.push(U64::from(100u64)).unwrap();
+        mock.push(recent.clone()).unwrap();
+        assert_eq!(client.get_block(95u64).await.unwrap(), Some(recent.clone()));
+    }
+
+    // the block is final according to the tip seen before
+    mock.push(old.clone()).unwrap();
+    assert_eq!(client.get_block(80u64).await.unwrap(), Some(old.clone()));
+    assert_eq!(client.get_block(80u64).await.unwrap(), Some(old));
+    assert_eq!(client.stats(), CacheStats { hits: 1, misses: 3 });
+}
+
+#[tokio::test]
+async fn never_caches_latest_or_pending_queries() {
+    let (client, mock) = client();
+    let address = Address::random();
+
+    mock.push(U256::from(2u64)).unwrap();
+    mock.push(U256::from(1u64)).unwrap();
+    assert_eq!(client.get_balance(address, None).await.unwrap(), 1u64.into());
+    assert_eq!(
+        client.get_balance(address, Some(BlockNumber::Pending.into())).await.unwrap(),
+        2u64.into()
+    );
+
+    mock.push(block(100)).unwrap();
+    client.get_block(BlockNumber::Latest).await.unwrap();
+    assert_eq!(client.stats(), CacheStats::default());
+}
+
+#[tokio::test]
+async fn caches_state_and_calls_at_block_hash() {
+    let (client, mock) = client();
+    let (address, block) = (Address::random(), H256::random());
+    let tx: TypedTransaction = TransactionRequest::new().to(address).data(vec![1, 2, 3]).into();
+
+    mock.push::<Bytes, _>(Bytes::from(vec![4])).unwrap();
+    mock.push(H256::repeat_byte(1)).unwrap();
+    mock.push::<Bytes, _>(Bytes::from(vec![1])).unwrap();
+    for _ in 0..2 {
+        assert_eq!(
+            client.get_code(address, Some(block.into())).await.unwrap(),
+            Bytes::from(vec![1])
+        );
+        assert_eq!(
+            client.get_storage_at(address, H256::zero(), Some(block.into())).await.unwrap(),
+            H256::repeat_byte(1)
+        );
+        assert_eq!(client.call(&tx, Some(block.into())).await.unwrap(), Bytes::from(vec![4]));
+    }
+    assert_eq!(client.stats(), CacheStats { hits: 3, misses: 3 });
+}
+
+#[tokio::test]
+async fn caches_receipts_once_final() {
+    let (client, mock) = client();
+    let hash = H256::random();
+    let pending = TransactionReceipt { transaction_hash: hash, ..Default::default() };
+    let mined = TransactionReceipt { block_number: Some(5u64.into()), ..pending.clone() };
+
+    // the receipt of a pending transaction is not cached
+    mock.push(pending.clone()).unwrap();
+    assert_eq!(client.get_transaction_receipt(hash).await.unwrap(), Some(pending));
+
+    mock.push(U64::from(100u64)).unwrap();
+    mock.push(mined.clone()).unwrap();
+    assert_eq!(client.get_transaction_receipt(hash).await.unwrap(), Some(mined.clone()));
+    assert_eq!(client.get_transaction_receipt(hash).await.unwrap(), Some(mined));
+    assert_eq!(client.stats(), CacheStats { hits: 1, misses: 2 });
+}
+
+#[tokio::test]
+async fn disk_cache_survives_restarts() {
+    let dir = std::env::temp_dir().join(format!("cache-middleware-{}", rand::random::<u64>()));
+    let block = block(1);
+    let hash = block.hash.unwrap();
+
+    let (provider, mock) = Provider::mocked();
+    let client = CacheMiddleware::new_with_cache(provider, DiskCache::new(&dir));
+    mock.push(block.clone()).unwrap();
+    assert_eq!(client.get_block(hash).await.unwrap(), Some(block.clone()));
+
+    let (provider, _) = Provider::mocked();
+    let client = CacheMiddleware::new_with_cache(provider, DiskCache::new(&dir));
+    assert_eq!(client.get_block(hash).await.unwrap(), Some(block));
+    assert_eq!(client.stats(), CacheStats { hits: 1, misses: 0 });
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,206 @@
+#![cfg(all(not(target_arch = "wasm32"), feature = "evm"))]
+use ethers_core::types::{Address, Block, Bytes, TransactionRequest, H256, U256, U64};
+use ethers_middleware::evm::{EvmMiddleware, EvmMiddlewareError};
+use ethers_providers::{
+    call_raw::{spoof, RawCall},
+    Middleware, MockProvider, Provider,
+};
+
+// bytecode that returns the result of the SELFBALANCE opcode
+const RETURN_BALANCE: &str = "0x4760005260206000f3";
+// bytecode that returns the value of storage slot 1
+const RETURN_STORAGE: &str = "0x60015460005260206000f3";
+// bytecode that reverts with empty data
+const REVERT: &str = "0x60006000fd";
+// bytecode that returns the result of the BASEFEE opcode
+const RETURN_BASE_FEE: &str = "0x4860005260206000f3";
+// bytecode that returns the sum of the storage slots 1, 2 and 3
+const SUM_STORAGE: &str = "0x600154600254016003540160005260206000f3";
+
+fn evm() -> (EvmMiddleware<Provider<MockProvider>>, MockProvider) {
+    let (provider, mock) = Provider::mocked();
+    (EvmMiddleware::new(provider), mock)
+}
+
+// pushes the responses for creating the fork, must be pushed last since responses are popped from
+// the back
+fn push_fork(mock: &MockProvider) {
+    push_fork_at(mock, 100, None)
+}
+
+fn push_fork_with_base_fee(mock: &MockProvider, base_fee: Option<U256>) {
+    push_fork_at(mock, 100, base_fee)
+}
+
+fn push_fork_at(mock: &MockProvider, number: u64, base_fee: Option<U256>) {
+    let block = Block::<H256> {
+        number: Some(U64::from(number)),
+        hash: Some(H256::random()),
+        gas_limit: 30_000_000u64.into(),
+        base_fee_per_gas: base_fee,
+        ..Default::default()
+    };
+    mock.push(U256::from(1u64)).unwrap();
+    mock.push(block).unwrap();
+}
+
+// overrides the complete state of the account so that it does not need to be fetched
+fn spoof_account(state: &mut spoof::State, address: Address, code: &str) {
+    state.account(address).nonce(0u64.into()).balance(U256::zero()).code(code.parse().unwrap());
+}
+
+#[tokio::test]
+async fn executes_with_state_overrides() {
+    let (evm, mock) = evm();
+    push_fork(&mock);
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, RETURN_BALANCE);
+    state.account(target).balance(100u64.into());
+
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(U256::from_big_endian(res.as_ref()), 100u64.into());
+    assert_eq!(evm.fork_block_number(), Some(100u64.into()));
+}
+
+#[tokio::test]
+async fn fetches_and_caches_storage() {
+    let (evm, mock) = evm();
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, RETURN_STORAGE);
+
+    let value = H256::from_low_u64_be(1337);
+    mock.push(value).unwrap();
+    push_fork(&mock);
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), value);
+
+    // served from the cache, only the chain head is checked
+    mock.push(U64::from(100u64)).unwrap();
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), value);
+
+    // storage overrides take precedence over the cached state
+    let overridden = H256::from_low_u64_be(42);
+    state.account(target).store(H256::from_low_u64_be(1), overridden);
+    mock.push(U64::from(100u64)).unwrap();
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), overridden);
+}
+
+#[tokio::test]
+async fn returns_revert() {
+    let (evm, mock) = evm();
+    push_fork(&mock);
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, REVERT);
+
+    let err = evm.call_raw(&tx).state(&state).await.unwrap_err();
+    assert!(matches!(err, EvmMiddlewareError::Revert(ref data) if data.as_ref().is_empty()));
+}
+
+#[tokio::test]
+async fn estimates_transfer_gas() {
+    let (evm, mock) = evm();
+    let tx = TransactionRequest::pay(Address::random(), 1u64).into();
+
+    // the sender and the receiver are fetched, responses for balance, nonce and code
+    for _ in 0..2 {
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push(U256::zero()).unwrap();
+        mock.push(U256::exp10(18)).unwrap();
+    }
+    push_fork(&mock);
+
+    let gas = evm.estimate_gas(&tx).await.unwrap();
+    assert_eq!(gas, 21_000u64.into());
+}
+
+#[tokio::test]
+async fn calls_without_gas_price_keep_the_base_fee() {
+    let (evm, mock) = evm();
+    push_fork_with_base_fee(&mock, Some(7u64.into()));
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, RETURN_BASE_FEE);
+
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(U256::from_big_endian(res.as_ref()), 7u64.into());
+}
+
+#[tokio::test]
+async fn rejects_gas_above_u64() {
+    let (evm, mock) = evm();
+    push_fork(&mock);
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).gas(U256::MAX).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, RETURN_BALANCE);
+
+    let err = evm.call_raw(&tx).state(&state).await.unwrap_err();
+    assert!(matches!(err, EvmMiddlewareError::Evm(_)));
+}
+
+#[tokio::test]
+async fn fetches_all_missing_storage_at_once() {
+    let (evm, mock) = evm();
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, SUM_STORAGE);
+
+    // all slots are read as empty in the first execution and fetched in order
+    for value in [3u64, 2, 1] {
+        mock.push(H256::from_low_u64_be(value)).unwrap();
+    }
+    push_fork(&mock);
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(U256::from_big_endian(res.as_ref()), 6u64.into());
+}
+
+#[tokio::test]
+async fn follows_the_latest_block() {
+    let (evm, mock) = evm();
+    let target = Address::random();
+    let tx = TransactionRequest::new().to(target).into();
+
+    let mut state = spoof::state();
+    spoof_account(&mut state, Address::zero(), "0x");
+    spoof_account(&mut state, target, RETURN_STORAGE);
+
+    mock.push(H256::from_low_u64_be(1)).unwrap();
+    push_fork(&mock);
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), H256::from_low_u64_be(1));
+
+    // the head moved, the new block is forked and the storage is fetched again
+    mock.push(H256::from_low_u64_be(2)).unwrap();
+    push_fork_at(&mock, 101, None);
+    mock.push(U64::from(101u64)).unwrap();
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), H256::from_low_u64_be(2));
+    assert_eq!(evm.fork_block_number(), Some(101u64.into()));
+
+    // a fixed fork block is never moved
+    let evm = evm.with_fork_block(101u64);
+    let res = evm.call_raw(&tx).state(&state).await.unwrap();
+    assert_eq!(H256::from_slice(res.as_ref()), H256::from_low_u64_be(2));
+}
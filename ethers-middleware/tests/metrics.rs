@@ -0,0 +1,24 @@
+#![cfg(not(target_arch = "wasm32"))]
+use ethers_core::types::{Block, H256, U64};
+use ethers_middleware::MetricsMiddleware;
+use ethers_providers::{Middleware, Provider};
+
+#[tokio::test]
+async fn records_middleware_calls() {
+    let (provider, mock) = Provider::mocked();
+    let client = MetricsMiddleware::new(provider);
+
+    mock.push(U64::from(12u64)).unwrap();
+    assert_eq!(client.get_block_number().await.unwrap(), 12u64.into());
+    mock.push::<Option<Block<H256>>, _>(None).unwrap();
+    assert_eq!(client.get_block(H256::zero()).await.unwrap(), None);
+    // the mock has no response left
+    client.get_block(1u64).await.unwrap_err();
+
+    let metrics = client.recorder().snapshot();
+    assert_eq!(metrics.keys().collect::<Vec<_>>(), ["get_block", "get_block_number"]);
+    assert_eq!(metrics["get_block_number"].requests, 1);
+    assert_eq!(metrics["get_block"].requests, 2);
+    assert_eq!(metrics["get_block"].other_errors, 1);
+    assert!(metrics.values().all(|metrics| metrics.in_flight == 0));
+}
@@ -0,0 +1,88 @@
+#![cfg(not(target_arch = "wasm32"))]
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, TransactionRequest, H256, U256,
+};
+use ethers_middleware::{
+    multi_signer::{MultiSignerMiddlewareError, SignerSelection},
+    MultiSignerMiddleware,
+};
+use ethers_providers::{Middleware, MockProvider, Provider};
+use ethers_signers::{LocalWallet, Signer};
+
+fn wallets() -> Vec<LocalWallet> {
+    vec![
+        "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap(),
+        "cd8c407233c0560f6de24bb2dc60a8b02335c959a1a17f749ce6c1ccf63d74a7".parse().unwrap(),
+    ]
+}
+
+fn tx() -> TransactionRequest {
+    TransactionRequest::new()
+        .to(Address::repeat_byte(1))
+        .value(100u64)
+        .gas(21_000u64)
+        .gas_price(1u64)
+        .nonce(0u64)
+}
+
+/// Sends the transaction and asserts that it was signed by the `wallet`
+async fn assert_sent_by(
+    client: &MultiSignerMiddleware<Provider<MockProvider>, LocalWallet>,
+    mock: &MockProvider,
+    tx: TransactionRequest,
+    wallet: &LocalWallet,
+) {
+    mock.push(H256::repeat_byte(0xaa)).unwrap();
+    client.send_transaction(tx.clone(), None).await.unwrap();
+
+    let tx: TypedTransaction = tx.from(wallet.address()).chain_id(wallet.chain_id()).into();
+    let signature = wallet.sign_transaction(&tx).await.unwrap();
+    mock.assert_request("eth_sendRawTransaction", [tx.rlp_signed(&signature)]).unwrap();
+}
+
+#[tokio::test]
+async fn routes_by_from() {
+    let (provider, mock) = Provider::mocked();
+    let wallets = wallets();
+    let client = MultiSignerMiddleware::new(provider, wallets.clone());
+
+    assert_eq!(
+        client.get_accounts().await.unwrap(),
+        wallets.iter().map(Signer::address).collect::<Vec<_>>()
+    );
+
+    assert_sent_by(&client, &mock, tx().from(wallets[1].address()), &wallets[1]).await;
+
+    let signature = client.sign(b"hello".to_vec(), &wallets[1].address()).await.unwrap();
+    signature.verify("hello", wallets[1].address()).unwrap();
+
+    match client.sign(b"hello".to_vec(), &Address::zero()).await.unwrap_err() {
+        MultiSignerMiddlewareError::UnknownSigner(address) => assert_eq!(address, Address::zero()),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn selects_round_robin() {
+    let (provider, mock) = Provider::mocked();
+    let wallets = wallets();
+    let client = MultiSignerMiddleware::new(provider, wallets.clone());
+
+    assert_sent_by(&client, &mock, tx(), &wallets[0]).await;
+    assert_sent_by(&client, &mock, tx(), &wallets[1]).await;
+    assert_sent_by(&client, &mock, tx(), &wallets[0]).await;
+}
+
+#[tokio::test]
+async fn selects_least_pending() {
+    let (provider, mock) = Provider::mocked();
+    let wallets = wallets();
+    let client = MultiSignerMiddleware::new(provider, wallets.clone())
+        .selection(SignerSelection::LeastPending);
+
+    // the first wallet has 2 pending transactions, the second one 1
+    for count in [4u64, 5, 8, 10] {
+        mock.push(U256::from(count)).unwrap();
+    }
+    assert_eq!(client.select_signer().await.unwrap(), wallets[1].address());
+}
@@ -53,3 +53,64 @@ async fn nonce_manager() {
 
     assert_eq!(nonces, (nonce..nonce + (num_tx as u64)).collect::<Vec<_>>())
 }
+
+#[tokio::test]
+async fn nonce_manager_tracks_senders() {
+    use ethers_core::types::{transaction::eip2718::TypedTransaction, *};
+    use ethers_middleware::nonce_manager::NonceManagerMiddleware;
+    use ethers_providers::{Middleware, Provider};
+
+    let (provider, mock) = Provider::mocked();
+    let (default, other) = (Address::random(), Address::random());
+    let provider = NonceManagerMiddleware::new(provider, default);
+
+    let request = |from: Option<Address>| -> TypedTransaction {
+        let mut tx = TransactionRequest::new().to(other).gas(21_000u64).gas_price(1u64);
+        tx.from = from;
+        tx.into()
+    };
+
+    // the transaction count is only fetched once per sender
+    mock.push(U256::from(5u64)).unwrap();
+    for expected in 5u64..8 {
+        let mut tx = request(None);
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+        assert_eq!(tx.nonce(), Some(&expected.into()));
+    }
+
+    mock.push(U256::zero()).unwrap();
+    for expected in 0u64..2 {
+        let mut tx = request(Some(other));
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+        assert_eq!(tx.nonce(), Some(&expected.into()));
+    }
+
+    assert_eq!(provider.initialize_nonce(None).await.unwrap(), 8u64.into());
+}
+
+#[tokio::test]
+async fn nonce_manager_detects_gaps() {
+    use ethers_core::types::*;
+    use ethers_middleware::nonce_manager::{NonceManagerMiddleware, NonceStore};
+    use ethers_providers::Provider;
+
+    let (provider, mock) = Provider::mocked();
+    let address = Address::random();
+    let provider = NonceManagerMiddleware::new(provider, address);
+    provider.store().sync(address, 10u64.into()).await.unwrap();
+
+    // responses are popped from the back: the pending count, then the txpool content
+    mock.push(serde_json::json!({ "pending": {}, "queued": {} })).unwrap();
+    mock.push(U256::from(7u64)).unwrap();
+    let gaps = provider.nonce_gaps(address).await.unwrap();
+    assert_eq!(gaps, vec![7u64.into(), 8u64.into(), 9u64.into()]);
+
+    // without the txpool api only the first gap is known
+    mock.push(U256::from(7u64)).unwrap();
+    let gaps = provider.nonce_gaps(address).await.unwrap();
+    assert_eq!(gaps, vec![7u64.into()]);
+
+    // no gaps if all nonces are pending
+    mock.push(U256::from(10u64)).unwrap();
+    assert!(provider.nonce_gaps(address).await.unwrap().is_empty());
+}
@@ -198,6 +198,27 @@ async fn send_transaction_handles_tx_from_field() {
     assert_eq!(sent_tx.from, other.address());
 }
 
+#[tokio::test]
+async fn send_transaction_with_boxed_signer() {
+    use ethers_core::types::{transaction::eip2718::TypedTransaction, H256};
+    use ethers_signers::DynSigner;
+
+    let (provider, mock) = Provider::mocked();
+    let wallet: LocalWallet =
+        "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+    let signer: Box<dyn DynSigner> = Box::new(wallet.clone());
+    let client = SignerMiddleware::new(provider, signer);
+    assert_eq!(client.address(), wallet.address());
+
+    let tx = TransactionRequest::pay(wallet.address(), 100).gas(21_000).gas_price(1).nonce(0);
+    mock.push(H256::repeat_byte(1)).unwrap();
+    client.send_transaction(tx.clone(), None).await.unwrap();
+
+    let tx: TypedTransaction = tx.from(wallet.address()).chain_id(wallet.chain_id()).into();
+    let signature = wallet.sign_transaction(&tx).await.unwrap();
+    mock.assert_request("eth_sendRawTransaction", [tx.rlp_signed(&signature)]).unwrap();
+}
+
 #[tokio::test]
 #[cfg(feature = "celo")]
 async fn deploy_and_call_contract() {
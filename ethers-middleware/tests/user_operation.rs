@@ -0,0 +1,167 @@
+#![cfg(not(target_arch = "wasm32"))]
+use ethers_core::{
+    abi::{self, Token},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, Eip1559TransactionRequest,
+        EntryPoint, TransactionReceipt, TransactionRequest, UserOperation,
+        UserOperationGasEstimate, UserOperationReceipt, H256, U256,
+    },
+};
+use ethers_middleware::{
+    user_operation::{simple_account_execute, UserOperationMiddlewareError},
+    UserOperationMiddleware,
+};
+use ethers_providers::{Middleware, MockProvider, Provider};
+use ethers_signers::{LocalWallet, Signer};
+use std::time::Duration;
+
+const OWNER: &str = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc";
+const DUMMY_SIGNATURE: &str = "fffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c";
+
+fn receipt(user_op_hash: H256, success: bool) -> UserOperationReceipt {
+    UserOperationReceipt {
+        user_op_hash,
+        entry_point: EntryPoint::v0_6().address,
+        sender: Address::repeat_byte(0xaa),
+        nonce: 3u64.into(),
+        paymaster: Address::zero(),
+        actual_gas_cost: 1u64.into(),
+        actual_gas_used: 1u64.into(),
+        success,
+        reason: (!success).then(|| "AA23 reverted".to_string()),
+        logs: vec![],
+        receipt: TransactionReceipt {
+            transaction_hash: H256::repeat_byte(0xbb),
+            ..Default::default()
+        },
+    }
+}
+
+/// Pushes the responses to sending a user operation, in the reverse order of the requests
+fn push_responses(
+    mock: &MockProvider,
+    estimate: UserOperationGasEstimate,
+    receipt: UserOperationReceipt,
+) {
+    mock.push(receipt.clone()).unwrap();
+    mock.push(receipt.user_op_hash).unwrap();
+    mock.push(estimate).unwrap();
+    let mut nonce = [0u8; 32];
+    nonce[31] = 3;
+    mock.push::<Bytes, _>(Bytes::from(nonce.to_vec())).unwrap();
+}
+
+#[test]
+fn encodes_simple_account_calls() {
+    let call_data = simple_account_execute(Address::repeat_byte(1), 2u64.into(), vec![3].into());
+    assert_eq!(call_data[..4], [0xb6, 0x1d, 0x27, 0xf6]);
+    // selector, address, value, offset, length, data
+    assert_eq!(call_data.len(), 4 + 5 * 32);
+}
+
+#[tokio::test]
+async fn sends_transactions_as_user_operations() {
+    let (provider, mock) = Provider::mocked();
+    let owner: LocalWallet = OWNER.parse().unwrap();
+    let account = Address::repeat_byte(0xaa);
+    let entry_point = EntryPoint::v0_6();
+    let client = UserOperationMiddleware::new(provider, owner.clone(), account, entry_point)
+        .interval(Duration::from_millis(10));
+    assert_eq!(client.default_sender(), Some(account));
+
+    let estimate = UserOperationGasEstimate {
+        pre_verification_gas: 50_000u64.into(),
+        verification_gas_limit: 200_000u64.into(),
+        call_gas_limit: 100_000u64.into(),
+    };
+    let user_op_hash = H256::repeat_byte(0xcc);
+    push_responses(&mock, estimate, receipt(user_op_hash, true));
+
+    let to = Address::repeat_byte(1);
+    let tx = Eip1559TransactionRequest::new()
+        .to(to)
+        .value(100u64)
+        .max_fee_per_gas(30u64)
+        .max_priority_fee_per_gas(2u64);
+    let pending = client.send_transaction(tx, None).await.unwrap();
+    assert_eq!(*pending, H256::repeat_byte(0xbb));
+
+    let op = UserOperation::new(account)
+        .nonce(3u64)
+        .call_data(simple_account_execute(to, 100u64.into(), Bytes::default()))
+        .max_fee_per_gas(30u64)
+        .max_priority_fee_per_gas(2u64);
+    // the gas is estimated with a dummy signature of the length of a real signature
+    let dummy = op.clone().signature(hex::decode(DUMMY_SIGNATURE).unwrap());
+    let op = op.gas(&estimate);
+    let signature = owner.sign_user_operation(&op, &entry_point).await.unwrap();
+    let op = op.signature(signature.to_vec());
+
+    let mut get_nonce = vec![0x35, 0x56, 0x7e, 0x1a];
+    get_nonce.extend(abi::encode(&[Token::Address(account), Token::Uint(U256::zero())]));
+    let call: TypedTransaction =
+        TransactionRequest::new().to(entry_point.address).data(get_nonce).into();
+    mock.assert_request("eth_call", (call, "latest")).unwrap();
+    mock.assert_request("eth_estimateUserOperationGas", (dummy, entry_point.address)).unwrap();
+    mock.assert_request("eth_sendUserOperation", (op, entry_point.address)).unwrap();
+    mock.assert_request("eth_getUserOperationReceipt", [user_op_hash]).unwrap();
+}
+
+#[tokio::test]
+async fn reverted_user_operations_error() {
+    let (provider, mock) = Provider::mocked();
+    let owner: LocalWallet = OWNER.parse().unwrap();
+    let client = UserOperationMiddleware::new(
+        provider,
+        owner,
+        Address::repeat_byte(0xaa),
+        EntryPoint::v0_6(),
+    );
+
+    let user_op_hash = H256::repeat_byte(0xcc);
+    push_responses(&mock, UserOperationGasEstimate::default(), receipt(user_op_hash, false));
+
+    let tx = Eip1559TransactionRequest::new()
+        .to(Address::repeat_byte(1))
+        .max_fee_per_gas(U256::one())
+        .max_priority_fee_per_gas(U256::one());
+    match client.send_transaction(tx, None).await.unwrap_err() {
+        UserOperationMiddlewareError::Reverted { user_op_hash: hash, reason } => {
+            assert_eq!(hash, user_op_hash);
+            assert_eq!(reason.as_deref(), Some("AA23 reverted"));
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn user_operations_time_out() {
+    let (provider, mock) = Provider::mocked();
+    let owner: LocalWallet = OWNER.parse().unwrap();
+    let client = UserOperationMiddleware::new(
+        provider,
+        owner,
+        Address::repeat_byte(0xaa),
+        EntryPoint::v0_6(),
+    )
+    .interval(Duration::from_millis(10))
+    .timeout(Duration::from_millis(50));
+
+    let user_op_hash = H256::repeat_byte(0xcc);
+    // the user operation is never included
+    for _ in 0..100 {
+        mock.push(Option::<UserOperationReceipt>::None).unwrap();
+    }
+    mock.push(user_op_hash).unwrap();
+    mock.push(UserOperationGasEstimate::default()).unwrap();
+    mock.push::<Bytes, _>(Bytes::from([0u8; 32].to_vec())).unwrap();
+
+    let tx = Eip1559TransactionRequest::new()
+        .to(Address::repeat_byte(1))
+        .max_fee_per_gas(U256::one())
+        .max_priority_fee_per_gas(U256::one());
+    match client.send_transaction(tx, None).await.unwrap_err() {
+        UserOperationMiddlewareError::Timeout(hash) => assert_eq!(hash, user_op_hash),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
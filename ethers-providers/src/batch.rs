@@ -0,0 +1,164 @@
+use crate::{transports::common::Request, ProviderError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{value::RawValue, Value};
+
+/// A single call inside a [`BatchRequest`]
+#[derive(Clone, Debug)]
+pub struct BatchCall {
+    method: String,
+    params: Value,
+}
+
+impl BatchCall {
+    /// The JSON-RPC method of this call
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The already serialized params of this call, `Value::Null` if the call has no params
+    pub fn params(&self) -> &Value {
+        &self.params
+    }
+}
+
+/// A set of JSON-RPC calls that are sent to the node in a single round-trip.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, U256, U64};
+/// use ethers_providers::{BatchRequest, Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+///
+/// let mut batch = BatchRequest::new();
+/// batch.add_request("eth_blockNumber", ())?;
+/// batch.add_request("eth_getBalance", (Address::zero(), "latest"))?;
+///
+/// let mut responses = provider.send_batch(&batch).await?;
+/// let block: U64 = responses.next_response().unwrap()?;
+/// let balance: U256 = responses.next_response().unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BatchRequest {
+    calls: Vec<BatchCall>,
+}
+
+impl BatchRequest {
+    /// Creates an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty batch with room for `capacity` calls
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { calls: Vec::with_capacity(capacity) }
+    }
+
+    /// Adds a call to the batch and returns its index, which is also the index of its response
+    /// in the [`BatchResponse`]
+    pub fn add_request<T: Serialize>(
+        &mut self,
+        method: &str,
+        params: T,
+    ) -> Result<usize, serde_json::Error> {
+        let params = serde_json::to_value(params)?;
+        self.calls.push(BatchCall { method: method.to_string(), params });
+        Ok(self.calls.len() - 1)
+    }
+
+    /// Returns all calls of this batch
+    pub fn calls(&self) -> &[BatchCall] {
+        &self.calls
+    }
+
+    /// Returns the number of calls in this batch
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Returns true if this batch contains no calls
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Serializes the batch into a JSON-RPC array, using consecutive ids starting at `first_id`
+    pub(crate) fn to_payload(&self, first_id: u64) -> String {
+        let requests = self
+            .calls
+            .iter()
+            .enumerate()
+            .map(|(idx, call)| {
+                let id = first_id + idx as u64;
+                // calls without params must not serialize the `params` field, see `Request`
+                if call.params.is_null() {
+                    serde_json::to_string(&Request::new(id, &call.method, ()))
+                } else {
+                    serde_json::to_string(&Request::new(id, &call.method, &call.params))
+                }
+                .expect("serializing a JSON value never fails")
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", requests.join(","))
+    }
+}
+
+/// The responses of a [`BatchRequest`], in the same order as its calls.
+///
+/// Every call of the batch yields its own result, so a single failing call does not affect the
+/// other responses.
+#[derive(Debug)]
+pub struct BatchResponse {
+    responses: std::vec::IntoIter<Result<Box<RawValue>, ProviderError>>,
+}
+
+impl BatchResponse {
+    pub(crate) fn new(responses: Vec<Result<Box<RawValue>, ProviderError>>) -> Self {
+        Self { responses: responses.into_iter() }
+    }
+
+    /// Returns the number of responses that have not been consumed yet
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns true if all responses have been consumed
+    pub fn is_empty(&self) -> bool {
+        self.responses.len() == 0
+    }
+
+    /// Deserializes the response of the next call in the batch.
+    ///
+    /// Returns `None` once all responses have been consumed.
+    pub fn next_response<R: DeserializeOwned>(&mut self) -> Option<Result<R, ProviderError>> {
+        let response = self.responses.next()?;
+        Some(response.and_then(|raw| Ok(serde_json::from_str(raw.get())?)))
+    }
+
+    /// Returns the remaining raw responses
+    pub fn into_raw(self) -> Vec<Result<Box<RawValue>, ProviderError>> {
+        self.responses.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ser_batch_payload() {
+        let mut batch = BatchRequest::new();
+        assert_eq!(batch.add_request("eth_blockNumber", ()).unwrap(), 0);
+        assert_eq!(batch.add_request("eth_getBalance", ("0x00", "latest")).unwrap(), 1);
+
+        let payload = batch.to_payload(5);
+        assert_eq!(
+            payload,
+            r#"[{"id":5,"jsonrpc":"2.0","method":"eth_blockNumber"},{"id":6,"jsonrpc":"2.0","method":"eth_getBalance","params":["0x00","latest"]}]"#
+        );
+    }
+}
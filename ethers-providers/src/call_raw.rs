@@ -316,6 +316,12 @@ pub mod spoof {
             self.0.entry(adr).or_default()
         }
     }
+    impl std::ops::Deref for State {
+        type Target = HashMap<Address, Account>;
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
 
     /// Returns an empty state override set.
     ///
@@ -15,12 +15,23 @@ pub mod ens;
 mod pending_transaction;
 pub use pending_transaction::PendingTransaction;
 
+mod pending_replacement;
+pub use pending_replacement::{PendingReplacement, ReplacementError, MIN_REPLACEMENT_BUMP_PCT};
+
 mod pending_escalator;
-pub use pending_escalator::EscalatingPending;
+pub use pending_escalator::{is_nonce_too_low, EscalatingPending};
+
+mod reorg;
+pub use reorg::{Reorg, ReorgEvent, ReorgStream, DEFAULT_REORG_WINDOW};
 
 mod log_query;
 pub use log_query::LogQuery;
 
+pub mod metrics;
+
+mod batch;
+pub use batch::{BatchCall, BatchRequest, BatchResponse};
+
 mod stream;
 pub use futures_util::StreamExt;
 pub use stream::{interval, FilterWatcher, TransactionStream, DEFAULT_POLL_INTERVAL};
@@ -35,6 +46,7 @@ use async_trait::async_trait;
 use auto_impl::auto_impl;
 use ethers_core::types::transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
 use std::{error::Error, fmt::Debug, future::Future, pin::Pin};
 use url::Url;
 
@@ -61,13 +73,38 @@ pub(crate) type PinBoxFut<'a, T> =
 /// JSON-RPC provider.
 pub trait JsonRpcClient: Debug + Send + Sync {
     /// A JSON-RPC Error
-    type Error: Error + Into<ProviderError>;
+    type Error: Error + Send + Into<ProviderError>;
 
     /// Sends a request with the provided JSON-RPC and parameters serialized as JSON
     async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
     where
         T: Debug + Serialize + Send + Sync,
         R: DeserializeOwned;
+
+    /// Sends all calls of the batch and returns the raw result of every call, in the order of
+    /// the batch.
+    ///
+    /// The outer error is only returned if the batch as a whole failed, errors of single calls
+    /// are returned in their slot.
+    ///
+    /// The default implementation sends the calls one after the other, transports that support
+    /// JSON-RPC batches send them in a single round-trip instead.
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let mut responses = Vec::with_capacity(batch.len());
+        for call in batch.calls() {
+            // calls without params must not send `params: null`, see `Request`
+            let response = if call.params().is_null() {
+                self.request(call.method(), ()).await
+            } else {
+                self.request(call.method(), call.params()).await
+            };
+            responses.push(response);
+        }
+        Ok(responses)
+    }
 }
 
 use ethers_core::types::*;
@@ -510,6 +547,36 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().txpool_status().await.map_err(FromErr::from)
     }
 
+    // Geth `debug` support
+
+    /// Replays a transaction and returns its trace, as produced by the given tracing options
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: TxHash,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<GethTrace, Self::Error> {
+        self.inner().debug_trace_transaction(tx_hash, trace_options).await.map_err(FromErr::from)
+    }
+
+    /// Executes the given call on top of the given block and returns its trace
+    async fn debug_trace_call<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        req: T,
+        block: Option<BlockId>,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<GethTrace, Self::Error> {
+        self.inner().debug_trace_call(req, block, trace_options).await.map_err(FromErr::from)
+    }
+
+    /// Replays all transactions of the given block and returns their traces
+    async fn debug_trace_block_by_number(
+        &self,
+        block: Option<BlockNumber>,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>, Self::Error> {
+        self.inner().debug_trace_block_by_number(block, trace_options).await.map_err(FromErr::from)
+    }
+
     // Parity `trace` support
 
     /// Executes the given call and returns a number of possible traces for it
@@ -591,6 +658,36 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().parity_block_receipts(block).await.map_err(FromErr::from)
     }
 
+    // EIP-4337 bundler support
+
+    /// Sends the signed user operation to the bundler, which submits it to the `entry_point` in a
+    /// bundle. Returns the hash of the user operation.
+    async fn send_user_operation(
+        &self,
+        op: &UserOperation,
+        entry_point: &EntryPoint,
+    ) -> Result<H256, Self::Error> {
+        self.inner().send_user_operation(op, entry_point).await.map_err(FromErr::from)
+    }
+
+    /// Estimates the gas limits of the user operation. The signature of the operation must have
+    /// the length of a real signature for the estimate to be accurate.
+    async fn estimate_user_operation_gas(
+        &self,
+        op: &UserOperation,
+        entry_point: &EntryPoint,
+    ) -> Result<UserOperationGasEstimate, Self::Error> {
+        self.inner().estimate_user_operation_gas(op, entry_point).await.map_err(FromErr::from)
+    }
+
+    /// Gets the receipt of the user operation, `None` if it was not included yet
+    async fn get_user_operation_receipt(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, Self::Error> {
+        self.inner().get_user_operation_receipt(user_op_hash).await.map_err(FromErr::from)
+    }
+
     async fn subscribe<T, R>(
         &self,
         params: T,
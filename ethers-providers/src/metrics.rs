@@ -0,0 +1,462 @@
+//! Request metrics for providers and middlewares.
+//!
+//! A [`MetricsRecorder`] is notified about every request sent through a
+//! [`MetricsClient`](crate::MetricsClient) transport or the `MetricsMiddleware` of
+//! `ethers-middleware`, and can forward them to any metrics exporter. [`RequestMetrics`] is a
+//! recorder that aggregates them in memory and renders them in the Prometheus text format.
+use crate::{provider::error_response, JsonRpcError};
+use auto_impl::auto_impl;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Debug, Write},
+    future::Future,
+    sync::Mutex,
+    time::Duration,
+};
+use tracing_futures::Instrument;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Instant;
+
+/// The default upper bounds of the latency histogram buckets, from 5ms to 10s
+pub const DEFAULT_LATENCY_BUCKETS: [Duration; 11] = [
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// The outcome of a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// The request succeeded
+    Success,
+    /// The node responded with a JSON-RPC error with this code
+    JsonRpcError(i64),
+    /// The request failed without a JSON-RPC error response, e.g. because of a connection error
+    Error,
+    /// The request was dropped before it completed
+    Cancelled,
+}
+
+impl RequestOutcome {
+    /// Returns the outcome of a request that failed with `err`, looking for a JSON-RPC error
+    /// response in the chain of sources of the error
+    pub fn from_error(err: &(dyn Error + 'static)) -> Self {
+        let mut next = Some(err);
+        while let Some(err) = next {
+            if let Some(JsonRpcError { code, .. }) = error_response(err) {
+                return RequestOutcome::JsonRpcError(*code)
+            }
+            next = err.source();
+        }
+        RequestOutcome::Error
+    }
+
+    /// Returns `true` if the request succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self, RequestOutcome::Success)
+    }
+}
+
+/// Receives the start and the end of every request, to export them to a metrics backend.
+///
+/// The method of a request is the JSON-RPC method for requests of a transport, or the name of the
+/// `Middleware` method for requests of a middleware.
+#[auto_impl(&, Box, Arc)]
+pub trait MetricsRecorder: Send + Sync + Debug {
+    /// Called when a request for the `method` is sent
+    fn request_started(&self, method: &str);
+
+    /// Called when a request for the `method` completed, or was dropped before it completed,
+    /// after `latency`
+    fn request_finished(&self, method: &str, latency: Duration, outcome: RequestOutcome);
+}
+
+/// Sends the `request` for the `method` in a `tracing` span and records it with the `recorder`
+pub async fn record_request<R, F, T, E>(recorder: &R, method: &str, request: F) -> Result<T, E>
+where
+    R: MetricsRecorder + ?Sized,
+    F: Future<Output = Result<T, E>>,
+    E: Error + 'static,
+{
+    let span = tracing::debug_span!("request", method);
+    async move {
+        let mut guard = RequestGuard::new(recorder, method);
+        let res = request.await;
+        let outcome = match res {
+            Ok(_) => RequestOutcome::Success,
+            Err(ref err) => RequestOutcome::from_error(err),
+        };
+        guard.finish(outcome);
+        res
+    }
+    .instrument(span)
+    .await
+}
+
+/// Records the end of a request when it is dropped, so that requests that are dropped before they
+/// complete are not counted as in-flight forever
+pub(crate) struct RequestGuard<'a, R: MetricsRecorder + ?Sized> {
+    recorder: &'a R,
+    method: &'a str,
+    started: Instant,
+    finished: bool,
+}
+
+impl<'a, R: MetricsRecorder + ?Sized> RequestGuard<'a, R> {
+    pub(crate) fn new(recorder: &'a R, method: &'a str) -> Self {
+        recorder.request_started(method);
+        Self { recorder, method, started: Instant::now(), finished: false }
+    }
+
+    pub(crate) fn finish(&mut self, outcome: RequestOutcome) {
+        if self.finished {
+            return
+        }
+        self.finished = true;
+        let latency = self.started.elapsed();
+        tracing::debug!(?latency, ?outcome, "request finished");
+        self.recorder.request_finished(self.method, latency, outcome);
+    }
+}
+
+impl<'a, R: MetricsRecorder + ?Sized> Drop for RequestGuard<'a, R> {
+    fn drop(&mut self) {
+        self.finish(RequestOutcome::Cancelled)
+    }
+}
+
+/// A histogram of request latencies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// The upper bound of every bucket with the number of requests that completed within it, not
+    /// including the requests of smaller buckets
+    pub buckets: Vec<(Duration, u64)>,
+    /// The sum of all latencies
+    pub sum: Duration,
+    /// The number of recorded latencies
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    /// Creates an empty histogram with the given bucket bounds
+    pub fn new(bounds: &[Duration]) -> Self {
+        let mut bounds = bounds.to_vec();
+        bounds.sort();
+        bounds.dedup();
+        Self {
+            buckets: bounds.into_iter().map(|bound| (bound, 0)).collect(),
+            sum: Duration::ZERO,
+            count: 0,
+        }
+    }
+
+    /// Adds a latency to the histogram
+    pub fn record(&mut self, latency: Duration) {
+        if let Some((_, count)) = self.buckets.iter_mut().find(|(bound, _)| latency <= *bound) {
+            *count += 1;
+        }
+        self.sum += latency;
+        self.count += 1;
+    }
+
+    /// Returns the mean latency, if any latency was recorded
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0)
+            .then(|| Duration::from_secs_f64(self.sum.as_secs_f64() / self.count as f64))
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new(&DEFAULT_LATENCY_BUCKETS)
+    }
+}
+
+/// The metrics of the requests of one method
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    /// The number of sent requests
+    pub requests: u64,
+    /// The number of requests that did not complete yet
+    pub in_flight: u64,
+    /// The number of JSON-RPC error responses by error code
+    pub errors: BTreeMap<i64, u64>,
+    /// The number of requests that failed without a JSON-RPC error response
+    pub other_errors: u64,
+    /// The number of requests that were dropped before they completed
+    pub cancelled: u64,
+    /// The latencies of the completed requests
+    pub latency: LatencyHistogram,
+}
+
+/// A [`MetricsRecorder`] that aggregates the metrics of every method in memory.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{metrics::RequestMetrics, Http, MetricsClient, Middleware, Provider};
+/// use std::{str::FromStr, sync::Arc};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let metrics = Arc::new(RequestMetrics::default());
+/// let http = Http::from_str("http://localhost:8545")?;
+/// let provider = Provider::new(MetricsClient::new_with_recorder(http, metrics.clone()));
+///
+/// provider.get_block_number().await?;
+/// let block_number = metrics.method("eth_blockNumber").unwrap();
+/// assert_eq!(block_number.requests, 1);
+///
+/// // serve this from the `/metrics` endpoint of the application
+/// let exposition = metrics.to_prometheus("ethers");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RequestMetrics {
+    methods: Mutex<BTreeMap<String, MethodMetrics>>,
+    buckets: Vec<Duration>,
+}
+
+impl RequestMetrics {
+    /// Creates a recorder whose latency histograms have the given bucket bounds
+    pub fn with_buckets(buckets: impl Into<Vec<Duration>>) -> Self {
+        Self { methods: Default::default(), buckets: buckets.into() }
+    }
+
+    /// Returns the metrics of the `method`, if it was requested
+    pub fn method(&self, method: &str) -> Option<MethodMetrics> {
+        self.methods.lock().unwrap().get(method).cloned()
+    }
+
+    /// Returns the metrics of every requested method
+    pub fn snapshot(&self) -> BTreeMap<String, MethodMetrics> {
+        self.methods.lock().unwrap().clone()
+    }
+
+    /// Clears all metrics
+    pub fn reset(&self) {
+        self.methods.lock().unwrap().clear()
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format, with every metric name
+    /// prefixed by `namespace`
+    pub fn to_prometheus(&self, namespace: &str) -> String {
+        let methods = self.snapshot();
+        let mut out = String::new();
+        self.write_prometheus(&mut out, namespace, &methods).expect("writing to string");
+        out
+    }
+
+    fn write_prometheus(
+        &self,
+        out: &mut String,
+        namespace: &str,
+        methods: &BTreeMap<String, MethodMetrics>,
+    ) -> fmt::Result {
+        writeln!(out, "# HELP {}_rpc_requests_total The number of sent requests.", namespace)?;
+        writeln!(out, "# TYPE {}_rpc_requests_total counter", namespace)?;
+        for (method, metrics) in methods {
+            writeln!(
+                out,
+                "{}_rpc_requests_total{{method=\"{}\"}} {}",
+                namespace, method, metrics.requests
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP {}_rpc_requests_in_flight The number of pending requests.",
+            namespace
+        )?;
+        writeln!(out, "# TYPE {}_rpc_requests_in_flight gauge", namespace)?;
+        for (method, metrics) in methods {
+            writeln!(
+                out,
+                "{}_rpc_requests_in_flight{{method=\"{}\"}} {}",
+                namespace, method, metrics.in_flight
+            )?;
+        }
+
+        writeln!(out, "# HELP {}_rpc_errors_total The number of failed requests.", namespace)?;
+        writeln!(out, "# TYPE {}_rpc_errors_total counter", namespace)?;
+        for (method, metrics) in methods {
+            for (code, count) in &metrics.errors {
+                writeln!(
+                    out,
+                    "{}_rpc_errors_total{{method=\"{}\",code=\"{}\"}} {}",
+                    namespace, method, code, count
+                )?;
+            }
+            if metrics.other_errors > 0 {
+                writeln!(
+                    out,
+                    "{}_rpc_errors_total{{method=\"{}\",code=\"other\"}} {}",
+                    namespace, method, metrics.other_errors
+                )?;
+            }
+            if metrics.cancelled > 0 {
+                writeln!(
+                    out,
+                    "{}_rpc_errors_total{{method=\"{}\",code=\"cancelled\"}} {}",
+                    namespace, method, metrics.cancelled
+                )?;
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP {}_rpc_request_duration_seconds The latency of completed requests.",
+            namespace
+        )?;
+        writeln!(out, "# TYPE {}_rpc_request_duration_seconds histogram", namespace)?;
+        for (method, metrics) in methods {
+            let histogram = &metrics.latency;
+            let mut cumulative = 0;
+            for (bound, count) in &histogram.buckets {
+                cumulative += count;
+                writeln!(
+                    out,
+                    "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    namespace,
+                    method,
+                    bound.as_secs_f64(),
+                    cumulative
+                )?;
+            }
+            writeln!(
+                out,
+                "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                namespace, method, histogram.count
+            )?;
+            writeln!(
+                out,
+                "{}_rpc_request_duration_seconds_sum{{method=\"{}\"}} {}",
+                namespace,
+                method,
+                histogram.sum.as_secs_f64()
+            )?;
+            writeln!(
+                out,
+                "{}_rpc_request_duration_seconds_count{{method=\"{}\"}} {}",
+                namespace, method, histogram.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for RequestMetrics {
+    fn default() -> Self {
+        Self::with_buckets(DEFAULT_LATENCY_BUCKETS)
+    }
+}
+
+impl MetricsRecorder for RequestMetrics {
+    fn request_started(&self, method: &str) {
+        let mut methods = self.methods.lock().unwrap();
+        let metrics = methods.entry(method.to_string()).or_insert_with(|| MethodMetrics {
+            latency: LatencyHistogram::new(&self.buckets),
+            ..Default::default()
+        });
+        metrics.requests += 1;
+        metrics.in_flight += 1;
+    }
+
+    fn request_finished(&self, method: &str, latency: Duration, outcome: RequestOutcome) {
+        let mut methods = self.methods.lock().unwrap();
+        // the metrics may have been reset while the request was in flight
+        let metrics = match methods.get_mut(method) {
+            Some(metrics) => metrics,
+            None => return,
+        };
+        metrics.in_flight = metrics.in_flight.saturating_sub(1);
+        match outcome {
+            RequestOutcome::Success => {}
+            RequestOutcome::JsonRpcError(code) => *metrics.errors.entry(code).or_default() += 1,
+            RequestOutcome::Error => metrics.other_errors += 1,
+            RequestOutcome::Cancelled => {
+                metrics.cancelled += 1;
+                return
+            }
+        }
+        metrics.latency.record(latency);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HttpClientError, ProviderError};
+
+    #[test]
+    fn finds_error_code() {
+        let err = JsonRpcError { code: -32000, message: "execution reverted".into(), data: None };
+        let err: ProviderError = HttpClientError::from(err).into();
+        assert_eq!(RequestOutcome::from_error(&err), RequestOutcome::JsonRpcError(-32000));
+        assert_eq!(
+            RequestOutcome::from_error(&ProviderError::CustomError("oops".into())),
+            RequestOutcome::Error
+        );
+    }
+
+    #[test]
+    fn records_dropped_requests_as_cancelled() {
+        let metrics = RequestMetrics::default();
+        let guard = RequestGuard::new(&metrics, "eth_call");
+        assert_eq!(metrics.method("eth_call").unwrap().in_flight, 1);
+        drop(guard);
+
+        let call = metrics.method("eth_call").unwrap();
+        assert_eq!(call.in_flight, 0);
+        assert_eq!(call.cancelled, 1);
+        assert_eq!(call.latency.count, 0);
+    }
+
+    #[test]
+    fn aggregates_requests() {
+        let metrics = RequestMetrics::default();
+        metrics.request_started("eth_call");
+        metrics.request_started("eth_call");
+        metrics.request_started("eth_call");
+        assert_eq!(metrics.method("eth_call").unwrap().in_flight, 3);
+
+        metrics.request_finished("eth_call", Duration::from_millis(3), RequestOutcome::Success);
+        metrics.request_finished(
+            "eth_call",
+            Duration::from_millis(30),
+            RequestOutcome::JsonRpcError(3),
+        );
+        metrics.request_finished("eth_call", Duration::from_secs(1), RequestOutcome::Cancelled);
+
+        let call = metrics.method("eth_call").unwrap();
+        assert_eq!(call.requests, 3);
+        assert_eq!(call.in_flight, 0);
+        assert_eq!(call.errors, BTreeMap::from([(3, 1)]));
+        assert_eq!(call.cancelled, 1);
+        assert_eq!(call.latency.count, 2);
+        assert_eq!(call.latency.buckets[0], (Duration::from_millis(5), 1));
+        assert_eq!(call.latency.buckets[3], (Duration::from_millis(50), 1));
+
+        let exposition = metrics.to_prometheus("ethers");
+        assert!(exposition.contains("ethers_rpc_requests_total{method=\"eth_call\"} 3\n"));
+        assert!(exposition.contains("ethers_rpc_errors_total{method=\"eth_call\",code=\"3\"} 1\n"));
+        assert!(exposition.contains(
+            "ethers_rpc_request_duration_seconds_bucket{method=\"eth_call\",le=\"0.05\"} 2\n"
+        ));
+        assert!(exposition.contains(
+            "ethers_rpc_request_duration_seconds_bucket{method=\"eth_call\",le=\"+Inf\"} 2\n"
+        ));
+    }
+}
@@ -4,6 +4,7 @@ use ethers_core::types::{Bytes, TransactionReceipt, H256};
 use futures_util::{stream::FuturesUnordered, StreamExt};
 use pin_project::pin_project;
 use std::{
+    fmt,
     future::Future,
     pin::Pin,
     task::Poll,
@@ -128,8 +129,9 @@ macro_rules! completed {
     };
 }
 
-/// Tests Provider error for nonce too low issue through debug contents
-fn is_nonce_too_low(e: &ProviderError) -> bool {
+/// Tests an error for the "nonce too low" issue through its debug contents, which indicates that
+/// a transaction with the same nonce was already included
+pub fn is_nonce_too_low<E: fmt::Debug + ?Sized>(e: &E) -> bool {
     let debug_str = format!("{:?}", e);
 
     debug_str.contains("nonce too low") // Geth, Arbitrum, Optimism
@@ -0,0 +1,358 @@
+use crate::{FromErr, JsonRpcClient, Middleware, PendingTransaction, ProviderError};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Bytes, TransactionReceipt, TxHash, U256,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use thiserror::Error;
+
+/// The minimum fee increase in percent most nodes require to accept a transaction that replaces a
+/// pending transaction with the same nonce
+pub const MIN_REPLACEMENT_BUMP_PCT: u64 = 10;
+
+/// The gas of a plain value transfer, used for cancellations
+const TRANSFER_GAS: u64 = 21_000;
+
+/// A transaction that was replaced by one or more transactions with the same nonce but higher
+/// fees, see [`PendingTransaction::speed_up`] and [`PendingTransaction::cancel`].
+///
+/// `await`'ing it resolves to the receipt of whichever version of the transaction gets mined. If
+/// all versions are dropped from the mempool it resolves to `None`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ethers_providers::{Provider, Http, Middleware};
+/// # use ethers_core::types::{Address, TransactionRequest};
+/// # use std::convert::TryFrom;
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// # let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// # let from = Address::random();
+/// let tx = TransactionRequest::pay(Address::random(), 1000).from(from);
+/// let pending = provider.send_transaction(tx, None).await?;
+///
+/// // the transaction is stuck, resend it with 25% higher fees
+/// let receipt = pending.speed_up(&provider, 25).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+#[derive(Debug)]
+pub struct PendingReplacement<'a, P> {
+    /// All versions of the transaction that may still be mined, the original first
+    pending: Vec<PendingTransaction<'a, P>>,
+    /// The most recently broadcast version of the transaction
+    latest: TypedTransaction,
+    confirmations: usize,
+}
+
+impl<'a, P: JsonRpcClient> PendingReplacement<'a, P> {
+    /// Instantiates a new replacement of the `original` pending transaction, whose request is
+    /// `tx`. No replacement is broadcast yet.
+    pub(crate) fn new(
+        original: PendingTransaction<'a, P>,
+        tx: TypedTransaction,
+        confirmations: usize,
+    ) -> Self {
+        Self { pending: vec![original], latest: tx, confirmations }
+    }
+
+    /// Returns the hashes of all versions of the transaction that are still awaited, the original
+    /// first
+    pub fn tx_hashes(&self) -> Vec<TxHash> {
+        self.pending.iter().map(|pending| pending.tx_hash()).collect()
+    }
+
+    /// Returns the most recently broadcast version of the transaction
+    pub fn latest(&self) -> &TypedTransaction {
+        &self.latest
+    }
+
+    /// Sets the number of confirmations for the mined version of the transaction to resolve to a
+    /// receipt
+    pub fn confirmations(mut self, confs: usize) -> Self {
+        self.confirmations = confs;
+        self.pending =
+            self.pending.into_iter().map(|pending| pending.confirmations(confs)).collect();
+        self
+    }
+
+    /// Broadcasts another replacement with the fees of the latest version increased by
+    /// `bump_pct` percent, but at least by [`MIN_REPLACEMENT_BUMP_PCT`]
+    pub async fn speed_up<M>(
+        self,
+        client: &'a M,
+        bump_pct: u64,
+    ) -> Result<PendingReplacement<'a, P>, ReplacementError<M>>
+    where
+        M: Middleware<Provider = P>,
+    {
+        let tx = self.latest.clone();
+        self.replace(client, tx, bump_pct).await
+    }
+
+    /// Cancels the transaction by broadcasting a transfer of 0 value from the sender to itself,
+    /// with the fees of the latest version increased by [`MIN_REPLACEMENT_BUMP_PCT`]
+    pub async fn cancel<M>(
+        self,
+        client: &'a M,
+    ) -> Result<PendingReplacement<'a, P>, ReplacementError<M>>
+    where
+        M: Middleware<Provider = P>,
+    {
+        let tx = cancellation(&self.latest);
+        self.replace(client, tx, MIN_REPLACEMENT_BUMP_PCT).await
+    }
+
+    /// Bumps the fees of `tx` and broadcasts it
+    async fn replace<M>(
+        mut self,
+        client: &'a M,
+        mut tx: TypedTransaction,
+        bump_pct: u64,
+    ) -> Result<PendingReplacement<'a, P>, ReplacementError<M>>
+    where
+        M: Middleware<Provider = P>,
+    {
+        bump_fees(&mut tx, bump_pct);
+        let pending = client
+            .send_transaction(tx.clone(), None)
+            .await
+            .map_err(ReplacementError::MiddlewareError)?;
+        tracing::debug!(
+            tx_hash = ?pending.tx_hash(),
+            replaced = ?self.pending.last().map(|pending| pending.tx_hash()),
+            "broadcast replacement transaction"
+        );
+        self.pending.push(pending.confirmations(self.confirmations));
+        self.latest = tx;
+        Ok(self)
+    }
+}
+
+impl<'a, P: JsonRpcClient> Future for PendingReplacement<'a, P> {
+    type Output = Result<Option<TransactionReceipt>, ProviderError>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let mut idx = 0;
+        while idx < this.pending.len() {
+            match Pin::new(&mut this.pending[idx]).poll(ctx) {
+                Poll::Ready(Ok(Some(receipt))) => {
+                    this.pending.clear();
+                    return Poll::Ready(Ok(Some(receipt)))
+                }
+                // this version was dropped from the mempool, most likely because another version
+                // with the same nonce was mined
+                Poll::Ready(Ok(None)) => {
+                    this.pending.remove(idx);
+                }
+                Poll::Ready(Err(err)) => {
+                    this.pending.clear();
+                    return Poll::Ready(Err(err))
+                }
+                Poll::Pending => idx += 1,
+            }
+        }
+
+        if this.pending.is_empty() {
+            Poll::Ready(Ok(None))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Thrown when a pending transaction can not be replaced
+pub enum ReplacementError<M: Middleware> {
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+    /// Thrown when the transaction to replace is unknown to the node
+    #[error("transaction {0:?} not found")]
+    NotFound(TxHash),
+    /// Thrown when the transaction to replace was already mined
+    #[error("transaction {0:?} was already mined")]
+    AlreadyMined(TxHash),
+}
+
+impl<M: Middleware> FromErr<M::Error> for ReplacementError<M> {
+    fn from(src: M::Error) -> Self {
+        ReplacementError::MiddlewareError(src)
+    }
+}
+
+/// Increases `fee` by `bump_pct` percent but at least by [`MIN_REPLACEMENT_BUMP_PCT`], rounding
+/// up so that the minimum is never missed
+fn bump_fee(fee: U256, bump_pct: u64) -> U256 {
+    let pct = U256::from(bump_pct.max(MIN_REPLACEMENT_BUMP_PCT)).saturating_add(100.into());
+    (fee.saturating_mul(pct) + 99) / 100
+}
+
+/// Increases the fees of the transaction, keeping its legacy or EIP-1559 shape
+pub(crate) fn bump_fees(tx: &mut TypedTransaction, bump_pct: u64) {
+    match tx {
+        TypedTransaction::Eip1559(ref mut inner) => {
+            // nodes require both fees to be bumped
+            inner.max_fee_per_gas = inner.max_fee_per_gas.map(|fee| bump_fee(fee, bump_pct));
+            inner.max_priority_fee_per_gas =
+                inner.max_priority_fee_per_gas.map(|fee| bump_fee(fee, bump_pct));
+        }
+        TypedTransaction::Legacy(_) | TypedTransaction::Eip2930(_) => {
+            if let Some(gas_price) = tx.gas_price() {
+                tx.set_gas_price(bump_fee(gas_price, bump_pct));
+            }
+        }
+    }
+}
+
+/// Turns the transaction into a transfer of 0 value from the sender to itself, keeping its nonce,
+/// fees and type
+pub(crate) fn cancellation(tx: &TypedTransaction) -> TypedTransaction {
+    let mut cancel = tx.clone();
+    if let Some(from) = tx.from().copied() {
+        cancel.set_to(from);
+    }
+    cancel.set_value(0u64).set_data(Bytes::default()).set_gas(TRANSFER_GAS);
+    if tx.access_list().is_some() {
+        cancel.set_access_list(Default::default());
+    }
+    cancel
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::Provider;
+    use ethers_core::types::{
+        Address, Eip1559TransactionRequest, Transaction, TransactionRequest, H256, U64,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn bumps_at_least_the_minimum() {
+        assert_eq!(bump_fee(100u64.into(), 0), 110u64.into());
+        assert_eq!(bump_fee(100u64.into(), 25), 125u64.into());
+        // rounds up
+        assert_eq!(bump_fee(11u64.into(), 10), 13u64.into());
+    }
+
+    #[test]
+    fn bumps_fees_of_the_transaction_type() {
+        let mut tx: TypedTransaction = TransactionRequest::new().gas_price(100u64).into();
+        bump_fees(&mut tx, 50);
+        assert_eq!(tx.gas_price(), Some(150u64.into()));
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(200u64)
+            .max_priority_fee_per_gas(10u64)
+            .into();
+        bump_fees(&mut tx, 0);
+        let tx = tx.as_eip1559_ref().unwrap();
+        assert_eq!(tx.max_fee_per_gas, Some(220u64.into()));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(11u64.into()));
+    }
+
+    #[test]
+    fn cancels_with_self_transfer() {
+        let from = Address::random();
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(from)
+            .to(Address::random())
+            .value(100u64)
+            .data(vec![1, 2, 3])
+            .gas(100_000u64)
+            .nonce(7u64)
+            .max_fee_per_gas(200u64)
+            .into();
+
+        let cancel = cancellation(&tx);
+        assert_eq!(cancel.to(), Some(&from.into()));
+        assert_eq!(cancel.value(), Some(&U256::zero()));
+        assert_eq!(cancel.data(), Some(&Bytes::default()));
+        assert_eq!(cancel.gas(), Some(&TRANSFER_GAS.into()));
+        assert_eq!(cancel.nonce(), Some(&7u64.into()));
+        assert!(cancel.as_eip1559_ref().is_some());
+    }
+
+    #[tokio::test]
+    async fn speeds_up_legacy_transaction() {
+        let (provider, mock) = Provider::mocked();
+        let from = Address::random();
+        let original = Transaction {
+            hash: H256::random(),
+            from,
+            to: Some(Address::random()),
+            nonce: 3u64.into(),
+            gas: 21_000u64.into(),
+            gas_price: Some(100u64.into()),
+            transaction_type: Some(U64::zero()),
+            ..Default::default()
+        };
+
+        let replacement_hash = H256::random();
+        mock.push(replacement_hash).unwrap();
+        mock.push(original.clone()).unwrap();
+
+        let pending = PendingTransaction::new(original.hash, &provider);
+        let replaced = pending.speed_up(&provider, 20).await.unwrap();
+        assert_eq!(replaced.tx_hashes(), vec![original.hash, replacement_hash]);
+
+        let mut expected: TypedTransaction = (&original).into();
+        expected.set_gas_price(120u64);
+        mock.assert_request("eth_getTransactionByHash", [original.hash]).unwrap();
+        mock.assert_request("eth_sendTransaction", [&expected]).unwrap();
+        assert_eq!(replaced.latest(), &expected);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_replace_mined_transaction() {
+        let (provider, mock) = Provider::mocked();
+        let tx = Transaction { block_number: Some(1u64.into()), ..Default::default() };
+        mock.push(tx.clone()).unwrap();
+
+        let err = PendingTransaction::new(tx.hash, &provider).cancel(&provider).await.unwrap_err();
+        assert!(matches!(err, ReplacementError::AlreadyMined(hash) if hash == tx.hash));
+    }
+
+    #[tokio::test]
+    async fn resolves_to_mined_replacement() {
+        // every version is polled through its own provider so the mocked responses do not depend
+        // on the order the versions are polled in
+        let (original_provider, original_mock) = Provider::mocked();
+        let (replacement_provider, replacement_mock) = Provider::mocked();
+        let interval = Duration::from_millis(1);
+
+        // the original was replaced and is no longer known
+        original_mock.push::<Option<Transaction>, _>(None).unwrap();
+
+        let replacement = Transaction {
+            hash: H256::random(),
+            block_number: Some(1u64.into()),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: replacement.hash,
+            block_number: Some(1u64.into()),
+            ..Default::default()
+        };
+        replacement_mock.push(receipt.clone()).unwrap();
+        replacement_mock.push(replacement.clone()).unwrap();
+
+        let original = PendingTransaction::new(H256::random(), &original_provider)
+            .interval(interval)
+            .retries(0);
+        let mut replaced = PendingReplacement::new(original, TypedTransaction::default(), 1);
+        replaced.pending.push(
+            PendingTransaction::new(replacement.hash, &replacement_provider).interval(interval),
+        );
+
+        assert_eq!(replaced.await.unwrap(), Some(receipt));
+    }
+}
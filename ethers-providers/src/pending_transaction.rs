@@ -1,8 +1,11 @@
 use crate::{
     stream::{interval, DEFAULT_POLL_INTERVAL},
-    JsonRpcClient, Middleware, PinBoxFut, Provider, ProviderError,
+    JsonRpcClient, Middleware, PendingReplacement, PinBoxFut, Provider, ProviderError,
+    ReplacementError,
+};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Transaction, TransactionReceipt, TxHash, U64,
 };
-use ethers_core::types::{Transaction, TransactionReceipt, TxHash, U64};
 use futures_core::stream::Stream;
 use futures_util::stream::StreamExt;
 use pin_project::pin_project;
@@ -122,6 +125,61 @@ impl<'a, P: JsonRpcClient> PendingTransaction<'a, P> {
         self.retries_remaining = retries;
         self
     }
+
+    /// Broadcasts a replacement of the pending transaction with the same nonce and the fees
+    /// increased by `bump_pct` percent, but at least by the
+    /// [`MIN_REPLACEMENT_BUMP_PCT`](crate::MIN_REPLACEMENT_BUMP_PCT) nodes require. EIP-1559
+    /// transactions get both their max fee and priority fee bumped, legacy transactions their
+    /// gas price.
+    ///
+    /// The replacement is signed and sent through `client`, the returned future resolves to the
+    /// receipt of whichever version of the transaction gets mined.
+    pub async fn speed_up<M>(
+        self,
+        client: &'a M,
+        bump_pct: u64,
+    ) -> Result<PendingReplacement<'a, P>, ReplacementError<M>>
+    where
+        M: Middleware<Provider = P>,
+    {
+        let tx = self.replaceable_request(client).await?;
+        let confirmations = self.confirmations;
+        PendingReplacement::new(self, tx, confirmations).speed_up(client, bump_pct).await
+    }
+
+    /// Cancels the pending transaction by broadcasting a transfer of 0 value from the sender to
+    /// itself with the same nonce and the fees increased by
+    /// [`MIN_REPLACEMENT_BUMP_PCT`](crate::MIN_REPLACEMENT_BUMP_PCT).
+    ///
+    /// The returned future resolves to the receipt of whichever version of the transaction gets
+    /// mined, which is not necessarily the cancellation.
+    pub async fn cancel<M>(
+        self,
+        client: &'a M,
+    ) -> Result<PendingReplacement<'a, P>, ReplacementError<M>>
+    where
+        M: Middleware<Provider = P>,
+    {
+        let tx = self.replaceable_request(client).await?;
+        let confirmations = self.confirmations;
+        PendingReplacement::new(self, tx, confirmations).cancel(client).await
+    }
+
+    /// Fetches the pending transaction and returns it as request, in its original shape
+    async fn replaceable_request<M: Middleware>(
+        &self,
+        client: &M,
+    ) -> Result<TypedTransaction, ReplacementError<M>> {
+        let tx = client
+            .get_transaction(self.tx_hash)
+            .await
+            .map_err(ReplacementError::MiddlewareError)?
+            .ok_or(ReplacementError::NotFound(self.tx_hash))?;
+        if tx.block_number.is_some() {
+            return Err(ReplacementError::AlreadyMined(self.tx_hash))
+        }
+        Ok((&tx).into())
+    }
 }
 
 impl<'a, P> PendingTransaction<'a, P> {
@@ -3,8 +3,9 @@ use crate::{
     ens, erc, maybe,
     pubsub::{PubsubClient, SubscriptionStream},
     stream::{FilterWatcher, DEFAULT_POLL_INTERVAL},
-    FromErr, Http as HttpProvider, JsonRpcClient, JsonRpcClientWrapper, LogQuery, MockProvider,
-    PendingTransaction, QuorumProvider, RwClient, SyncingStatus,
+    BatchRequest, BatchResponse, FromErr, Http as HttpProvider, HttpClientError, JsonRpcClient,
+    JsonRpcClientWrapper, JsonRpcError, LogQuery, MockProvider, PendingTransaction, QuorumProvider,
+    RwClient, SyncingStatus,
 };
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "ws"))]
@@ -21,10 +22,11 @@ use ethers_core::{
     abi::{self, Detokenize, ParamType},
     types::{
         transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
-        Address, Block, BlockId, BlockNumber, BlockTrace, Bytes, EIP1186ProofResponse, FeeHistory,
-        Filter, FilterBlockOption, Log, NameOrAddress, Selector, Signature, Trace, TraceFilter,
-        TraceType, Transaction, TransactionReceipt, TransactionRequest, TxHash, TxpoolContent,
-        TxpoolInspect, TxpoolStatus, H256, U256, U64,
+        Address, Block, BlockId, BlockNumber, BlockTrace, Bytes, EIP1186ProofResponse, EntryPoint,
+        FeeHistory, Filter, FilterBlockOption, GethDebugTracingOptions, GethTrace, GethTraceResult,
+        Log, NameOrAddress, Selector, Signature, Trace, TraceFilter, TraceType, Transaction,
+        TransactionReceipt, TransactionRequest, TxHash, TxpoolContent, TxpoolInspect, TxpoolStatus,
+        UserOperation, UserOperationGasEstimate, UserOperationReceipt, H256, U256, U64,
     },
     utils,
 };
@@ -146,6 +148,62 @@ pub enum ProviderError {
     SignerUnavailable,
 }
 
+impl ProviderError {
+    /// Returns the JSON-RPC error response of the node, if the request failed with one.
+    ///
+    /// This looks through the error types of the transports of this crate, including the wrapping
+    /// transports like [`RetryClient`], [`QuorumProvider`](crate::QuorumProvider) and
+    /// [`FallbackProvider`](crate::FallbackProvider), and the [`source`](std::error::Error::source)
+    /// chain of custom transport errors.
+    pub fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            ProviderError::JsonRpcClientError(err) => error_response(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the JSON-RPC error response of the node if `err`, or one of its sources, is one of the
+/// error types of the transports of this crate that failed with one
+pub(crate) fn error_response<'a>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a JsonRpcError> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(err) = err.downcast_ref::<ProviderError>() {
+            return err.as_error_response()
+        }
+        if let Some(err) = err.downcast_ref::<JsonRpcError>() {
+            return Some(err)
+        }
+        if let Some(err) = err.downcast_ref::<HttpClientError>() {
+            return err.as_error_response()
+        }
+        #[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+        if let Some(err) = err.downcast_ref::<crate::WsClientError>() {
+            return err.as_error_response()
+        }
+        #[cfg(all(target_family = "unix", feature = "ipc"))]
+        if let Some(err) = err.downcast_ref::<crate::IpcError>() {
+            return err.as_error_response()
+        }
+        if let Some(crate::QuorumError::NoQuorumReached { errors, .. }) =
+            err.downcast_ref::<crate::QuorumError>()
+        {
+            return errors.iter().find_map(ProviderError::as_error_response)
+        }
+        if let Some(crate::FallbackError::AllProvidersFailed(errors)) =
+            err.downcast_ref::<crate::FallbackError>()
+        {
+            return errors.iter().find_map(ProviderError::as_error_response)
+        }
+        // wrapping transports like `RetryClient` and `RwClient` expose the error of the inner
+        // transport as source
+        current = err.source();
+    }
+    None
+}
+
 /// Types of filters supported by the JSON-RPC.
 #[derive(Clone, Debug)]
 pub enum FilterKind<'a> {
@@ -215,6 +273,23 @@ impl<P: JsonRpcClient> Provider<P> {
         Ok(res)
     }
 
+    /// Sends all calls of the batch in a single round-trip, if supported by the transport.
+    ///
+    /// The responses are returned in the order of the calls in the batch, each with its own
+    /// result. See [`BatchRequest`] for an example.
+    pub async fn send_batch(&self, batch: &BatchRequest) -> Result<BatchResponse, ProviderError> {
+        let span = tracing::trace_span!("rpc_batch", calls = batch.len());
+        let responses = async move {
+            trace!("tx");
+            let responses = self.inner.request_batch(batch).await.map_err(Into::into)?;
+            trace!(rx = responses.len());
+            Ok::<_, ProviderError>(responses)
+        }
+        .instrument(span)
+        .await?;
+        Ok(BatchResponse::new(responses.into_iter().map(|res| res.map_err(Into::into)).collect()))
+    }
+
     async fn get_block_gen<Tx: Default + Serialize + DeserializeOwned + Debug>(
         &self,
         id: BlockId,
@@ -481,6 +556,37 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("parity_getBlockReceipts", vec![block.into()]).await
     }
 
+    async fn send_user_operation(
+        &self,
+        op: &UserOperation,
+        entry_point: &EntryPoint,
+    ) -> Result<H256, ProviderError> {
+        let op = op
+            .to_rpc(entry_point.version)
+            .map_err(|err| ProviderError::CustomError(err.to_string()))?;
+        let entry_point = utils::serialize(&entry_point.address);
+        self.request("eth_sendUserOperation", [op, entry_point]).await
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        op: &UserOperation,
+        entry_point: &EntryPoint,
+    ) -> Result<UserOperationGasEstimate, ProviderError> {
+        let op = op
+            .to_rpc(entry_point.version)
+            .map_err(|err| ProviderError::CustomError(err.to_string()))?;
+        let entry_point = utils::serialize(&entry_point.address);
+        self.request("eth_estimateUserOperationGas", [op, entry_point]).await
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, ProviderError> {
+        self.request("eth_getUserOperationReceipt", [user_op_hash]).await
+    }
+
     /// Gets the current gas price as estimated by the node
     async fn get_gas_price(&self) -> Result<U256, ProviderError> {
         self.request("eth_gasPrice", ()).await
@@ -1021,6 +1127,52 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("txpool_status", ()).await
     }
 
+    /// Replays a transaction and returns its trace, as produced by the given tracing options.
+    /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracetransaction)
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: TxHash,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<GethTrace, ProviderError> {
+        let tx_hash = utils::serialize(&tx_hash);
+        let opts = utils::serialize(&trace_options);
+        let trace = self.request("debug_traceTransaction", [tx_hash, opts]).await?;
+        Ok(GethTrace::from_tracer_output(trace, &trace_options)?)
+    }
+
+    /// Executes the given call on top of the given block and returns its trace.
+    /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracecall)
+    async fn debug_trace_call<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        req: T,
+        block: Option<BlockId>,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<GethTrace, ProviderError> {
+        let req = req.into();
+        let req = utils::serialize(&req);
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        let opts = utils::serialize(&trace_options);
+        let trace = self.request("debug_traceCall", [req, block, opts]).await?;
+        Ok(GethTrace::from_tracer_output(trace, &trace_options)?)
+    }
+
+    /// Replays all transactions of the given block and returns their traces.
+    /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-debug#debug_traceblockbynumber)
+    async fn debug_trace_block_by_number(
+        &self,
+        block: Option<BlockNumber>,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>, ProviderError> {
+        let block = utils::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let opts = utils::serialize(&trace_options);
+        let traces: Vec<serde_json::Value> =
+            self.request("debug_traceBlockByNumber", [block, opts]).await?;
+        Ok(traces
+            .into_iter()
+            .map(|trace| GethTraceResult::from_tracer_output(trace, &trace_options))
+            .collect::<Result<_, _>>()?)
+    }
+
     /// Executes the given call and returns a number of possible traces for it
     async fn trace_call<T: Into<TypedTransaction> + Send + Sync>(
         &self,
@@ -1310,6 +1462,17 @@ impl Provider<crate::Ws> {
         Ok(Self::new(ws))
     }
 
+    /// Connection to a websocket endpoint that reconnects once the connection dropped, see
+    /// [`crate::Ws::connect_with_reconnects`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect_with_reconnects(
+        conn: impl Into<crate::ConnectionDetails>,
+        config: crate::ReconnectConfig,
+    ) -> Result<Self, ProviderError> {
+        let ws = crate::Ws::connect_with_reconnects(conn, config).await?;
+        Ok(Self::new(ws))
+    }
+
     /// Direct connection to a websocket endpoint
     #[cfg(target_arch = "wasm32")]
     pub async fn connect(url: &str) -> Result<Self, ProviderError> {
@@ -1603,7 +1766,7 @@ pub mod dev_rpc {
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
     use super::*;
-    use crate::Http;
+    use crate::{Http, RetryClientError, RwClientError};
     use ethers_core::{
         types::{
             transaction::eip2930::AccessList, Eip1559TransactionRequest, TransactionRequest, H256,
@@ -1628,6 +1791,42 @@ mod tests {
         assert_eq!(params, r#"["0x295a70b2de5e3953354a6a8344e616ed314d7251","0x0","latest"]"#);
     }
 
+    #[test]
+    fn can_find_error_response_of_wrapping_transports() {
+        let response = || JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some("0x08c379a0".into()),
+        };
+        let http_err = || HttpClientError::from(response());
+        let revert_data =
+            |err: &ProviderError| err.as_error_response().and_then(JsonRpcError::as_revert_data);
+        let expected = Some(Bytes::from(vec![0x08, 0xc3, 0x79, 0xa0]));
+
+        let err: ProviderError = RetryClientError::<RetryClient<Http>>::ProviderError(
+            RetryClientError::ProviderError(http_err()),
+        )
+        .into();
+        assert_eq!(revert_data(&err), expected);
+
+        let err: ProviderError = RwClientError::<MockProvider, Http>::Write(http_err()).into();
+        assert_eq!(revert_data(&err), expected);
+
+        let err: ProviderError = crate::QuorumError::NoQuorumReached {
+            values: vec![],
+            errors: vec![ProviderError::CustomError("timeout".to_string()), http_err().into()],
+        }
+        .into();
+        assert_eq!(revert_data(&err), expected);
+
+        let err: ProviderError =
+            crate::FallbackError::AllProvidersFailed(vec![http_err().into()]).into();
+        assert_eq!(revert_data(&err), expected);
+
+        let err: ProviderError = RetryClientError::<Http>::TimeoutError.into();
+        assert!(err.as_error_response().is_none());
+    }
+
     #[tokio::test]
     // Test vector from: https://docs.ethers.io/ethers.js/v5-beta/api-providers.html#id2
     async fn mainnet_resolve_name() {
@@ -1852,6 +2051,78 @@ mod tests {
         dbg!(traces);
     }
 
+    #[tokio::test]
+    async fn test_debug_trace_transaction() {
+        use ethers_core::types::{CallConfig, GethDebugBuiltInTracerType, GethDebugTracerConfig};
+
+        let (provider, mock) = Provider::mocked();
+        let call_frame = serde_json::json!({
+            "type": "CALL",
+            "from": "0x0000000000000000000000000000000000000001",
+            "to": "0x0000000000000000000000000000000000000002",
+            "gas": "0x5208",
+            "gasUsed": "0x5208",
+            "input": "0x",
+        });
+        mock.push(call_frame).unwrap();
+
+        let tx_hash = H256::repeat_byte(1);
+        let options = GethDebugTracingOptions::default()
+            .tracer(GethDebugBuiltInTracerType::CallTracer)
+            .tracer_config(GethDebugTracerConfig::CallTracer(CallConfig {
+                only_top_call: Some(true),
+                with_log: None,
+            }));
+        let trace = provider.debug_trace_transaction(tx_hash, options).await.unwrap();
+        match trace {
+            GethTrace::CallTracer(frame) => assert_eq!(frame.gas_used, 21000u64.into()),
+            _ => panic!("expected a call frame"),
+        }
+
+        mock.assert_request(
+            "debug_traceTransaction",
+            [
+                serde_json::json!(tx_hash),
+                serde_json::json!({ "tracer": "callTracer", "tracerConfig": { "onlyTopCall": true } }),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_user_operations() {
+        let (provider, mock) = Provider::mocked();
+        let op = UserOperation::new(Address::repeat_byte(0x11)).nonce(1u64);
+        let entry_point = EntryPoint::v0_7();
+
+        mock.push(serde_json::json!({
+            "preVerificationGas": "0xc350",
+            "verificationGasLimit": "0x30d40",
+            "callGasLimit": "0x186a0",
+        }))
+        .unwrap();
+        let estimate = provider.estimate_user_operation_gas(&op, &entry_point).await.unwrap();
+        assert_eq!(estimate.call_gas_limit, 100_000u64.into());
+        mock.assert_request(
+            "eth_estimateUserOperationGas",
+            [op.to_rpc(entry_point.version).unwrap(), serde_json::json!(entry_point.address)],
+        )
+        .unwrap();
+
+        let user_op_hash = H256::repeat_byte(1);
+        mock.push(user_op_hash).unwrap();
+        assert_eq!(provider.send_user_operation(&op, &entry_point).await.unwrap(), user_op_hash);
+        mock.assert_request(
+            "eth_sendUserOperation",
+            [op.to_rpc(entry_point.version).unwrap(), serde_json::json!(entry_point.address)],
+        )
+        .unwrap();
+
+        mock.push(serde_json::Value::Null).unwrap();
+        assert!(provider.get_user_operation_receipt(user_op_hash).await.unwrap().is_none());
+        mock.assert_request("eth_getUserOperationReceipt", [user_op_hash]).unwrap();
+    }
+
     #[tokio::test]
     async fn test_fill_transaction_1559() {
         let (mut provider, mock) = Provider::mocked();
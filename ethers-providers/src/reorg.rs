@@ -0,0 +1,442 @@
+#![allow(clippy::return_self_not_must_use)]
+
+use crate::{interval, Middleware};
+use ethers_core::types::{Block, BlockNumber, Filter, Log, TxHash, H256, U64};
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+    vec::IntoIter,
+};
+
+/// The default number of recent blocks that are tracked to detect reorgs
+pub const DEFAULT_REORG_WINDOW: usize = 64;
+
+/// A reorganization of the canonical chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// The number of blocks that were removed from the canonical chain
+    pub depth: usize,
+    /// The hashes of the blocks that were removed, lowest block first
+    pub removed: Vec<H256>,
+    /// The hashes of the blocks that replaced the removed blocks, lowest block first
+    pub added: Vec<H256>,
+}
+
+/// An event emitted by the [`ReorgStream`]
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ReorgEvent {
+    /// A new block was added to the canonical chain. Its logs follow as separate events.
+    Block(Block<TxHash>),
+    /// A log of a block. If the block was removed by a reorg, the log is emitted again with
+    /// `removed` set to `true`.
+    Log(Log),
+    /// The canonical chain was reorganized. The logs of the removed blocks are emitted again
+    /// with `removed` set to `true` right after this event, the most recent first, followed by
+    /// the blocks that replaced them.
+    Reorg(Reorg),
+}
+
+/// A block of the tracked window
+#[derive(Debug, Clone)]
+struct TrackedBlock {
+    number: U64,
+    hash: H256,
+    logs: Vec<Log>,
+}
+
+/// Keeps the window of the most recent canonical blocks
+#[derive(Debug, Clone)]
+struct ChainTracker {
+    window: VecDeque<TrackedBlock>,
+    window_size: usize,
+    filter: Option<Filter>,
+}
+
+impl ChainTracker {
+    /// Fetches the latest block and compares it to the tracked window, returns the events of all
+    /// changes.
+    ///
+    /// The window is only updated if all requests succeeded, so it can be retried on error.
+    async fn update<M: Middleware>(&mut self, client: &M) -> Result<Vec<ReorgEvent>, M::Error> {
+        let head = match client.get_block(BlockNumber::Latest).await? {
+            Some(head) if head.hash.is_some() && head.number.is_some() => head,
+            _ => return Ok(Vec::new()),
+        };
+        if self.position(head.hash.unwrap_or_default()).is_some() {
+            // nothing new, or a node that lags behind
+            return Ok(Vec::new())
+        }
+        let number = head.number.unwrap_or_default();
+        if self.window.front().map(|oldest| number < oldest.number).unwrap_or_default() {
+            // can't be connected to the window, most likely a node that lags far behind
+            tracing::debug!(%number, "ignoring head below the tracked window");
+            return Ok(Vec::new())
+        }
+
+        // walk back the new chain until it connects to the window
+        let mut added = vec![head];
+        while let (Some(first), Some(oldest)) = (added.last(), self.window.front()) {
+            if self.position(first.parent_hash).is_some() ||
+                first.number.unwrap_or_default() <= oldest.number
+            {
+                break
+            }
+            match client.get_block(first.parent_hash).await? {
+                Some(parent) => added.push(parent),
+                None => {
+                    tracing::debug!(hash = ?first.parent_hash, "parent block not found");
+                    return Ok(Vec::new())
+                }
+            }
+        }
+        added.reverse();
+
+        let mut blocks = Vec::with_capacity(added.len());
+        for block in added {
+            let logs = match self.filter {
+                Some(ref filter) => {
+                    client
+                        .get_logs(&filter.clone().at_block_hash(block.hash.unwrap_or_default()))
+                        .await?
+                }
+                None => Vec::new(),
+            };
+            blocks.push((block, logs));
+        }
+
+        // everything from the first new block on was replaced
+        let first = blocks[0].0.number.unwrap_or_default();
+        let keep = self.window.iter().take_while(|block| block.number < first).count();
+        let removed = self.window.split_off(keep);
+
+        let mut events = Vec::new();
+        if !removed.is_empty() {
+            tracing::debug!(depth = removed.len(), "chain reorganization");
+            events.push(ReorgEvent::Reorg(Reorg {
+                depth: removed.len(),
+                removed: removed.iter().map(|block| block.hash).collect(),
+                added: blocks.iter().map(|(block, _)| block.hash.unwrap_or_default()).collect(),
+            }));
+            for block in removed.into_iter().rev() {
+                events.extend(block.logs.into_iter().rev().map(|mut log| {
+                    log.removed = Some(true);
+                    ReorgEvent::Log(log)
+                }));
+            }
+        }
+
+        for (block, logs) in blocks {
+            self.window.push_back(TrackedBlock {
+                number: block.number.unwrap_or_default(),
+                hash: block.hash.unwrap_or_default(),
+                logs: logs.clone(),
+            });
+            events.push(ReorgEvent::Block(block));
+            events.extend(logs.into_iter().map(ReorgEvent::Log));
+        }
+        while self.window.len() > self.window_size {
+            self.window.pop_front();
+        }
+
+        Ok(events)
+    }
+
+    fn position(&self, hash: H256) -> Option<usize> {
+        self.window.iter().position(|block| block.hash == hash)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type UpdateFut<'a, E> =
+    Pin<Box<dyn Future<Output = (ChainTracker, Result<Vec<ReorgEvent>, E>)> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+type UpdateFut<'a, E> =
+    Pin<Box<dyn Future<Output = (ChainTracker, Result<Vec<ReorgEvent>, E>)> + 'a>>;
+
+enum ReorgStreamState<'a, E> {
+    Initial,
+    WaitForInterval,
+    Update(UpdateFut<'a, E>),
+    NextItem(IntoIter<ReorgEvent>),
+}
+
+/// Streams the new blocks of the canonical chain, and optionally their logs, while detecting
+/// reorgs.
+///
+/// The latest block is polled and the hashes of the most recent blocks are kept in a window. If
+/// the parent of a new block is not the last block of the window, the new chain is walked back
+/// until it connects to the window and a [`Reorg`] event is emitted for the blocks that were
+/// replaced, followed by their logs with `removed` set to `true`, so that downstream state can be
+/// rolled back before the logs of the new blocks are applied.
+///
+/// Reorgs deeper than the window remove the whole window. Heads below the window, for example of
+/// a node that lags behind, are ignored. Requests that fail are yielded as errors and retried at
+/// the next interval.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ethers_providers::{Provider, Http, ReorgEvent, ReorgStream, StreamExt};
+/// # use ethers_core::types::{Address, Filter};
+/// # use std::convert::TryFrom;
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// # let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let filter = Filter::new().address(Address::random());
+/// let mut stream = ReorgStream::new(&provider).filter(filter);
+/// while let Some(event) = stream.next().await {
+///     match event? {
+///         ReorgEvent::Block(block) => println!("new block {:?}", block.number),
+///         ReorgEvent::Log(log) if log.removed == Some(true) => println!("rollback {:?}", log),
+///         ReorgEvent::Log(log) => println!("apply {:?}", log),
+///         ReorgEvent::Reorg(reorg) => println!("reorg of depth {}", reorg.depth),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct ReorgStream<'a, M: Middleware> {
+    client: &'a M,
+    /// `None` while an update is in progress
+    tracker: Option<ChainTracker>,
+    interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
+    state: ReorgStreamState<'a, M::Error>,
+}
+
+impl<'a, M: Middleware> ReorgStream<'a, M> {
+    /// Creates a new stream that polls the latest block at the provider's interval. Starts at the
+    /// latest block, without logs.
+    pub fn new(client: &'a M) -> Self {
+        Self {
+            client,
+            tracker: Some(ChainTracker {
+                window: VecDeque::new(),
+                window_size: DEFAULT_REORG_WINDOW,
+                filter: None,
+            }),
+            interval: Box::new(interval(client.provider().get_interval())),
+            state: ReorgStreamState::Initial,
+        }
+    }
+
+    /// Sets the stream's polling interval
+    pub fn interval(mut self, duration: Duration) -> Self {
+        self.interval = Box::new(interval(duration));
+        self
+    }
+
+    /// Sets the number of recent blocks that are tracked, which is the maximum depth of reorgs
+    /// that can be rolled back precisely
+    pub fn window_size(mut self, size: usize) -> Self {
+        if let Some(ref mut tracker) = self.tracker {
+            tracker.window_size = size.max(1);
+        }
+        self
+    }
+
+    /// Emits the logs matching the filter for every new block. The block range of the filter is
+    /// ignored.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        if let Some(ref mut tracker) = self.tracker {
+            tracker.filter = Some(filter);
+        }
+        self
+    }
+
+    /// Returns the hashes of the tracked blocks, lowest block first
+    pub fn window(&self) -> Vec<H256> {
+        self.tracker
+            .as_ref()
+            .map(|tracker| tracker.window.iter().map(|block| block.hash).collect())
+            .unwrap_or_default()
+    }
+
+    fn update(&mut self) -> ReorgStreamState<'a, M::Error> {
+        let mut tracker = self.tracker.take().expect("update already in progress");
+        let client = self.client;
+        ReorgStreamState::Update(Box::pin(async move {
+            let res = tracker.update(client).await;
+            (tracker, res)
+        }))
+    }
+}
+
+impl<'a, M: Middleware> Stream for ReorgStream<'a, M> {
+    type Item = Result<ReorgEvent, M::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            this.state = match &mut this.state {
+                ReorgStreamState::Initial => this.update(),
+                ReorgStreamState::WaitForInterval => {
+                    // Wait the polling period
+                    let _ready = futures_util::ready!(this.interval.poll_next_unpin(cx));
+                    this.update()
+                }
+                ReorgStreamState::Update(fut) => {
+                    let (tracker, res) = futures_util::ready!(fut.as_mut().poll(cx));
+                    this.tracker = Some(tracker);
+                    match res {
+                        Ok(events) => ReorgStreamState::NextItem(events.into_iter()),
+                        Err(err) => {
+                            this.state = ReorgStreamState::WaitForInterval;
+                            return Poll::Ready(Some(Err(err)))
+                        }
+                    }
+                }
+                ReorgStreamState::NextItem(iter) => {
+                    if let Some(event) = iter.next() {
+                        return Poll::Ready(Some(Ok(event)))
+                    }
+                    ReorgStreamState::WaitForInterval
+                }
+            };
+        }
+    }
+}
+
+impl<'a, M: Middleware> fmt::Debug for ReorgStream<'a, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReorgStream").field("tracker", &self.tracker).finish()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{MockProvider, Provider};
+
+    fn block(number: u64, parent: &Block<TxHash>) -> Block<TxHash> {
+        Block {
+            number: Some(number.into()),
+            hash: Some(H256::random()),
+            parent_hash: parent.hash.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn log(block: &Block<TxHash>) -> Log {
+        Log { block_hash: block.hash, block_number: block.number, ..Default::default() }
+    }
+
+    async fn next_events(
+        stream: &mut ReorgStream<'_, Provider<MockProvider>>,
+        n: usize,
+    ) -> Vec<ReorgEvent> {
+        stream.by_ref().take(n).map(|event| event.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn emits_new_blocks() {
+        let (provider, mock) = Provider::mocked();
+        let mut stream = ReorgStream::new(&provider).interval(Duration::from_millis(1));
+
+        let genesis = Block { number: Some(0u64.into()), ..Default::default() };
+        let b1 = block(1, &genesis);
+        let b2 = block(2, &b1);
+        let b3 = block(3, &b2);
+
+        // the first block starts the window, then a block is skipped and fetched via its parent
+        mock.push(b2.clone()).unwrap();
+        mock.push(b3.clone()).unwrap();
+        mock.push(b1.clone()).unwrap();
+
+        let events = next_events(&mut stream, 3).await;
+        assert_eq!(
+            events,
+            vec![
+                ReorgEvent::Block(b1.clone()),
+                ReorgEvent::Block(b2.clone()),
+                ReorgEvent::Block(b3.clone())
+            ]
+        );
+        assert_eq!(stream.window(), vec![b1.hash.unwrap(), b2.hash.unwrap(), b3.hash.unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn detects_reorgs_and_removes_logs() {
+        let (provider, mock) = Provider::mocked();
+        let mut stream =
+            ReorgStream::new(&provider).interval(Duration::from_millis(1)).filter(Filter::new());
+
+        let genesis = Block { number: Some(0u64.into()), ..Default::default() };
+        let b1 = block(1, &genesis);
+        let b2 = block(2, &b1);
+        // a competing chain of 2 blocks replaces b2
+        let b2_new = block(2, &b1);
+        let b3_new = block(3, &b2_new);
+
+        // responses for the reorg: new head, its parent and the logs of both
+        mock.push::<Vec<Log>, _>(vec![log(&b3_new)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push(b2_new.clone()).unwrap();
+        mock.push(b3_new.clone()).unwrap();
+        // responses for the initial chain
+        mock.push::<Vec<Log>, _>(vec![log(&b2)]).unwrap();
+        mock.push(b2.clone()).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(&b1)]).unwrap();
+        mock.push(b1.clone()).unwrap();
+
+        let events = next_events(&mut stream, 4).await;
+        assert_eq!(events[2], ReorgEvent::Block(b2.clone()));
+
+        let events = next_events(&mut stream, 5).await;
+        assert_eq!(
+            events[0],
+            ReorgEvent::Reorg(Reorg {
+                depth: 1,
+                removed: vec![b2.hash.unwrap()],
+                added: vec![b2_new.hash.unwrap(), b3_new.hash.unwrap()],
+            })
+        );
+        let mut removed = log(&b2);
+        removed.removed = Some(true);
+        assert_eq!(events[1], ReorgEvent::Log(removed));
+        assert_eq!(events[2], ReorgEvent::Block(b2_new.clone()));
+        assert_eq!(events[3], ReorgEvent::Block(b3_new.clone()));
+        assert_eq!(events[4], ReorgEvent::Log(log(&b3_new)));
+        assert_eq!(
+            stream.window(),
+            vec![b1.hash.unwrap(), b2_new.hash.unwrap(), b3_new.hash.unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_heads_below_the_window() {
+        let (provider, mock) = Provider::mocked();
+        let mut stream =
+            ReorgStream::new(&provider).interval(Duration::from_millis(1)).window_size(2);
+
+        let genesis = Block { number: Some(0u64.into()), ..Default::default() };
+        let b1 = block(1, &genesis);
+        let b2 = block(2, &b1);
+        let b3 = block(3, &b2);
+        let b4 = block(4, &b3);
+        // a head of a lagging node on another chain, below the window of b2 and b3
+        let stale = block(1, &genesis);
+
+        mock.push(b4.clone()).unwrap();
+        mock.push(stale).unwrap();
+        mock.push(b3.clone()).unwrap();
+        mock.push(b2.clone()).unwrap();
+        mock.push(b1.clone()).unwrap();
+
+        next_events(&mut stream, 3).await;
+        assert_eq!(stream.window(), vec![b2.hash.unwrap(), b3.hash.unwrap()]);
+
+        // the stale head emits nothing, not a reorg of the whole window
+        let events = next_events(&mut stream, 1).await;
+        assert_eq!(events, vec![ReorgEvent::Block(b4.clone())]);
+        assert_eq!(stream.window(), vec![b3.hash.unwrap(), b4.hash.unwrap()]);
+    }
+}
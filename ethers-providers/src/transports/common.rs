@@ -2,13 +2,13 @@
 use std::fmt;
 
 use serde::{
-    de::{self, MapAccess, Unexpected, Visitor},
+    de::{self, MapAccess, SeqAccess, Unexpected, Visitor},
     Deserialize, Serialize,
 };
 use serde_json::{value::RawValue, Value};
 use thiserror::Error;
 
-use ethers_core::types::U256;
+use ethers_core::types::{Bytes, U256};
 
 #[derive(Deserialize, Debug, Clone, Error)]
 /// A JSON-RPC 2.0 error
@@ -27,6 +27,21 @@ impl fmt::Display for JsonRpcError {
     }
 }
 
+impl JsonRpcError {
+    /// Returns the revert data of a failed `eth_call` or `eth_estimateGas`, if present.
+    ///
+    /// Most nodes put the hex encoded revert data directly into the `data` field, some wrap it in
+    /// an object like `{"data": "0x.."}`.
+    pub fn as_revert_data(&self) -> Option<Bytes> {
+        let data = match self.data.as_ref()? {
+            Value::String(data) => data,
+            Value::Object(obj) => obj.get("data")?.as_str()?,
+            _ => return None,
+        };
+        data.parse().ok()
+    }
+}
+
 fn is_zst<T>(_t: &T) -> bool {
     std::mem::size_of::<T>() == 0
 }
@@ -46,13 +61,30 @@ impl<'a, T> Request<'a, T> {
     pub fn new(id: u64, method: &'a str, params: T) -> Self {
         Self { id, jsonrpc: "2.0", method, params }
     }
+
+    /// The id of the request
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The method of the request
+    pub fn method(&self) -> &'a str {
+        self.method
+    }
+
+    /// The params of the request
+    pub fn params(&self) -> &T {
+        &self.params
+    }
 }
 
 /// A JSON-RPC response
 #[derive(Debug)]
 pub enum Response<'a> {
     Success { id: u64, result: &'a RawValue },
-    Error { id: u64, error: JsonRpcError },
+    /// The `id` is `None` if the server could not determine the id of the request, e.g. because
+    /// it rejected a whole batch
+    Error { id: Option<u64>, error: JsonRpcError },
     Notification { method: &'a str, params: Params<'a> },
 }
 
@@ -70,114 +102,158 @@ impl<'de: 'a, 'a> Deserialize<'de> for Response<'a> {
     where
         D: serde::Deserializer<'de>,
     {
-        struct ResponseVisitor<'a>(&'a ());
-        impl<'de: 'a, 'a> Visitor<'de> for ResponseVisitor<'a> {
-            type Value = Response<'a>;
+        deserializer.deserialize_map(ResponseVisitor(&()))
+    }
+}
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a valid jsonrpc 2.0 response object")
-            }
+struct ResponseVisitor<'a>(&'a ());
+impl<'de: 'a, 'a> Visitor<'de> for ResponseVisitor<'a> {
+    type Value = Response<'a>;
 
-            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-            where
-                A: MapAccess<'de>,
-            {
-                let mut jsonrpc = false;
-
-                // response & error
-                let mut id = None;
-                // only response
-                let mut result = None;
-                // only error
-                let mut error = None;
-                // only notification
-                let mut method = None;
-                let mut params = None;
-
-                while let Some(key) = map.next_key()? {
-                    match key {
-                        "jsonrpc" => {
-                            if jsonrpc {
-                                return Err(de::Error::duplicate_field("jsonrpc"))
-                            }
-
-                            let value = map.next_value()?;
-                            if value != "2.0" {
-                                return Err(de::Error::invalid_value(Unexpected::Str(value), &"2.0"))
-                            }
-
-                            jsonrpc = true;
-                        }
-                        "id" => {
-                            if id.is_some() {
-                                return Err(de::Error::duplicate_field("id"))
-                            }
-
-                            let value: u64 = map.next_value()?;
-                            id = Some(value);
-                        }
-                        "result" => {
-                            if result.is_some() {
-                                return Err(de::Error::duplicate_field("result"))
-                            }
-
-                            let value: &RawValue = map.next_value()?;
-                            result = Some(value);
-                        }
-                        "error" => {
-                            if error.is_some() {
-                                return Err(de::Error::duplicate_field("error"))
-                            }
-
-                            let value: JsonRpcError = map.next_value()?;
-                            error = Some(value);
-                        }
-                        "method" => {
-                            if method.is_some() {
-                                return Err(de::Error::duplicate_field("method"))
-                            }
-
-                            let value: &str = map.next_value()?;
-                            method = Some(value);
-                        }
-                        "params" => {
-                            if params.is_some() {
-                                return Err(de::Error::duplicate_field("params"))
-                            }
-
-                            let value: Params = map.next_value()?;
-                            params = Some(value);
-                        }
-                        key => {
-                            return Err(de::Error::unknown_field(
-                                key,
-                                &["id", "jsonrpc", "result", "error", "params", "method"],
-                            ))
-                        }
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a valid jsonrpc 2.0 response object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut jsonrpc = false;
+
+        // response & error, the id of an error may be `null`
+        let mut id: Option<Option<u64>> = None;
+        // only response
+        let mut result = None;
+        // only error
+        let mut error = None;
+        // only notification
+        let mut method = None;
+        let mut params = None;
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                "jsonrpc" => {
+                    if jsonrpc {
+                        return Err(de::Error::duplicate_field("jsonrpc"))
+                    }
+
+                    let value = map.next_value()?;
+                    if value != "2.0" {
+                        return Err(de::Error::invalid_value(Unexpected::Str(value), &"2.0"))
                     }
+
+                    jsonrpc = true;
+                }
+                "id" => {
+                    if id.is_some() {
+                        return Err(de::Error::duplicate_field("id"))
+                    }
+
+                    let value: Option<u64> = map.next_value()?;
+                    id = Some(value);
                 }
+                "result" => {
+                    if result.is_some() {
+                        return Err(de::Error::duplicate_field("result"))
+                    }
 
-                // jsonrpc version must be present in all responses
-                if !jsonrpc {
-                    return Err(de::Error::missing_field("jsonrpc"))
+                    let value: &RawValue = map.next_value()?;
+                    result = Some(value);
                 }
+                "error" => {
+                    if error.is_some() {
+                        return Err(de::Error::duplicate_field("error"))
+                    }
 
-                match (id, result, error, method, params) {
-                    (Some(id), Some(result), None, None, None) => {
-                        Ok(Response::Success { id, result })
+                    let value: JsonRpcError = map.next_value()?;
+                    error = Some(value);
+                }
+                "method" => {
+                    if method.is_some() {
+                        return Err(de::Error::duplicate_field("method"))
                     }
-                    (Some(id), None, Some(error), None, None) => Ok(Response::Error { id, error }),
-                    (None, None, None, Some(method), Some(params)) => {
-                        Ok(Response::Notification { method, params })
+
+                    let value: &str = map.next_value()?;
+                    method = Some(value);
+                }
+                "params" => {
+                    if params.is_some() {
+                        return Err(de::Error::duplicate_field("params"))
                     }
-                    _ => Err(de::Error::custom(
-                        "response must be either a success/error or notification object",
-                    )),
+
+                    let value: Params = map.next_value()?;
+                    params = Some(value);
+                }
+                key => {
+                    return Err(de::Error::unknown_field(
+                        key,
+                        &["id", "jsonrpc", "result", "error", "params", "method"],
+                    ))
                 }
             }
         }
 
-        deserializer.deserialize_map(ResponseVisitor(&()))
+        // jsonrpc version must be present in all responses
+        if !jsonrpc {
+            return Err(de::Error::missing_field("jsonrpc"))
+        }
+
+        match (id, result, error, method, params) {
+            (Some(Some(id)), Some(result), None, None, None) => {
+                Ok(Response::Success { id, result })
+            }
+            (Some(id), None, Some(error), None, None) => Ok(Response::Error { id, error }),
+            (None, None, None, Some(method), Some(params)) => {
+                Ok(Response::Notification { method, params })
+            }
+            _ => Err(de::Error::custom(
+                "response must be either a success/error or notification object",
+            )),
+        }
+    }
+}
+
+/// A single JSON-RPC response or the array of responses to a batch request
+#[derive(Debug)]
+pub enum Responses<'a> {
+    Single(Response<'a>),
+    Batch(Vec<Response<'a>>),
+}
+
+// can't be derived as an untagged enum for the same reason as `Response`
+impl<'de: 'a, 'a> Deserialize<'de> for Responses<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ResponsesVisitor<'a>(&'a ());
+        impl<'de: 'a, 'a> Visitor<'de> for ResponsesVisitor<'a> {
+            type Value = Responses<'a>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a jsonrpc 2.0 response object or an array of them")
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                ResponseVisitor(self.0).visit_map(map).map(Responses::Single)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut responses = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(response) = seq.next_element()? {
+                    responses.push(response);
+                }
+                Ok(Responses::Batch(responses))
+            }
+        }
+
+        deserializer.deserialize_any(ResponsesVisitor(&()))
     }
 }
 
@@ -242,7 +318,7 @@ mod tests {
 
         match response {
             Response::Error { id, error } => {
-                assert_eq!(id, 2);
+                assert_eq!(id, Some(2));
                 assert_eq!(error.code, -32000);
                 assert_eq!(error.message, "error occurred");
                 assert!(error.data.is_none());
@@ -263,6 +339,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deser_batch_response() {
+        let responses: Responses<'_> = serde_json::from_str(
+            r#"[{"jsonrpc":"2.0","result":"0xfa","id":2},{"jsonrpc":"2.0","error":{"code":-32000,"message":"error occurred"},"id":1}]"#,
+        )
+        .unwrap();
+
+        match responses {
+            Responses::Batch(responses) => {
+                assert_eq!(responses.len(), 2);
+                assert!(matches!(responses[0], Response::Success { id: 2, .. }));
+                assert!(matches!(responses[1], Response::Error { id: Some(1), .. }));
+            }
+            _ => panic!("expected `Batch` response"),
+        }
+
+        let responses: Responses<'_> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","result":19,"id":1}"#).unwrap();
+        assert!(matches!(responses, Responses::Single(Response::Success { id: 1, .. })));
+
+        let _ = serde_json::from_str::<Responses<'_>>(r#"[{"jsonrpc":"2.0","result":19}]"#)
+            .unwrap_err();
+
+        // a batch rejected as a whole is answered with a single error without id
+        let responses: Responses<'_> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"invalid request"},"id":null}"#,
+        )
+        .unwrap();
+        assert!(matches!(responses, Responses::Single(Response::Error { id: None, .. })));
+
+        let _ = serde_json::from_str::<Responses<'_>>(r#"{"jsonrpc":"2.0","result":19,"id":null}"#)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn revert_data() {
+        let error: JsonRpcError = serde_json::from_str(
+            r#"{"code":3,"message":"execution reverted","data":"0x4e487b710000000000000000000000000000000000000000000000000000000000000011"}"#,
+        )
+        .unwrap();
+        let data = error.as_revert_data().unwrap();
+        assert_eq!(data.len(), 36);
+        assert_eq!(&data[..4], &[0x4e, 0x48, 0x7b, 0x71]);
+
+        let error: JsonRpcError = serde_json::from_str(
+            r#"{"code":-32603,"message":"Error: VM Exception","data":{"message":"revert","data":"0x08c379a0"}}"#,
+        )
+        .unwrap();
+        assert_eq!(error.as_revert_data().unwrap().as_ref(), &[0x08, 0xc3, 0x79, 0xa0]);
+
+        let error: JsonRpcError =
+            serde_json::from_str(r#"{"code":-32000,"message":"nonce too low"}"#).unwrap();
+        assert!(error.as_revert_data().is_none());
+    }
+
     #[test]
     fn ser_request() {
         let request: Request<()> = Request::new(0, "eth_chainId", ());
@@ -0,0 +1,516 @@
+//! A [JsonRpcClient] implementation that sends every request to the best of multiple providers
+//! and fails over to the next best one
+
+use crate::{provider::ProviderError, BatchRequest, JsonRpcClient, JsonRpcClientWrapper};
+
+use async_trait::async_trait;
+use ethers_core::types::U64;
+use futures_util::{
+    future::{join_all, select, Either},
+    pin_mut,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{value::RawValue, Value};
+use std::{fmt::Debug, sync::Mutex, time::Duration};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures_timer::Delay;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::{Delay, Instant};
+
+/// The default number of blocks a provider may lag behind the most recent block of all providers
+/// before it is taken out of rotation
+pub const DEFAULT_MAX_BLOCK_LAG: u64 = 3;
+
+/// The default interval of the health probes
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The default time after which a provider that did not answer a health probe counts as failed
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The weight of a new sample in the rolling latency and error rate
+const SMOOTHING: f64 = 0.2;
+
+/// How much slower a provider that always fails is ranked
+const ERROR_RATE_PENALTY: Duration = Duration::from_secs(1);
+
+/// A provider that sends every request to the best of its providers only, and fails over to the
+/// next best provider if a request fails with a transport error.
+///
+/// Providers are ranked by their rolling latency and error rate. Providers whose block number
+/// lags more than `max_block_lag` blocks behind the other providers, according to the periodic
+/// `eth_blockNumber` health probes, are taken out of rotation until they caught up, and are only
+/// used if all other providers fail. Errors returned by the node, like a reverted `eth_call`, are
+/// returned right away since another provider would return the same error.
+///
+/// The health probes are sent when a request is made after the probe interval elapsed, or
+/// manually with [`FallbackProvider::check_health`]. A provider that doesn't answer a probe within
+/// the probe timeout counts as failed, so a hanging provider doesn't stall the request.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::U64;
+/// use ethers_providers::{FallbackProvider, Http, JsonRpcClient};
+/// use std::{str::FromStr, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = FallbackProvider::builder()
+///     .add_provider(Http::from_str("http://localhost:8545")?)
+///     .add_provider(Http::from_str("http://localhost:8546")?)
+///     .max_block_lag(5)
+///     .probe_interval(Duration::from_secs(10))
+///     .build();
+///
+/// let block_number: U64 = provider.request("eth_blockNumber", ()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FallbackProvider<T = Box<dyn JsonRpcClientWrapper>> {
+    providers: Vec<T>,
+    stats: Mutex<Vec<ProviderStats>>,
+    max_block_lag: u64,
+    probe_interval: Option<Duration>,
+    probe_timeout: Duration,
+    last_probe: Mutex<Option<Instant>>,
+}
+
+/// The health of a provider of the [`FallbackProvider`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderStats {
+    /// The rolling latency of successful requests, if any request succeeded
+    pub latency: Option<Duration>,
+    /// The rolling share of requests that failed with a transport error, between 0 and 1
+    pub error_rate: f64,
+    /// The most recent block number of the provider, if known
+    pub block_number: Option<U64>,
+    /// Whether the provider is in rotation
+    pub healthy: bool,
+}
+
+impl ProviderStats {
+    fn new() -> Self {
+        Self { healthy: true, ..Default::default() }
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        self.latency = Some(match self.latency {
+            Some(prev) => prev.mul_f64(1.0 - SMOOTHING) + latency.mul_f64(SMOOTHING),
+            None => latency,
+        });
+        self.error_rate *= 1.0 - SMOOTHING;
+    }
+
+    fn record_error(&mut self) {
+        self.error_rate = self.error_rate * (1.0 - SMOOTHING) + SMOOTHING;
+    }
+
+    /// The lower the better, providers without a successful request are tried first
+    fn score(&self) -> f64 {
+        let latency = self.latency.unwrap_or_default().as_secs_f64();
+        latency + ERROR_RATE_PENALTY.as_secs_f64() * self.error_rate
+    }
+}
+
+impl FallbackProvider<Box<dyn JsonRpcClientWrapper>> {
+    /// Create a `FallbackProvider` for different `JsonRpcClient` types
+    pub fn dyn_rpc() -> FallbackProviderBuilder<Box<dyn JsonRpcClientWrapper>> {
+        Self::builder()
+    }
+}
+
+impl<T> FallbackProvider<T> {
+    /// Convenience method for creating a `FallbackProviderBuilder` with same `JsonRpcClient` types
+    pub fn builder() -> FallbackProviderBuilder<T> {
+        FallbackProviderBuilder::default()
+    }
+
+    /// Creates a provider for the given providers with the default configuration
+    pub fn new(providers: impl IntoIterator<Item = T>) -> Self {
+        Self::builder().add_providers(providers).build()
+    }
+
+    /// Returns the providers
+    pub fn providers(&self) -> &[T] {
+        &self.providers
+    }
+
+    /// Returns the health of every provider, in the order of the providers
+    pub fn provider_stats(&self) -> Vec<ProviderStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Returns the indices of the providers in the order they are tried
+    fn ranked(&self) -> Vec<usize> {
+        let stats = self.stats.lock().unwrap();
+        let mut ranked = (0..stats.len()).collect::<Vec<_>>();
+        ranked.sort_by(|a, b| {
+            let (a, b) = (&stats[*a], &stats[*b]);
+            b.healthy.cmp(&a.healthy).then_with(|| {
+                a.score().partial_cmp(&b.score()).unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        ranked
+    }
+
+    fn record_success(&self, idx: usize, latency: Duration) {
+        self.stats.lock().unwrap()[idx].record_success(latency)
+    }
+
+    fn record_error(&self, idx: usize) {
+        self.stats.lock().unwrap()[idx].record_error()
+    }
+
+    /// Updates the block number of the provider and returns `false` if it lags behind the other
+    /// providers
+    fn update_block_number(&self, idx: usize, block_number: U64) -> bool {
+        let mut stats = self.stats.lock().unwrap();
+        stats[idx].block_number = Some(block_number);
+        let head = stats.iter().filter_map(|stats| stats.block_number).max().unwrap_or_default();
+        let healthy = head.saturating_sub(block_number) <= self.max_block_lag.into();
+        stats[idx].healthy = healthy;
+        healthy
+    }
+}
+
+impl<T: JsonRpcClientWrapper> FallbackProvider<T> {
+    /// Probes the block number of all providers and takes the providers out of rotation that fail,
+    /// time out or lag behind the others, and puts the others back into rotation
+    pub async fn check_health(&self) {
+        let probes = join_all(self.providers.iter().map(|provider| async move {
+            let started = Instant::now();
+            let probe = provider.request("eth_blockNumber", Value::Null);
+            let timeout = Delay::new(self.probe_timeout);
+            pin_mut!(probe, timeout);
+            let res = match select(probe, timeout).await {
+                Either::Left((res, _)) => res.and_then(|value| {
+                    serde_json::from_value::<U64>(value).map_err(ProviderError::from)
+                }),
+                Either::Right(_) => Err(ProviderError::CustomError(format!(
+                    "health probe timed out after {:?}",
+                    self.probe_timeout
+                ))),
+            };
+            (res, started.elapsed())
+        }))
+        .await;
+
+        let mut stats = self.stats.lock().unwrap();
+        for (stats, (res, latency)) in stats.iter_mut().zip(&probes) {
+            match res {
+                Ok(block_number) => {
+                    stats.record_success(*latency);
+                    stats.block_number = Some(*block_number);
+                }
+                Err(err) => {
+                    tracing::debug!(?err, "health probe failed");
+                    stats.record_error();
+                }
+            }
+        }
+        let head = stats.iter().filter_map(|stats| stats.block_number).max().unwrap_or_default();
+        for (stats, (res, _)) in stats.iter_mut().zip(&probes) {
+            stats.healthy = match res {
+                Ok(block_number) => head.saturating_sub(*block_number) <= self.max_block_lag.into(),
+                Err(_) => false,
+            };
+        }
+    }
+
+    /// Probes the providers if the probe interval elapsed since the last probe
+    async fn maybe_check_health(&self) {
+        let interval = match self.probe_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        {
+            // only one request sends the probes
+            let mut last_probe = self.last_probe.lock().unwrap();
+            if matches!(*last_probe, Some(last_probe) if last_probe.elapsed() < interval) {
+                return
+            }
+            *last_probe = Some(Instant::now());
+        }
+        self.check_health().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FallbackProviderBuilder<T> {
+    providers: Vec<T>,
+    max_block_lag: u64,
+    probe_interval: Option<Duration>,
+    probe_timeout: Duration,
+}
+
+impl<T> Default for FallbackProviderBuilder<T> {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            max_block_lag: DEFAULT_MAX_BLOCK_LAG,
+            probe_interval: Some(DEFAULT_PROBE_INTERVAL),
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+        }
+    }
+}
+
+impl<T> FallbackProviderBuilder<T> {
+    pub fn add_provider(mut self, provider: T) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    pub fn add_providers(mut self, providers: impl IntoIterator<Item = T>) -> Self {
+        self.providers.extend(providers);
+        self
+    }
+
+    /// Sets the number of blocks a provider may lag behind the other providers before it is taken
+    /// out of rotation
+    pub fn max_block_lag(mut self, max_block_lag: u64) -> Self {
+        self.max_block_lag = max_block_lag;
+        self
+    }
+
+    /// Sets the interval of the health probes
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = Some(interval);
+        self
+    }
+
+    /// Sets the time after which a provider that did not answer a health probe counts as failed
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Disables the periodic health probes, providers are then only taken out of rotation if
+    /// they return a lagging block number to an `eth_blockNumber` request
+    pub fn disable_probes(mut self) -> Self {
+        self.probe_interval = None;
+        self
+    }
+
+    pub fn build(self) -> FallbackProvider<T> {
+        let stats = self.providers.iter().map(|_| ProviderStats::new()).collect();
+        FallbackProvider {
+            providers: self.providers,
+            stats: Mutex::new(stats),
+            max_block_lag: self.max_block_lag,
+            probe_interval: self.probe_interval,
+            probe_timeout: self.probe_timeout,
+            last_probe: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when no provider returned a response
+pub enum FallbackError {
+    #[error("all providers failed")]
+    AllProvidersFailed(Vec<ProviderError>),
+}
+
+impl From<FallbackError> for ProviderError {
+    fn from(src: FallbackError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(src))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for FallbackProvider<C>
+where
+    C: JsonRpcClientWrapper,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)?;
+        self.maybe_check_health().await;
+
+        let mut errors = Vec::new();
+        // the most recent block number of the lagging providers, in case all others fail
+        let mut stale = None;
+        for idx in self.ranked() {
+            let started = Instant::now();
+            match self.providers[idx].request(method, params.clone()).await {
+                Ok(value) => {
+                    self.record_success(idx, started.elapsed());
+                    if method == "eth_blockNumber" {
+                        let block_number = serde_json::from_value::<U64>(value.clone())?;
+                        if !self.update_block_number(idx, block_number) {
+                            tracing::debug!(provider = idx, ?block_number, "stale block number");
+                            stale = stale.max(Some(block_number));
+                            continue
+                        }
+                    }
+                    return Ok(serde_json::from_value(value)?)
+                }
+                // the node rejected the request, other nodes would do the same
+                Err(err) if err.as_error_response().is_some() => {
+                    self.record_success(idx, started.elapsed());
+                    return Err(err)
+                }
+                Err(err) => {
+                    tracing::debug!(provider = idx, ?err, "request failed, failing over");
+                    self.record_error(idx);
+                    errors.push(err);
+                }
+            }
+        }
+
+        match stale {
+            Some(block_number) => Ok(serde_json::from_value(serde_json::to_value(block_number)?)?),
+            None => Err(FallbackError::AllProvidersFailed(errors).into()),
+        }
+    }
+
+    /// Sends the batch to the best provider and fails over to the next best provider if the batch
+    /// as a whole fails
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        self.maybe_check_health().await;
+
+        let mut errors = Vec::new();
+        for idx in self.ranked() {
+            let started = Instant::now();
+            match self.providers[idx].request_batch(batch).await {
+                Ok(responses) => {
+                    self.record_success(idx, started.elapsed());
+                    return Ok(responses
+                        .into_iter()
+                        .map(|res| Ok(serde_json::value::to_raw_value(&res?)?))
+                        .collect())
+                }
+                Err(err) => {
+                    tracing::debug!(provider = idx, ?err, "batch failed, failing over");
+                    self.record_error(idx);
+                    errors.push(err);
+                }
+            }
+        }
+        Err(FallbackError::AllProvidersFailed(errors).into())
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+
+    fn provider(mocks: &[MockProvider]) -> FallbackProvider<MockProvider> {
+        FallbackProvider::builder().add_providers(mocks.to_vec()).disable_probes().build()
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_transport_errors() {
+        let mocks = [MockProvider::new(), MockProvider::new()];
+        let provider = provider(&mocks);
+
+        // the first provider has no response
+        mocks[1].push(U64::from(1u64)).unwrap();
+        let chain_id: U64 = JsonRpcClient::request(&provider, "eth_chainId", ()).await.unwrap();
+        assert_eq!(chain_id, 1u64.into());
+
+        let stats = provider.provider_stats();
+        assert!(stats[0].error_rate > 0.0);
+        assert!(stats[1].latency.is_some());
+        // the failing provider is now ranked last
+        assert_eq!(provider.ranked(), vec![1, 0]);
+
+        let err = JsonRpcClient::request::<_, U64>(&provider, "eth_chainId", ()).await.unwrap_err();
+        assert!(err.to_string().contains("all providers failed"));
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_stale_block_numbers() {
+        let mocks = [MockProvider::new(), MockProvider::new()];
+        let provider = provider(&mocks);
+
+        mocks[0].push(U64::from(100u64)).unwrap();
+        let block_number: U64 =
+            JsonRpcClient::request(&provider, "eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block_number, 100u64.into());
+
+        // the second provider was not tried yet so it is ranked first, but it lags behind
+        mocks[1].push(U64::from(90u64)).unwrap();
+        mocks[0].push(U64::from(101u64)).unwrap();
+        let block_number: U64 =
+            JsonRpcClient::request(&provider, "eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block_number, 101u64.into());
+
+        let stats = provider.provider_stats();
+        assert!(stats[0].healthy);
+        assert!(!stats[1].healthy);
+        assert_eq!(provider.ranked(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn health_probes_take_lagging_providers_out_of_rotation() {
+        let mocks = [MockProvider::new(), MockProvider::new(), MockProvider::new()];
+        let provider = FallbackProvider::builder()
+            .add_providers(mocks.to_vec())
+            .probe_interval(Duration::from_secs(60))
+            .build();
+
+        // the probes are sent with the first request, the third provider does not respond
+        mocks[0].push(U64::from(50u64)).unwrap();
+        mocks[1].push(U64::from(1u64)).unwrap();
+        mocks[1].push(U64::from(100u64)).unwrap();
+        // the first provider is out of rotation and the response to the request comes from the
+        // second provider
+        let chain_id: U64 = JsonRpcClient::request(&provider, "eth_chainId", ()).await.unwrap();
+        assert_eq!(chain_id, 1u64.into());
+
+        let stats = provider.provider_stats();
+        assert_eq!(
+            stats.iter().map(|stats| stats.healthy).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+        assert_eq!(stats[0].block_number, Some(50u64.into()));
+        assert_eq!(provider.ranked()[0], 1);
+    }
+
+    #[derive(Debug)]
+    struct Hanging;
+
+    #[async_trait]
+    impl JsonRpcClientWrapper for Hanging {
+        async fn request(&self, _: &str, _: Value) -> Result<Value, ProviderError> {
+            futures_util::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn health_probes_time_out() {
+        let mock = MockProvider::new();
+        let provider = FallbackProvider::dyn_rpc()
+            .add_provider(Box::new(Hanging) as Box<dyn JsonRpcClientWrapper>)
+            .add_provider(Box::new(mock.clone()))
+            .probe_timeout(Duration::from_millis(50))
+            .build();
+
+        mock.push(U64::from(1u64)).unwrap();
+        mock.push(U64::from(100u64)).unwrap();
+        // the request is sent once the probe of the hanging provider timed out
+        let chain_id: U64 = JsonRpcClient::request(&provider, "eth_chainId", ()).await.unwrap();
+        assert_eq!(chain_id, 1u64.into());
+
+        let stats = provider.provider_stats();
+        assert!(!stats[0].healthy);
+        assert!(stats[0].error_rate > 0.0);
+        assert!(stats[1].healthy);
+    }
+}
@@ -1,9 +1,13 @@
 // Code adapted from: https://github.com/althea-net/guac_rs/tree/master/web3/src/jsonrpc
-use crate::{provider::ProviderError, JsonRpcClient};
+use crate::{provider::ProviderError, BatchRequest, JsonRpcClient};
 
 use async_trait::async_trait;
-use reqwest::{header::HeaderValue, Client, Error as ReqwestError};
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE},
+    Client, Error as ReqwestError,
+};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
 use std::{
     str::FromStr,
     sync::atomic::{AtomicU64, Ordering},
@@ -11,7 +15,7 @@ use std::{
 use thiserror::Error;
 use url::Url;
 
-use super::common::{Authorization, JsonRpcError, Request, Response};
+use super::common::{Authorization, JsonRpcError, Request, Response, Responses};
 
 /// A low-level JSON-RPC Client over HTTP.
 ///
@@ -50,6 +54,16 @@ pub enum ClientError {
     SerdeJson { err: serde_json::Error, text: String },
 }
 
+impl ClientError {
+    /// Returns the error response of the node, if this is a JSON-RPC error
+    pub fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            ClientError::JsonRpcError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<ClientError> for ProviderError {
     fn from(src: ClientError) -> Self {
         ProviderError::JsonRpcClientError(Box::new(src))
@@ -92,6 +106,72 @@ impl JsonRpcClient for Provider {
 
         Ok(res)
     }
+
+    /// Sends all calls of the batch as a JSON-RPC array in a single POST request and matches
+    /// the responses to their calls by id
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, ClientError>>, ClientError> {
+        // an empty array is not a valid JSON-RPC batch
+        if batch.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let first_id = self.id.fetch_add(batch.len() as u64, Ordering::SeqCst);
+        let payload = batch.to_payload(first_id);
+
+        let res = self
+            .client
+            .post(self.url.as_ref())
+            .header(CONTENT_TYPE, "application/json")
+            .body(payload)
+            .send()
+            .await?;
+        let text = res.text().await?;
+
+        let responses = match serde_json::from_str(&text) {
+            Ok(Responses::Batch(responses)) => responses,
+            // the batch as a whole was rejected
+            Ok(Responses::Single(Response::Error { error, .. })) => return Err(error.into()),
+            Ok(_) => {
+                let err = ClientError::SerdeJson {
+                    err: serde::de::Error::custom("expected an array of responses to the batch"),
+                    text,
+                };
+                return Err(err)
+            }
+            Err(err) => return Err(ClientError::SerdeJson { err, text }),
+        };
+
+        // responses may arrive in any order
+        let mut results: Vec<Option<Result<Box<RawValue>, ClientError>>> =
+            (0..batch.len()).map(|_| None).collect();
+        for response in responses {
+            let (id, result) = match response {
+                Response::Success { id, result } => (id, Ok(result.to_owned())),
+                Response::Error { id: Some(id), error } => (id, Err(error.into())),
+                Response::Error { id: None, .. } | Response::Notification { .. } => continue,
+            };
+            if let Some(slot) =
+                id.checked_sub(first_id).and_then(|idx| results.get_mut(idx as usize))
+            {
+                *slot = Some(result);
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    Err(ClientError::SerdeJson {
+                        err: serde::de::Error::custom("missing response for batched request"),
+                        text: text.clone(),
+                    })
+                })
+            })
+            .collect())
+    }
 }
 
 impl Provider {
@@ -1,5 +1,6 @@
 use std::{
     cell::RefCell,
+    collections::VecDeque,
     convert::Infallible,
     hash::BuildHasherDefault,
     path::Path,
@@ -31,8 +32,8 @@ use tokio::{
 
 use crate::{
     provider::ProviderError,
-    transports::common::{JsonRpcError, Request, Response},
-    JsonRpcClient, PubsubClient,
+    transports::common::{JsonRpcError, Request, Response, Responses},
+    BatchRequest, JsonRpcClient, PubsubClient,
 };
 
 use super::common::Params;
@@ -52,6 +53,7 @@ pub struct Ipc {
 #[derive(Debug)]
 enum TransportMessage {
     Request { id: u64, request: Box<[u8]>, sender: Pending },
+    Batch { requests: Vec<(u64, Pending)>, request: Box<[u8]> },
     Subscribe { id: U256, sink: Subscription },
     Unsubscribe { id: U256 },
 }
@@ -105,6 +107,38 @@ impl JsonRpcClient for Ipc {
         // Parse JSON response.
         Ok(serde_json::from_str(res.get())?)
     }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, IpcError>>, IpcError> {
+        // an empty array is not a valid JSON-RPC batch
+        if batch.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let first_id = self.id.fetch_add(batch.len() as u64, Ordering::SeqCst);
+
+        // Create one response channel per call of the batch
+        let (requests, receivers): (Vec<_>, Vec<_>) = (first_id..first_id + batch.len() as u64)
+            .map(|id| {
+                let (sender, receiver) = oneshot::channel();
+                ((id, sender), receiver)
+            })
+            .unzip();
+        let payload = TransportMessage::Batch {
+            requests,
+            request: batch.to_payload(first_id).into_bytes().into_boxed_slice(),
+        };
+
+        self.send(payload)?;
+
+        let mut responses = Vec::with_capacity(receivers.len());
+        for receiver in receivers {
+            responses.push(receiver.await?.map_err(Into::into));
+        }
+        Ok(responses)
+    }
 }
 
 impl PubsubClient for Ipc {
@@ -150,6 +184,7 @@ async fn run_ipc_server(
     let shared = Shared {
         pending: FxHashMap::with_capacity_and_hasher(64, BuildHasherDefault::default()).into(),
         subs: FxHashMap::with_capacity_and_hasher(64, BuildHasherDefault::default()).into(),
+        batches: VecDeque::new().into(),
     };
 
     // split the stream and run two independent concurrently (local), thereby
@@ -170,6 +205,8 @@ async fn run_ipc_server(
 struct Shared {
     pending: RefCell<FxHashMap<u64, Pending>>,
     subs: RefCell<FxHashMap<U256, Subscription>>,
+    /// The ids of the calls of the batches that were sent, in the order they were sent
+    batches: RefCell<VecDeque<Vec<u64>>>,
 }
 
 impl Shared {
@@ -212,6 +249,30 @@ impl Shared {
                         self.pending.borrow_mut().remove(&id);
                     }
                 }
+                Batch { requests, request } => {
+                    let ids = requests.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                    {
+                        let mut pending = self.pending.borrow_mut();
+                        for (id, sender) in requests {
+                            let prev = pending.insert(id, sender);
+                            assert!(prev.is_none(), "replaced pending IPC request (id={})", id);
+                        }
+                    }
+
+                    if let Err(err) = writer.write_all(&request).await {
+                        tracing::error!("IPC connection error: {:?}", err);
+                        let mut pending = self.pending.borrow_mut();
+                        for id in ids {
+                            pending.remove(&id);
+                        }
+                    } else {
+                        // forget the batches that were answered in the meantime
+                        let pending = self.pending.borrow();
+                        let mut batches = self.batches.borrow_mut();
+                        batches.retain(|batch| batch.iter().any(|id| pending.contains_key(id)));
+                        batches.push_back(ids);
+                    }
+                }
                 Subscribe { id, sink } => {
                     if self.subs.borrow_mut().insert(id, sink).is_some() {
                         tracing::warn!(
@@ -241,17 +302,46 @@ impl Shared {
     fn handle_bytes(&self, bytes: &BytesMut) -> Result<usize, IpcError> {
         // deserialize all complete jsonrpc responses in the buffer
         let mut de = Deserializer::from_slice(bytes.as_ref()).into_iter();
-        while let Some(Ok(response)) = de.next() {
-            match response {
-                Response::Success { id, result } => self.send_response(id, Ok(result.to_owned())),
-                Response::Error { id, error } => self.send_response(id, Err(error)),
-                Response::Notification { params, .. } => self.send_notification(params),
-            };
+        while let Some(Ok(responses)) = de.next() {
+            match responses {
+                Responses::Single(response) => self.handle_response(response),
+                Responses::Batch(responses) => {
+                    responses.into_iter().for_each(|response| self.handle_response(response))
+                }
+            }
         }
 
         Ok(de.byte_offset())
     }
 
+    fn handle_response(&self, response: Response<'_>) {
+        match response {
+            Response::Success { id, result } => self.send_response(id, Ok(result.to_owned())),
+            Response::Error { id: Some(id), error } => self.send_response(id, Err(error)),
+            Response::Error { id: None, error } => self.send_batch_error(error),
+            Response::Notification { params, .. } => self.send_notification(params),
+        };
+    }
+
+    /// Fails the oldest batch none of whose calls were answered yet, because an error without id
+    /// is sent if the server rejected a batch as a whole.
+    fn send_batch_error(&self, error: JsonRpcError) {
+        let mut batches = self.batches.borrow_mut();
+        let mut pending = self.pending.borrow_mut();
+        while let Some(batch) = batches.pop_front() {
+            if !batch.iter().all(|id| pending.contains_key(id)) {
+                continue
+            }
+            for id in batch {
+                if let Some(response_tx) = pending.remove(&id) {
+                    let _ = response_tx.send(Err(error.clone()));
+                }
+            }
+            return
+        }
+        tracing::warn!(%error, "received an error response without id");
+    }
+
     fn send_response(&self, id: u64, result: Result<Box<RawValue>, JsonRpcError>) {
         // retrieve the channel sender for responding to the pending request
         let response_tx = match self.pending.borrow_mut().remove(&id) {
@@ -314,6 +404,16 @@ pub enum IpcError {
     ServerExit,
 }
 
+impl IpcError {
+    /// Returns the error response of the node, if this is a JSON-RPC error
+    pub fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            IpcError::JsonRpcError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<IpcError> for ProviderError {
     fn from(src: IpcError) -> Self {
         ProviderError::JsonRpcClientError(Box::new(src))
@@ -0,0 +1,164 @@
+//! A [JsonRpcClient] implementation that records the metrics of every request of the wrapped
+//! client and sends it in a `tracing` span
+
+use crate::{
+    metrics::{record_request, MetricsRecorder, RequestGuard, RequestMetrics, RequestOutcome},
+    BatchRequest, JsonRpcClient,
+};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+use std::fmt::Debug;
+use tracing_futures::Instrument;
+
+/// A client that records the latency, the outcome and the number of in-flight requests of every
+/// JSON-RPC method with a [`MetricsRecorder`].
+///
+/// The requests are recorded under their JSON-RPC method, the calls of a batch are recorded
+/// individually with the latency of the whole batch.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Http, MetricsClient, Middleware, Provider};
+/// use std::str::FromStr;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let http = Http::from_str("http://localhost:8545")?;
+/// let provider = Provider::new(MetricsClient::new(http));
+///
+/// provider.get_block_number().await?;
+/// let metrics = provider.as_ref().recorder().method("eth_blockNumber").unwrap();
+/// println!("eth_blockNumber took {:?} on average", metrics.latency.mean());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetricsClient<C, R = RequestMetrics> {
+    inner: C,
+    recorder: R,
+}
+
+impl<C> MetricsClient<C, RequestMetrics> {
+    /// Wraps the client and aggregates the metrics with a [`RequestMetrics`]
+    pub fn new(inner: C) -> Self {
+        Self::new_with_recorder(inner, RequestMetrics::default())
+    }
+}
+
+impl<C, R> MetricsClient<C, R> {
+    /// Wraps the client and sends the metrics to the `recorder`
+    pub fn new_with_recorder(inner: C, recorder: R) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Returns the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the recorder of the metrics
+    pub fn recorder(&self) -> &R {
+        &self.recorder
+    }
+
+    /// Consumes the client and returns the wrapped client
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C, R> JsonRpcClient for MetricsClient<C, R>
+where
+    C: JsonRpcClient,
+    C::Error: 'static,
+    R: MetricsRecorder,
+{
+    type Error = C::Error;
+
+    async fn request<T, Res>(&self, method: &str, params: T) -> Result<Res, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        Res: DeserializeOwned,
+    {
+        record_request(&self.recorder, method, self.inner.request(method, params)).await
+    }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let span = tracing::debug_span!("request_batch", calls = batch.len());
+        async move {
+            let mut guards = batch
+                .calls()
+                .iter()
+                .map(|call| RequestGuard::new(&self.recorder, call.method()))
+                .collect::<Vec<_>>();
+            let res = self.inner.request_batch(batch).await;
+            match res {
+                Ok(ref responses) => {
+                    for (guard, response) in guards.iter_mut().zip(responses) {
+                        guard.finish(match response {
+                            Ok(_) => RequestOutcome::Success,
+                            Err(err) => RequestOutcome::from_error(err),
+                        });
+                    }
+                }
+                Err(ref err) => {
+                    let outcome = RequestOutcome::from_error(err);
+                    guards.iter_mut().for_each(|guard| guard.finish(outcome));
+                }
+            }
+            res
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, Provider};
+    use ethers_core::types::U64;
+
+    #[tokio::test]
+    async fn records_requests() {
+        let mock = MockProvider::new();
+        let provider = Provider::new(MetricsClient::new(mock.clone()));
+
+        mock.push(U64::from(12u64)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), 12u64.into());
+        // the mock has no response left
+        provider.get_block_number().await.unwrap_err();
+
+        let metrics = provider.as_ref().recorder().method("eth_blockNumber").unwrap();
+        assert_eq!(metrics.requests, 2);
+        assert_eq!(metrics.in_flight, 0);
+        assert_eq!(metrics.other_errors, 1);
+        assert_eq!(metrics.latency.count, 2);
+    }
+
+    #[tokio::test]
+    async fn records_batch_calls() {
+        let mock = MockProvider::new();
+        let client = MetricsClient::new(mock.clone());
+
+        let mut batch = BatchRequest::new();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+        batch.add_request("eth_chainId", ()).unwrap();
+        mock.push(U64::from(1u64)).unwrap();
+        mock.push(U64::from(12u64)).unwrap();
+        client.request_batch(&batch).await.unwrap();
+
+        let metrics = client.recorder().snapshot();
+        assert_eq!(metrics["eth_blockNumber"].requests, 1);
+        assert_eq!(metrics["eth_chainId"].requests, 1);
+        assert!(metrics.values().all(|metrics| metrics.in_flight == 0));
+    }
+}
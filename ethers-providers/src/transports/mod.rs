@@ -1,5 +1,5 @@
-mod common;
-pub use common::Authorization;
+pub(crate) mod common;
+pub use common::{Authorization, JsonRpcError};
 
 // only used with WS
 #[cfg(feature = "ws")]
@@ -31,10 +31,18 @@ pub use self::http::{ClientError as HttpClientError, Provider as Http};
 mod ws;
 #[cfg(feature = "ws")]
 pub use ws::{ClientError as WsClientError, Ws};
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+pub use ws::{ConnectionDetails, ReconnectConfig};
 
 mod quorum;
 pub use quorum::{JsonRpcClientWrapper, Quorum, QuorumError, QuorumProvider, WeightedProvider};
 
+mod fallback;
+pub use fallback::{
+    FallbackError, FallbackProvider, FallbackProviderBuilder, ProviderStats, DEFAULT_MAX_BLOCK_LAG,
+    DEFAULT_PROBE_INTERVAL,
+};
+
 mod rw;
 pub use rw::{RwClient, RwClientError};
 
@@ -43,5 +51,13 @@ mod retry;
 #[cfg(not(target_arch = "wasm32"))]
 pub use retry::*;
 
+mod metrics;
+pub use metrics::MetricsClient;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod rate_limit;
+#[cfg(not(target_arch = "wasm32"))]
+pub use rate_limit::{ComputeUnitCosts, RateLimit, RateLimitClient};
+
 mod mock;
 pub use mock::{MockError, MockProvider};
@@ -6,7 +6,7 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::{provider::ProviderError, JsonRpcClient, PubsubClient};
+use crate::{provider::ProviderError, BatchRequest, JsonRpcClient, PubsubClient};
 use async_trait::async_trait;
 use ethers_core::types::{U256, U64};
 use futures_core::Stream;
@@ -182,30 +182,56 @@ impl<T: JsonRpcClientWrapper> QuorumProvider<T> {
 
     /// Normalizes the request payload depending on the call
     async fn normalize_request(&self, method: &str, params: &mut Value) {
-        match method {
-            "eth_call" |
-            "eth_createAccessList" |
-            "eth_getStorageAt" |
-            "eth_getCode" |
-            "eth_getProof" |
-            "trace_call" |
-            "trace_block" => {
-                // calls that include the block number in the params at the last index of json array
-                if let Some(block) = params.as_array_mut().and_then(|arr| arr.last_mut()) {
-                    if Some("latest") == block.as_str() {
-                        // replace `latest` with the minimum block height of all providers
-                        if let Ok(minimum) = self
-                            .get_minimum_block_number()
-                            .await
-                            .and_then(|num| Ok(serde_json::to_value(num)?))
-                        {
-                            *block = minimum
-                        }
-                    }
+        if let Some(block) = latest_block_param(method, params) {
+            // replace `latest` with the minimum block height of all providers
+            if let Ok(minimum) =
+                self.get_minimum_block_number().await.and_then(|num| Ok(serde_json::to_value(num)?))
+            {
+                *block = minimum
+            }
+        }
+    }
+
+    /// Normalizes the payload of all calls in the batch, see `normalize_request`
+    async fn normalize_batch(&self, batch: &BatchRequest) -> Result<BatchRequest, ProviderError> {
+        let mut normalized = BatchRequest::with_capacity(batch.len());
+        // the minimum block height is only fetched once for the entire batch
+        let mut minimum = None;
+        for call in batch.calls() {
+            let mut params = call.params().clone();
+            if let Some(block) = latest_block_param(call.method(), &mut params) {
+                if minimum.is_none() {
+                    minimum = self
+                        .get_minimum_block_number()
+                        .await
+                        .and_then(|num| Ok(serde_json::to_value(num)?))
+                        .ok();
+                }
+                if let Some(ref minimum) = minimum {
+                    *block = minimum.clone();
                 }
             }
-            _ => {}
+            normalized.add_request(call.method(), params)?;
         }
+        Ok(normalized)
+    }
+}
+
+/// Returns the block param of calls that include the block number at the last index of the
+/// params array, if it is set to `latest`
+fn latest_block_param<'a>(method: &str, params: &'a mut Value) -> Option<&'a mut Value> {
+    match method {
+        "eth_call" |
+        "eth_createAccessList" |
+        "eth_getStorageAt" |
+        "eth_getCode" |
+        "eth_getProof" |
+        "trace_call" |
+        "trace_block" => params
+            .as_array_mut()
+            .and_then(|arr| arr.last_mut())
+            .filter(|block| Some("latest") == block.as_str()),
+        _ => None,
     }
 }
 
@@ -366,6 +392,22 @@ impl From<QuorumError> for ProviderError {
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait JsonRpcClientWrapper: Send + Sync + fmt::Debug {
     async fn request(&self, method: &str, params: Value) -> Result<Value, ProviderError>;
+
+    /// Sends all calls of the batch, the outer error is only returned if the batch as a whole
+    /// failed.
+    ///
+    /// The default implementation sends the calls one after the other via
+    /// [`request`](Self::request).
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Value, ProviderError>>, ProviderError> {
+        let mut responses = Vec::with_capacity(batch.len());
+        for call in batch.calls() {
+            responses.push(self.request(call.method(), call.params().clone()).await);
+        }
+        Ok(responses)
+    }
 }
 type NotificationStream =
     Box<dyn futures_core::Stream<Item = Box<RawValue>> + Send + Unpin + 'static>;
@@ -384,6 +426,17 @@ impl<C: JsonRpcClient> JsonRpcClientWrapper for C {
     async fn request(&self, method: &str, params: Value) -> Result<Value, ProviderError> {
         Ok(JsonRpcClient::request(self, method, params).await.map_err(C::Error::into)?)
     }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Value, ProviderError>>, ProviderError> {
+        let responses = JsonRpcClient::request_batch(self, batch).await.map_err(C::Error::into)?;
+        Ok(responses
+            .into_iter()
+            .map(|res| Ok(serde_json::from_str(res.map_err(C::Error::into)?.get())?))
+            .collect())
+    }
 }
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -391,6 +444,13 @@ impl JsonRpcClientWrapper for Box<dyn JsonRpcClientWrapper> {
     async fn request(&self, method: &str, params: Value) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Value, ProviderError>>, ProviderError> {
+        self.as_ref().request_batch(batch).await
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -399,6 +459,13 @@ impl JsonRpcClientWrapper for Box<dyn PubsubClientWrapper> {
     async fn request(&self, method: &str, params: Value) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Value, ProviderError>>, ProviderError> {
+        self.as_ref().request_batch(batch).await
+    }
 }
 
 impl<C: PubsubClient> PubsubClientWrapper for C
@@ -454,6 +521,70 @@ where
         let value = QuorumRequest::new(self, requests).await?;
         Ok(serde_json::from_value(value)?)
     }
+
+    /// Sends the batch to all providers and resolves every call of the batch separately, a call
+    /// only succeeds if the providers reached a quorum on its result.
+    ///
+    /// Unlike single requests, this waits for the responses of all providers.
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let batch = self.normalize_batch(batch).await?;
+
+        let responses =
+            join_all(self.providers.iter().map(|provider| provider.inner.request_batch(&batch)))
+                .await;
+
+        let mut responses = self
+            .providers
+            .iter()
+            .zip(responses)
+            .map(|(provider, res)| {
+                let res = res.unwrap_or_else(|err| {
+                    // every call of a failed batch counts as failed for this provider
+                    (0..batch.len())
+                        .map(|_| Err(ProviderError::CustomError(err.to_string())))
+                        .collect()
+                });
+                (provider.weight, res.into_iter())
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(batch.len());
+        for _ in 0..batch.len() {
+            // the different answers with their cumulative weight
+            let mut values: Vec<(Value, u64)> = Vec::new();
+            let mut errors = Vec::new();
+            for (response_weight, responses) in responses.iter_mut() {
+                match responses.next() {
+                    Some(Ok(val)) => {
+                        if let Some((_, weight)) = values.iter_mut().find(|(v, _)| &val == v) {
+                            *weight += *response_weight;
+                        } else {
+                            values.push((val, *response_weight));
+                        }
+                    }
+                    Some(Err(err)) => errors.push(err),
+                    None => {}
+                }
+            }
+
+            values.sort_by(|a, b| b.1.cmp(&a.1));
+            let result = match values.first() {
+                Some((val, weight)) if *weight >= self.quorum_weight => {
+                    serde_json::value::to_raw_value(val).map_err(Into::into)
+                }
+                _ => {
+                    let values = values.into_iter().map(|r| r.0).collect();
+                    Err(QuorumError::NoQuorumReached { values, errors }.into())
+                }
+            };
+            results.push(result);
+        }
+
+        Ok(results)
+    }
 }
 
 // A stream that returns a value and the weight of its provider
@@ -559,9 +690,11 @@ where
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
-    use super::{Quorum, QuorumProvider, WeightedProvider};
-    use crate::{Middleware, MockProvider, Provider};
+    use super::{JsonRpcClientWrapper, Quorum, QuorumProvider, WeightedProvider};
+    use crate::{BatchRequest, JsonRpcClient, Middleware, MockProvider, Provider, ProviderError};
+    use async_trait::async_trait;
     use ethers_core::types::U64;
+    use serde_json::Value;
 
     async fn test_quorum(q: Quorum) {
         let num = 5u64;
@@ -629,4 +762,60 @@ mod tests {
     async fn all_quorum() {
         test_quorum(Quorum::All).await
     }
+
+    #[tokio::test]
+    async fn batch_quorum() {
+        let mut providers = Vec::new();
+        for value in [42u64, 42, 7] {
+            let mock = MockProvider::new();
+            // responses are popped from the back
+            mock.push(U64::from(value)).unwrap();
+            mock.push(U64::from(1)).unwrap();
+            providers.push(WeightedProvider::new(mock));
+        }
+        let quorum = QuorumProvider::builder().add_providers(providers).quorum(Quorum::All).build();
+        let provider = Provider::quorum(quorum);
+
+        let mut batch = BatchRequest::new();
+        batch.add_request("eth_chainId", ()).unwrap();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+
+        let mut responses = provider.send_batch(&batch).await.unwrap();
+        assert_eq!(responses.next_response::<U64>().unwrap().unwrap(), U64::from(1));
+        // providers disagree on the second call
+        assert!(responses.next_response::<U64>().unwrap().is_err());
+        assert!(responses.next_response::<U64>().is_none());
+    }
+
+    /// Only implements single requests, batches are sent one call after the other
+    #[derive(Debug)]
+    struct SingleRequests(MockProvider);
+
+    #[async_trait]
+    impl JsonRpcClientWrapper for SingleRequests {
+        async fn request(&self, method: &str, params: Value) -> Result<Value, ProviderError> {
+            Ok(JsonRpcClient::request(&self.0, method, params).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_quorum_without_batch_support() {
+        let mut providers = Vec::new();
+        for _ in 0..2 {
+            let mock = MockProvider::new();
+            mock.push(U64::from(42)).unwrap();
+            mock.push(U64::from(1)).unwrap();
+            providers.push(WeightedProvider::new(SingleRequests(mock)));
+        }
+        let quorum = QuorumProvider::builder().add_providers(providers).quorum(Quorum::All).build();
+        let provider = Provider::quorum(quorum);
+
+        let mut batch = BatchRequest::new();
+        batch.add_request("eth_chainId", ()).unwrap();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+
+        let mut responses = provider.send_batch(&batch).await.unwrap();
+        assert_eq!(responses.next_response::<U64>().unwrap().unwrap(), U64::from(1));
+        assert_eq!(responses.next_response::<U64>().unwrap().unwrap(), U64::from(42));
+    }
 }
@@ -0,0 +1,319 @@
+//! A [JsonRpcClient] implementation that delays requests to stay within the rate limit of the
+//! endpoint, weighting every request by its compute units
+
+use crate::{BatchRequest, JsonRpcClient};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::trace;
+
+/// The cost in compute units of the methods of the JSON-RPC API.
+///
+/// Hosted providers weight their rate limits by the cost of the method, e.g. `eth_getLogs` costs
+/// more than `eth_blockNumber`. Methods without a configured cost cost the default cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeUnitCosts {
+    default: u64,
+    costs: HashMap<String, u64>,
+}
+
+impl ComputeUnitCosts {
+    /// Every method costs `cost`, with a cost of `1` the budget is measured in requests
+    pub fn uniform(cost: u64) -> Self {
+        Self { default: cost, costs: HashMap::new() }
+    }
+
+    /// The costs of the methods on Alchemy, see <https://docs.alchemy.com/reference/compute-units>
+    pub fn alchemy() -> Self {
+        // the average cost of a request, see `RetryClient`
+        let mut costs = Self::uniform(17);
+        for (method, cost) in [
+            ("net_version", 0),
+            ("eth_chainId", 0),
+            ("eth_syncing", 0),
+            ("eth_protocolVersion", 0),
+            ("net_listening", 0),
+            ("eth_blockNumber", 10),
+            ("eth_feeHistory", 10),
+            ("eth_maxPriorityFeePerGas", 10),
+            ("eth_subscribe", 10),
+            ("eth_unsubscribe", 10),
+            ("eth_getTransactionReceipt", 15),
+            ("eth_getBlockByNumber", 16),
+            ("eth_getStorageAt", 17),
+            ("eth_getTransactionByHash", 17),
+            ("eth_getBalance", 19),
+            ("eth_getCode", 19),
+            ("eth_gasPrice", 19),
+            ("eth_getBlockByHash", 21),
+            ("eth_newFilter", 20),
+            ("eth_newBlockFilter", 20),
+            ("eth_newPendingTransactionFilter", 20),
+            ("eth_getFilterChanges", 20),
+            ("eth_getFilterLogs", 75),
+            ("eth_uninstallFilter", 10),
+            ("eth_getTransactionCount", 26),
+            ("eth_call", 26),
+            ("eth_createAccessList", 10),
+            ("eth_getLogs", 75),
+            ("eth_estimateGas", 87),
+            ("eth_getProof", 21),
+            ("eth_sendRawTransaction", 250),
+            ("trace_transaction", 40),
+            ("trace_block", 50),
+            ("trace_get", 17),
+            ("trace_call", 75),
+            ("trace_filter", 75),
+            ("trace_replayTransaction", 2983),
+            ("trace_replayBlockTransactions", 2983),
+            ("debug_traceTransaction", 309),
+            ("debug_traceCall", 309),
+        ] {
+            costs = costs.with_cost(method, cost);
+        }
+        costs
+    }
+
+    /// Sets the cost of the `method`
+    #[must_use]
+    pub fn with_cost(mut self, method: impl Into<String>, cost: u64) -> Self {
+        self.costs.insert(method.into(), cost);
+        self
+    }
+
+    /// Returns the cost of the `method`
+    pub fn cost(&self, method: &str) -> u64 {
+        self.costs.get(method).copied().unwrap_or(self.default)
+    }
+}
+
+impl Default for ComputeUnitCosts {
+    fn default() -> Self {
+        Self::uniform(1)
+    }
+}
+
+/// The rate limit of an endpoint: a budget of compute units per second and the cost of every
+/// method.
+///
+/// The presets match the limits of the free plans of the providers at the time of writing, the
+/// budget of paid plans can be set with [`RateLimit::per_second`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    per_second: u64,
+    burst: u64,
+    costs: ComputeUnitCosts,
+}
+
+impl RateLimit {
+    /// A budget of `compute_units_per_second`, measured with the `costs` of the methods
+    pub fn compute_units(compute_units_per_second: u64, costs: ComputeUnitCosts) -> Self {
+        Self { per_second: compute_units_per_second, burst: compute_units_per_second, costs }
+    }
+
+    /// A budget of `requests_per_second`, regardless of the method
+    pub fn requests(requests_per_second: u64) -> Self {
+        Self::compute_units(requests_per_second, ComputeUnitCosts::uniform(1))
+    }
+
+    /// Alchemy: 330 compute units per second
+    pub fn alchemy() -> Self {
+        Self::compute_units(330, ComputeUnitCosts::alchemy())
+    }
+
+    /// Infura: 10 requests per second
+    pub fn infura() -> Self {
+        Self::requests(10)
+    }
+
+    /// QuickNode: 25 requests per second
+    pub fn quicknode() -> Self {
+        Self::requests(25)
+    }
+
+    /// Sets the budget per second, e.g. for paid plans
+    #[must_use]
+    pub fn per_second(mut self, per_second: u64) -> Self {
+        self.per_second = per_second;
+        self.burst = per_second;
+        self
+    }
+
+    /// Sets the budget that may be spent at once after the endpoint was idle, defaults to the
+    /// budget per second
+    #[must_use]
+    pub fn burst(mut self, burst: u64) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Returns the costs of the methods
+    pub fn costs(&self) -> &ComputeUnitCosts {
+        &self.costs
+    }
+}
+
+/// A token bucket that hands out reservations in the order they are requested
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative if reservations are queued
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(burst: u64, now: Instant) -> Self {
+        Self { tokens: burst as f64, refilled_at: now }
+    }
+
+    /// Reserves `cost` tokens and returns how long to wait until they are available
+    fn reserve(&mut self, limit: &RateLimit, cost: u64, now: Instant) -> Duration {
+        let per_second = limit.per_second.max(1) as f64;
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(limit.burst as f64);
+        self.refilled_at = now;
+
+        self.tokens -= cost as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / per_second)
+        }
+    }
+}
+
+/// A client that delays requests to stay within a [`RateLimit`] instead of waiting for the
+/// endpoint to reject them with a 429 like the [`RetryClient`](crate::RetryClient).
+///
+/// Every request reserves its cost from a token bucket that is refilled with the budget per
+/// second. Requests that exceed the budget are queued and sent in order as soon as the budget
+/// allows it.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Http, Middleware, Provider, RateLimit, RateLimitClient};
+/// use std::str::FromStr;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let http = Http::from_str("https://eth-mainnet.g.alchemy.com/v2/<key>")?;
+/// // growth plan
+/// let provider = Provider::new(RateLimitClient::new(http, RateLimit::alchemy().per_second(660)));
+/// let block_number = provider.get_block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RateLimitClient<C> {
+    inner: C,
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl<C> RateLimitClient<C> {
+    /// Wraps the client and limits its requests to the `limit`
+    pub fn new(inner: C, limit: RateLimit) -> Self {
+        let bucket = Mutex::new(Bucket::new(limit.burst, Instant::now()));
+        Self { inner, limit, bucket }
+    }
+
+    /// Returns the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the rate limit
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// Waits until the budget allows to spend `cost`
+    async fn acquire(&self, cost: u64) {
+        let wait = self.bucket.lock().unwrap().reserve(&self.limit, cost, Instant::now());
+        if !wait.is_zero() {
+            trace!(cost, ?wait, "rate limited");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<C> JsonRpcClient for RateLimitClient<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = C::Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        self.acquire(self.limit.costs.cost(method)).await;
+        self.inner.request(method, params).await
+    }
+
+    /// Reserves the cost of all calls of the batch at once
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let cost = batch.calls().iter().map(|call| self.limit.costs.cost(call.method())).sum();
+        self.acquire(cost).await;
+        self.inner.request_batch(batch).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+    use ethers_core::types::U64;
+
+    #[test]
+    fn queues_reservations_in_order() {
+        let limit = RateLimit::compute_units(100, ComputeUnitCosts::alchemy());
+        let start = Instant::now();
+        let mut bucket = Bucket::new(limit.burst, start);
+
+        // the burst is spent right away
+        assert_eq!(bucket.reserve(&limit, 75, start), Duration::ZERO);
+        assert_eq!(bucket.reserve(&limit, 25, start), Duration::ZERO);
+        // then every reservation waits for the previous ones
+        assert_eq!(bucket.reserve(&limit, 50, start), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(&limit, 50, start), Duration::from_secs(1));
+
+        // the bucket refills over time
+        let later = start + Duration::from_secs(3);
+        assert_eq!(bucket.reserve(&limit, 100, later), Duration::ZERO);
+    }
+
+    #[test]
+    fn weights_methods() {
+        let costs = ComputeUnitCosts::alchemy();
+        assert!(costs.cost("eth_getLogs") > costs.cost("eth_blockNumber"));
+        assert_eq!(costs.cost("eth_unknownMethod"), 17);
+        assert_eq!(RateLimit::infura().costs().cost("eth_getLogs"), 1);
+    }
+
+    #[tokio::test]
+    async fn delays_requests_over_budget() {
+        let mock = MockProvider::new();
+        let client = RateLimitClient::new(mock.clone(), RateLimit::requests(20).burst(1));
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            mock.push(U64::from(1u64)).unwrap();
+            let _: U64 = client.request("eth_blockNumber", ()).await.unwrap();
+        }
+        // the first request is sent right away, the others 50ms apart
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
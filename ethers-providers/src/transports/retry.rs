@@ -2,7 +2,7 @@
 //! with an exponential backoff.
 
 use super::{common::JsonRpcError, http::ClientError};
-use crate::{provider::ProviderError, JsonRpcClient};
+use crate::{provider::ProviderError, BatchRequest, JsonRpcClient};
 
 use std::{
     fmt::Debug,
@@ -12,6 +12,7 @@ use std::{
 
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
 use thiserror::Error;
 use tracing::trace;
 
@@ -82,6 +83,36 @@ where
         self.compute_units_per_second = cpus;
         self
     }
+
+    /// Returns how long to wait before the next retry, taking into account the number of retries
+    /// so far and the requests that are currently queued
+    fn backoff(&self, retry_number: u32, ahead_in_queue: u64) -> Duration {
+        let current_queued_requests = self.requests_enqueued.load(Ordering::SeqCst) as u64;
+        // using `retry_number` for creating back pressure because
+        // of already queued requests
+        // this increases exponentially with retries and adds a delay based on how many
+        // requests are currently queued
+        let mut next_backoff = self.initial_backoff * 2u64.pow(retry_number);
+
+        // requests are usually weighted and can vary from 10 CU to several 100 CU, cheaper
+        // requests are more common some example alchemy weights:
+        // - `eth_getStorageAt`: 17
+        // - `eth_getBlockByNumber`: 16
+        // - `eth_newFilter`: 20
+        //
+        // (coming from forking mode) assuming here that storage request will be the driver
+        // for Rate limits we choose `17` as the average cost of any request
+        const AVG_COST: u64 = 17u64;
+        let seconds_to_wait_for_compute_budge = compute_unit_offset_in_secs(
+            AVG_COST,
+            self.compute_units_per_second,
+            current_queued_requests,
+            ahead_in_queue,
+        );
+        // backoff is measured in millis
+        next_backoff += seconds_to_wait_for_compute_budge * 1000;
+        Duration::from_millis(next_backoff)
+    }
 }
 
 /// Error thrown when:
@@ -94,8 +125,7 @@ where
     T: JsonRpcClient,
     T::Error: Sync + Send + 'static,
 {
-    #[error(transparent)]
-    ProviderError(T::Error),
+    ProviderError(#[source] T::Error),
     TimeoutError,
     #[error(transparent)]
     SerdeJson(serde_json::Error),
@@ -181,33 +211,9 @@ where
 
             let should_retry = self.policy.should_retry(&err);
             if should_retry {
-                let current_queued_requests = self.requests_enqueued.load(Ordering::SeqCst) as u64;
-                // using `retry_number` for creating back pressure because
-                // of already queued requests
-                // this increases exponentially with retries and adds a delay based on how many
-                // requests are currently queued
-                let mut next_backoff = self.initial_backoff * 2u64.pow(retry_number);
-
-                // requests are usually weighted and can vary from 10 CU to several 100 CU, cheaper
-                // requests are more common some example alchemy weights:
-                // - `eth_getStorageAt`: 17
-                // - `eth_getBlockByNumber`: 16
-                // - `eth_newFilter`: 20
-                //
-                // (coming from forking mode) assuming here that storage request will be the driver
-                // for Rate limits we choose `17` as the average cost of any request
-                const AVG_COST: u64 = 17u64;
-                let seconds_to_wait_for_compute_budge = compute_unit_offset_in_secs(
-                    AVG_COST,
-                    self.compute_units_per_second,
-                    current_queued_requests,
-                    ahead_in_queue,
-                );
-                // backoff is measured in millis
-                next_backoff += seconds_to_wait_for_compute_budge * 1000;
-
-                trace!("retrying and backing off for {}ms", next_backoff);
-                tokio::time::sleep(Duration::from_millis(next_backoff)).await;
+                let next_backoff = self.backoff(retry_number, ahead_in_queue);
+                trace!("retrying and backing off for {:?}", next_backoff);
+                tokio::time::sleep(next_backoff).await;
             } else {
                 trace!(err = ?err, "should not retry");
                 self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
@@ -215,6 +221,78 @@ where
             }
         }
     }
+
+    /// Sends the batch and retries the calls that failed with a retryable error, either because
+    /// the whole batch failed or because the call itself failed
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let ahead_in_queue = self.requests_enqueued.fetch_add(1, Ordering::SeqCst) as u64;
+
+        let mut responses: Vec<Option<Result<Box<RawValue>, Self::Error>>> =
+            (0..batch.len()).map(|_| None).collect();
+        // indices of the calls that still need to be sent
+        let mut remaining: Vec<usize> = (0..batch.len()).collect();
+        let mut retry_number: u32 = 0;
+
+        while !remaining.is_empty() {
+            let mut pending = BatchRequest::with_capacity(remaining.len());
+            for call in remaining.iter().map(|idx| &batch.calls()[*idx]) {
+                pending
+                    .add_request(call.method(), call.params())
+                    .map_err(RetryClientError::SerdeJson)?;
+            }
+
+            let batch_error = match self.inner.request_batch(&pending).await {
+                Ok(results) => {
+                    let mut retry = Vec::new();
+                    for (idx, result) in remaining.drain(..).zip(results) {
+                        if matches!(result, Err(ref err) if self.policy.should_retry(err)) {
+                            retry.push(idx);
+                        }
+                        // a retried call keeps its last error in case it runs out of retries
+                        responses[idx] = Some(result.map_err(RetryClientError::ProviderError));
+                    }
+                    remaining = retry;
+                    if remaining.is_empty() {
+                        break
+                    }
+                    None
+                }
+                Err(err) => {
+                    if !self.policy.should_retry(&err) {
+                        trace!(err = ?err, "should not retry");
+                        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+                        return Err(RetryClientError::ProviderError(err))
+                    }
+                    Some(err)
+                }
+            };
+
+            retry_number += 1;
+            if retry_number > self.max_retry {
+                trace!("batch timed out after {} retries", self.max_retry);
+                if let Some(err) = batch_error {
+                    self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+                    return Err(RetryClientError::ProviderError(err))
+                }
+                break
+            }
+
+            let next_backoff = self.backoff(retry_number, ahead_in_queue);
+            trace!("retrying {} calls and backing off for {:?}", remaining.len(), next_backoff);
+            tokio::time::sleep(next_backoff).await;
+        }
+
+        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+
+        // calls that ran out of retries keep their last error
+        Ok(responses
+            .into_iter()
+            .map(|response| response.unwrap_or(Err(RetryClientError::TimeoutError)))
+            .collect())
+    }
 }
 
 /// Implements [RetryPolicy] that will retry requests that errored with
@@ -263,6 +341,8 @@ fn compute_unit_offset_in_secs(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{MockError, MockProvider};
+    use ethers_core::types::U64;
     // assumed average cost of a request
     const AVG_COST: u64 = 17u64;
     const COMPUTE_UNITS: u64 = 330u64;
@@ -312,4 +392,31 @@ mod tests {
         // need to wait 1 second
         assert_eq!(to_wait, 1);
     }
+
+    #[tokio::test]
+    async fn batch_calls_keep_their_last_error() {
+        #[derive(Debug)]
+        struct RetryEmptyResponses;
+
+        impl RetryPolicy<MockError> for RetryEmptyResponses {
+            fn should_retry(&self, error: &MockError) -> bool {
+                matches!(error, MockError::EmptyResponses)
+            }
+        }
+
+        let mock = MockProvider::new();
+        mock.push(U64::from(1)).unwrap();
+        let client = RetryClient::new(mock, Box::new(RetryEmptyResponses), 2, 0);
+
+        let mut batch = BatchRequest::new();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+        batch.add_request("eth_chainId", ()).unwrap();
+
+        let responses = client.request_batch(&batch).await.unwrap();
+        assert_eq!(responses[0].as_ref().unwrap().get(), r#""0x1""#);
+        assert!(matches!(
+            responses[1],
+            Err(RetryClientError::ProviderError(MockError::EmptyResponses))
+        ));
+    }
 }
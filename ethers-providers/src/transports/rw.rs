@@ -1,11 +1,12 @@
 //! A [JsonRpcClient] implementation that serves as a wrapper around two different [JsonRpcClient]
 //! and uses a dedicated client for read and the other for write operations
 
-use crate::{provider::ProviderError, JsonRpcClient};
+use crate::{provider::ProviderError, BatchRequest, JsonRpcClient};
 
 use async_trait::async_trait;
 
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
 
 use thiserror::Error;
 
@@ -76,11 +77,11 @@ where
     <Write as JsonRpcClient>::Error: Sync + Send + 'static,
 {
     /// Thrown if the _read_ request failed
-    #[error(transparent)]
-    Read(Read::Error),
-    #[error(transparent)]
+    #[error("{0}")]
+    Read(#[source] Read::Error),
+    #[error("{0}")]
     /// Thrown if the _write_ request failed
-    Write(Write::Error),
+    Write(#[source] Write::Error),
 }
 
 impl<Read, Write> From<RwClientError<Read, Write>> for ProviderError
@@ -117,11 +118,51 @@ where
         T: std::fmt::Debug + Serialize + Send + Sync,
         R: DeserializeOwned,
     {
-        match method {
-            "eth_sendTransaction" | "eth_sendRawTransaction" => {
-                self.w.request(method, params).await.map_err(RwClientError::Write)
-            }
-            _ => self.r.request(method, params).await.map_err(RwClientError::Read),
+        if is_write(method) {
+            self.w.request(method, params).await.map_err(RwClientError::Write)
+        } else {
+            self.r.request(method, params).await.map_err(RwClientError::Read)
         }
     }
+
+    /// Splits the batch into a _read_ and a _write_ batch and sends them to the respective client
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, Self::Error>>, Self::Error> {
+        let mut reads = BatchRequest::new();
+        let mut writes = BatchRequest::new();
+        for call in batch.calls() {
+            let target = if is_write(call.method()) { &mut writes } else { &mut reads };
+            // the params are already a valid json value
+            let _ = target.add_request(call.method(), call.params());
+        }
+
+        let mut reads = self
+            .r
+            .request_batch(&reads)
+            .await
+            .map_err(RwClientError::Read)?
+            .into_iter()
+            .map(|res| res.map_err(RwClientError::Read));
+        let mut writes = self
+            .w
+            .request_batch(&writes)
+            .await
+            .map_err(RwClientError::Write)?
+            .into_iter()
+            .map(|res| res.map_err(RwClientError::Write));
+
+        // restore the original order of the batch
+        Ok(batch
+            .calls()
+            .iter()
+            .filter_map(|call| if is_write(call.method()) { writes.next() } else { reads.next() })
+            .collect())
+    }
+}
+
+/// Returns true if the method is sent via the _write_ client
+fn is_write(method: &str) -> bool {
+    matches!(method, "eth_sendTransaction" | "eth_sendRawTransaction")
 }
@@ -1,7 +1,7 @@
 use crate::{
     provider::ProviderError,
     transports::common::{JsonRpcError, Request},
-    JsonRpcClient, PubsubClient,
+    BatchRequest, JsonRpcClient, PubsubClient,
 };
 use ethers_core::types::U256;
 
@@ -14,7 +14,7 @@ use futures_util::{
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::value::RawValue;
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::{btree_map::Entry, BTreeMap, VecDeque},
     fmt::{self, Debug},
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -23,7 +23,7 @@ use std::{
 };
 use thiserror::Error;
 
-use super::common::{Params, Response};
+use super::common::{Params, Response, Responses};
 
 if_wasm! {
     use wasm_bindgen::prelude::*;
@@ -62,19 +62,34 @@ if_not_wasm! {
     type Message = tungstenite::protocol::Message;
     type WsError = tungstenite::Error;
     type WsStreamItem = Result<Message, WsError>;
+    type WsStream = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
     use super::Authorization;
     use tracing::{debug, error, warn};
     use http::Request as HttpRequest;
     use tungstenite::client::IntoClientRequest;
+    use std::time::Duration;
 }
 
 type Pending = oneshot::Sender<Result<Box<RawValue>, JsonRpcError>>;
 type Subscription = mpsc::UnboundedSender<Box<RawValue>>;
 
+/// A request that is waiting for its response
+struct PendingRequest {
+    /// The serialized request, only kept by servers that reconnect.
+    ///
+    /// For batches, this is only set on the first call of the batch.
+    request: Option<String>,
+    sender: Pending,
+}
+
 /// Instructions for the `WsServer`.
 enum Instruction {
     /// JSON-RPC request
     Request { id: u64, request: String, sender: Pending },
+    /// JSON-RPC batch request, with one pending response per call
+    Batch { requests: Vec<(u64, Pending)>, request: String },
     /// Create a new subscription
     Subscribe { id: U256, sink: Subscription },
     /// Cancel an existing subscription
@@ -152,11 +167,109 @@ impl Ws {
         Self::connect(request).await
     }
 
+    /// Initializes a new WebSocket Client that reconnects once the connection dropped.
+    ///
+    /// After a reconnect, all requests that did not receive a response yet are sent again and
+    /// all active subscriptions are re-issued via `eth_subscribe`. The ids of the re-issued
+    /// subscriptions are mapped to the original ids, so existing subscription streams keep
+    /// yielding notifications.
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::{ReconnectConfig, Ws};
+    ///
+    /// let ws = Ws::connect_with_reconnects("wss://localhost:8545", ReconnectConfig::default())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect_with_reconnects(
+        conn: impl Into<ConnectionDetails>,
+        config: ReconnectConfig,
+    ) -> Result<Self, ClientError> {
+        let conn = conn.into();
+        let (ws, _) = connect_async(conn.clone()).await?;
+
+        let id = Arc::new(AtomicU64::new(1));
+        let (sink, stream) = mpsc::unbounded();
+        WsServer::new(ws, stream).with_replay(id.clone()).spawn_reconnecting(conn, config);
+
+        Ok(Self { id, instructions: sink })
+    }
+
     fn send(&self, msg: Instruction) -> Result<(), ClientError> {
         self.instructions.unbounded_send(msg).map_err(to_client_error)
     }
 }
 
+if_not_wasm! {
+    /// The url and optional authentication used to (re)connect a [`Ws`]
+    #[derive(Debug, Clone)]
+    pub struct ConnectionDetails {
+        /// The url of the websocket endpoint
+        pub url: String,
+        /// The credentials sent in the `Authorization` header of every (re)connect, if any
+        pub auth: Option<Authorization>,
+    }
+
+    impl ConnectionDetails {
+        /// Creates the details to connect to `url`, optionally with authentication
+        pub fn new(url: impl Into<String>, auth: Option<Authorization>) -> Self {
+            Self { url: url.into(), auth }
+        }
+    }
+
+    impl From<&str> for ConnectionDetails {
+        fn from(url: &str) -> Self {
+            Self::new(url, None)
+        }
+    }
+
+    impl From<String> for ConnectionDetails {
+        fn from(url: String) -> Self {
+            Self::new(url, None)
+        }
+    }
+
+    impl IntoClientRequest for ConnectionDetails {
+        fn into_client_request(self) -> Result<HttpRequest<()>, WsError> {
+            let mut request: HttpRequest<()> = self.url.into_client_request()?;
+            if let Some(auth) = self.auth {
+                let mut auth_value = http::HeaderValue::from_str(&auth.to_string())?;
+                auth_value.set_sensitive(true);
+
+                request.headers_mut().insert(http::header::AUTHORIZATION, auth_value);
+            }
+            Ok(request)
+        }
+    }
+
+    /// Configures how a [`Ws`] created with [`Ws::connect_with_reconnects`] reconnects.
+    ///
+    /// The delay before the first attempt is `initial_backoff`, it is doubled after every failed
+    /// attempt up to `max_backoff`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ReconnectConfig {
+        /// The number of failed attempts in a row after which the client gives up
+        pub max_retries: u32,
+        /// The delay before the first attempt
+        pub initial_backoff: Duration,
+        /// The maximum delay between two attempts
+        pub max_backoff: Duration,
+    }
+
+    impl Default for ReconnectConfig {
+        fn default() -> Self {
+            Self {
+                max_retries: 10,
+                initial_backoff: Duration::from_millis(100),
+                max_backoff: Duration::from_secs(30),
+            }
+        }
+    }
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl JsonRpcClient for Ws {
@@ -186,6 +299,33 @@ impl JsonRpcClient for Ws {
         // parse it
         Ok(serde_json::from_str(res.get())?)
     }
+
+    async fn request_batch(
+        &self,
+        batch: &BatchRequest,
+    ) -> Result<Vec<Result<Box<RawValue>, ClientError>>, ClientError> {
+        // an empty array is not a valid JSON-RPC batch
+        if batch.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let first_id = self.id.fetch_add(batch.len() as u64, Ordering::SeqCst);
+        let (requests, receivers): (Vec<_>, Vec<_>) = (first_id..first_id + batch.len() as u64)
+            .map(|id| {
+                let (sender, receiver) = oneshot::channel();
+                ((id, sender), receiver)
+            })
+            .unzip();
+
+        self.send(Instruction::Batch { requests, request: batch.to_payload(first_id) })?;
+
+        // the server resolves every call of the batch separately
+        let mut responses = Vec::with_capacity(receivers.len());
+        for receiver in receivers {
+            responses.push(receiver.await?.map_err(Into::into));
+        }
+        Ok(responses)
+    }
 }
 
 impl PubsubClient for Ws {
@@ -206,8 +346,82 @@ struct WsServer<S> {
     ws: Fuse<S>,
     instructions: Fuse<mpsc::UnboundedReceiver<Instruction>>,
 
-    pending: BTreeMap<u64, Pending>,
+    pending: BTreeMap<u64, PendingRequest>,
     subscriptions: BTreeMap<U256, Subscription>,
+    /// The ids of the calls of the batches that were sent, in the order they were sent
+    batches: VecDeque<Vec<u64>>,
+
+    /// Only set if the server reconnects once the connection dropped
+    replay: Option<Replay>,
+}
+
+/// Everything a reconnecting server needs to restore its session
+struct Replay {
+    /// The request id counter shared with the `Ws` handle
+    ids: Arc<AtomicU64>,
+    /// The `eth_subscribe` params of all active subscriptions by their original id
+    subscriptions: BTreeMap<U256, Box<RawValue>>,
+    /// Maps the ids the server assigned to re-issued subscriptions to their original id
+    aliases: BTreeMap<U256, U256>,
+    /// Original subscription ids of the re-issued `eth_subscribe` requests by request id
+    resubscribing: BTreeMap<u64, U256>,
+}
+
+impl Replay {
+    fn new(ids: Arc<AtomicU64>) -> Self {
+        Self {
+            ids,
+            subscriptions: BTreeMap::default(),
+            aliases: BTreeMap::default(),
+            resubscribing: BTreeMap::default(),
+        }
+    }
+
+    /// Remembers the params of a successful `eth_subscribe` request
+    fn track_subscription(&mut self, request: &str, result: &RawValue) {
+        // only single requests can be replayed
+        if let Ok(request) = serde_json::from_str::<Request<'_, Box<RawValue>>>(request) {
+            if request.method() == "eth_subscribe" {
+                if let Ok(id) = serde_json::from_str::<U256>(result.get()) {
+                    self.subscriptions.insert(id, request.params().clone());
+                }
+            }
+        }
+    }
+
+    /// Maps the new id of a re-issued subscription to its original id
+    fn restore_subscription(&mut self, id: U256, result: Result<Box<RawValue>, JsonRpcError>) {
+        match result.map(|raw| serde_json::from_str::<U256>(raw.get())) {
+            Ok(Ok(new_id)) => {
+                self.aliases.insert(new_id, id);
+            }
+            Ok(Err(err)) => error!("Failed to re-issue subscription with id {:?}: {}", id, err),
+            Err(err) => error!("Failed to re-issue subscription with id {:?}: {}", id, err),
+        }
+    }
+
+    /// Returns the original id of the subscription the server knows as `id`
+    fn original_id(&self, id: U256) -> U256 {
+        self.aliases.get(&id).copied().unwrap_or(id)
+    }
+
+    /// Replaces the original subscription id of an `eth_unsubscribe` request with the id the
+    /// server assigned after the last reconnect
+    fn rewrite_unsubscribe(&self, request: String) -> String {
+        if self.aliases.is_empty() || !request.contains("eth_unsubscribe") {
+            return request
+        }
+
+        let rewritten = serde_json::from_str::<Request<'_, [U256; 1]>>(&request)
+            .ok()
+            .filter(|req| req.method() == "eth_unsubscribe")
+            .and_then(|req| {
+                let [id] = *req.params();
+                let (new_id, _) = self.aliases.iter().find(|(_, original)| **original == id)?;
+                serde_json::to_string(&Request::new(req.id(), req.method(), [new_id])).ok()
+            });
+        rewritten.unwrap_or(request)
+    }
 }
 
 impl<S> WsServer<S>
@@ -223,9 +437,18 @@ where
             instructions: requests.fuse(),
             pending: BTreeMap::default(),
             subscriptions: BTreeMap::default(),
+            batches: VecDeque::default(),
+            replay: None,
         }
     }
 
+    /// Keeps track of requests and subscriptions so they can be restored after a reconnect
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_replay(mut self, ids: Arc<AtomicU64>) -> Self {
+        self.replay = Some(Replay::new(ids));
+        self
+    }
+
     /// Returns whether the all work has been completed.
     ///
     /// If this method returns `true`, then the `instructions` channel has been closed and all
@@ -272,17 +495,58 @@ where
         request: String,
         sender: Pending,
     ) -> Result<(), ClientError> {
-        if self.pending.insert(id, sender).is_some() {
+        let request = match self.replay {
+            Some(ref replay) => replay.rewrite_unsubscribe(request),
+            None => request,
+        };
+        let kept = self.replay.as_ref().map(|_| request.clone());
+        if self.pending.insert(id, PendingRequest { request: kept, sender }).is_some() {
             warn!("Replacing a pending request with id {:?}", id);
         }
 
         if let Err(e) = self.ws.send(Message::Text(request)).await {
             error!("WS connection error: {:?}", e);
-            self.pending.remove(&id);
+            // a reconnecting server sends the request again once it is reconnected
+            if self.replay.is_none() {
+                self.pending.remove(&id);
+            }
         }
         Ok(())
     }
 
+    // dispatch an RPC batch request
+    async fn service_batch_request(
+        &mut self,
+        requests: Vec<(u64, Pending)>,
+        request: String,
+    ) -> Result<(), ClientError> {
+        let ids = requests.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let mut kept = self.replay.as_ref().map(|_| request.clone());
+        for (id, sender) in requests {
+            // the batch is answered as a whole, so it only needs to be kept once
+            let pending = PendingRequest { request: kept.take(), sender };
+            if self.pending.insert(id, pending).is_some() {
+                warn!("Replacing a pending request with id {:?}", id);
+            }
+        }
+
+        if let Err(e) = self.ws.send(Message::Text(request)).await {
+            error!("WS connection error: {:?}", e);
+            if self.replay.is_none() {
+                for id in ids {
+                    self.pending.remove(&id);
+                }
+                return Ok(())
+            }
+        }
+
+        // forget the batches that were answered in the meantime
+        let pending = &self.pending;
+        self.batches.retain(|batch| batch.iter().any(|id| pending.contains_key(id)));
+        self.batches.push_back(ids);
+        Ok(())
+    }
+
     /// Dispatch a subscription request
     async fn service_subscribe(&mut self, id: U256, sink: Subscription) -> Result<(), ClientError> {
         if self.subscriptions.insert(id, sink).is_some() {
@@ -296,6 +560,9 @@ where
         if self.subscriptions.remove(&id).is_none() {
             warn!("Unsubscribing from non-existent subscription with id {:?}", id);
         }
+        if let Some(ref mut replay) = self.replay {
+            replay.subscriptions.remove(&id);
+        }
         Ok(())
     }
 
@@ -305,6 +572,9 @@ where
             Instruction::Request { id, request, sender } => {
                 self.service_request(id, request, sender).await
             }
+            Instruction::Batch { requests, request } => {
+                self.service_batch_request(requests, request).await
+            }
             Instruction::Subscribe { id, sink } => self.service_subscribe(id, sink).await,
             Instruction::Unsubscribe { id } => self.service_unsubscribe(id).await,
         }
@@ -317,23 +587,73 @@ where
     }
 
     async fn handle_text(&mut self, inner: String) -> Result<(), ClientError> {
-        let (id, result) = match serde_json::from_str(&inner)? {
+        match serde_json::from_str(&inner)? {
+            Responses::Single(response) => self.handle_response(response),
+            Responses::Batch(responses) => {
+                for response in responses {
+                    self.handle_response(response)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_response(&mut self, response: Response<'_>) -> Result<(), ClientError> {
+        let (id, result) = match response {
             Response::Success { id, result } => (id, Ok(result.to_owned())),
-            Response::Error { id, error } => (id, Err(error)),
+            Response::Error { id: Some(id), error } => (id, Err(error)),
+            Response::Error { id: None, error } => return self.handle_batch_error(error),
             Response::Notification { params, .. } => return self.handle_notification(params),
         };
 
-        if let Some(request) = self.pending.remove(&id) {
-            if !request.is_canceled() {
-                request.send(result).map_err(to_client_error)?;
+        if let Some(ref mut replay) = self.replay {
+            if let Some(original) = replay.resubscribing.remove(&id) {
+                replay.restore_subscription(original, result);
+                return Ok(())
+            }
+        }
+
+        if let Some(PendingRequest { request, sender }) = self.pending.remove(&id) {
+            if let (Some(replay), Some(request), Ok(result)) =
+                (self.replay.as_mut(), request, result.as_ref())
+            {
+                replay.track_subscription(&request, result);
+            }
+            if !sender.is_canceled() {
+                sender.send(result).map_err(to_client_error)?;
             }
         }
 
         Ok(())
     }
 
+    /// Handles an error without id, which the server sends if it rejected a batch as a whole.
+    ///
+    /// The error can't be matched to its batch, so it fails the oldest batch none of whose calls
+    /// were answered yet.
+    fn handle_batch_error(&mut self, error: JsonRpcError) -> Result<(), ClientError> {
+        while let Some(batch) = self.batches.pop_front() {
+            if !batch.iter().all(|id| self.pending.contains_key(id)) {
+                continue
+            }
+            for id in batch {
+                if let Some(PendingRequest { sender, .. }) = self.pending.remove(&id) {
+                    if !sender.is_canceled() {
+                        sender.send(Err(error.clone())).map_err(to_client_error)?;
+                    }
+                }
+            }
+            return Ok(())
+        }
+        warn!("Received an error response without id: {}", error);
+        Ok(())
+    }
+
     fn handle_notification(&mut self, params: Params<'_>) -> Result<(), ClientError> {
-        let id = params.subscription;
+        let id = match self.replay {
+            Some(ref replay) => replay.original_id(params.subscription),
+            None => params.subscription,
+        };
         if let Entry::Occupied(stream) = self.subscriptions.entry(id) {
             if let Err(err) = stream.get().unbounded_send(params.result.to_owned()) {
                 if err.is_disconnected() {
@@ -415,6 +735,93 @@ where
     }
 }
 
+if_not_wasm! {
+    impl WsServer<WsStream> {
+        /// Spawns the event loop, reconnecting whenever the connection drops
+        fn spawn_reconnecting(mut self, conn: ConnectionDetails, config: ReconnectConfig) {
+            let f = async move {
+                loop {
+                    if self.is_done() {
+                        debug!("work complete");
+                        break
+                    }
+                    match self.tick().await {
+                        Err(
+                            err @ (ClientError::UnexpectedClose |
+                            ClientError::WsClosed(_) |
+                            ClientError::TungsteniteError(_)),
+                        ) => {
+                            warn!("WS connection dropped: {}", err);
+                            if let Err(err) = self.reconnect(&conn, config).await {
+                                // dropping the server ends all pending requests and subscriptions
+                                error!("Failed to reconnect: {}", err);
+                                break
+                            }
+                        }
+                        Err(e) => {
+                            panic!("WS Server panic: {}", e);
+                        }
+                        _ => {}
+                    }
+                }
+            };
+
+            tokio::spawn(f);
+        }
+
+        /// Reconnects with exponential backoff and restores the session
+        async fn reconnect(
+            &mut self,
+            conn: &ConnectionDetails,
+            config: ReconnectConfig,
+        ) -> Result<(), ClientError> {
+            let mut backoff = config.initial_backoff;
+            let mut attempt = 0;
+            let ws = loop {
+                tokio::time::sleep(backoff).await;
+                match connect_async(conn.clone()).await {
+                    Ok((ws, _)) => break ws,
+                    Err(err) => {
+                        attempt += 1;
+                        if attempt >= config.max_retries {
+                            return Err(err.into())
+                        }
+                        warn!("Reconnect attempt {} failed: {}", attempt, err);
+                        backoff = (backoff * 2).min(config.max_backoff);
+                    }
+                }
+            };
+            debug!("reconnected after {} failed attempts", attempt);
+            self.ws = ws.fuse();
+            self.replay().await
+        }
+
+        /// Re-issues all subscriptions and resends all requests that are still pending
+        async fn replay(&mut self) -> Result<(), ClientError> {
+            let replay = match self.replay {
+                Some(ref mut replay) => replay,
+                None => return Ok(()),
+            };
+            // the new connection knows none of the previous subscription ids
+            replay.aliases.clear();
+            replay.resubscribing.clear();
+
+            let mut messages = Vec::with_capacity(replay.subscriptions.len() + self.pending.len());
+            for (original, params) in replay.subscriptions.iter() {
+                let id = replay.ids.fetch_add(1, Ordering::SeqCst);
+                replay.resubscribing.insert(id, *original);
+                messages.push(serde_json::to_string(&Request::new(id, "eth_subscribe", params))?);
+            }
+            messages.extend(self.pending.values().filter_map(|pending| pending.request.clone()));
+
+            for message in messages {
+                self.ws.send(Message::Text(message)).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
 // TrySendError is private :(
 fn to_client_error<T: Debug>(err: T) -> ClientError {
     ClientError::ChannelError(format!("{:?}", err))
@@ -475,6 +882,16 @@ pub enum ClientError {
     RequestError(#[from] http::Error),
 }
 
+impl ClientError {
+    /// Returns the error response of the node, if this is a JSON-RPC error
+    pub fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            ClientError::JsonRpcError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<ClientError> for ProviderError {
     fn from(src: ClientError) -> Self {
         ProviderError::JsonRpcClientError(Box::new(src))
@@ -531,4 +948,132 @@ mod tests {
         let resp = WsServer::new(ws, stream).handle_text(malformed_data).await;
         assert!(resp.is_err(), "Deserialization should not fail silently");
     }
+
+    #[tokio::test]
+    async fn rejected_batch_fails_all_calls() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            // reject the batch as a whole
+            ws.next().await.unwrap().unwrap();
+            let error = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"batch"},"id":null}"#;
+            ws.send(Message::Text(error.to_string())).await.unwrap();
+            // keep the connection open
+            ws.next().await;
+        });
+
+        let ws = Ws::connect(format!("ws://{}", addr)).await.unwrap();
+        let mut batch = BatchRequest::new();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+        batch.add_request("eth_chainId", ()).unwrap();
+
+        let responses = ws.request_batch(&batch).await.unwrap();
+        assert_eq!(responses.len(), 2);
+        for response in responses {
+            match response.unwrap_err().as_error_response() {
+                Some(error) => assert_eq!(error.code, -32600),
+                None => panic!("expected an error response"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnect_replays_requests_and_subscriptions() {
+        type Server = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+
+        async fn receive(ws: &mut Server) -> serde_json::Value {
+            match ws.next().await.unwrap().unwrap() {
+                Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                msg => panic!("unexpected message {:?}", msg),
+            }
+        }
+
+        async fn respond(ws: &mut Server, request: &serde_json::Value, result: &str) {
+            let response =
+                serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+        }
+
+        async fn notify(ws: &mut Server, subscription: &str, result: u64) {
+            let params = serde_json::json!({ "subscription": subscription, "result": result });
+            let notification = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_subscription",
+                "params": params,
+            });
+            ws.send(Message::Text(notification.to_string())).await.unwrap();
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let subscribe = receive(&mut ws).await;
+            assert_eq!(subscribe["method"], "eth_subscribe");
+            respond(&mut ws, &subscribe, "0x1").await;
+            notify(&mut ws, "0x1", 1).await;
+
+            // drop the connection without answering the request
+            let request = receive(&mut ws).await;
+            assert_eq!(request["method"], "eth_blockNumber");
+            ws.close(None).await.unwrap();
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            // the subscription is re-issued with a new request id
+            let resubscribe = receive(&mut ws).await;
+            assert_eq!(resubscribe["method"], "eth_subscribe");
+            assert_eq!(resubscribe["params"], subscribe["params"]);
+            assert_ne!(resubscribe["id"], subscribe["id"]);
+            respond(&mut ws, &resubscribe, "0x2").await;
+            // the pending request is resent as is
+            assert_eq!(receive(&mut ws).await, request);
+            respond(&mut ws, &request, "0x64").await;
+            notify(&mut ws, "0x2", 2).await;
+            ws
+        });
+
+        let config = ReconnectConfig {
+            initial_backoff: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let ws = Ws::connect_with_reconnects(format!("ws://{}", addr), config).await.unwrap();
+        let sub_id: U256 = ws.request("eth_subscribe", ["newHeads"]).await.unwrap();
+        assert_eq!(sub_id, U256::from(1));
+        let mut stream = ws.subscribe(sub_id).unwrap();
+        assert_eq!(stream.next().await.unwrap().get(), "1");
+
+        let block_num: U256 = ws.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block_num, U256::from(100));
+        // notifications of the re-issued subscription arrive under the original id
+        assert_eq!(stream.next().await.unwrap().get(), "2");
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn replay_maps_subscription_ids() {
+        let mut replay = Replay::new(Arc::new(AtomicU64::new(1)));
+
+        let request =
+            serde_json::to_string(&Request::new(1, "eth_subscribe", ["newHeads"])).unwrap();
+        let result = RawValue::from_string(r#""0x1""#.to_string()).unwrap();
+        replay.track_subscription(&request, &result);
+        assert_eq!(replay.subscriptions[&U256::from(1)].get(), r#"["newHeads"]"#);
+
+        // the subscription was re-issued after a reconnect and got a new id
+        replay.resubscribing.insert(2, U256::from(1));
+        let result = RawValue::from_string(r#""0xa""#.to_string()).unwrap();
+        replay.restore_subscription(U256::from(1), Ok(result));
+        assert_eq!(replay.original_id(U256::from(10)), U256::from(1));
+
+        let unsubscribe =
+            serde_json::to_string(&Request::new(3, "eth_unsubscribe", [U256::from(1)])).unwrap();
+        assert_eq!(
+            replay.rewrite_unsubscribe(unsubscribe),
+            r#"{"id":3,"jsonrpc":"2.0","method":"eth_unsubscribe","params":["0xa"]}"#
+        );
+    }
 }
@@ -1,7 +1,9 @@
 //! AWS KMS-based Signer
 
 use ethers_core::{
-    k256::ecdsa::{Error as K256Error, Signature as KSig, VerifyingKey},
+    k256::ecdsa::{
+        recoverable::Signature as RSig, Error as K256Error, Signature as KSig, VerifyingKey,
+    },
     types::{
         transaction::{eip2718::TypedTransaction, eip712::Eip712},
         Address, Signature as EthSig, H256,
@@ -15,7 +17,9 @@ use rusoto_kms::{
 use tracing::{debug, instrument, trace};
 
 mod utils;
-use utils::{apply_eip155, rsig_to_ethsig, verifying_key_to_address};
+use crate::kms_utils::{
+    apply_eip155, rsig_from_digest_bytes_trial_recovery, rsig_to_ethsig, verifying_key_to_address,
+};
 
 /// An ethers Signer that uses keys held in Amazon AWS KMS.
 ///
@@ -212,12 +216,18 @@ impl<'a> AwsSigner<'a> {
     ) -> Result<EthSig, AwsSignerError> {
         let sig = self.sign_digest(digest.into()).await?;
 
-        let sig = utils::rsig_from_digest_bytes_trial_recovery(&sig, digest.into(), &self.pubkey);
+        let sig = self.recover(&sig, digest.into())?;
 
         let mut sig = rsig_to_ethsig(&sig);
         apply_eip155(&mut sig, chain_id);
         Ok(sig)
     }
+
+    /// Adds the recovery id to a signature made by this signer's key
+    fn recover(&self, sig: &KSig, digest: [u8; 32]) -> Result<RSig, String> {
+        rsig_from_digest_bytes_trial_recovery(sig, digest, &self.pubkey)
+            .ok_or_else(|| "Signature not made by the signer's key".to_owned())
+    }
 }
 
 #[async_trait::async_trait]
@@ -255,7 +265,7 @@ impl<'a> super::Signer for AwsSigner<'a> {
             payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
 
         let sig = self.sign_digest(digest).await?;
-        let sig = utils::rsig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey);
+        let sig = self.recover(&sig, digest)?;
         let sig = rsig_to_ethsig(&sig);
 
         Ok(sig)
@@ -0,0 +1,239 @@
+//! An object-safe companion of the [`Signer`] trait, for picking the signer at runtime
+use crate::Signer;
+
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::{
+        eip2718::TypedTransaction,
+        eip712::{EIP712Domain, Eip712, Eip712Error},
+    },
+    Address, Signature,
+};
+use std::{error::Error, fmt::Debug};
+use thiserror::Error;
+
+/// Error thrown by a [`DynSigner`]
+#[derive(Error, Debug)]
+pub enum DynSignerError {
+    /// Error of the wrapped signer
+    #[error("{0}")]
+    Signer(Box<dyn Error + Send + Sync>),
+    /// Error encoding the typed data
+    #[error("error encoding eip712 struct: {0}")]
+    Eip712Error(String),
+}
+
+/// The hashes of EIP-712 typed data, which is all signers need to sign it.
+///
+/// This is how typed data is passed to a [`DynSigner`], as the [`Eip712`] trait is not
+/// object-safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Hashes {
+    domain: EIP712Domain,
+    domain_separator: [u8; 32],
+    struct_hash: [u8; 32],
+}
+
+impl Eip712Hashes {
+    /// Hashes the typed data
+    pub fn new<T: Eip712>(payload: &T) -> Result<Self, Eip712Error> {
+        let inner = |e: T::Error| Eip712Error::Inner(e.to_string());
+        Ok(Self {
+            domain: payload.domain().map_err(inner)?,
+            domain_separator: payload.domain_separator().map_err(inner)?,
+            struct_hash: payload.struct_hash().map_err(inner)?,
+        })
+    }
+}
+
+impl Eip712 for Eip712Hashes {
+    type Error = Eip712Error;
+
+    fn domain_separator(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.domain_separator)
+    }
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.clone())
+    }
+
+    /// The type hash is not part of the signed digest and is not kept
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Err(Eip712Error::Message("the type hash of hashed typed data is unknown".to_string()))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.struct_hash)
+    }
+}
+
+/// An object-safe version of the [`Signer`] trait, implemented for every [`Signer`].
+///
+/// `Box<dyn DynSigner>` implements [`Signer`] in turn, so signers of different types, e.g. a
+/// Ledger or a local wallet picked at runtime, can be used wherever a [`Signer`] is expected.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_signers::{DynSigner, LocalWallet, Signer};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let wallet: LocalWallet =
+///     "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse()?;
+/// let signer: Box<dyn DynSigner> = Box::new(wallet);
+/// let signer = signer.with_chain_id(5u64);
+/// let signature = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait DynSigner: Debug + Send + Sync {
+    /// Signs the hash of the provided message after prefixing it
+    async fn sign_message_dyn(&self, message: &[u8]) -> Result<Signature, DynSignerError>;
+
+    /// Signs the transaction
+    async fn sign_transaction_dyn(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<Signature, DynSignerError>;
+
+    /// Signs the hashed typed data according EIP-712
+    async fn sign_typed_data_dyn(
+        &self,
+        payload: &Eip712Hashes,
+    ) -> Result<Signature, DynSignerError>;
+
+    /// Returns the signer's Ethereum Address
+    fn address_dyn(&self) -> Address;
+
+    /// Returns the signer's chain id
+    fn chain_id_dyn(&self) -> u64;
+
+    /// Sets the signer's chain id
+    fn with_chain_id_dyn(self: Box<Self>, chain_id: u64) -> Box<dyn DynSigner>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S> DynSigner for S
+where
+    S: Signer + 'static,
+    S::Error: 'static,
+{
+    async fn sign_message_dyn(&self, message: &[u8]) -> Result<Signature, DynSignerError> {
+        self.sign_message(message).await.map_err(|e| DynSignerError::Signer(Box::new(e)))
+    }
+
+    async fn sign_transaction_dyn(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<Signature, DynSignerError> {
+        self.sign_transaction(tx).await.map_err(|e| DynSignerError::Signer(Box::new(e)))
+    }
+
+    async fn sign_typed_data_dyn(
+        &self,
+        payload: &Eip712Hashes,
+    ) -> Result<Signature, DynSignerError> {
+        self.sign_typed_data(payload).await.map_err(|e| DynSignerError::Signer(Box::new(e)))
+    }
+
+    fn address_dyn(&self) -> Address {
+        self.address()
+    }
+
+    fn chain_id_dyn(&self) -> u64 {
+        self.chain_id()
+    }
+
+    fn with_chain_id_dyn(self: Box<Self>, chain_id: u64) -> Box<dyn DynSigner> {
+        Box::new((*self).with_chain_id(chain_id))
+    }
+}
+
+// the methods are called on the trait object, as `Box<dyn DynSigner>` is a `DynSigner` itself
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Signer for Box<dyn DynSigner> {
+    type Error = DynSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        (**self).sign_message_dyn(message.as_ref()).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        (**self).sign_transaction_dyn(tx).await
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let hashes =
+            Eip712Hashes::new(payload).map_err(|e| DynSignerError::Eip712Error(e.to_string()))?;
+        (**self).sign_typed_data_dyn(&hashes).await
+    }
+
+    fn address(&self) -> Address {
+        (**self).address_dyn()
+    }
+
+    fn chain_id(&self) -> u64 {
+        (**self).chain_id_dyn()
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        <dyn DynSigner>::with_chain_id_dyn(self, chain_id.into())
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::LocalWallet;
+    use ethers_core::types::{transaction::eip712::TypedData, TransactionRequest};
+
+    #[tokio::test]
+    async fn boxed_signer_signs_like_the_signer() {
+        let wallet: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let wallet = wallet.with_chain_id(5u64);
+        let signer: Box<dyn DynSigner> = Box::new(wallet.clone()).with_chain_id_dyn(5);
+        assert_eq!(signer.address(), wallet.address());
+        assert_eq!(Signer::chain_id(&signer), 5);
+
+        assert_eq!(
+            signer.sign_message("hello").await.unwrap(),
+            wallet.sign_message("hello").await.unwrap()
+        );
+
+        let tx = TransactionRequest::pay(Address::zero(), 100u64).nonce(0u64).into();
+        assert_eq!(
+            signer.sign_transaction(&tx).await.unwrap(),
+            wallet.sign_transaction(&tx).await.unwrap()
+        );
+
+        let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [{ "name": "name", "type": "string" }],
+                "Mail": [{ "name": "contents", "type": "string" }]
+            },
+            "primaryType": "Mail",
+            "domain": { "name": "Ether Mail" },
+            "message": { "contents": "Hello, Bob!" }
+        }))
+        .unwrap();
+        assert_eq!(
+            signer.sign_typed_data(&typed_data).await.unwrap(),
+            wallet.sign_typed_data(&typed_data).await.unwrap()
+        );
+
+        let signer = signer.with_chain_id(1u64);
+        assert_eq!(Signer::chain_id(&signer), 1);
+    }
+}
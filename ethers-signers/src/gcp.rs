@@ -0,0 +1,347 @@
+//! Google Cloud KMS-based Signer
+
+use ethers_core::{
+    k256::ecdsa::{
+        recoverable::Signature as RSig, Error as K256Error, Signature as KSig, VerifyingKey,
+    },
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature as EthSig,
+    },
+    utils::hash_message,
+};
+use reqwest::{Client, IntoUrl, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+
+use crate::kms_utils::{
+    apply_eip155, decode_der_signature, decode_pem_pubkey, rsig_from_digest_bytes_trial_recovery,
+    rsig_to_ethsig, verifying_key_to_address,
+};
+
+/// The endpoint of the Cloud KMS API
+pub const GCP_KMS_ENDPOINT: &str = "https://cloudkms.googleapis.com";
+
+/// The algorithm of Cloud KMS keys for Ethereum signatures
+const SECP256K1_ALGORITHM: &str = "EC_SIGN_SECP256K1_SHA256";
+
+/// An ethers Signer that uses keys held in Google Cloud KMS.
+///
+/// The GCP Signer passes signing requests to the Cloud KMS REST API. Keys are identified by the
+/// resource name of a key version with the `EC_SIGN_SECP256K1_SHA256` algorithm, like
+/// `projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1`.
+///
+/// Requests are authenticated with an OAuth2 access token, which has to be replaced with
+/// [`GcpKmsSigner::with_access_token`] before it expires. The `reqwest` client must be built
+/// with TLS support, e.g. by enabling the `rustls-tls` feature of `reqwest`.
+///
+/// Because the public key is unknown, we retrieve it on instantiation of the
+/// signer. This means that the new function is `async` and must be called
+/// within some runtime.
+///
+/// ```no_run
+/// use ethers_signers::{GcpKmsSigner, Signer};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let key_name = "projects/my-project/locations/global/keyRings/my-ring/cryptoKeys/my-key/cryptoKeyVersions/1";
+/// let access_token = std::env::var("GCP_ACCESS_TOKEN")?;
+/// let signer = GcpKmsSigner::new(reqwest::Client::new(), access_token, key_name, 1).await?;
+/// let sig = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GcpKmsSigner {
+    client: Client,
+    endpoint: Url,
+    access_token: String,
+    chain_id: u64,
+    key_name: String,
+    pubkey: VerifyingKey,
+    address: Address,
+}
+
+impl std::fmt::Debug for GcpKmsSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcpKmsSigner")
+            .field("endpoint", &self.endpoint.as_str())
+            .field("key_name", &self.key_name)
+            .field("chain_id", &self.chain_id)
+            .field("pubkey", &hex::encode(self.pubkey.to_bytes()))
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for GcpKmsSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GcpKmsSigner {{ address: {}, chain_id: {}, key_name: {} }}",
+            self.address, self.chain_id, self.key_name
+        )
+    }
+}
+
+/// Errors produced by the GcpKmsSigner
+#[derive(thiserror::Error, Debug)]
+pub enum GcpKmsSignerError {
+    /// Error of the HTTP client
+    #[error(transparent)]
+    ReqwestError(#[from] reqwest::Error),
+    /// Error returned by the Cloud KMS API
+    #[error("Cloud KMS request failed with status {status}: {message}")]
+    ApiError {
+        /// The HTTP status of the response
+        status: u16,
+        /// The body of the response
+        message: String,
+    },
+    /// Thrown if the key is not a secp256k1 signing key
+    #[error("unsupported key algorithm {0}, expected {}", SECP256K1_ALGORITHM)]
+    UnsupportedAlgorithm(String),
+    #[error("{0}")]
+    K256(#[from] K256Error),
+    #[error("{0}")]
+    Spki(spki::Error),
+    #[error(transparent)]
+    /// Error when decoding base64
+    Base64Error(#[from] base64::DecodeError),
+    #[error("{0}")]
+    Other(String),
+    /// Error type from Eip712Error message
+    #[error("error encoding eip712 struct: {0:?}")]
+    Eip712Error(String),
+}
+
+impl From<String> for GcpKmsSignerError {
+    fn from(s: String) -> Self {
+        Self::Other(s)
+    }
+}
+
+impl From<spki::Error> for GcpKmsSignerError {
+    fn from(e: spki::Error) -> Self {
+        Self::Spki(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+impl GcpKmsSigner {
+    /// Instantiate a new signer for the key version `key_name`, authenticated with the OAuth2
+    /// `access_token`.
+    ///
+    /// This function retrieves the public key from Cloud KMS and calculates the
+    /// Ethereum address. It is therefore `async`.
+    pub async fn new<T, K>(
+        client: Client,
+        access_token: T,
+        key_name: K,
+        chain_id: u64,
+    ) -> Result<Self, GcpKmsSignerError>
+    where
+        T: Into<String>,
+        K: Into<String>,
+    {
+        Self::new_with_endpoint(client, GCP_KMS_ENDPOINT, access_token, key_name, chain_id).await
+    }
+
+    /// Instantiate a new signer which sends its requests to `endpoint` instead of
+    /// [`GCP_KMS_ENDPOINT`], e.g. to a regional endpoint or a proxy
+    pub async fn new_with_endpoint<U, T, K>(
+        client: Client,
+        endpoint: U,
+        access_token: T,
+        key_name: K,
+        chain_id: u64,
+    ) -> Result<Self, GcpKmsSignerError>
+    where
+        U: IntoUrl,
+        T: Into<String>,
+        K: Into<String>,
+    {
+        let endpoint = endpoint.into_url()?;
+        let access_token = access_token.into();
+        let key_name = key_name.into();
+
+        let url = format!("{}/v1/{}/publicKey", endpoint.as_str().trim_end_matches('/'), key_name);
+        let resp: PublicKeyResponse =
+            parse_response(client.get(url).bearer_auth(&access_token).send().await?).await?;
+        if resp.algorithm != SECP256K1_ALGORITHM {
+            return Err(GcpKmsSignerError::UnsupportedAlgorithm(resp.algorithm))
+        }
+        let pubkey = decode_pem_pubkey::<GcpKmsSignerError>(&resp.pem)?;
+        let address = verifying_key_to_address(&pubkey);
+
+        Ok(Self { client, endpoint, access_token, chain_id, key_name, pubkey, address })
+    }
+
+    /// Sets the OAuth2 access token of the requests, e.g. after the previous one expired
+    #[must_use]
+    pub fn with_access_token<T: Into<String>>(mut self, access_token: T) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    /// Returns the pubkey of this signer's key
+    pub fn pubkey(&self) -> &VerifyingKey {
+        &self.pubkey
+    }
+
+    /// Sign a digest with this signer's key
+    pub async fn sign_digest(&self, digest: [u8; 32]) -> Result<KSig, GcpKmsSignerError> {
+        let url = format!(
+            "{}/v1/{}:asymmetricSign",
+            self.endpoint.as_str().trim_end_matches('/'),
+            self.key_name
+        );
+        let body = json!({ "digest": { "sha256": base64::encode(digest) } });
+        let resp: AsymmetricSignResponse = parse_response(
+            self.client.post(url).bearer_auth(&self.access_token).json(&body).send().await?,
+        )
+        .await?;
+        Ok(decode_der_signature(&base64::decode(resp.signature)?)?)
+    }
+
+    /// Sign a digest with this signer's key and add the recovery id as `v` value
+    async fn sign_hash(&self, digest: [u8; 32]) -> Result<EthSig, GcpKmsSignerError> {
+        let sig = self.sign_digest(digest).await?;
+        let sig = self.recover(&sig, digest)?;
+        Ok(rsig_to_ethsig(&sig))
+    }
+
+    /// Adds the recovery id to a signature made by this signer's key
+    fn recover(&self, sig: &KSig, digest: [u8; 32]) -> Result<RSig, GcpKmsSignerError> {
+        rsig_from_digest_bytes_trial_recovery(sig, digest, &self.pubkey).ok_or_else(|| {
+            GcpKmsSignerError::from("Signature not made by the signer's key".to_owned())
+        })
+    }
+}
+
+/// Deserializes a successful response, or returns the body of an error response
+async fn parse_response<T: DeserializeOwned>(
+    resp: reqwest::Response,
+) -> Result<T, GcpKmsSignerError> {
+    let status = resp.status();
+    if !status.is_success() {
+        let message = resp.text().await?;
+        return Err(GcpKmsSignerError::ApiError { status: status.as_u16(), message })
+    }
+    Ok(resp.json().await?)
+}
+
+#[async_trait::async_trait]
+impl super::Signer for GcpKmsSigner {
+    type Error = GcpKmsSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<EthSig, Self::Error> {
+        let message_hash = hash_message(message.as_ref());
+        self.sign_hash(message_hash.into()).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<EthSig, Self::Error> {
+        let mut tx_with_chain = tx.clone();
+        let chain_id = tx_with_chain.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx_with_chain.set_chain_id(chain_id);
+
+        let sighash = tx_with_chain.sighash();
+        let mut sig = self.sign_hash(sighash.into()).await?;
+        apply_eip155(&mut sig, chain_id);
+        Ok(sig)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<EthSig, Self::Error> {
+        let digest =
+            payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
+
+        self.sign_hash(digest).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the signer's chain id
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets the signer's chain id
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kms_utils::mock::{der_signature, pem_pubkey, serve},
+        LocalWallet, Signer,
+    };
+    use ethers_core::types::TransactionRequest;
+
+    const KEY_NAME: &str =
+        "projects/test/locations/global/keyRings/ring/cryptoKeys/key/cryptoKeyVersions/1";
+
+    fn mock_kms(wallet: LocalWallet, algorithm: &'static str) -> String {
+        serve(move |req| {
+            assert_eq!(req.header("authorization"), Some("Bearer token"));
+            match (req.method.as_str(), req.path.as_str()) {
+                ("GET", path) if path == format!("/v1/{}/publicKey", KEY_NAME) => {
+                    json!({ "pem": pem_pubkey(&wallet), "algorithm": algorithm })
+                }
+                ("POST", path) if path == format!("/v1/{}:asymmetricSign", KEY_NAME) => {
+                    let digest = req.body["digest"]["sha256"].as_str().unwrap();
+                    let digest = base64::decode(digest).unwrap();
+                    json!({ "signature": base64::encode(der_signature(&wallet, &digest)) })
+                }
+                (method, path) => panic!("unexpected request {} {}", method, path),
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn signs_like_the_key() {
+        let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(5u64);
+        let endpoint = mock_kms(wallet.clone(), SECP256K1_ALGORITHM);
+        let signer = GcpKmsSigner::new_with_endpoint(Client::new(), endpoint, "token", KEY_NAME, 5)
+            .await
+            .unwrap();
+        assert_eq!(signer.address(), wallet.address());
+
+        let sig = signer.sign_message("hello").await.unwrap();
+        assert_eq!(sig, wallet.sign_message("hello").await.unwrap());
+
+        let tx = TransactionRequest::pay(Address::zero(), 100u64).nonce(0u64).into();
+        let sig = signer.sign_transaction(&tx).await.unwrap();
+        assert_eq!(sig, wallet.sign_transaction(&tx).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn rejects_other_algorithms() {
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        let endpoint = mock_kms(wallet, "EC_SIGN_P256_SHA256");
+        let err = GcpKmsSigner::new_with_endpoint(Client::new(), endpoint, "token", KEY_NAME, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GcpKmsSignerError::UnsupportedAlgorithm(_)));
+    }
+}
@@ -0,0 +1,482 @@
+//! Management of a directory of [Web3 Secret Storage](https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition)
+//! keystores, like the `keystore` directory of geth
+use crate::{LocalWallet, MnemonicBuilder, Signer, WalletError};
+
+use coins_bip39::Wordlist;
+use ctr::cipher::{NewCipher, StreamCipher};
+use eth_keystore::{CipherparamsJson, CryptoJson, KdfType, KdfparamsType, KeystoreError};
+use ethers_core::{
+    k256::ecdsa::SigningKey,
+    rand::{CryptoRng, Rng},
+    types::Address,
+    utils::keccak256,
+};
+use hmac::Hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// The key derivation function used to derive the encryption key from the password.
+///
+/// The cost of the function is the cost of brute-forcing the password, and of every unlock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    /// [Scrypt](https://tools.ietf.org/html/rfc7914.html) with a cost of `n = 2^log_n`
+    Scrypt {
+        /// The log2 of the CPU/memory cost
+        log_n: u8,
+        /// The block size
+        r: u32,
+        /// The parallelization
+        p: u32,
+    },
+    /// PBKDF2 with HMAC-SHA256
+    Pbkdf2 {
+        /// The number of iterations
+        c: u32,
+    },
+}
+
+impl Kdf {
+    /// The scrypt parameters of geth, `n = 2^18`
+    pub fn scrypt_standard() -> Self {
+        Kdf::Scrypt { log_n: 18, r: 8, p: 1 }
+    }
+
+    /// The parameters of `Wallet::new_keystore`, `n = 2^13`
+    pub fn scrypt_light() -> Self {
+        Kdf::Scrypt { log_n: 13, r: 8, p: 1 }
+    }
+
+    /// PBKDF2 with `2^18` iterations
+    pub fn pbkdf2() -> Self {
+        Kdf::Pbkdf2 { c: 262_144 }
+    }
+
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<[u8; 32], KeystoreError> {
+        let mut key = [0u8; 32];
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p)?;
+                scrypt::scrypt(password, salt, &params, &mut key)?;
+            }
+            Kdf::Pbkdf2 { c } => pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, c, &mut key),
+        }
+        Ok(key)
+    }
+
+    fn params(&self, salt: Vec<u8>) -> (KdfType, KdfparamsType) {
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                (KdfType::Scrypt, KdfparamsType::Scrypt { dklen: 32, n: 1 << log_n, p, r, salt })
+            }
+            Kdf::Pbkdf2 { c } => (
+                KdfType::Pbkdf2,
+                KdfparamsType::Pbkdf2 { c, dklen: 32, prf: "hmac-sha256".to_string(), salt },
+            ),
+        }
+    }
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::scrypt_light()
+    }
+}
+
+/// Error thrown by the [`KeystoreManager`]
+#[derive(Error, Debug)]
+pub enum KeystoreManagerError {
+    /// Error propagated from the eth-keystore crate, e.g. on a wrong password
+    #[error(transparent)]
+    KeystoreError(#[from] KeystoreError),
+    /// Error propagated from the wallet, e.g. on an invalid private key or mnemonic
+    #[error(transparent)]
+    WalletError(#[from] WalletError),
+    /// Error propagated by IO operations
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    /// Error (de)serializing a keystore
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    /// Thrown if there is no keystore for the address
+    #[error("no keystore for account {0:?}")]
+    AccountNotFound(Address),
+    /// Thrown when importing an account that already has a keystore
+    #[error("account {0:?} already exists")]
+    AccountExists(Address),
+}
+
+/// A keystore file as written by geth, with the address of the account
+#[derive(Debug, Serialize, Deserialize)]
+struct KeystoreFile {
+    /// The address without `0x` prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    crypto: CryptoJson,
+    id: String,
+    version: u8,
+}
+
+impl KeystoreFile {
+    fn address(&self) -> Option<Address> {
+        let address = self.address.as_deref()?;
+        let bytes = hex::decode(address.strip_prefix("0x").unwrap_or(address)).ok()?;
+        (bytes.len() == 20).then(|| Address::from_slice(&bytes))
+    }
+}
+
+#[derive(Debug)]
+struct Unlocked {
+    wallet: LocalWallet,
+    until: Option<Instant>,
+}
+
+/// Manages a directory of encrypted JSON keystores, one per account.
+///
+/// Accounts are looked up by the `address` field of the keystores, so keystores written by geth
+/// can be used as well. New keystores are encrypted with the [`Kdf`] of the manager, decryption
+/// supports scrypt and PBKDF2.
+///
+/// Wallets can be unlocked for a limited time, during which they are available without the
+/// password.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_signers::{Kdf, KeystoreManager, Signer};
+/// use std::time::Duration;
+///
+/// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let keystore = KeystoreManager::open("./keystore")?.kdf(Kdf::scrypt_standard());
+/// let wallet = keystore.new_account(&mut rand::thread_rng(), "password")?;
+/// assert_eq!(keystore.accounts()?, vec![wallet.address()]);
+///
+/// keystore.unlock(wallet.address(), "password", Some(Duration::from_secs(300)))?;
+/// let wallet = keystore.wallet(wallet.address()).expect("unlocked");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct KeystoreManager {
+    dir: PathBuf,
+    kdf: Kdf,
+    unlocked: Mutex<HashMap<Address, Unlocked>>,
+}
+
+impl KeystoreManager {
+    /// Manages the keystores in `dir`, which is created if it does not exist
+    pub fn open<P: Into<PathBuf>>(dir: P) -> Result<Self, KeystoreManagerError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, kdf: Kdf::default(), unlocked: Mutex::new(HashMap::new()) })
+    }
+
+    /// Sets the key derivation function used to encrypt new keystores
+    #[must_use]
+    pub fn kdf(mut self, kdf: Kdf) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Returns the directory of the keystores
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the addresses of all accounts in the directory, sorted
+    pub fn accounts(&self) -> Result<Vec<Address>, KeystoreManagerError> {
+        let mut accounts =
+            self.keystores()?.into_iter().map(|(address, _)| address).collect::<Vec<_>>();
+        accounts.sort();
+        accounts.dedup();
+        Ok(accounts)
+    }
+
+    /// Returns the path of the keystore of the account
+    pub fn path(&self, address: Address) -> Result<PathBuf, KeystoreManagerError> {
+        self.keystores()?
+            .into_iter()
+            .find(|(addr, _)| *addr == address)
+            .map(|(_, path)| path)
+            .ok_or(KeystoreManagerError::AccountNotFound(address))
+    }
+
+    /// Creates a new random account, encrypted with the `password`
+    pub fn new_account<R, S>(
+        &self,
+        rng: &mut R,
+        password: S,
+    ) -> Result<LocalWallet, KeystoreManagerError>
+    where
+        R: Rng + CryptoRng,
+        S: AsRef<[u8]>,
+    {
+        let wallet = LocalWallet::new(rng);
+        self.import_wallet(&wallet, password)?;
+        Ok(wallet)
+    }
+
+    /// Imports the private key, encrypted with the `password`. Returns the address of the account.
+    pub fn import_private_key<K, S>(
+        &self,
+        private_key: K,
+        password: S,
+    ) -> Result<Address, KeystoreManagerError>
+    where
+        K: AsRef<[u8]>,
+        S: AsRef<[u8]>,
+    {
+        let signer = SigningKey::from_bytes(private_key.as_ref()).map_err(WalletError::from)?;
+        let wallet = LocalWallet::from(signer);
+        self.import_wallet(&wallet, password)?;
+        Ok(wallet.address())
+    }
+
+    /// Imports the account derived from the mnemonic, encrypted with the `password`. Returns the
+    /// address of the account.
+    pub fn import_mnemonic<W, S>(
+        &self,
+        mnemonic: &MnemonicBuilder<W>,
+        password: S,
+    ) -> Result<Address, KeystoreManagerError>
+    where
+        W: Wordlist,
+        S: AsRef<[u8]>,
+    {
+        let wallet = mnemonic.build()?;
+        self.import_wallet(&wallet, password)?;
+        Ok(wallet.address())
+    }
+
+    /// Imports the wallet, encrypted with the `password`
+    pub fn import_wallet<S: AsRef<[u8]>>(
+        &self,
+        wallet: &LocalWallet,
+        password: S,
+    ) -> Result<(), KeystoreManagerError> {
+        let address = wallet.address();
+        if self.path(address).is_ok() {
+            return Err(KeystoreManagerError::AccountExists(address))
+        }
+        let keystore = self.encrypt(wallet, password.as_ref())?;
+        write_atomic(&self.dir.join(hex::encode(address)), &keystore)
+    }
+
+    /// Decrypts the keystore of the account
+    pub fn decrypt<S: AsRef<[u8]>>(
+        &self,
+        address: Address,
+        password: S,
+    ) -> Result<LocalWallet, KeystoreManagerError> {
+        let path = self.path(address)?;
+        let secret = eth_keystore::decrypt_key(path, password)?;
+        let wallet = LocalWallet::from(SigningKey::from_bytes(&secret).map_err(WalletError::from)?);
+        // the address of the keystore is not authenticated
+        if wallet.address() != address {
+            return Err(KeystoreManagerError::AccountNotFound(address))
+        }
+        Ok(wallet)
+    }
+
+    /// Re-encrypts the keystore of the account with the `new_password`, and the key derivation
+    /// function of the manager
+    pub fn change_password<S, T>(
+        &self,
+        address: Address,
+        old_password: S,
+        new_password: T,
+    ) -> Result<(), KeystoreManagerError>
+    where
+        S: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        let wallet = self.decrypt(address, old_password)?;
+        let keystore = self.encrypt(&wallet, new_password.as_ref())?;
+        write_atomic(&self.path(address)?, &keystore)
+    }
+
+    /// Returns the encrypted keystore of the account as JSON, e.g. to import it into another
+    /// wallet
+    pub fn export(&self, address: Address) -> Result<String, KeystoreManagerError> {
+        Ok(fs::read_to_string(self.path(address)?)?)
+    }
+
+    /// Removes the keystore of the account after checking the `password`
+    pub fn delete<S: AsRef<[u8]>>(
+        &self,
+        address: Address,
+        password: S,
+    ) -> Result<(), KeystoreManagerError> {
+        self.decrypt(address, password)?;
+        self.lock(address);
+        Ok(fs::remove_file(self.path(address)?)?)
+    }
+
+    /// Decrypts the keystore of the account and keeps the wallet unlocked for the `duration`, or
+    /// until it is locked if `None`
+    pub fn unlock<S: AsRef<[u8]>>(
+        &self,
+        address: Address,
+        password: S,
+        duration: Option<Duration>,
+    ) -> Result<LocalWallet, KeystoreManagerError> {
+        let wallet = self.decrypt(address, password)?;
+        let until = duration.map(|duration| Instant::now() + duration);
+        self.unlocked.lock().unwrap().insert(address, Unlocked { wallet: wallet.clone(), until });
+        Ok(wallet)
+    }
+
+    /// Locks the wallet of the account
+    pub fn lock(&self, address: Address) {
+        self.unlocked.lock().unwrap().remove(&address);
+    }
+
+    /// Returns the wallet of the account if it is unlocked
+    pub fn wallet(&self, address: Address) -> Option<LocalWallet> {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        let now = Instant::now();
+        unlocked.retain(|_, unlocked| unlocked.until.map(|until| until > now).unwrap_or(true));
+        unlocked.get(&address).map(|unlocked| unlocked.wallet.clone())
+    }
+
+    /// Returns the address and the path of every keystore in the directory, skipping files that
+    /// are not keystores
+    fn keystores(&self) -> Result<Vec<(Address, PathBuf)>, KeystoreManagerError> {
+        let mut keystores = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let hidden =
+                path.file_name().and_then(|name| name.to_str()).map(|name| name.starts_with('.'));
+            if !path.is_file() || hidden.unwrap_or(true) {
+                continue
+            }
+            let keystore = fs::read(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_slice::<KeystoreFile>(&contents).ok());
+            if let Some(address) = keystore.as_ref().and_then(KeystoreFile::address) {
+                keystores.push((address, path));
+            }
+        }
+        Ok(keystores)
+    }
+
+    /// Encrypts the private key of the wallet with AES-128-CTR and a key derived from the
+    /// `password`
+    fn encrypt(
+        &self,
+        wallet: &LocalWallet,
+        password: &[u8],
+    ) -> Result<KeystoreFile, KeystoreManagerError> {
+        let mut rng = ethers_core::rand::thread_rng();
+        let salt = rng.gen::<[u8; 32]>().to_vec();
+        let iv = rng.gen::<[u8; 16]>().to_vec();
+
+        let key = self.kdf.derive_key(password, &salt)?;
+        let mut ciphertext = wallet.signer().to_bytes().to_vec();
+        Aes128Ctr::new(key[..16].into(), iv[..].into()).apply_keystream(&mut ciphertext);
+        let mac = keccak256([&key[16..], &ciphertext[..]].concat());
+
+        let (kdf, kdfparams) = self.kdf.params(salt);
+        Ok(KeystoreFile {
+            address: Some(hex::encode(wallet.address())),
+            crypto: CryptoJson {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherparamsJson { iv },
+                ciphertext,
+                kdf,
+                kdfparams,
+                mac: mac.to_vec(),
+            },
+            id: uuid::Uuid::new_v4().to_string(),
+            version: 3,
+        })
+    }
+}
+
+/// Writes the keystore to a temporary file first, so the keystore is never partially written
+fn write_atomic(path: &Path, keystore: &KeystoreFile) -> Result<(), KeystoreManagerError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(keystore)?)?;
+    Ok(fs::rename(tmp, path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coins_bip39::English;
+    use tempfile::tempdir;
+
+    #[test]
+    fn manages_accounts() {
+        let dir = tempdir().unwrap();
+        let keystore = KeystoreManager::open(dir.path()).unwrap();
+        let mut rng = rand::thread_rng();
+
+        let wallet = keystore.new_account(&mut rng, "password").unwrap();
+        let key = [1u8; 32];
+        let imported = keystore.import_private_key(key, "password").unwrap();
+        let mnemonic = MnemonicBuilder::<English>::default().phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        );
+        let derived = keystore.import_mnemonic(&mnemonic, "password").unwrap();
+
+        let mut accounts = vec![wallet.address(), imported, derived];
+        accounts.sort();
+        assert_eq!(keystore.accounts().unwrap(), accounts);
+        assert!(matches!(
+            keystore.import_private_key(key, "other").unwrap_err(),
+            KeystoreManagerError::AccountExists(_)
+        ));
+
+        // readable by `Wallet::decrypt_keystore`
+        let path = keystore.path(imported).unwrap();
+        let decrypted = LocalWallet::decrypt_keystore(&path, "password").unwrap();
+        assert_eq!(decrypted.address(), imported);
+        assert!(keystore.export(imported).unwrap().contains(&hex::encode(imported)));
+
+        keystore.delete(derived, "password").unwrap();
+        assert_eq!(keystore.accounts().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn changes_password_and_kdf() {
+        let dir = tempdir().unwrap();
+        let keystore = KeystoreManager::open(dir.path()).unwrap();
+        let address = keystore.import_private_key([1u8; 32], "old").unwrap();
+
+        let keystore = keystore.kdf(Kdf::Pbkdf2 { c: 1024 });
+        keystore.change_password(address, "old", "new").unwrap();
+        assert!(keystore.export(address).unwrap().contains("pbkdf2"));
+        assert!(matches!(
+            keystore.decrypt(address, "old").unwrap_err(),
+            KeystoreManagerError::KeystoreError(KeystoreError::MacMismatch)
+        ));
+        assert_eq!(keystore.decrypt(address, "new").unwrap().address(), address);
+    }
+
+    #[test]
+    fn unlocks_for_a_duration() {
+        let dir = tempdir().unwrap();
+        let keystore = KeystoreManager::open(dir.path()).unwrap();
+        let address = keystore.import_private_key([1u8; 32], "password").unwrap();
+        assert!(keystore.wallet(address).is_none());
+
+        keystore.unlock(address, "password", None).unwrap();
+        assert_eq!(keystore.wallet(address).unwrap().address(), address);
+        keystore.lock(address);
+        assert!(keystore.wallet(address).is_none());
+
+        keystore.unlock(address, "password", Some(Duration::ZERO)).unwrap();
+        assert!(keystore.wallet(address).is_none());
+    }
+}
@@ -0,0 +1,195 @@
+//! Key and signature conversions shared by the signers whose keys are held by a remote key
+//! management service, which return DER encoded public keys and signatures.
+//!
+//! These utils are NOT meant for general usage. They are ONLY meant for use
+//! within this crate.
+
+use std::convert::TryFrom;
+
+use ethers_core::{
+    k256::{
+        ecdsa::{
+            recoverable::{Id, Signature as RSig},
+            Error as K256Error, Signature as KSig, VerifyingKey,
+        },
+        elliptic_curve::sec1::ToEncodedPoint,
+        FieldBytes,
+    },
+    types::{Address, Signature as EthSig, U256},
+    utils::keccak256,
+};
+
+/// Converts a recoverable signature to an ethers signature
+pub(crate) fn rsig_to_ethsig(sig: &RSig) -> EthSig {
+    let v: u8 = sig.recovery_id().into();
+    let v = (v + 27) as u64;
+    let r_bytes: FieldBytes = sig.r().into();
+    let s_bytes: FieldBytes = sig.s().into();
+    let r = U256::from_big_endian(r_bytes.as_slice());
+    let s = U256::from_big_endian(s_bytes.as_slice());
+    EthSig { r, s, v }
+}
+
+/// Makes a trial recovery to check whether an RSig corresponds to a known
+/// `VerifyingKey`
+fn check_candidate(sig: &RSig, digest: [u8; 32], vk: &VerifyingKey) -> bool {
+    if let Ok(key) = sig.recover_verifying_key_from_digest_bytes(digest.as_ref().into()) {
+        key == *vk
+    } else {
+        false
+    }
+}
+
+/// Recover an rsig from a signature under a known key by trial/error. Returns `None` if the
+/// signature was not made by the key.
+pub(crate) fn rsig_from_digest_bytes_trial_recovery(
+    sig: &KSig,
+    digest: [u8; 32],
+    vk: &VerifyingKey,
+) -> Option<RSig> {
+    (0..2)
+        .filter_map(|id| RSig::new(sig, Id::new(id).unwrap()).ok())
+        .find(|candidate| check_candidate(candidate, digest, vk))
+}
+
+/// Modify the v value of a signature to conform to eip155
+pub(crate) fn apply_eip155(sig: &mut EthSig, chain_id: u64) {
+    let v = (chain_id * 2 + 35) + ((sig.v - 1) % 2);
+    sig.v = v;
+}
+
+/// Convert a verifying key to an ethereum address
+pub(crate) fn verifying_key_to_address(key: &VerifyingKey) -> Address {
+    // false for uncompressed
+    let uncompressed_pub_key = key.to_encoded_point(false);
+    let public_key = uncompressed_pub_key.to_bytes();
+    debug_assert_eq!(public_key[0], 0x04);
+    let hash = keccak256(&public_key[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+/// Decode a DER encoded `SubjectPublicKeyInfo` holding a secp256k1 key
+pub(crate) fn decode_spki_pubkey<E>(der: &[u8]) -> Result<VerifyingKey, E>
+where
+    E: From<spki::Error> + From<K256Error>,
+{
+    let spk = spki::SubjectPublicKeyInfo::try_from(der)?;
+    Ok(VerifyingKey::from_sec1_bytes(spk.subject_public_key)?)
+}
+
+/// Decode a PEM encoded `SubjectPublicKeyInfo` holding a secp256k1 key
+#[cfg(any(feature = "gcp", feature = "vault"))]
+pub(crate) fn decode_pem_pubkey<E>(pem: &str) -> Result<VerifyingKey, E>
+where
+    E: From<spki::Error> + From<K256Error> + From<base64::DecodeError>,
+{
+    let body = pem.lines().filter(|line| !line.starts_with("-----")).collect::<String>();
+    decode_spki_pubkey(&base64::decode(body.trim())?)
+}
+
+/// Decode a DER encoded signature, normalizing its `s` value
+pub(crate) fn decode_der_signature(der: &[u8]) -> Result<KSig, K256Error> {
+    let sig = KSig::from_der(der)?;
+    Ok(sig.normalize_s().unwrap_or(sig))
+}
+
+#[cfg(test)]
+#[cfg(any(feature = "gcp", feature = "vault"))]
+pub(crate) mod mock {
+    //! A minimal HTTP server for testing the signers against mocks of the remote APIs
+    use crate::LocalWallet;
+    use ethers_core::{
+        k256::{ecdsa::Signature as KSig, elliptic_curve::sec1::ToEncodedPoint},
+        types::H256,
+    };
+    use std::{
+        convert::TryFrom,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    /// The DER prefix of the `SubjectPublicKeyInfo` of an uncompressed secp256k1 key
+    const SPKI_PREFIX: &str = "3056301006072a8648ce3d020106052b8104000a034200";
+
+    /// Returns the PEM encoded public key of the wallet
+    pub(crate) fn pem_pubkey(wallet: &LocalWallet) -> String {
+        let point = wallet.signer().verifying_key().to_encoded_point(false);
+        let der = [hex::decode(SPKI_PREFIX).unwrap(), point.as_bytes().to_vec()].concat();
+        format!("-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n", base64::encode(der))
+    }
+
+    /// Signs the digest and returns the DER encoded signature, like a key management service
+    pub(crate) fn der_signature(wallet: &LocalWallet, digest: &[u8]) -> Vec<u8> {
+        let sig = wallet.sign_hash(H256::from_slice(digest)).to_vec();
+        KSig::try_from(&sig[..64]).unwrap().to_der().as_bytes().to_vec()
+    }
+
+    /// A request received by the mock server
+    #[derive(Debug)]
+    pub(crate) struct Request {
+        pub(crate) method: String,
+        pub(crate) path: String,
+        pub(crate) headers: Vec<(String, String)>,
+        pub(crate) body: serde_json::Value,
+    }
+
+    impl Request {
+        pub(crate) fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// Serves the responses of the `handler` on a local port and returns the URL of the server
+    pub(crate) fn serve<F>(handler: F) -> String
+    where
+        F: Fn(Request) -> serde_json::Value + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    match line.trim_end().split_once(':') {
+                        Some((key, value)) => {
+                            headers.push((key.trim().to_string(), value.trim().to_string()))
+                        }
+                        None => break,
+                    }
+                }
+                let len = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, value)| value.parse().unwrap())
+                    .unwrap_or(0);
+                let mut body = vec![0u8; len];
+                reader.read_exact(&mut body).unwrap();
+                let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+
+                let response = handler(Request { method, path, headers, body }).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+}
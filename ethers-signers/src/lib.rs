@@ -5,6 +5,14 @@
 mod wallet;
 pub use wallet::{MnemonicBuilder, Wallet, WalletError};
 
+mod dyn_signer;
+pub use dyn_signer::{DynSigner, DynSignerError, Eip712Hashes};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod keystore;
+#[cfg(not(target_arch = "wasm32"))]
+pub use keystore::{Kdf, KeystoreManager, KeystoreManagerError};
+
 /// Re-export the BIP-32 crate so that wordlists can be accessed conveniently.
 pub use coins_bip39;
 
@@ -40,10 +48,25 @@ mod aws;
 #[cfg(feature = "aws")]
 pub use aws::{AwsSigner, AwsSignerError};
 
+#[cfg(feature = "gcp")]
+mod gcp;
+
+#[cfg(feature = "gcp")]
+pub use gcp::{GcpKmsSigner, GcpKmsSignerError, GCP_KMS_ENDPOINT};
+
+#[cfg(feature = "vault")]
+mod vault;
+
+#[cfg(feature = "vault")]
+pub use vault::{VaultTransitSigner, VaultTransitSignerError, VAULT_TRANSIT_MOUNT};
+
+#[cfg(any(feature = "aws", feature = "gcp", feature = "vault"))]
+mod kms_utils;
+
 use async_trait::async_trait;
 use ethers_core::types::{
     transaction::{eip2718::TypedTransaction, eip712::Eip712},
-    Address, Signature,
+    Address, EntryPoint, Signature, UserOperation,
 };
 use std::error::Error;
 
@@ -75,6 +98,16 @@ pub trait Signer: std::fmt::Debug + Send + Sync {
         payload: &T,
     ) -> Result<Signature, Self::Error>;
 
+    /// Signs the hash of the EIP-4337 user operation for the `entry_point` on the signer's chain,
+    /// after prefixing it like a message as expected by most smart contract accounts.
+    async fn sign_user_operation(
+        &self,
+        op: &UserOperation,
+        entry_point: &EntryPoint,
+    ) -> Result<Signature, Self::Error> {
+        self.sign_message(op.hash(entry_point, self.chain_id())).await
+    }
+
     /// Returns the signer's Ethereum Address
     fn address(&self) -> Address;
 
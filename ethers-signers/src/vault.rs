@@ -0,0 +1,392 @@
+//! HashiCorp Vault Transit-based Signer
+
+use ethers_core::{
+    k256::ecdsa::{
+        recoverable::Signature as RSig, Error as K256Error, Signature as KSig, VerifyingKey,
+    },
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature as EthSig,
+    },
+    utils::hash_message,
+};
+use reqwest::{Client, IntoUrl, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::kms_utils::{
+    apply_eip155, decode_der_signature, decode_pem_pubkey, rsig_from_digest_bytes_trial_recovery,
+    rsig_to_ethsig, verifying_key_to_address,
+};
+
+/// The default mount path of the transit secrets engine
+pub const VAULT_TRANSIT_MOUNT: &str = "transit";
+
+/// An ethers Signer that uses keys held in the transit secrets engine of HashiCorp Vault.
+///
+/// The Vault Signer passes signing requests to the transit API. Keys are identified by their
+/// name and must be secp256k1 keys, which requires a transit engine supporting them. Keys of
+/// other curves are rejected on instantiation.
+///
+/// The signer uses the latest version of the key at the time of its instantiation, so its
+/// address does not change when the key is rotated.
+///
+/// Because the public key is unknown, we retrieve it on instantiation of the
+/// signer. This means that the new function is `async` and must be called
+/// within some runtime.
+///
+/// ```no_run
+/// use ethers_signers::{Signer, VaultTransitSigner};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let token = std::env::var("VAULT_TOKEN")?;
+/// let signer =
+///     VaultTransitSigner::new(reqwest::Client::new(), "http://127.0.0.1:8200", token, "eth", 1)
+///         .await?;
+/// let sig = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct VaultTransitSigner {
+    client: Client,
+    vault_address: Url,
+    token: String,
+    mount: String,
+    key_name: String,
+    key_version: u64,
+    chain_id: u64,
+    pubkey: VerifyingKey,
+    address: Address,
+}
+
+impl std::fmt::Debug for VaultTransitSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultTransitSigner")
+            .field("vault_address", &self.vault_address.as_str())
+            .field("mount", &self.mount)
+            .field("key_name", &self.key_name)
+            .field("key_version", &self.key_version)
+            .field("chain_id", &self.chain_id)
+            .field("pubkey", &hex::encode(self.pubkey.to_bytes()))
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for VaultTransitSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "VaultTransitSigner {{ address: {}, chain_id: {}, key_name: {}, key_version: {} }}",
+            self.address, self.chain_id, self.key_name, self.key_version
+        )
+    }
+}
+
+/// Errors produced by the VaultTransitSigner
+#[derive(thiserror::Error, Debug)]
+pub enum VaultTransitSignerError {
+    /// Error of the HTTP client
+    #[error(transparent)]
+    ReqwestError(#[from] reqwest::Error),
+    /// Error returned by the Vault API
+    #[error("Vault request failed with status {status}: {message}")]
+    ApiError {
+        /// The HTTP status of the response
+        status: u16,
+        /// The body of the response
+        message: String,
+    },
+    #[error("{0}")]
+    K256(#[from] K256Error),
+    #[error("{0}")]
+    Spki(spki::Error),
+    #[error(transparent)]
+    /// Error when decoding base64
+    Base64Error(#[from] base64::DecodeError),
+    #[error("{0}")]
+    Other(String),
+    /// Error type from Eip712Error message
+    #[error("error encoding eip712 struct: {0:?}")]
+    Eip712Error(String),
+}
+
+impl From<String> for VaultTransitSignerError {
+    fn from(s: String) -> Self {
+        Self::Other(s)
+    }
+}
+
+impl From<spki::Error> for VaultTransitSignerError {
+    fn from(e: spki::Error) -> Self {
+        Self::Spki(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct VaultResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct KeyVersion {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct ReadKeyResponse {
+    latest_version: u64,
+    keys: HashMap<String, KeyVersion>,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl VaultTransitSigner {
+    /// Instantiate a new signer for the key `key_name` of the transit engine mounted at
+    /// [`VAULT_TRANSIT_MOUNT`], authenticated with the Vault `token`.
+    ///
+    /// This function retrieves the public key from Vault and calculates the
+    /// Ethereum address. It is therefore `async`.
+    pub async fn new<U, T, K>(
+        client: Client,
+        vault_address: U,
+        token: T,
+        key_name: K,
+        chain_id: u64,
+    ) -> Result<Self, VaultTransitSignerError>
+    where
+        U: IntoUrl,
+        T: Into<String>,
+        K: Into<String>,
+    {
+        Self::new_with_mount(client, vault_address, token, VAULT_TRANSIT_MOUNT, key_name, chain_id)
+            .await
+    }
+
+    /// Instantiate a new signer for the key `key_name` of the transit engine mounted at `mount`
+    pub async fn new_with_mount<U, T, P, K>(
+        client: Client,
+        vault_address: U,
+        token: T,
+        mount: P,
+        key_name: K,
+        chain_id: u64,
+    ) -> Result<Self, VaultTransitSignerError>
+    where
+        U: IntoUrl,
+        T: Into<String>,
+        P: Into<String>,
+        K: Into<String>,
+    {
+        let vault_address = vault_address.into_url()?;
+        let token = token.into();
+        let mount = mount.into().trim_matches('/').to_string();
+        let key_name = key_name.into();
+
+        let url = format!(
+            "{}/v1/{}/keys/{}",
+            vault_address.as_str().trim_end_matches('/'),
+            mount,
+            key_name
+        );
+        let resp: ReadKeyResponse =
+            parse_response(client.get(url).header("X-Vault-Token", &token).send().await?).await?;
+        let key_version = resp.latest_version;
+        let key = resp.keys.get(&key_version.to_string()).ok_or_else(|| {
+            VaultTransitSignerError::from(format!("Version {} of the key not found", key_version))
+        })?;
+        let pubkey = decode_pem_pubkey::<VaultTransitSignerError>(&key.public_key)?;
+        let address = verifying_key_to_address(&pubkey);
+
+        Ok(Self {
+            client,
+            vault_address,
+            token,
+            mount,
+            key_name,
+            key_version,
+            chain_id,
+            pubkey,
+            address,
+        })
+    }
+
+    /// Sets the Vault token of the requests, e.g. after the previous one expired
+    #[must_use]
+    pub fn with_token<T: Into<String>>(mut self, token: T) -> Self {
+        self.token = token.into();
+        self
+    }
+
+    /// Returns the pubkey of this signer's key
+    pub fn pubkey(&self) -> &VerifyingKey {
+        &self.pubkey
+    }
+
+    /// Returns the version of the key used by this signer
+    pub fn key_version(&self) -> u64 {
+        self.key_version
+    }
+
+    /// Sign a digest with this signer's key
+    pub async fn sign_digest(&self, digest: [u8; 32]) -> Result<KSig, VaultTransitSignerError> {
+        let url = format!(
+            "{}/v1/{}/sign/{}",
+            self.vault_address.as_str().trim_end_matches('/'),
+            self.mount,
+            self.key_name
+        );
+        let body = json!({
+            "input": base64::encode(digest),
+            "key_version": self.key_version,
+            "prehashed": true,
+            "hash_algorithm": "sha2-256",
+            "marshaling_algorithm": "asn1",
+        });
+        let resp: SignResponse = parse_response(
+            self.client.post(url).header("X-Vault-Token", &self.token).json(&body).send().await?,
+        )
+        .await?;
+
+        // the signature is prefixed with `vault:v{key_version}:`
+        let signature = resp.signature.rsplit(':').next().unwrap_or_default();
+        Ok(decode_der_signature(&base64::decode(signature)?)?)
+    }
+
+    /// Sign a digest with this signer's key and add the recovery id as `v` value
+    async fn sign_hash(&self, digest: [u8; 32]) -> Result<EthSig, VaultTransitSignerError> {
+        let sig = self.sign_digest(digest).await?;
+        let sig = self.recover(&sig, digest)?;
+        Ok(rsig_to_ethsig(&sig))
+    }
+
+    /// Adds the recovery id to a signature made by this signer's key
+    fn recover(&self, sig: &KSig, digest: [u8; 32]) -> Result<RSig, VaultTransitSignerError> {
+        rsig_from_digest_bytes_trial_recovery(sig, digest, &self.pubkey).ok_or_else(|| {
+            VaultTransitSignerError::from("Signature not made by the signer's key".to_owned())
+        })
+    }
+}
+
+/// Deserializes the data of a successful response, or returns the body of an error response
+async fn parse_response<T: DeserializeOwned>(
+    resp: reqwest::Response,
+) -> Result<T, VaultTransitSignerError> {
+    let status = resp.status();
+    if !status.is_success() {
+        let message = resp.text().await?;
+        return Err(VaultTransitSignerError::ApiError { status: status.as_u16(), message })
+    }
+    Ok(resp.json::<VaultResponse<T>>().await?.data)
+}
+
+#[async_trait::async_trait]
+impl super::Signer for VaultTransitSigner {
+    type Error = VaultTransitSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<EthSig, Self::Error> {
+        let message_hash = hash_message(message.as_ref());
+        self.sign_hash(message_hash.into()).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<EthSig, Self::Error> {
+        let mut tx_with_chain = tx.clone();
+        let chain_id = tx_with_chain.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx_with_chain.set_chain_id(chain_id);
+
+        let sighash = tx_with_chain.sighash();
+        let mut sig = self.sign_hash(sighash.into()).await?;
+        apply_eip155(&mut sig, chain_id);
+        Ok(sig)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<EthSig, Self::Error> {
+        let digest =
+            payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
+
+        self.sign_hash(digest).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the signer's chain id
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets the signer's chain id
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kms_utils::mock::{der_signature, pem_pubkey, serve},
+        LocalWallet, Signer,
+    };
+    use ethers_core::types::TransactionRequest;
+
+    fn mock_vault(wallet: LocalWallet) -> String {
+        serve(move |req| {
+            assert_eq!(req.header("x-vault-token"), Some("token"));
+            match (req.method.as_str(), req.path.as_str()) {
+                ("GET", "/v1/eth-transit/keys/eth") => json!({
+                    "data": {
+                        "latest_version": 2,
+                        "keys": { "2": { "public_key": pem_pubkey(&wallet) } }
+                    }
+                }),
+                ("POST", "/v1/eth-transit/sign/eth") => {
+                    assert_eq!(req.body["key_version"], 2);
+                    assert_eq!(req.body["prehashed"], true);
+                    let digest = base64::decode(req.body["input"].as_str().unwrap()).unwrap();
+                    let signature = base64::encode(der_signature(&wallet, &digest));
+                    json!({ "data": { "signature": format!("vault:v2:{}", signature) } })
+                }
+                (method, path) => panic!("unexpected request {} {}", method, path),
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn signs_like_the_key() {
+        let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(5u64);
+        let vault_address = mock_vault(wallet.clone());
+        let signer = VaultTransitSigner::new_with_mount(
+            Client::new(),
+            vault_address,
+            "token",
+            "eth-transit",
+            "eth",
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(signer.address(), wallet.address());
+        assert_eq!(signer.key_version(), 2);
+
+        let sig = signer.sign_message("hello").await.unwrap();
+        assert_eq!(sig, wallet.sign_message("hello").await.unwrap());
+
+        let tx = TransactionRequest::pay(Address::zero(), 100u64).nonce(0u64).into();
+        let sig = signer.sign_transaction(&tx).await.unwrap();
+        assert_eq!(sig, wallet.sign_transaction(&tx).await.unwrap());
+    }
+}
@@ -192,6 +192,68 @@ mod tests {
         assert_eq!(recovered2, address);
     }
 
+    #[tokio::test]
+    async fn signs_user_operation() {
+        use ethers_core::types::{EntryPoint, UserOperation};
+
+        let wallet = Wallet::<SigningKey>::new(&mut rand::thread_rng()).with_chain_id(5u64);
+        let op = UserOperation::new(Address::repeat_byte(0x11)).nonce(1u64);
+        let entry_point = EntryPoint::v0_6();
+
+        let signature = wallet.sign_user_operation(&op, &entry_point).await.unwrap();
+        signature.verify(op.hash(&entry_point, 5).as_bytes(), wallet.address).unwrap();
+    }
+
+    #[tokio::test]
+    async fn signs_typed_data() {
+        use ethers_core::{types::transaction::eip712::TypedData, utils::keccak256};
+
+        let json = serde_json::json!({
+          "types": {
+            "EIP712Domain": [
+              { "name": "name", "type": "string" },
+              { "name": "version", "type": "string" },
+              { "name": "chainId", "type": "uint256" },
+              { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+              { "name": "name", "type": "string" },
+              { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+              { "name": "from", "type": "Person" },
+              { "name": "to", "type": "Person" },
+              { "name": "contents", "type": "string" }
+            ]
+          },
+          "primaryType": "Mail",
+          "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+          },
+          "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+          }
+        });
+        let typed_data: TypedData = serde_json::from_value(json).unwrap();
+
+        // test vector from https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js
+        let wallet: Wallet<SigningKey> = hex::encode(keccak256("cow")).parse().unwrap();
+        assert_eq!(
+            wallet.address,
+            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826".parse::<Address>().unwrap()
+        );
+        let signature = wallet.sign_typed_data(&typed_data).await.unwrap();
+        assert_eq!(
+            signature.to_string(),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+        );
+    }
+
     #[tokio::test]
     #[cfg(not(feature = "celo"))]
     async fn signs_tx() {
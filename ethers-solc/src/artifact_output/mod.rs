@@ -688,7 +688,8 @@ pub trait ArtifactOutput {
     ) -> Artifacts<Self::Artifact> {
         let mut artifacts = ArtifactsMap::new();
 
-        // this tracks all the `SourceFile`s that we successfully mapped to a contract
+        // this tracks all the `SourceFile`s that we successfully mapped to a contract, by file
+        // since source ids are only unique within the output of a single compiler invocation
         let mut non_standalone_sources = HashSet::new();
 
         // loop over all files and their contracts
@@ -702,8 +703,8 @@ pub trait ArtifactOutput {
                 for contract in versioned_contracts {
                     let source_file = sources.find_file_and_version(file, &contract.version);
 
-                    if let Some(source) = source_file {
-                        non_standalone_sources.insert((source.id, &contract.version));
+                    if source_file.is_some() {
+                        non_standalone_sources.insert((file, &contract.version));
                     }
 
                     let artifact_path = if versioned_contracts.len() > 1 {
@@ -733,7 +734,7 @@ pub trait ArtifactOutput {
         // extend with standalone source files and convert them to artifacts
         for (file, sources) in sources.as_ref().iter() {
             for source in sources {
-                if !non_standalone_sources.contains(&(source.source_file.id, &source.version)) {
+                if !non_standalone_sources.contains(&(file, &source.version)) {
                     // scan the ast as a safe measure to ensure this file does not include any
                     // source units
                     // there's also no need to create a standalone artifact for source files that
@@ -1,5 +1,6 @@
-//! Bindings for solc's `ast` output field
+//! A low fidelity representation of the AST, where every node is a generic [`Node`].
 
+use super::SourceUnit;
 use crate::artifacts::serde_helpers;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt, fmt::Write, str::FromStr};
@@ -24,6 +25,13 @@ pub struct Ast {
     pub other: BTreeMap<String, serde_json::Value>,
 }
 
+impl Ast {
+    /// Converts this into the strongly typed [`SourceUnit`]
+    pub fn to_source_unit(&self) -> serde_json::Result<SourceUnit> {
+        serde_json::from_value(serde_json::to_value(self)?)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Node {
     /// The node ID.
@@ -211,7 +219,7 @@ mod tests {
 
     #[test]
     fn can_parse_ast() {
-        let ast = include_str!("../../test-data/ast/ast-erc4626.json");
+        let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
         let _ast: Ast = serde_json::from_str(ast).unwrap();
     }
 }
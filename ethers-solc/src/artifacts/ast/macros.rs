@@ -0,0 +1,153 @@
+/// Defines a struct for an AST node.
+///
+/// Every node has an `id` and a `src`, is (de)serialized with its `nodeType` tag and keeps the
+/// attributes that are not modelled in `other`.
+macro_rules! ast_node {
+    (
+        $(#[$struct_meta:meta])*
+        struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$struct_meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(tag = "nodeType", rename_all = "camelCase")]
+        pub struct $name {
+            /// The node ID.
+            pub id: usize,
+            /// The location of the node in the source file.
+            #[serde(with = "serde_helpers::display_from_str")]
+            pub src: SourceLocation,
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+            /// Node attributes that were not deserialized.
+            #[serde(flatten, deserialize_with = "deserialize_other")]
+            pub other: BTreeMap<String, serde_json::Value>,
+        }
+    };
+}
+
+/// Defines a struct for a Yul AST node.
+///
+/// Like [`ast_node`], but Yul nodes do not have an `id`.
+macro_rules! yul_node {
+    (
+        $(#[$struct_meta:meta])*
+        struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$struct_meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(tag = "nodeType", rename_all = "camelCase")]
+        pub struct $name {
+            /// The location of the node in the source file.
+            #[serde(with = "serde_helpers::display_from_str")]
+            pub src: SourceLocation,
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+            /// Node attributes that were not deserialized.
+            #[serde(flatten, deserialize_with = "deserialize_other")]
+            pub other: BTreeMap<String, serde_json::Value>,
+        }
+    };
+}
+
+/// Defines a struct for an expression node, which has the common expression attributes in
+/// addition to the given fields.
+macro_rules! expr_node {
+    (
+        $(#[$struct_meta:meta])*
+        struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        ast_node!(
+            $(#[$struct_meta])*
+            struct $name {
+                /// The types of the arguments if this expression is the callee of a function call.
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                argument_types: Option<Vec<TypeDescriptions>>,
+                is_constant: bool,
+                is_l_value: bool,
+                is_pure: bool,
+                l_value_requested: bool,
+                type_descriptions: TypeDescriptions,
+                $(
+                    $(#[$field_meta])*
+                    $field: $ty
+                ),*
+            }
+        );
+    };
+}
+
+/// Defines a struct for a statement node, which can have a documentation comment in addition to
+/// the given fields.
+macro_rules! stmt_node {
+    (
+        $(#[$struct_meta:meta])*
+        struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        ast_node!(
+            $(#[$struct_meta])*
+            struct $name {
+                /// The documentation comment of the statement.
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                documentation: Option<String>,
+                $(
+                    $(#[$field_meta])*
+                    $field: $ty
+                ),*
+            }
+        );
+    };
+}
+
+/// Defines an enum over several node types that can appear in the same position.
+///
+/// The variant is selected by the `nodeType` tag when deserializing. Serializing delegates to the
+/// node, which writes its own tag.
+macro_rules! node_group {
+    (
+        $(#[$meta:meta])*
+        $group:ident;
+        $( $name:ident ),* $(,)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+        #[serde(tag = "nodeType")]
+        pub enum $group {
+            $( $name($name), )*
+        }
+
+        impl Serialize for $group {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                match self {
+                    $( $group::$name(node) => node.serialize(serializer), )*
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,215 @@
+//! Types and enums that are shared by several AST nodes
+
+use super::{deserialize_other, Identifier, SourceLocation, UserDefinedTypeName};
+use crate::artifacts::serde_helpers;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The type of an expression or a declaration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeDescriptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_string: Option<String>,
+}
+
+ast_node!(
+    /// A NatSpec documentation comment.
+    struct StructuredDocumentation {
+        text: String,
+    }
+);
+
+/// The documentation of a declaration.
+///
+/// Older compiler versions emit the documentation as a plain string.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Documentation {
+    Structured(StructuredDocumentation),
+    Raw(String),
+}
+
+impl Documentation {
+    /// Returns the text of the documentation comment
+    pub fn text(&self) -> &str {
+        match self {
+            Documentation::Structured(doc) => &doc.text,
+            Documentation::Raw(text) => text,
+        }
+    }
+}
+
+/// A symbol imported by an `import {a as b} from "..."` directive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolAlias {
+    pub foreign: Identifier,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_location: Option<String>,
+}
+
+/// A function listed in a `using {f, g as +} for T` directive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsingForFunctionItem {
+    /// The attached function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<IdentifierPath>,
+    /// The function that implements `operator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<IdentifierPath>,
+    /// The user defined operator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+}
+
+/// A reference from inline assembly to a Solidity declaration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalInlineAssemblyReference {
+    pub declaration: usize,
+    #[serde(default)]
+    pub is_offset: bool,
+    #[serde(default)]
+    pub is_slot: bool,
+    #[serde(with = "serde_helpers::display_from_str")]
+    pub src: SourceLocation,
+    pub value_size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+}
+
+ast_node!(
+    /// A path to a declaration, e.g. `A.B` in `contract C is A.B`.
+    struct IdentifierPath {
+        name: String,
+        referenced_declaration: isize,
+    }
+);
+
+ast_node!(
+    /// The list of base contracts or functions named in an `override(...)` specifier.
+    struct OverrideSpecifier {
+        overrides: Vec<UserDefinedTypeNameOrIdentifierPath>,
+    }
+);
+
+ast_node!(
+    /// A value of an enum definition.
+    struct EnumValue {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+    }
+);
+
+node_group! {
+    /// A reference to a declaration, which is a `UserDefinedTypeName` in older compiler versions.
+    UserDefinedTypeNameOrIdentifierPath;
+
+    UserDefinedTypeName,
+    IdentifierPath,
+}
+
+node_group! {
+    /// The name of a modifier, which is an `Identifier` in older compiler versions.
+    IdentifierOrIdentifierPath;
+
+    Identifier,
+    IdentifierPath,
+}
+
+/// The kind of a contract definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContractKind {
+    Contract,
+    Interface,
+    Library,
+}
+
+/// The kind of a function definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FunctionKind {
+    Constructor,
+    Function,
+    Fallback,
+    Receive,
+    /// A function defined outside of a contract.
+    FreeFunction,
+}
+
+/// The kind of a function call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FunctionCallKind {
+    FunctionCall,
+    TypeConversion,
+    StructConstructorCall,
+}
+
+/// The kind of a literal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LiteralKind {
+    Bool,
+    Number,
+    String,
+    HexString,
+    UnicodeString,
+}
+
+/// The kind of a modifier invocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModifierInvocationKind {
+    ModifierInvocation,
+    /// A call to a base constructor in the header of a constructor.
+    BaseConstructorSpecifier,
+}
+
+/// The visibility of a declaration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    External,
+    Public,
+    Internal,
+    Private,
+}
+
+/// The state mutability of a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateMutability {
+    Payable,
+    Pure,
+    Nonpayable,
+    View,
+}
+
+/// The mutability of a variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mutability {
+    Mutable,
+    Immutable,
+    Constant,
+}
+
+/// The data location of a variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageLocation {
+    Calldata,
+    Default,
+    Memory,
+    Storage,
+    Transient,
+}
@@ -0,0 +1,830 @@
+//! Bindings for solc's `ast` output field
+//!
+//! The [`SourceUnit`] and the nodes it contains are a strongly typed model of the AST, which can be
+//! walked with a [`Visitor`]. The low fidelity [`Ast`], where every node is a generic [`Node`], is
+//! what solc's output deserializes into by default, see [`Ast::to_source_unit()`].
+//!
+//! Every node keeps the attributes that are not modelled in its `other` field, so a node
+//! serializes back to the JSON it was deserialized from. The only exception are attributes that
+//! are explicitly set to `null`, which are omitted.
+//!
+//! # Example
+//!
+//! ```
+//! use ethers_solc::artifacts::ast::{
+//!     visitor::walk_function_definition, FunctionDefinition, SourceUnit, Visitor,
+//! };
+//!
+//! #[derive(Default)]
+//! struct FunctionNames(Vec<String>);
+//!
+//! impl Visitor for FunctionNames {
+//!     fn visit_function_definition(&mut self, function: &FunctionDefinition) {
+//!         self.0.push(function.name.clone());
+//!         walk_function_definition(self, function);
+//!     }
+//! }
+//!
+//! let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
+//! let source_unit: SourceUnit = serde_json::from_str(ast).unwrap();
+//! let mut names = FunctionNames::default();
+//! names.visit_source_unit(&source_unit);
+//! assert!(names.0.contains(&"totalAssets".to_string()));
+//! ```
+
+use crate::artifacts::serde_helpers;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+#[macro_use]
+mod macros;
+
+mod lowfidelity;
+pub use lowfidelity::{Ast, Node, NodeType, SourceLocation};
+
+mod misc;
+pub use misc::*;
+
+mod yul;
+pub use yul::*;
+
+pub mod visitor;
+pub use visitor::Visitor;
+
+/// Deserializes the `other` attributes of a node, without the `nodeType` tag which is written by
+/// the node itself.
+fn deserialize_other<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, serde_json::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut other = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    other.remove("nodeType");
+    Ok(other)
+}
+
+// === Source unit ===
+
+ast_node!(
+    /// The root node of a source file.
+    struct SourceUnit {
+        absolute_path: String,
+        #[serde(default)]
+        exported_symbols: BTreeMap<String, Vec<usize>>,
+        /// The SPDX license identifier.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        license: Option<String>,
+        #[serde(default)]
+        nodes: Vec<SourceUnitPart>,
+    }
+);
+
+impl SourceUnit {
+    /// Returns all contract definitions of the source unit
+    pub fn contracts(&self) -> impl Iterator<Item = &ContractDefinition> + '_ {
+        self.nodes.iter().filter_map(|node| match node {
+            SourceUnitPart::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+    }
+}
+
+node_group! {
+    /// A node that can appear at the top level of a source unit.
+    #[allow(clippy::large_enum_variant)]
+    SourceUnitPart;
+
+    PragmaDirective,
+    ImportDirective,
+    UsingForDirective,
+    VariableDeclaration,
+    EnumDefinition,
+    ErrorDefinition,
+    EventDefinition,
+    FunctionDefinition,
+    StructDefinition,
+    UserDefinedValueTypeDefinition,
+    ContractDefinition,
+}
+
+ast_node!(
+    /// A `pragma` directive.
+    struct PragmaDirective {
+        literals: Vec<String>,
+    }
+);
+
+ast_node!(
+    /// An `import` directive.
+    struct ImportDirective {
+        absolute_path: String,
+        file: String,
+        /// The location of the name, `-1:-1:-1` if the name is not in the source.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        scope: usize,
+        source_unit: usize,
+        symbol_aliases: Vec<SymbolAlias>,
+        unit_alias: String,
+    }
+);
+
+ast_node!(
+    /// A `using A for B` directive.
+    struct UsingForDirective {
+        /// The library, if this is not a `using {f, g} for B` directive.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        library_name: Option<UserDefinedTypeNameOrIdentifierPath>,
+        /// The functions of a `using {f, g} for B` directive.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function_list: Option<Vec<UsingForFunctionItem>>,
+        /// The type, absent for `using A for *`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        type_name: Option<TypeName>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        global: Option<bool>,
+    }
+);
+
+// === Definitions ===
+
+ast_node!(
+    /// A `contract`, `interface` or `library` definition.
+    struct ContractDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        contract_kind: ContractKind,
+        #[serde(rename = "abstract")]
+        is_abstract: bool,
+        base_contracts: Vec<InheritanceSpecifier>,
+        contract_dependencies: Vec<usize>,
+        fully_implemented: bool,
+        linearized_base_contracts: Vec<usize>,
+        #[serde(default)]
+        nodes: Vec<ContractDefinitionPart>,
+        scope: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        canonical_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        used_errors: Option<Vec<usize>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        used_events: Option<Vec<usize>>,
+    }
+);
+
+impl ContractDefinition {
+    /// Returns all function definitions of the contract
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionDefinition> + '_ {
+        self.nodes.iter().filter_map(|node| match node {
+            ContractDefinitionPart::FunctionDefinition(function) => Some(function),
+            _ => None,
+        })
+    }
+}
+
+node_group! {
+    /// A node that can appear in the body of a contract definition.
+    #[allow(clippy::large_enum_variant)]
+    ContractDefinitionPart;
+
+    EnumDefinition,
+    ErrorDefinition,
+    EventDefinition,
+    FunctionDefinition,
+    ModifierDefinition,
+    StructDefinition,
+    UserDefinedValueTypeDefinition,
+    UsingForDirective,
+    VariableDeclaration,
+}
+
+ast_node!(
+    /// A base contract in the inheritance list of a contract definition.
+    struct InheritanceSpecifier {
+        base_name: UserDefinedTypeNameOrIdentifierPath,
+        /// The arguments of the base constructor, if any are given.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<Vec<Expression>>,
+    }
+);
+
+ast_node!(
+    /// A function, constructor, `receive` or `fallback` definition.
+    struct FunctionDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        kind: FunctionKind,
+        state_mutability: StateMutability,
+        visibility: Visibility,
+        #[serde(rename = "virtual")]
+        is_virtual: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        overrides: Option<OverrideSpecifier>,
+        parameters: ParameterList,
+        return_parameters: ParameterList,
+        modifiers: Vec<ModifierInvocation>,
+        /// The body of the function, absent if it is not implemented.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<Block>,
+        implemented: bool,
+        scope: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function_selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base_functions: Option<Vec<usize>>,
+    }
+);
+
+ast_node!(
+    /// A `modifier` definition.
+    struct ModifierDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        visibility: Visibility,
+        parameters: ParameterList,
+        #[serde(rename = "virtual")]
+        is_virtual: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        overrides: Option<OverrideSpecifier>,
+        /// The body of the modifier, absent if it is not implemented.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<Block>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base_modifiers: Option<Vec<usize>>,
+    }
+);
+
+ast_node!(
+    /// A modifier, or base constructor call, in the header of a function.
+    struct ModifierInvocation {
+        modifier_name: IdentifierOrIdentifierPath,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<Vec<Expression>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kind: Option<ModifierInvocationKind>,
+    }
+);
+
+ast_node!(
+    /// An `event` definition.
+    struct EventDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        parameters: ParameterList,
+        anonymous: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        event_selector: Option<String>,
+    }
+);
+
+ast_node!(
+    /// An `error` definition.
+    struct ErrorDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        parameters: ParameterList,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_selector: Option<String>,
+    }
+);
+
+ast_node!(
+    /// A `struct` definition.
+    struct StructDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        visibility: Visibility,
+        members: Vec<VariableDeclaration>,
+        scope: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        canonical_name: Option<String>,
+    }
+);
+
+ast_node!(
+    /// An `enum` definition.
+    struct EnumDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        members: Vec<EnumValue>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        canonical_name: Option<String>,
+    }
+);
+
+ast_node!(
+    /// A `type A is B` definition.
+    struct UserDefinedValueTypeDefinition {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        underlying_type: TypeName,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        canonical_name: Option<String>,
+    }
+);
+
+ast_node!(
+    /// A state variable, local variable, parameter or struct member declaration.
+    struct VariableDeclaration {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_location: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        documentation: Option<Documentation>,
+        /// The declared type, absent for `var` declarations of old compiler versions.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        type_name: Option<TypeName>,
+        constant: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mutability: Option<Mutability>,
+        state_variable: bool,
+        storage_location: StorageLocation,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        overrides: Option<OverrideSpecifier>,
+        visibility: Visibility,
+        /// The initial value of a state variable.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<Expression>,
+        scope: usize,
+        type_descriptions: TypeDescriptions,
+        /// Whether this is an indexed event parameter.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        indexed: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function_selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base_functions: Option<Vec<usize>>,
+    }
+);
+
+ast_node!(
+    /// The parameters or return parameters of a function, event or error.
+    struct ParameterList {
+        parameters: Vec<VariableDeclaration>,
+    }
+);
+
+// === Type names ===
+
+node_group! {
+    /// A type name.
+    TypeName;
+
+    ArrayTypeName,
+    ElementaryTypeName,
+    FunctionTypeName,
+    Mapping,
+    UserDefinedTypeName,
+}
+
+ast_node!(
+    /// An array type, e.g. `uint256[]` or `bytes32[4]`.
+    struct ArrayTypeName {
+        base_type: Box<TypeName>,
+        /// The length of a fixed size array.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        length: Option<Box<Expression>>,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+ast_node!(
+    /// A built-in type, e.g. `uint256` or `address payable`.
+    struct ElementaryTypeName {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_mutability: Option<StateMutability>,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+ast_node!(
+    /// A function type, e.g. `function (uint256) external returns (bool)`.
+    struct FunctionTypeName {
+        visibility: Visibility,
+        state_mutability: StateMutability,
+        parameter_types: ParameterList,
+        return_parameter_types: ParameterList,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+ast_node!(
+    /// A mapping type.
+    struct Mapping {
+        key_type: Box<TypeName>,
+        value_type: Box<TypeName>,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+ast_node!(
+    /// A reference to a contract, struct, enum or user defined value type.
+    struct UserDefinedTypeName {
+        /// The path to the declaration, absent in old compiler versions.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path_node: Option<IdentifierPath>,
+        /// The name of the declaration in old compiler versions.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        referenced_declaration: isize,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+// === Statements ===
+
+node_group! {
+    /// A statement.
+    Statement;
+
+    Block,
+    Break,
+    Continue,
+    DoWhileStatement,
+    EmitStatement,
+    ExpressionStatement,
+    ForStatement,
+    IfStatement,
+    InlineAssembly,
+    PlaceholderStatement,
+    Return,
+    RevertStatement,
+    TryStatement,
+    UncheckedBlock,
+    VariableDeclarationStatement,
+    WhileStatement,
+}
+
+stmt_node!(
+    /// A `{ ... }` block.
+    struct Block {
+        statements: Vec<Statement>,
+    }
+);
+
+stmt_node!(
+    /// An `unchecked { ... }` block.
+    struct UncheckedBlock {
+        statements: Vec<Statement>,
+    }
+);
+
+stmt_node!(
+    /// A `break` statement.
+    struct Break {}
+);
+
+stmt_node!(
+    /// A `continue` statement.
+    struct Continue {}
+);
+
+stmt_node!(
+    /// The `_` placeholder in a modifier body.
+    struct PlaceholderStatement {}
+);
+
+stmt_node!(
+    /// A `do { ... } while (...)` loop.
+    struct DoWhileStatement {
+        condition: Expression,
+        body: Box<Statement>,
+    }
+);
+
+stmt_node!(
+    /// A `while (...) { ... }` loop.
+    struct WhileStatement {
+        condition: Expression,
+        body: Box<Statement>,
+    }
+);
+
+stmt_node!(
+    /// A `for (...; ...; ...) { ... }` loop.
+    struct ForStatement {
+        /// Either a `VariableDeclarationStatement` or an `ExpressionStatement`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        initialization_expression: Option<Box<Statement>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        condition: Option<Expression>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        loop_expression: Option<Box<ExpressionStatement>>,
+        body: Box<Statement>,
+    }
+);
+
+stmt_node!(
+    /// An `if (...) { ... } else { ... }` statement.
+    struct IfStatement {
+        condition: Expression,
+        true_body: Box<Statement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        false_body: Option<Box<Statement>>,
+    }
+);
+
+stmt_node!(
+    /// A `try ... returns (...) { ... } catch { ... }` statement.
+    struct TryStatement {
+        external_call: FunctionCall,
+        clauses: Vec<TryCatchClause>,
+    }
+);
+
+ast_node!(
+    /// The success or a `catch` clause of a `try` statement.
+    struct TryCatchClause {
+        /// The name of the error, e.g. `Error` or `Panic`, or empty.
+        error_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parameters: Option<ParameterList>,
+        block: Block,
+    }
+);
+
+stmt_node!(
+    /// An `assembly { ... }` block.
+    struct InlineAssembly {
+        /// The Yul AST of the block, absent in old compiler versions.
+        #[serde(default, rename = "AST", skip_serializing_if = "Option::is_none")]
+        ast: Option<YulBlock>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        evm_version: Option<String>,
+        #[serde(default)]
+        external_references: Vec<ExternalInlineAssemblyReference>,
+        /// The flags of the block, e.g. `memory-safe`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        flags: Option<Vec<String>>,
+    }
+);
+
+stmt_node!(
+    /// A `return` statement.
+    struct Return {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expression: Option<Expression>,
+        /// The ID of the return parameter list of the enclosing function.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function_return_parameters: Option<usize>,
+    }
+);
+
+stmt_node!(
+    /// An `emit` statement.
+    struct EmitStatement {
+        event_call: FunctionCall,
+    }
+);
+
+stmt_node!(
+    /// A `revert CustomError(...)` statement.
+    struct RevertStatement {
+        error_call: FunctionCall,
+    }
+);
+
+stmt_node!(
+    /// An expression that is used as a statement.
+    struct ExpressionStatement {
+        expression: Expression,
+    }
+);
+
+stmt_node!(
+    /// A local variable declaration, e.g. `uint256 a = 1;` or `(, uint256 b) = f();`.
+    struct VariableDeclarationStatement {
+        /// The IDs of the declared variables, `None` for skipped tuple components.
+        assignments: Vec<Option<usize>>,
+        /// The declared variables, `None` for skipped tuple components.
+        declarations: Vec<Option<VariableDeclaration>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        initial_value: Option<Expression>,
+    }
+);
+
+// === Expressions ===
+
+node_group! {
+    /// An expression.
+    Expression;
+
+    Assignment,
+    BinaryOperation,
+    Conditional,
+    ElementaryTypeNameExpression,
+    FunctionCall,
+    FunctionCallOptions,
+    Identifier,
+    IndexAccess,
+    IndexRangeAccess,
+    Literal,
+    MemberAccess,
+    NewExpression,
+    TupleExpression,
+    UnaryOperation,
+}
+
+expr_node!(
+    /// An assignment, e.g. `a = b` or `a += b`.
+    struct Assignment {
+        operator: String,
+        left_hand_side: Box<Expression>,
+        right_hand_side: Box<Expression>,
+    }
+);
+
+expr_node!(
+    /// A binary operation, e.g. `a + b`.
+    struct BinaryOperation {
+        operator: String,
+        left_expression: Box<Expression>,
+        right_expression: Box<Expression>,
+        common_type: TypeDescriptions,
+        /// The ID of the function that implements a user defined operator.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function: Option<usize>,
+    }
+);
+
+expr_node!(
+    /// A ternary `a ? b : c` expression.
+    struct Conditional {
+        condition: Box<Expression>,
+        true_expression: Box<Expression>,
+        false_expression: Box<Expression>,
+    }
+);
+
+expr_node!(
+    /// An elementary type used as an expression, e.g. `address` in `address(this)`.
+    struct ElementaryTypeNameExpression {
+        type_name: ElementaryTypeName,
+    }
+);
+
+expr_node!(
+    /// A function call, type conversion or struct constructor call.
+    struct FunctionCall {
+        expression: Box<Expression>,
+        /// The names of the arguments for calls with named arguments.
+        names: Vec<String>,
+        arguments: Vec<Expression>,
+        try_call: bool,
+        kind: FunctionCallKind,
+    }
+);
+
+expr_node!(
+    /// A function with call options, e.g. `f{value: 1}`.
+    struct FunctionCallOptions {
+        expression: Box<Expression>,
+        names: Vec<String>,
+        options: Vec<Expression>,
+    }
+);
+
+ast_node!(
+    /// A reference to a declaration by name.
+    struct Identifier {
+        name: String,
+        /// The types of the arguments if this identifier is the callee of a function call.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        argument_types: Option<Vec<TypeDescriptions>>,
+        overloaded_declarations: Vec<isize>,
+        /// The ID of the referenced declaration, negative for built-ins like `require`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        referenced_declaration: Option<isize>,
+        type_descriptions: TypeDescriptions,
+    }
+);
+
+expr_node!(
+    /// An index access, e.g. `a[i]`.
+    struct IndexAccess {
+        base_expression: Box<Expression>,
+        /// The index, absent in type expressions like `uint256[]`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index_expression: Option<Box<Expression>>,
+    }
+);
+
+expr_node!(
+    /// A slice of a calldata array, e.g. `a[i:j]`.
+    struct IndexRangeAccess {
+        base_expression: Box<Expression>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_expression: Option<Box<Expression>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        end_expression: Option<Box<Expression>>,
+    }
+);
+
+expr_node!(
+    /// A literal, e.g. `1 ether`, `"abc"` or `true`.
+    struct Literal {
+        kind: LiteralKind,
+        /// The value of the literal, absent if it is not valid UTF-8.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        hex_value: String,
+        /// The unit of a number, e.g. `ether` or `days`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subdenomination: Option<String>,
+    }
+);
+
+expr_node!(
+    /// A member access, e.g. `a.b`.
+    struct MemberAccess {
+        expression: Box<Expression>,
+        member_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        referenced_declaration: Option<usize>,
+    }
+);
+
+expr_node!(
+    /// A `new T` expression.
+    struct NewExpression {
+        type_name: TypeName,
+    }
+);
+
+expr_node!(
+    /// A tuple or inline array, e.g. `(a, , b)` or `[1, 2]`.
+    struct TupleExpression {
+        /// The components, `None` for skipped components.
+        components: Vec<Option<Expression>>,
+        is_inline_array: bool,
+    }
+);
+
+expr_node!(
+    /// A unary operation, e.g. `!a` or `i++`.
+    struct UnaryOperation {
+        operator: String,
+        prefix: bool,
+        sub_expression: Box<Expression>,
+        /// The ID of the function that implements a user defined operator.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        function: Option<usize>,
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_typed_ast() {
+        let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
+        let source_unit: SourceUnit = serde_json::from_str(ast).unwrap();
+
+        let contract = source_unit.contracts().next().unwrap();
+        assert_eq!(contract.name, "ERC4626");
+        assert_eq!(contract.contract_kind, ContractKind::Contract);
+        assert!(contract.is_abstract);
+        assert!(contract.functions().any(|f| f.name == "deposit"));
+    }
+
+    #[test]
+    fn typed_ast_roundtrips() {
+        let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
+        let value: serde_json::Value = serde_json::from_str(ast).unwrap();
+        let source_unit: SourceUnit = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&source_unit).unwrap(), value);
+    }
+
+    #[test]
+    fn can_convert_low_fidelity_ast() {
+        let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
+        let typed: SourceUnit = serde_json::from_str(ast).unwrap();
+        let ast: Ast = serde_json::from_str(ast).unwrap();
+        assert_eq!(ast.to_source_unit().unwrap(), typed);
+    }
+}
@@ -0,0 +1,711 @@
+//! Walking the typed AST
+//!
+//! Every `visit_*` method of the [`Visitor`] trait calls the matching `walk_*` function by default,
+//! which visits the children of the node. An implementation that overrides a `visit_*` method
+//! should call the `walk_*` function itself if it wants to descend into the node.
+
+use super::*;
+
+/// A visitor over the nodes of a [`SourceUnit`].
+#[allow(unused_variables)]
+pub trait Visitor {
+    fn visit_source_unit(&mut self, source_unit: &SourceUnit) {
+        walk_source_unit(self, source_unit)
+    }
+
+    fn visit_pragma_directive(&mut self, pragma: &PragmaDirective) {}
+
+    fn visit_import_directive(&mut self, import: &ImportDirective) {
+        walk_import_directive(self, import)
+    }
+
+    fn visit_using_for_directive(&mut self, using_for: &UsingForDirective) {
+        walk_using_for_directive(self, using_for)
+    }
+
+    fn visit_contract_definition(&mut self, contract: &ContractDefinition) {
+        walk_contract_definition(self, contract)
+    }
+
+    fn visit_inheritance_specifier(&mut self, specifier: &InheritanceSpecifier) {
+        walk_inheritance_specifier(self, specifier)
+    }
+
+    fn visit_function_definition(&mut self, function: &FunctionDefinition) {
+        walk_function_definition(self, function)
+    }
+
+    fn visit_modifier_definition(&mut self, modifier: &ModifierDefinition) {
+        walk_modifier_definition(self, modifier)
+    }
+
+    fn visit_modifier_invocation(&mut self, invocation: &ModifierInvocation) {
+        walk_modifier_invocation(self, invocation)
+    }
+
+    fn visit_override_specifier(&mut self, specifier: &OverrideSpecifier) {
+        walk_override_specifier(self, specifier)
+    }
+
+    fn visit_event_definition(&mut self, event: &EventDefinition) {
+        walk_event_definition(self, event)
+    }
+
+    fn visit_error_definition(&mut self, error: &ErrorDefinition) {
+        walk_error_definition(self, error)
+    }
+
+    fn visit_struct_definition(&mut self, definition: &StructDefinition) {
+        walk_struct_definition(self, definition)
+    }
+
+    fn visit_enum_definition(&mut self, definition: &EnumDefinition) {
+        walk_enum_definition(self, definition)
+    }
+
+    fn visit_enum_value(&mut self, value: &EnumValue) {}
+
+    fn visit_user_defined_value_type_definition(
+        &mut self,
+        definition: &UserDefinedValueTypeDefinition,
+    ) {
+        walk_user_defined_value_type_definition(self, definition)
+    }
+
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        walk_variable_declaration(self, declaration)
+    }
+
+    fn visit_parameter_list(&mut self, parameters: &ParameterList) {
+        walk_parameter_list(self, parameters)
+    }
+
+    fn visit_identifier_path(&mut self, path: &IdentifierPath) {}
+
+    fn visit_type_name(&mut self, type_name: &TypeName) {
+        walk_type_name(self, type_name)
+    }
+
+    fn visit_array_type_name(&mut self, type_name: &ArrayTypeName) {
+        walk_array_type_name(self, type_name)
+    }
+
+    fn visit_elementary_type_name(&mut self, type_name: &ElementaryTypeName) {}
+
+    fn visit_function_type_name(&mut self, type_name: &FunctionTypeName) {
+        walk_function_type_name(self, type_name)
+    }
+
+    fn visit_mapping(&mut self, mapping: &Mapping) {
+        walk_mapping(self, mapping)
+    }
+
+    fn visit_user_defined_type_name(&mut self, type_name: &UserDefinedTypeName) {
+        walk_user_defined_type_name(self, type_name)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_unchecked_block(&mut self, block: &UncheckedBlock) {
+        walk_unchecked_block(self, block)
+    }
+
+    fn visit_break(&mut self, statement: &Break) {}
+
+    fn visit_continue(&mut self, statement: &Continue) {}
+
+    fn visit_placeholder_statement(&mut self, statement: &PlaceholderStatement) {}
+
+    fn visit_do_while_statement(&mut self, statement: &DoWhileStatement) {
+        walk_do_while_statement(self, statement)
+    }
+
+    fn visit_while_statement(&mut self, statement: &WhileStatement) {
+        walk_while_statement(self, statement)
+    }
+
+    fn visit_for_statement(&mut self, statement: &ForStatement) {
+        walk_for_statement(self, statement)
+    }
+
+    fn visit_if_statement(&mut self, statement: &IfStatement) {
+        walk_if_statement(self, statement)
+    }
+
+    fn visit_try_statement(&mut self, statement: &TryStatement) {
+        walk_try_statement(self, statement)
+    }
+
+    fn visit_try_catch_clause(&mut self, clause: &TryCatchClause) {
+        walk_try_catch_clause(self, clause)
+    }
+
+    fn visit_inline_assembly(&mut self, assembly: &InlineAssembly) {}
+
+    fn visit_return(&mut self, statement: &Return) {
+        walk_return(self, statement)
+    }
+
+    fn visit_emit_statement(&mut self, statement: &EmitStatement) {
+        walk_emit_statement(self, statement)
+    }
+
+    fn visit_revert_statement(&mut self, statement: &RevertStatement) {
+        walk_revert_statement(self, statement)
+    }
+
+    fn visit_expression_statement(&mut self, statement: &ExpressionStatement) {
+        walk_expression_statement(self, statement)
+    }
+
+    fn visit_variable_declaration_statement(&mut self, statement: &VariableDeclarationStatement) {
+        walk_variable_declaration_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_assignment(&mut self, assignment: &Assignment) {
+        walk_assignment(self, assignment)
+    }
+
+    fn visit_binary_operation(&mut self, operation: &BinaryOperation) {
+        walk_binary_operation(self, operation)
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) {
+        walk_conditional(self, conditional)
+    }
+
+    fn visit_elementary_type_name_expression(&mut self, expression: &ElementaryTypeNameExpression) {
+        walk_elementary_type_name_expression(self, expression)
+    }
+
+    fn visit_function_call(&mut self, call: &FunctionCall) {
+        walk_function_call(self, call)
+    }
+
+    fn visit_function_call_options(&mut self, call: &FunctionCallOptions) {
+        walk_function_call_options(self, call)
+    }
+
+    fn visit_identifier(&mut self, identifier: &Identifier) {}
+
+    fn visit_index_access(&mut self, access: &IndexAccess) {
+        walk_index_access(self, access)
+    }
+
+    fn visit_index_range_access(&mut self, access: &IndexRangeAccess) {
+        walk_index_range_access(self, access)
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) {}
+
+    fn visit_member_access(&mut self, access: &MemberAccess) {
+        walk_member_access(self, access)
+    }
+
+    fn visit_new_expression(&mut self, expression: &NewExpression) {
+        walk_new_expression(self, expression)
+    }
+
+    fn visit_tuple_expression(&mut self, tuple: &TupleExpression) {
+        walk_tuple_expression(self, tuple)
+    }
+
+    fn visit_unary_operation(&mut self, operation: &UnaryOperation) {
+        walk_unary_operation(self, operation)
+    }
+}
+
+pub fn walk_source_unit<V: Visitor + ?Sized>(visitor: &mut V, source_unit: &SourceUnit) {
+    for node in &source_unit.nodes {
+        match node {
+            SourceUnitPart::PragmaDirective(node) => visitor.visit_pragma_directive(node),
+            SourceUnitPart::ImportDirective(node) => visitor.visit_import_directive(node),
+            SourceUnitPart::UsingForDirective(node) => visitor.visit_using_for_directive(node),
+            SourceUnitPart::VariableDeclaration(node) => visitor.visit_variable_declaration(node),
+            SourceUnitPart::EnumDefinition(node) => visitor.visit_enum_definition(node),
+            SourceUnitPart::ErrorDefinition(node) => visitor.visit_error_definition(node),
+            SourceUnitPart::EventDefinition(node) => visitor.visit_event_definition(node),
+            SourceUnitPart::FunctionDefinition(node) => visitor.visit_function_definition(node),
+            SourceUnitPart::StructDefinition(node) => visitor.visit_struct_definition(node),
+            SourceUnitPart::UserDefinedValueTypeDefinition(node) => {
+                visitor.visit_user_defined_value_type_definition(node)
+            }
+            SourceUnitPart::ContractDefinition(node) => visitor.visit_contract_definition(node),
+        }
+    }
+}
+
+pub fn walk_import_directive<V: Visitor + ?Sized>(visitor: &mut V, import: &ImportDirective) {
+    for alias in &import.symbol_aliases {
+        visitor.visit_identifier(&alias.foreign);
+    }
+}
+
+pub fn walk_using_for_directive<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    using_for: &UsingForDirective,
+) {
+    if let Some(ref library_name) = using_for.library_name {
+        walk_user_defined_type_name_or_identifier_path(visitor, library_name);
+    }
+    for item in using_for.function_list.iter().flatten() {
+        for path in item.function.iter().chain(item.definition.iter()) {
+            visitor.visit_identifier_path(path);
+        }
+    }
+    if let Some(ref type_name) = using_for.type_name {
+        visitor.visit_type_name(type_name);
+    }
+}
+
+pub fn walk_contract_definition<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    contract: &ContractDefinition,
+) {
+    for base in &contract.base_contracts {
+        visitor.visit_inheritance_specifier(base);
+    }
+    for node in &contract.nodes {
+        match node {
+            ContractDefinitionPart::EnumDefinition(node) => visitor.visit_enum_definition(node),
+            ContractDefinitionPart::ErrorDefinition(node) => visitor.visit_error_definition(node),
+            ContractDefinitionPart::EventDefinition(node) => visitor.visit_event_definition(node),
+            ContractDefinitionPart::FunctionDefinition(node) => {
+                visitor.visit_function_definition(node)
+            }
+            ContractDefinitionPart::ModifierDefinition(node) => {
+                visitor.visit_modifier_definition(node)
+            }
+            ContractDefinitionPart::StructDefinition(node) => visitor.visit_struct_definition(node),
+            ContractDefinitionPart::UserDefinedValueTypeDefinition(node) => {
+                visitor.visit_user_defined_value_type_definition(node)
+            }
+            ContractDefinitionPart::UsingForDirective(node) => {
+                visitor.visit_using_for_directive(node)
+            }
+            ContractDefinitionPart::VariableDeclaration(node) => {
+                visitor.visit_variable_declaration(node)
+            }
+        }
+    }
+}
+
+pub fn walk_inheritance_specifier<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    specifier: &InheritanceSpecifier,
+) {
+    walk_user_defined_type_name_or_identifier_path(visitor, &specifier.base_name);
+    for argument in specifier.arguments.iter().flatten() {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_function_definition<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    function: &FunctionDefinition,
+) {
+    visitor.visit_parameter_list(&function.parameters);
+    visitor.visit_parameter_list(&function.return_parameters);
+    if let Some(ref overrides) = function.overrides {
+        visitor.visit_override_specifier(overrides);
+    }
+    for modifier in &function.modifiers {
+        visitor.visit_modifier_invocation(modifier);
+    }
+    if let Some(ref body) = function.body {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_modifier_definition<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    modifier: &ModifierDefinition,
+) {
+    visitor.visit_parameter_list(&modifier.parameters);
+    if let Some(ref overrides) = modifier.overrides {
+        visitor.visit_override_specifier(overrides);
+    }
+    if let Some(ref body) = modifier.body {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_modifier_invocation<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    invocation: &ModifierInvocation,
+) {
+    match invocation.modifier_name {
+        IdentifierOrIdentifierPath::Identifier(ref identifier) => {
+            visitor.visit_identifier(identifier)
+        }
+        IdentifierOrIdentifierPath::IdentifierPath(ref path) => visitor.visit_identifier_path(path),
+    }
+    for argument in invocation.arguments.iter().flatten() {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_override_specifier<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    specifier: &OverrideSpecifier,
+) {
+    for name in &specifier.overrides {
+        walk_user_defined_type_name_or_identifier_path(visitor, name);
+    }
+}
+
+pub fn walk_event_definition<V: Visitor + ?Sized>(visitor: &mut V, event: &EventDefinition) {
+    visitor.visit_parameter_list(&event.parameters);
+}
+
+pub fn walk_error_definition<V: Visitor + ?Sized>(visitor: &mut V, error: &ErrorDefinition) {
+    visitor.visit_parameter_list(&error.parameters);
+}
+
+pub fn walk_struct_definition<V: Visitor + ?Sized>(visitor: &mut V, definition: &StructDefinition) {
+    for member in &definition.members {
+        visitor.visit_variable_declaration(member);
+    }
+}
+
+pub fn walk_enum_definition<V: Visitor + ?Sized>(visitor: &mut V, definition: &EnumDefinition) {
+    for value in &definition.members {
+        visitor.visit_enum_value(value);
+    }
+}
+
+pub fn walk_user_defined_value_type_definition<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    definition: &UserDefinedValueTypeDefinition,
+) {
+    visitor.visit_type_name(&definition.underlying_type);
+}
+
+pub fn walk_variable_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &VariableDeclaration,
+) {
+    if let Some(ref type_name) = declaration.type_name {
+        visitor.visit_type_name(type_name);
+    }
+    if let Some(ref overrides) = declaration.overrides {
+        visitor.visit_override_specifier(overrides);
+    }
+    if let Some(ref value) = declaration.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_parameter_list<V: Visitor + ?Sized>(visitor: &mut V, parameters: &ParameterList) {
+    for parameter in &parameters.parameters {
+        visitor.visit_variable_declaration(parameter);
+    }
+}
+
+fn walk_user_defined_type_name_or_identifier_path<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    name: &UserDefinedTypeNameOrIdentifierPath,
+) {
+    match name {
+        UserDefinedTypeNameOrIdentifierPath::UserDefinedTypeName(type_name) => {
+            visitor.visit_user_defined_type_name(type_name)
+        }
+        UserDefinedTypeNameOrIdentifierPath::IdentifierPath(path) => {
+            visitor.visit_identifier_path(path)
+        }
+    }
+}
+
+pub fn walk_type_name<V: Visitor + ?Sized>(visitor: &mut V, type_name: &TypeName) {
+    match type_name {
+        TypeName::ArrayTypeName(node) => visitor.visit_array_type_name(node),
+        TypeName::ElementaryTypeName(node) => visitor.visit_elementary_type_name(node),
+        TypeName::FunctionTypeName(node) => visitor.visit_function_type_name(node),
+        TypeName::Mapping(node) => visitor.visit_mapping(node),
+        TypeName::UserDefinedTypeName(node) => visitor.visit_user_defined_type_name(node),
+    }
+}
+
+pub fn walk_array_type_name<V: Visitor + ?Sized>(visitor: &mut V, type_name: &ArrayTypeName) {
+    visitor.visit_type_name(&type_name.base_type);
+    if let Some(ref length) = type_name.length {
+        visitor.visit_expression(length);
+    }
+}
+
+pub fn walk_function_type_name<V: Visitor + ?Sized>(visitor: &mut V, type_name: &FunctionTypeName) {
+    visitor.visit_parameter_list(&type_name.parameter_types);
+    visitor.visit_parameter_list(&type_name.return_parameter_types);
+}
+
+pub fn walk_mapping<V: Visitor + ?Sized>(visitor: &mut V, mapping: &Mapping) {
+    visitor.visit_type_name(&mapping.key_type);
+    visitor.visit_type_name(&mapping.value_type);
+}
+
+pub fn walk_user_defined_type_name<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    type_name: &UserDefinedTypeName,
+) {
+    if let Some(ref path) = type_name.path_node {
+        visitor.visit_identifier_path(path);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Block(node) => visitor.visit_block(node),
+        Statement::Break(node) => visitor.visit_break(node),
+        Statement::Continue(node) => visitor.visit_continue(node),
+        Statement::DoWhileStatement(node) => visitor.visit_do_while_statement(node),
+        Statement::EmitStatement(node) => visitor.visit_emit_statement(node),
+        Statement::ExpressionStatement(node) => visitor.visit_expression_statement(node),
+        Statement::ForStatement(node) => visitor.visit_for_statement(node),
+        Statement::IfStatement(node) => visitor.visit_if_statement(node),
+        Statement::InlineAssembly(node) => visitor.visit_inline_assembly(node),
+        Statement::PlaceholderStatement(node) => visitor.visit_placeholder_statement(node),
+        Statement::Return(node) => visitor.visit_return(node),
+        Statement::RevertStatement(node) => visitor.visit_revert_statement(node),
+        Statement::TryStatement(node) => visitor.visit_try_statement(node),
+        Statement::UncheckedBlock(node) => visitor.visit_unchecked_block(node),
+        Statement::VariableDeclarationStatement(node) => {
+            visitor.visit_variable_declaration_statement(node)
+        }
+        Statement::WhileStatement(node) => visitor.visit_while_statement(node),
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_unchecked_block<V: Visitor + ?Sized>(visitor: &mut V, block: &UncheckedBlock) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_do_while_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &DoWhileStatement) {
+    visitor.visit_statement(&statement.body);
+    visitor.visit_expression(&statement.condition);
+}
+
+pub fn walk_while_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &WhileStatement) {
+    visitor.visit_expression(&statement.condition);
+    visitor.visit_statement(&statement.body);
+}
+
+pub fn walk_for_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &ForStatement) {
+    if let Some(ref initialization) = statement.initialization_expression {
+        visitor.visit_statement(initialization);
+    }
+    if let Some(ref condition) = statement.condition {
+        visitor.visit_expression(condition);
+    }
+    if let Some(ref loop_expression) = statement.loop_expression {
+        visitor.visit_expression_statement(loop_expression);
+    }
+    visitor.visit_statement(&statement.body);
+}
+
+pub fn walk_if_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &IfStatement) {
+    visitor.visit_expression(&statement.condition);
+    visitor.visit_statement(&statement.true_body);
+    if let Some(ref false_body) = statement.false_body {
+        visitor.visit_statement(false_body);
+    }
+}
+
+pub fn walk_try_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &TryStatement) {
+    visitor.visit_function_call(&statement.external_call);
+    for clause in &statement.clauses {
+        visitor.visit_try_catch_clause(clause);
+    }
+}
+
+pub fn walk_try_catch_clause<V: Visitor + ?Sized>(visitor: &mut V, clause: &TryCatchClause) {
+    if let Some(ref parameters) = clause.parameters {
+        visitor.visit_parameter_list(parameters);
+    }
+    visitor.visit_block(&clause.block);
+}
+
+pub fn walk_return<V: Visitor + ?Sized>(visitor: &mut V, statement: &Return) {
+    if let Some(ref expression) = statement.expression {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_emit_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &EmitStatement) {
+    visitor.visit_function_call(&statement.event_call);
+}
+
+pub fn walk_revert_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &RevertStatement) {
+    visitor.visit_function_call(&statement.error_call);
+}
+
+pub fn walk_expression_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    statement: &ExpressionStatement,
+) {
+    visitor.visit_expression(&statement.expression);
+}
+
+pub fn walk_variable_declaration_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    statement: &VariableDeclarationStatement,
+) {
+    for declaration in statement.declarations.iter().flatten() {
+        visitor.visit_variable_declaration(declaration);
+    }
+    if let Some(ref initial_value) = statement.initial_value {
+        visitor.visit_expression(initial_value);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Assignment(node) => visitor.visit_assignment(node),
+        Expression::BinaryOperation(node) => visitor.visit_binary_operation(node),
+        Expression::Conditional(node) => visitor.visit_conditional(node),
+        Expression::ElementaryTypeNameExpression(node) => {
+            visitor.visit_elementary_type_name_expression(node)
+        }
+        Expression::FunctionCall(node) => visitor.visit_function_call(node),
+        Expression::FunctionCallOptions(node) => visitor.visit_function_call_options(node),
+        Expression::Identifier(node) => visitor.visit_identifier(node),
+        Expression::IndexAccess(node) => visitor.visit_index_access(node),
+        Expression::IndexRangeAccess(node) => visitor.visit_index_range_access(node),
+        Expression::Literal(node) => visitor.visit_literal(node),
+        Expression::MemberAccess(node) => visitor.visit_member_access(node),
+        Expression::NewExpression(node) => visitor.visit_new_expression(node),
+        Expression::TupleExpression(node) => visitor.visit_tuple_expression(node),
+        Expression::UnaryOperation(node) => visitor.visit_unary_operation(node),
+    }
+}
+
+pub fn walk_assignment<V: Visitor + ?Sized>(visitor: &mut V, assignment: &Assignment) {
+    visitor.visit_expression(&assignment.left_hand_side);
+    visitor.visit_expression(&assignment.right_hand_side);
+}
+
+pub fn walk_binary_operation<V: Visitor + ?Sized>(visitor: &mut V, operation: &BinaryOperation) {
+    visitor.visit_expression(&operation.left_expression);
+    visitor.visit_expression(&operation.right_expression);
+}
+
+pub fn walk_conditional<V: Visitor + ?Sized>(visitor: &mut V, conditional: &Conditional) {
+    visitor.visit_expression(&conditional.condition);
+    visitor.visit_expression(&conditional.true_expression);
+    visitor.visit_expression(&conditional.false_expression);
+}
+
+pub fn walk_elementary_type_name_expression<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    expression: &ElementaryTypeNameExpression,
+) {
+    visitor.visit_elementary_type_name(&expression.type_name);
+}
+
+pub fn walk_function_call<V: Visitor + ?Sized>(visitor: &mut V, call: &FunctionCall) {
+    visitor.visit_expression(&call.expression);
+    for argument in &call.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_function_call_options<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    call: &FunctionCallOptions,
+) {
+    visitor.visit_expression(&call.expression);
+    for option in &call.options {
+        visitor.visit_expression(option);
+    }
+}
+
+pub fn walk_index_access<V: Visitor + ?Sized>(visitor: &mut V, access: &IndexAccess) {
+    visitor.visit_expression(&access.base_expression);
+    if let Some(ref index) = access.index_expression {
+        visitor.visit_expression(index);
+    }
+}
+
+pub fn walk_index_range_access<V: Visitor + ?Sized>(visitor: &mut V, access: &IndexRangeAccess) {
+    visitor.visit_expression(&access.base_expression);
+    if let Some(ref start) = access.start_expression {
+        visitor.visit_expression(start);
+    }
+    if let Some(ref end) = access.end_expression {
+        visitor.visit_expression(end);
+    }
+}
+
+pub fn walk_member_access<V: Visitor + ?Sized>(visitor: &mut V, access: &MemberAccess) {
+    visitor.visit_expression(&access.expression);
+}
+
+pub fn walk_new_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &NewExpression) {
+    visitor.visit_type_name(&expression.type_name);
+}
+
+pub fn walk_tuple_expression<V: Visitor + ?Sized>(visitor: &mut V, tuple: &TupleExpression) {
+    for component in tuple.components.iter().flatten() {
+        visitor.visit_expression(component);
+    }
+}
+
+pub fn walk_unary_operation<V: Visitor + ?Sized>(visitor: &mut V, operation: &UnaryOperation) {
+    visitor.visit_expression(&operation.sub_expression);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        functions: usize,
+        identifiers: usize,
+        emits: usize,
+    }
+
+    impl Visitor for Counter {
+        fn visit_function_definition(&mut self, function: &FunctionDefinition) {
+            self.functions += 1;
+            walk_function_definition(self, function)
+        }
+
+        fn visit_identifier(&mut self, _: &Identifier) {
+            self.identifiers += 1;
+        }
+
+        fn visit_emit_statement(&mut self, statement: &EmitStatement) {
+            self.emits += 1;
+            walk_emit_statement(self, statement)
+        }
+    }
+
+    #[test]
+    fn can_visit_ast() {
+        let ast = include_str!("../../../test-data/ast/ast-erc4626.json");
+        let source_unit: SourceUnit = serde_json::from_str(ast).unwrap();
+        let mut counter = Counter::default();
+        counter.visit_source_unit(&source_unit);
+        assert_eq!(counter.functions, 18);
+        assert_eq!(counter.identifiers, 141);
+        assert_eq!(counter.emits, 4);
+    }
+}
@@ -0,0 +1,165 @@
+//! The Yul AST of `InlineAssembly` statements
+
+use super::{deserialize_other, SourceLocation};
+use crate::artifacts::serde_helpers;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+yul_node!(
+    struct YulBlock {
+        statements: Vec<YulStatement>,
+    }
+);
+
+node_group! {
+    YulStatement;
+
+    YulAssignment,
+    YulBlock,
+    YulBreak,
+    YulContinue,
+    YulExpressionStatement,
+    YulLeave,
+    YulForLoop,
+    YulFunctionDefinition,
+    YulIf,
+    YulSwitch,
+    YulVariableDeclaration,
+}
+
+node_group! {
+    YulExpression;
+
+    YulFunctionCall,
+    YulIdentifier,
+    YulLiteral,
+}
+
+yul_node!(
+    struct YulAssignment {
+        value: YulExpression,
+        variable_names: Vec<YulIdentifier>,
+    }
+);
+
+yul_node!(
+    struct YulBreak {}
+);
+
+yul_node!(
+    struct YulContinue {}
+);
+
+yul_node!(
+    struct YulLeave {}
+);
+
+yul_node!(
+    struct YulExpressionStatement {
+        expression: YulExpression,
+    }
+);
+
+yul_node!(
+    struct YulForLoop {
+        pre: YulBlock,
+        condition: YulExpression,
+        post: YulBlock,
+        body: YulBlock,
+    }
+);
+
+yul_node!(
+    struct YulFunctionDefinition {
+        name: String,
+        /// Only present if the function has parameters.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parameters: Option<Vec<YulTypedName>>,
+        /// Only present if the function has return variables.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        return_variables: Option<Vec<YulTypedName>>,
+        body: YulBlock,
+    }
+);
+
+yul_node!(
+    struct YulIf {
+        condition: YulExpression,
+        body: YulBlock,
+    }
+);
+
+yul_node!(
+    struct YulSwitch {
+        expression: YulExpression,
+        cases: Vec<YulCase>,
+    }
+);
+
+yul_node!(
+    struct YulCase {
+        value: YulCaseValue,
+        body: YulBlock,
+    }
+);
+
+/// The value of a `case` in a `switch`, the `default` case is represented by the string
+/// `"default"`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum YulCaseValue {
+    Literal(YulLiteral),
+    Default(String),
+}
+
+yul_node!(
+    struct YulVariableDeclaration {
+        variables: Vec<YulTypedName>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<YulExpression>,
+    }
+);
+
+yul_node!(
+    struct YulTypedName {
+        name: String,
+        #[serde(rename = "type")]
+        ty: String,
+    }
+);
+
+yul_node!(
+    struct YulFunctionCall {
+        function_name: YulIdentifier,
+        arguments: Vec<YulExpression>,
+    }
+);
+
+yul_node!(
+    struct YulIdentifier {
+        name: String,
+    }
+);
+
+yul_node!(
+    struct YulLiteral {
+        kind: YulLiteralKind,
+        /// The value of the literal, absent if it is not valid UTF-8.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        /// The hex encoded value of the literal.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hex_value: Option<String>,
+        #[serde(rename = "type")]
+        ty: String,
+    }
+);
+
+/// The kind of a Yul literal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YulLiteralKind {
+    Bool,
+    Number,
+    String,
+}
@@ -16,11 +16,12 @@ use std::{
 use tracing::warn;
 
 pub mod ast;
-pub use ast::*;
+pub use ast::{Ast, Node, NodeType};
 pub mod bytecode;
 pub mod contract;
 pub mod output_selection;
 pub mod serde_helpers;
+pub mod vyper;
 use crate::{
     artifacts::output_selection::{ContractOutputSelection, OutputSelection},
     filter::FilteredSources,
@@ -28,6 +29,7 @@ use crate::{
 pub use bytecode::*;
 pub use contract::*;
 pub use serde_helpers::{deserialize_bytes, deserialize_opt_bytes};
+pub use vyper::{VyperInput, VyperOutput};
 
 /// Solidity files are made up of multiple `source units`, a solidity contract is such a `source
 /// unit`, therefore a solidity file can contain multiple contracts: (1-N*) relationship.
@@ -66,14 +68,19 @@ impl CompilerInput {
 
     /// Creates a new [CompilerInput]s with default settings and the given sources
     ///
-    /// A [CompilerInput] expects a language setting, supported by solc are solidity or yul.
-    /// In case the `sources` is a mix of solidity and yul files, 2 CompilerInputs are returned
+    /// A [CompilerInput] expects a language setting, supported by solc are solidity or yul, vyper
+    /// sources are compiled by [Vyper](crate::Vyper).
+    /// In case the `sources` is a mix of solidity, yul and vyper files, a CompilerInput is
+    /// returned for each language
     pub fn with_sources(sources: Sources) -> Vec<Self> {
         let mut solidity_sources = BTreeMap::new();
         let mut yul_sources = BTreeMap::new();
+        let mut vyper_sources = BTreeMap::new();
         for (path, source) in sources {
             if path.extension() == Some(std::ffi::OsStr::new("yul")) {
                 yul_sources.insert(path, source);
+            } else if utils::is_vyper_file(&path) {
+                vyper_sources.insert(path, source);
             } else {
                 solidity_sources.insert(path, source);
             }
@@ -93,9 +100,21 @@ impl CompilerInput {
                 settings: Default::default(),
             });
         }
+        if !vyper_sources.is_empty() {
+            res.push(Self {
+                language: vyper::VYPER.to_string(),
+                sources: vyper_sources,
+                settings: Default::default(),
+            });
+        }
         res
     }
 
+    /// Returns `true` if this input holds vyper sources
+    pub fn is_vyper(&self) -> bool {
+        self.language == vyper::VYPER
+    }
+
     /// This will remove/adjust values in the `CompilerInput` that are not compatible with this
     /// version
     pub fn sanitized(mut self, version: &Version) -> Self {
@@ -0,0 +1,263 @@
+//! Vyper artifact types
+//!
+//! `vyper --standard-json` is modeled after the solc standard json interface but differs in some
+//! details, these types map the vyper input and output to the solc types used everywhere else.
+
+use crate::artifacts::{
+    serde_helpers, Bytecode, CompilerInput, CompilerOutput, Contract, DeployedBytecode, Error, Evm,
+    EvmVersion, LosslessAbi, Severity, SourceFile, Sources,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The language of a [`CompilerInput`] with vyper sources
+pub const VYPER: &str = "Vyper";
+
+/// The outputs requested from vyper for all files
+pub const VYPER_OUTPUT_SELECTION: [&str; 4] =
+    ["abi", "evm.bytecode", "evm.deployedBytecode", "evm.methodIdentifiers"];
+
+/// Input type `vyper` expects
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VyperInput {
+    pub language: String,
+    pub sources: Sources,
+    pub settings: VyperSettings,
+}
+
+impl VyperInput {
+    /// Creates the vyper input for the sources of the given [`CompilerInput`], the solc specific
+    /// settings are ignored
+    pub fn new(input: &CompilerInput) -> Self {
+        let output_selection = BTreeMap::from([(
+            "*".to_string(),
+            VYPER_OUTPUT_SELECTION.iter().map(|s| s.to_string()).collect(),
+        )]);
+        Self {
+            language: VYPER.to_string(),
+            sources: input.sources.clone(),
+            settings: VyperSettings {
+                evm_version: input.settings.evm_version,
+                optimize: input.settings.optimizer.enabled,
+                output_selection,
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a CompilerInput> for VyperInput {
+    fn from(input: &'a CompilerInput) -> Self {
+        VyperInput::new(input)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperSettings {
+    #[serde(
+        default,
+        with = "serde_helpers::display_from_str_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub evm_version: Option<EvmVersion>,
+    /// Whether to enable the optimizer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimize: Option<bool>,
+    /// file name -> outputs, `*` selects all files
+    #[serde(default)]
+    pub output_selection: BTreeMap<String, Vec<String>>,
+}
+
+/// Output type `vyper` produces
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VyperOutput {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<VyperError>,
+    #[serde(default)]
+    pub sources: BTreeMap<String, VyperSourceFile>,
+    /// file -> (contract name -> contract)
+    #[serde(default)]
+    pub contracts: BTreeMap<String, BTreeMap<String, VyperContract>>,
+}
+
+impl From<VyperOutput> for CompilerOutput {
+    fn from(output: VyperOutput) -> Self {
+        let VyperOutput { errors, sources, contracts } = output;
+        CompilerOutput {
+            errors: errors.into_iter().map(Into::into).collect(),
+            // the vyper AST is not compatible with the solidity AST
+            sources: sources
+                .into_iter()
+                .map(|(path, source)| (path, SourceFile { id: source.id, ast: None }))
+                .collect(),
+            contracts: contracts
+                .into_iter()
+                .map(|(path, contracts)| {
+                    (path, contracts.into_iter().map(|(name, c)| (name, c.into())).collect())
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VyperSourceFile {
+    pub id: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VyperContract {
+    pub abi: Option<LosslessAbi>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm: Option<VyperEvm>,
+}
+
+impl From<VyperContract> for Contract {
+    fn from(contract: VyperContract) -> Self {
+        let VyperContract { abi, evm } = contract;
+        Contract {
+            abi,
+            metadata: None,
+            userdoc: Default::default(),
+            devdoc: Default::default(),
+            ir: None,
+            storage_layout: Default::default(),
+            evm: evm.map(Into::into),
+            ewasm: None,
+            ir_optimized: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperEvm {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytecode: Option<Bytecode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployed_bytecode: Option<Bytecode>,
+    /// The list of function hashes, `0x` prefixed
+    #[serde(default, skip_serializing_if = "::std::collections::BTreeMap::is_empty")]
+    pub method_identifiers: BTreeMap<String, String>,
+}
+
+impl From<VyperEvm> for Evm {
+    fn from(evm: VyperEvm) -> Self {
+        let VyperEvm { bytecode, deployed_bytecode, method_identifiers } = evm;
+        Evm {
+            assembly: None,
+            legacy_assembly: None,
+            bytecode,
+            deployed_bytecode: deployed_bytecode.map(DeployedBytecode::from),
+            // solc emits the selectors without prefix
+            method_identifiers: method_identifiers
+                .into_iter()
+                .map(|(sig, selector)| {
+                    let selector = selector.strip_prefix("0x").unwrap_or(&selector).to_string();
+                    (sig, selector)
+                })
+                .collect(),
+            gas_estimates: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperError {
+    #[serde(default)]
+    pub r#type: String,
+    #[serde(default)]
+    pub component: String,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_message: Option<String>,
+}
+
+impl From<VyperError> for Error {
+    fn from(err: VyperError) -> Self {
+        let VyperError { r#type, component, severity, message, formatted_message } = err;
+        Error {
+            source_location: None,
+            secondary_source_locations: Vec::new(),
+            r#type,
+            component,
+            severity,
+            error_code: None,
+            message,
+            formatted_message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::Source;
+    use std::path::PathBuf;
+
+    #[test]
+    fn can_serialize_vyper_input() {
+        let sources = Sources::from([(
+            PathBuf::from("contracts/Counter.vy"),
+            Source { content: "# @version ^0.3.7".to_string() },
+        )]);
+        let mut input = CompilerInput::with_sources(sources).remove(0);
+        assert_eq!(input.language, VYPER);
+        input.settings.evm_version = Some(EvmVersion::London);
+
+        let value = serde_json::to_value(VyperInput::new(&input)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "language": "Vyper",
+                "sources": {
+                    "contracts/Counter.vy": { "content": "# @version ^0.3.7" }
+                },
+                "settings": {
+                    "evmVersion": "london",
+                    "optimize": false,
+                    "outputSelection": {
+                        "*": ["abi", "evm.bytecode", "evm.deployedBytecode", "evm.methodIdentifiers"]
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn can_parse_vyper_output() {
+        let output = r#"{
+            "compiler": "vyper-0.3.7",
+            "contracts": {
+                "contracts/Counter.vy": {
+                    "Counter": {
+                        "abi": [{"stateMutability": "view", "type": "function", "name": "count", "inputs": [], "outputs": [{"name": "", "type": "uint256"}]}],
+                        "evm": {
+                            "bytecode": {"object": "0x6100", "opcodes": "PUSH2 0x0"},
+                            "deployedBytecode": {"object": "0x6001", "opcodes": "PUSH1 0x1", "sourceMap": "-1:-1:0:-;"},
+                            "methodIdentifiers": {"count()": "0x06661abd"}
+                        }
+                    }
+                }
+            },
+            "sources": {"contracts/Counter.vy": {"id": 0, "ast": {"ast_type": "Module"}}},
+            "errors": [{"type": "Warning", "component": "compiler", "severity": "warning", "message": "unused"}]
+        }"#;
+        let output: CompilerOutput = serde_json::from_str::<VyperOutput>(output).unwrap().into();
+
+        assert!(!output.has_error());
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.sources["contracts/Counter.vy"], SourceFile { id: 0, ast: None });
+
+        let contract = &output.contracts["contracts/Counter.vy"]["Counter"];
+        assert_eq!(contract.abi.as_ref().unwrap().abi.functions().count(), 1);
+        let evm = contract.evm.as_ref().unwrap();
+        assert_eq!(evm.method_identifiers["count()"], "06661abd");
+        assert_eq!(
+            evm.deployed_bytecode.as_ref().unwrap().bytecode.as_ref().unwrap().object.as_bytes(),
+            Some(&vec![0x60, 0x01].into())
+        );
+    }
+}
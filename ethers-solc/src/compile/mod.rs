@@ -16,6 +16,8 @@ pub mod many;
 pub mod output;
 pub use output::{contracts, info, sources};
 pub mod project;
+pub mod vyper;
+pub use vyper::{Vyper, VYPER};
 
 /// The name of the `solc` binary on the system
 pub const SOLC: &str = "solc";
@@ -465,10 +467,12 @@ impl Solc {
     }
 
     /// Convenience function for compiling all sources under the given path
+    ///
+    /// Vyper sources are skipped, see [`Vyper::compile_source()`]
     pub fn compile_source(&self, path: impl AsRef<Path>) -> Result<CompilerOutput> {
         let path = path.as_ref();
         let mut res: CompilerOutput = Default::default();
-        for input in CompilerInput::new(path)? {
+        for input in CompilerInput::new(path)?.into_iter().filter(|input| !input.is_vyper()) {
             let output = self.compile(&input)?;
             res.merge(output)
         }
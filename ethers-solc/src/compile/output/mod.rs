@@ -381,8 +381,8 @@ pub struct AggregatedCompilerOutput {
     pub sources: VersionedSourceFiles,
     /// All compiled contracts combined with the solc version used to compile them
     pub contracts: VersionedContracts,
-    // All the `BuildInfo`s of solc invocations.
-    pub build_infos: BTreeMap<Version, RawBuildInfo>,
+    // All the `BuildInfo`s of solc invocations, by the language and version of the compiler.
+    pub build_infos: BTreeMap<(String, Version), RawBuildInfo>,
 }
 
 impl AggregatedCompilerOutput {
@@ -455,8 +455,8 @@ impl AggregatedCompilerOutput {
         let build_info_dir = build_info_dir.as_ref();
         std::fs::create_dir_all(build_info_dir)
             .map_err(|err| SolcIoError::new(err, build_info_dir))?;
-        for (version, build_info) in &self.build_infos {
-            trace!("writing build info file for solc {}", version);
+        for ((language, version), build_info) in &self.build_infos {
+            trace!("writing build info file for {} {}", language, version);
             let file_name = format!("{}.json", build_info.id);
             let file = build_info_dir.join(file_name);
             std::fs::write(&file, &build_info.build_info)
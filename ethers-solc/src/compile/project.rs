@@ -23,6 +23,11 @@
 //!
 //! The final step is invoking `Solc` via the standard JSON format.
 //!
+//! Vyper sources (`*.vy`) are part of the graph as well, but are not assigned to any `Solc`
+//! version set. Instead, they're compiled with the project's [`crate::Vyper`], the version of which
+//! must satisfy the `# @version` pragma of all vyper sources. Their output is added to the `solc`
+//! output, so vyper sources are cached and emit artifacts and build infos like solidity sources.
+//!
 //! ### Notes on [Import Path Resolution](https://docs.soliditylang.org/en/develop/path-resolution.html#path-resolution)
 //!
 //! In order to be able to support reproducible builds on all platforms, the Solidity compiler has
@@ -106,15 +111,16 @@ use crate::{
     artifacts::{Settings, VersionedFilteredSources, VersionedSources},
     buildinfo::RawBuildInfo,
     cache::ArtifactsCache,
-    error::Result,
-    filter::SparseOutputFilter,
+    error::{Result, SolcError},
+    filter::{FilteredSources, SparseOutputFilter},
     output::AggregatedCompilerOutput,
     report,
     resolver::GraphEdges,
-    ArtifactOutput, CompilerInput, Graph, Project, ProjectCompileOutput, ProjectPathsConfig, Solc,
-    Sources,
+    utils, ArtifactOutput, CompilerInput, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
+    Solc, Sources, Vyper,
 };
 use rayon::prelude::*;
+use semver::Version;
 use std::{collections::btree_map::BTreeMap, path::PathBuf, time::Instant};
 
 #[derive(Debug)]
@@ -124,6 +130,8 @@ pub struct ProjectCompiler<'a, T: ArtifactOutput> {
     project: &'a Project<T>,
     /// how to compile all the sources
     sources: CompilerSources,
+    /// the vyper sources and the `Vyper` to compile them with
+    vyper_sources: Option<(Vyper, Version, Sources)>,
     /// How to select solc [`crate::artifacts::CompilerOutput`] for files
     sparse_output: SparseOutputFilter,
 }
@@ -153,8 +161,14 @@ impl<'a, T: ArtifactOutput> ProjectCompiler<'a, T> {
     /// multiple `jobs`, see [`crate::Project::set_solc_jobs()`].
     #[cfg(all(feature = "svm-solc"))]
     pub fn with_sources(project: &'a Project<T>, sources: Sources) -> Result<Self> {
+        let vyper_sources = sources
+            .iter()
+            .filter(|(file, _)| utils::is_vyper_file(file))
+            .map(|(file, source)| (file.clone(), source.clone()))
+            .collect();
         let graph = Graph::resolve_sources(&project.paths, sources)?;
         let (versions, edges) = graph.into_sources_by_version(project.offline)?;
+        let vyper_sources = with_vyper(project, vyper_sources, &edges)?;
 
         let base_path = project.root();
         let sources_by_version = versions.get(&project.allowed_lib_paths, base_path)?;
@@ -167,7 +181,7 @@ impl<'a, T: ArtifactOutput> ProjectCompiler<'a, T> {
             CompilerSources::Sequential(sources_by_version)
         };
 
-        Ok(Self { edges, project, sources, sparse_output: Default::default() })
+        Ok(Self { edges, project, sources, vyper_sources, sparse_output: Default::default() })
     }
 
     /// Compiles the sources with a pinned `Solc` instance
@@ -178,10 +192,13 @@ impl<'a, T: ArtifactOutput> ProjectCompiler<'a, T> {
     ) -> Result<Self> {
         let version = solc.version()?;
         let (sources, edges) = Graph::resolve_sources(&project.paths, sources)?.into_sources();
+        let (vyper_sources, sources): (Sources, Sources) =
+            sources.into_iter().partition(|(file, _)| utils::is_vyper_file(file));
+        let vyper_sources = with_vyper(project, vyper_sources, &edges)?;
         let sources_by_version = BTreeMap::from([(solc, (version, sources))]);
         let sources = CompilerSources::Sequential(sources_by_version);
 
-        Ok(Self { edges, project, sources, sparse_output: Default::default() })
+        Ok(Self { edges, project, sources, vyper_sources, sparse_output: Default::default() })
     }
 
     /// Applies the specified filter to be applied when selecting solc output for
@@ -215,13 +232,18 @@ impl<'a, T: ArtifactOutput> ProjectCompiler<'a, T> {
     ///   - sets proper source unit names
     ///   - check cache
     fn preprocess(self) -> Result<PreprocessedState<'a, T>> {
-        let Self { edges, project, sources, sparse_output } = self;
+        let Self { edges, project, sources, vyper_sources, sparse_output } = self;
 
         let mut cache = ArtifactsCache::new(project, edges)?;
         // retain and compile only dirty sources and all their imports
         let sources = sources.filtered(&mut cache);
+        let vyper_sources = vyper_sources.map(|(vyper, version, sources)| {
+            cache.fill_content_hashes(&sources);
+            let sources = cache.filter(sources, &version);
+            (vyper, version, sources)
+        });
 
-        Ok(PreprocessedState { sources, cache, sparse_output })
+        Ok(PreprocessedState { sources, vyper_sources, cache, sparse_output })
     }
 }
 
@@ -232,6 +254,8 @@ impl<'a, T: ArtifactOutput> ProjectCompiler<'a, T> {
 struct PreprocessedState<'a, T: ArtifactOutput> {
     /// contains all sources to compile
     sources: FilteredCompilerSources,
+    /// contains all vyper sources to compile
+    vyper_sources: Option<(Vyper, Version, FilteredSources)>,
     /// cache that holds [CacheEntry] object if caching is enabled and the project is recompiled
     cache: ArtifactsCache<'a, T>,
     sparse_output: SparseOutputFilter,
@@ -240,7 +264,7 @@ struct PreprocessedState<'a, T: ArtifactOutput> {
 impl<'a, T: ArtifactOutput> PreprocessedState<'a, T> {
     /// advance to the next state by compiling all sources
     fn compile(self) -> Result<CompiledState<'a, T>> {
-        let PreprocessedState { sources, cache, sparse_output } = self;
+        let PreprocessedState { sources, vyper_sources, cache, sparse_output } = self;
         let project = cache.project();
         let mut output = sources.compile(
             &project.solc_config.settings,
@@ -249,6 +273,17 @@ impl<'a, T: ArtifactOutput> PreprocessedState<'a, T> {
             cache.graph(),
             project.build_info,
         )?;
+        if let Some((vyper, version, sources)) = vyper_sources {
+            compile_vyper(
+                &vyper,
+                version,
+                sources,
+                &project.solc_config.settings,
+                &project.paths,
+                project.build_info,
+                &mut output,
+            )?;
+        }
 
         // source paths get stripped before handing them over to solc, so solc never uses absolute
         // paths, instead `--base-path <root dir>` is set. this way any metadata that's derived from
@@ -421,6 +456,86 @@ impl FilteredCompilerSources {
     }
 }
 
+/// Returns the vyper sources together with the project's `Vyper` and its version, or `None` if
+/// there are no vyper sources
+///
+/// Returns an error if the version of `Vyper` doesn't satisfy the version pragma of all sources.
+fn with_vyper<T: ArtifactOutput>(
+    project: &Project<T>,
+    sources: Sources,
+    edges: &GraphEdges,
+) -> Result<Option<(Vyper, Version, Sources)>> {
+    if sources.is_empty() {
+        return Ok(None)
+    }
+    let vyper = project.vyper.clone().with_base_path(project.root());
+    let version = vyper.version()?;
+
+    let incompatible = sources
+        .keys()
+        .filter_map(|file| {
+            let req = edges.version_requirement(file)?;
+            (!req.matches(&version)).then(|| {
+                format!("{} ({})", utils::source_name(file, project.root()).display(), req)
+            })
+        })
+        .collect::<Vec<_>>();
+    if !incompatible.is_empty() {
+        return Err(SolcError::msg(format!(
+            "Discovered vyper sources incompatible with vyper {}:\n{}",
+            version,
+            incompatible.join("\n")
+        )))
+    }
+
+    Ok(Some((vyper, version, sources)))
+}
+
+/// Compiles the vyper sources and adds their output to the aggregated `solc` output
+///
+/// Like solidity sources, the dirty vyper sources are compiled together with the files they import.
+fn compile_vyper(
+    vyper: &Vyper,
+    version: Version,
+    filtered_sources: FilteredSources,
+    settings: &Settings,
+    paths: &ProjectPathsConfig,
+    create_build_info: bool,
+    aggregated: &mut AggregatedCompilerOutput,
+) -> Result<()> {
+    if filtered_sources.is_empty() {
+        tracing::trace!(
+            "skip vyper {} {} for empty sources set",
+            vyper.as_ref().display(),
+            version
+        );
+        return Ok(())
+    }
+
+    for input in CompilerInput::with_sources(filtered_sources.into()) {
+        let input = input.settings(settings.clone()).with_base_path(&paths.root);
+
+        tracing::trace!(
+            "calling vyper `{}` with {} sources {:?}",
+            version,
+            input.sources.len(),
+            input.sources.keys()
+        );
+        let output = vyper.compile(&input)?;
+        tracing::trace!("compiled input, output has error: {}", output.has_error());
+        tracing::trace!("received compiler output: {:?}", output.contracts.keys());
+
+        // if configured also create the build info
+        if create_build_info {
+            let build_info = RawBuildInfo::new(&input, &output, &version)?;
+            aggregated.build_infos.insert((input.language.clone(), version.clone()), build_info);
+        }
+
+        aggregated.extend(version.clone(), output);
+    }
+    Ok(())
+}
+
 /// Compiles the input set sequentially and returns an aggregated set of the solc `CompilerOutput`s
 fn compile_sequential(
     input: VersionedFilteredSources,
@@ -498,7 +613,9 @@ fn compile_sequential(
             // if configured also create the build info
             if create_build_info {
                 let build_info = RawBuildInfo::new(&input, &output, &version)?;
-                aggregated.build_infos.insert(version.clone(), build_info);
+                aggregated
+                    .build_infos
+                    .insert((input.language.clone(), version.clone()), build_info);
             }
 
             aggregated.extend(version.clone(), output);
@@ -609,7 +726,7 @@ fn compile_parallel(
         // if configured also create the build info
         if create_build_info {
             let build_info = RawBuildInfo::new(&input, &output, &version)?;
-            aggregated.build_infos.insert(version.clone(), build_info);
+            aggregated.build_infos.insert((input.language.clone(), version.clone()), build_info);
         }
         aggregated.extend(version, output);
     }
@@ -0,0 +1,196 @@
+//! Support for compiling vyper sources
+
+use crate::{
+    artifacts::{Source, VyperInput, VyperOutput},
+    compile::{compile_output, version_from_output},
+    error::{Result, SolcError},
+    utils, CompilerInput, CompilerOutput, Solc,
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The name of the `vyper` binary on the system
+pub const VYPER: &str = "vyper";
+
+/// Abstraction over `vyper` command line utility
+///
+/// By default the vyper path is configured as follows, with descending priority:
+///   1. `VYPER_PATH` environment variable
+///   2. `vyper` otherwise
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Vyper {
+    /// Path to the `vyper` executable
+    pub vyper: PathBuf,
+    /// The directory in which vyper is invoked, relative imports are resolved from here
+    pub base_path: Option<PathBuf>,
+    /// Additional arguments passed to the `vyper` exectuable
+    pub args: Vec<String>,
+}
+
+impl Default for Vyper {
+    fn default() -> Self {
+        if let Ok(vyper) = std::env::var("VYPER_PATH") {
+            return Vyper::new(vyper)
+        }
+        Vyper::new(VYPER)
+    }
+}
+
+impl fmt::Display for Vyper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.vyper.display())?;
+        if !self.args.is_empty() {
+            write!(f, " {}", self.args.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Vyper {
+    /// A new instance which points to `vyper`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Vyper { vyper: path.into(), base_path: None, args: Vec::new() }
+    }
+
+    /// Sets the directory in which vyper is invoked
+    #[must_use]
+    pub fn with_base_path(mut self, base_path: impl Into<PathBuf>) -> Self {
+        self.base_path = Some(base_path.into());
+        self
+    }
+
+    /// Adds an argument to pass to the `vyper` command.
+    #[must_use]
+    pub fn arg<T: Into<String>>(mut self, arg: T) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds multiple arguments to pass to the `vyper`.
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for arg in args {
+            self = self.arg(arg);
+        }
+        self
+    }
+
+    /// Parses the given source looking for the `# @version` pragma and returns the corresponding
+    /// SemVer version requirement.
+    pub fn source_version_req(source: &Source) -> Result<VersionReq> {
+        let version =
+            utils::find_vyper_version_pragma(&source.content).ok_or(SolcError::PragmaNotFound)?;
+        Self::version_req(version.as_str())
+    }
+
+    /// Returns the corresponding SemVer version requirement for the vyper version pragma
+    ///
+    /// Vyper follows the npm semver syntax, same as solidity, but also accepts the `==`
+    /// operator of python.
+    pub fn version_req(version: &str) -> Result<VersionReq> {
+        Solc::version_req(&version.trim().replace("==", "="))
+    }
+
+    /// Convenience function for compiling all vyper sources under the given path
+    pub fn compile_source(&self, path: impl AsRef<Path>) -> Result<CompilerOutput> {
+        let mut res: CompilerOutput = Default::default();
+        for input in CompilerInput::new(path)?.into_iter().filter(CompilerInput::is_vyper) {
+            let output = self.compile(&input)?;
+            res.merge(output)
+        }
+        Ok(res)
+    }
+
+    /// Run `vyper --standard-json` with the sources of the `CompilerInput` and return the output
+    /// as `CompilerOutput`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///  use ethers_solc::{CompilerInput, Vyper};
+    /// let vyper = Vyper::default();
+    /// let input = CompilerInput::new("./contracts")?.into_iter().find(|i| i.is_vyper()).unwrap();
+    /// let output = vyper.compile(&input)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile(&self, input: &CompilerInput) -> Result<CompilerOutput> {
+        let output: VyperOutput = serde_json::from_slice(&self.compile_output(input)?)?;
+        Ok(output.into())
+    }
+
+    /// Run `vyper --standard-json` and return vyper's raw output
+    pub fn compile_output(&self, input: &CompilerInput) -> Result<Vec<u8>> {
+        let mut cmd = Command::new(&self.vyper);
+        if let Some(ref base_path) = self.base_path {
+            cmd.current_dir(base_path);
+        }
+        let mut child = cmd
+            .args(&self.args)
+            .arg("--standard-json")
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| SolcError::io(err, &self.vyper))?;
+        let stdin = child.stdin.take().expect("Stdin exists.");
+        serde_json::to_writer(stdin, &VyperInput::new(input))?;
+        compile_output(child.wait_with_output().map_err(|err| SolcError::io(err, &self.vyper))?)
+    }
+
+    /// Returns the version from the configured `vyper`
+    pub fn version(&self) -> Result<Version> {
+        version_from_output(
+            Command::new(&self.vyper)
+                .arg("--version")
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .stdout(Stdio::piped())
+                .output()
+                .map_err(|err| SolcError::io(err, &self.vyper))?,
+        )
+    }
+}
+
+impl AsRef<Path> for Vyper {
+    fn as_ref(&self) -> &Path {
+        &self.vyper
+    }
+}
+
+impl<T: Into<PathBuf>> From<T> for Vyper {
+    fn from(vyper: T) -> Self {
+        Vyper::new(vyper.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_vyper_version_req() {
+        let source =
+            Source { content: "# @version ^0.3.7\n\ncount: public(uint256)\n".to_string() };
+        let req = Vyper::source_version_req(&source).unwrap();
+        assert!(req.matches(&Version::new(0, 3, 9)));
+        assert!(!req.matches(&Version::new(0, 4, 0)));
+
+        let req = Vyper::version_req("==0.3.7").unwrap();
+        assert!(req.matches(&Version::new(0, 3, 7)));
+        assert!(!req.matches(&Version::new(0, 3, 8)));
+
+        let req = Vyper::version_req("0.3.7").unwrap();
+        assert!(!req.matches(&Version::new(0, 3, 8)));
+    }
+}
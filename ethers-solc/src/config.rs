@@ -170,6 +170,23 @@ impl ProjectPathsConfig {
         }
     }
 
+    /// Resolves the path of a file imported by a vyper source in `cwd`
+    ///
+    /// Relative imports are resolved from `cwd`, all other imports from the project root, like
+    /// vyper does.
+    ///
+    /// Returns `None` if the import doesn't point to an existing vyper source, e.g. if it imports a
+    /// `.json` interface.
+    pub fn resolve_vyper_import(&self, cwd: &Path, import: &Path) -> Option<PathBuf> {
+        let component = import.components().next()?;
+        let path = if component == Component::CurDir || component == Component::ParentDir {
+            cwd.join(import)
+        } else {
+            self.root.join(import)
+        };
+        utils::canonicalize(path).ok()
+    }
+
     /// Attempts to find the path to the real solidity file that's imported via the given `import`
     /// path by applying the configured remappings and checking the library dirs
     ///
@@ -44,6 +44,10 @@ pub enum SolcError {
     #[cfg(feature = "project-util")]
     #[error(transparent)]
     FsExtra(#[from] fs_extra::error::Error),
+
+    #[cfg(feature = "watch")]
+    #[error(transparent)]
+    Notify(#[from] notify::Error),
 }
 
 impl SolcError {
@@ -9,6 +9,7 @@ mod artifact_output;
 pub mod buildinfo;
 pub mod cache;
 pub mod hh;
+pub mod linker;
 pub use artifact_output::*;
 
 pub mod resolver;
@@ -49,6 +50,11 @@ use std::path::{Path, PathBuf};
 #[cfg(feature = "project-util")]
 pub mod project_util;
 
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "watch")]
+pub use watch::ProjectWatcher;
+
 /// Represents a project workspace and handles `solc` compiling of all contracts in that workspace.
 #[derive(Debug)]
 pub struct Project<T: ArtifactOutput = ConfigurableArtifacts> {
@@ -58,6 +64,8 @@ pub struct Project<T: ArtifactOutput = ConfigurableArtifacts> {
     pub solc: Solc,
     /// How solc invocation should be configured.
     pub solc_config: SolcConfig,
+    /// Where to find vyper, only used if the project contains vyper sources
+    pub vyper: Vyper,
     /// Whether caching is enabled
     pub cached: bool,
     /// Whether to output build information with each solc call.
@@ -197,6 +205,33 @@ impl<T: ArtifactOutput> Project<T> {
         println!("cargo:rerun-if-changed={}", self.paths.sources.display())
     }
 
+    /// Watches the project's sources, tests, scripts, libraries and remapped directories and
+    /// recompiles the files affected by a change.
+    ///
+    /// The returned [`ProjectWatcher`] takes ownership of the project and yields the output of each
+    /// recompilation, see [`ProjectWatcher::next_output()`] and [`ProjectWatcher::into_stream()`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_solc::Project;
+    /// # async fn demo() {
+    /// let project = Project::builder().build().unwrap();
+    /// let mut watcher = project.watch().unwrap();
+    /// while let Some(output) = watcher.next_output().await {
+    ///     println!("{}", output.unwrap());
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "watch")]
+    pub fn watch(self) -> Result<ProjectWatcher<T>>
+    where
+        T: Send + Sync + 'static,
+        T::Artifact: Send,
+    {
+        ProjectWatcher::new(self)
+    }
+
     /// Attempts to compile the contracts found at the configured source location, see
     /// `ProjectPathsConfig::sources`.
     ///
@@ -206,6 +241,8 @@ impl<T: ArtifactOutput> Project<T> {
     /// NB: If the `svm` feature is enabled, this function will automatically detect
     /// solc versions across files.
     ///
+    /// Vyper sources (`*.vy`) are compiled with the project's [`Vyper`].
+    ///
     /// # Example
     ///
     /// ```
@@ -519,6 +556,8 @@ pub struct ProjectBuilder<T: ArtifactOutput = ConfigurableArtifacts> {
     solc: Option<Solc>,
     /// How solc invocation should be configured.
     solc_config: Option<SolcConfig>,
+    /// Where to find vyper
+    vyper: Option<Vyper>,
     /// Whether caching is enabled, default is true.
     cached: bool,
     /// Whether to output build information with each solc call.
@@ -545,6 +584,7 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
             paths: None,
             solc: None,
             solc_config: None,
+            vyper: None,
             cached: true,
             build_info: false,
             no_artifacts: false,
@@ -575,6 +615,13 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
         self
     }
 
+    /// Sets the `vyper` used to compile the vyper sources of the project
+    #[must_use]
+    pub fn vyper(mut self, vyper: impl Into<Vyper>) -> Self {
+        self.vyper = Some(vyper.into());
+        self
+    }
+
     #[must_use]
     pub fn ignore_error_code(mut self, code: u64) -> Self {
         self.ignored_error_codes.push(code);
@@ -674,6 +721,7 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
             paths,
             solc,
             solc_config,
+            vyper,
             cached,
             no_artifacts,
             auto_detect,
@@ -688,6 +736,7 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
             paths,
             solc,
             solc_config,
+            vyper,
             cached,
             no_artifacts,
             auto_detect,
@@ -725,6 +774,7 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
             paths,
             solc,
             solc_config,
+            vyper,
             cached,
             no_artifacts,
             auto_detect,
@@ -750,6 +800,7 @@ impl<T: ArtifactOutput> ProjectBuilder<T> {
             paths,
             solc,
             solc_config,
+            vyper: vyper.unwrap_or_default(),
             cached,
             build_info,
             no_artifacts,
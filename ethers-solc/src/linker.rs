@@ -0,0 +1,408 @@
+//! Automatic linking of contracts against the libraries they depend on
+//!
+//! Library addresses are not known before the libraries are deployed, so solc leaves placeholders
+//! in the bytecode of every contract that calls an external library function, see
+//! <https://docs.soliditylang.org/en/develop/using-the-compiler.html#library-linking>.
+//!
+//! The [`Linker`] collects the libraries a contract needs, orders them so that every library is
+//! deployed after the libraries it depends on, predicts their addresses and links the bytecode of
+//! the libraries and the contract.
+
+use crate::{
+    artifacts::{contract::CompactContractBytecode, CompactBytecode},
+    error::{bail, format_err, Result},
+    Artifact, ArtifactId, ArtifactOutput, ProjectCompileOutput,
+};
+use ethers_core::{
+    abi::Abi,
+    types::{Address, Bytes, U256},
+    utils::{get_contract_address, get_create2_address},
+};
+use semver::Version;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// Links contracts against the libraries they depend on.
+///
+/// Contracts and libraries are identified by their fully qualified name, `<source path>:<name>`,
+/// see [`ArtifactId::identifier()`]. A source file compiled with multiple solc versions yields one
+/// artifact per version for the same name, the linker only returns an error if such an ambiguous
+/// contract or library is actually needed.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Address;
+/// use ethers_solc::{linker::Linker, Project};
+/// # fn demo() -> ethers_solc::error::Result<()> {
+/// let project = Project::builder().build()?;
+/// let linker = Linker::from_output(project.compile()?, project.root());
+/// let sender = Address::repeat_byte(0x11);
+/// let output = linker.link_with_nonce("src/Greeter.sol:Greeter", sender, 0u64)?;
+/// for library in &output.libraries {
+///     println!("deploy {} at {:?}", library.name, library.address);
+/// }
+/// let bytecode = output.contract.bytecode.unwrap().object.into_bytes().unwrap();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Linker {
+    /// fully qualified name -> all artifacts with that name, one per solc version
+    contracts: BTreeMap<String, Vec<(Version, CompactContractBytecode)>>,
+}
+
+impl Linker {
+    /// Creates a new linker for the given contracts
+    ///
+    /// The source paths of the contracts must match the paths solc uses in link references, which
+    /// are relative to the project's root.
+    pub fn new(contracts: impl IntoIterator<Item = (ArtifactId, CompactContractBytecode)>) -> Self {
+        let mut linker = Self::default();
+        for (id, contract) in contracts {
+            linker.contracts.entry(id.identifier()).or_default().push((id.version, contract));
+        }
+        linker
+    }
+
+    /// Creates a new linker for all artifacts of the compiler output of the project at `root`
+    pub fn from_output<T: ArtifactOutput>(
+        output: ProjectCompileOutput<T>,
+        root: impl AsRef<Path>,
+    ) -> Self {
+        Self::new(
+            output
+                .with_stripped_file_prefixes(root)
+                .into_artifacts()
+                .map(|(id, artifact)| (id, artifact.into_contract_bytecode())),
+        )
+    }
+
+    /// Returns the contract with the given fully qualified name
+    ///
+    /// Returns an error if there is no such contract, or if it was compiled with multiple solc
+    /// versions.
+    pub fn get(&self, name: impl AsRef<str>) -> Result<&CompactContractBytecode> {
+        let name = name.as_ref();
+        match self.contracts.get(name).map(Vec::as_slice) {
+            None | Some([]) => bail!("No artifact found for `{}`", name),
+            Some([(_, contract)]) => Ok(contract),
+            Some(contracts) => {
+                let versions =
+                    contracts.iter().map(|(version, _)| version.to_string()).collect::<Vec<_>>();
+                bail!(
+                    "Multiple artifacts found for `{}`, compiled with solc versions {}",
+                    name,
+                    versions.join(", ")
+                )
+            }
+        }
+    }
+
+    /// Returns the fully qualified names of all libraries the contract depends on, directly or
+    /// transitively, in the order they need to be deployed.
+    ///
+    /// Every library comes after all the libraries it depends on.
+    ///
+    /// Returns an error if the contract or a library is unknown or ambiguous, see [`Self::get()`],
+    /// or if the libraries depend on each other in a cycle.
+    pub fn library_deploy_order(&self, name: impl AsRef<str>) -> Result<Vec<String>> {
+        let name = name.as_ref();
+        let mut order = Vec::new();
+        let mut visiting = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        self.visit(name, &mut visiting, &mut visited, &mut order)?;
+        // the contract itself is the last element
+        order.pop();
+        Ok(order)
+    }
+
+    /// Post-order traversal of the library dependencies of the contract
+    fn visit(
+        &self,
+        name: &str,
+        visiting: &mut BTreeSet<String>,
+        visited: &mut BTreeSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if visited.contains(name) {
+            return Ok(())
+        }
+        if !visiting.insert(name.to_string()) {
+            bail!("Cyclic library dependency on `{}`", name)
+        }
+        let contract = self.get(name)?;
+
+        for (file, libraries) in contract.all_link_references() {
+            for library in libraries.keys() {
+                self.visit(&format!("{}:{}", file, library), visiting, visited, order)?;
+            }
+        }
+
+        visiting.remove(name);
+        visited.insert(name.to_string());
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    /// Links the contract and the libraries it needs, assuming that `sender` deploys the libraries
+    /// with regular `CREATE` transactions in the returned order, starting at `nonce`.
+    pub fn link_with_nonce(
+        &self,
+        name: impl AsRef<str>,
+        sender: Address,
+        nonce: impl Into<U256>,
+    ) -> Result<LinkOutput> {
+        let mut nonce = nonce.into();
+        self.link(name.as_ref(), |_| {
+            let address = get_contract_address(sender, nonce);
+            nonce += U256::one();
+            address
+        })
+    }
+
+    /// Links the contract and the libraries it needs, assuming that the `deployer` contract
+    /// deploys every library with `CREATE2` and the given `salt`.
+    ///
+    /// Note that this does not deploy the libraries with `CREATE2` itself: the deployments of the
+    /// returned [`LinkOutput`] need to be sent through the `deployer`.
+    pub fn link_with_create2(
+        &self,
+        name: impl AsRef<str>,
+        deployer: Address,
+        salt: impl Into<Bytes>,
+    ) -> Result<LinkOutput> {
+        let salt = salt.into();
+        self.link(name.as_ref(), |init_code| {
+            get_create2_address(deployer, salt.clone(), init_code.clone())
+        })
+    }
+
+    /// Links the libraries in deploy order, `address` is called with the linked creation code of
+    /// every library and returns the address the library will be deployed at.
+    fn link(&self, name: &str, mut address: impl FnMut(&Bytes) -> Address) -> Result<LinkOutput> {
+        let mut linked = BTreeMap::new();
+        let mut libraries = Vec::new();
+
+        for library_name in self.library_deploy_order(name)? {
+            let library = self.link_contract(&library_name, &linked)?;
+            let bytecode = library
+                .bytecode
+                .and_then(|bytecode| bytecode.object.into_bytes())
+                .ok_or_else(|| format_err!("Library `{}` has no bytecode", library_name))?;
+            let library_address = address(&bytecode);
+            linked.insert(library_name.clone(), library_address);
+            libraries.push(LibraryDeployment {
+                name: library_name,
+                address: library_address,
+                abi: library.abi.unwrap_or_default(),
+                bytecode,
+            });
+        }
+
+        let contract = self.link_contract(name, &linked)?;
+        Ok(LinkOutput { libraries, contract })
+    }
+
+    /// Returns the contract with all its library placeholders replaced by the addresses in
+    /// `libraries`
+    fn link_contract(
+        &self,
+        name: &str,
+        libraries: &BTreeMap<String, Address>,
+    ) -> Result<CompactContractBytecode> {
+        let mut contract = self.get(name)?.clone();
+
+        let link = |bytecode: &mut CompactBytecode| -> Result<()> {
+            for (file, file_libraries) in bytecode.link_references.clone() {
+                for library in file_libraries.keys() {
+                    let library_name = format!("{}:{}", file, library);
+                    let address = libraries
+                        .get(&library_name)
+                        .ok_or_else(|| format_err!("Library `{}` is not linked", library_name))?;
+                    bytecode.link(&file, library, *address);
+                }
+            }
+            if bytecode.object.is_unlinked() {
+                bail!("Failed to link `{}`", name)
+            }
+            Ok(())
+        };
+
+        if let Some(bytecode) = contract.bytecode.as_mut() {
+            link(bytecode)?;
+        }
+        if let Some(bytecode) =
+            contract.deployed_bytecode.as_mut().and_then(|deployed| deployed.bytecode.as_mut())
+        {
+            link(bytecode)?;
+        }
+        Ok(contract)
+    }
+}
+
+/// A library that needs to be deployed, see [`LinkOutput`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryDeployment {
+    /// The fully qualified name of the library
+    pub name: String,
+    /// The predicted address of the library
+    pub address: Address,
+    /// The ABI of the library
+    pub abi: Abi,
+    /// The linked creation code of the library
+    pub bytecode: Bytes,
+}
+
+/// The result of linking a contract, see [`Linker`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkOutput {
+    /// The libraries the contract needs, in the order they need to be deployed
+    pub libraries: Vec<LibraryDeployment>,
+    /// The fully linked contract
+    pub contract: CompactContractBytecode,
+}
+
+impl LinkOutput {
+    /// Returns the predicted addresses of all libraries by their fully qualified name
+    pub fn library_addresses(&self) -> BTreeMap<String, Address> {
+        self.libraries.iter().map(|library| (library.name.clone(), library.address)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        artifacts::{BytecodeObject, Offsets},
+        utils,
+    };
+    use std::path::PathBuf;
+
+    /// Returns a contract that calls the given libraries of `src/Lib.sol`
+    fn contract(libraries: &[&str]) -> CompactContractBytecode {
+        let mut object = "6080".to_string();
+        let mut link_references = BTreeMap::new();
+        for library in libraries {
+            let start = object.len() as u32 / 2 + 1;
+            object.push_str("73");
+            object.push_str(&format!(
+                "__{}__",
+                utils::library_hash_placeholder(format!("src/Lib.sol:{}", library))
+            ));
+            link_references
+                .entry("src/Lib.sol".to_string())
+                .or_insert_with(BTreeMap::new)
+                .insert(library.to_string(), vec![Offsets { start, length: 20 }]);
+        }
+        object.push_str("00");
+        let object = if link_references.is_empty() {
+            BytecodeObject::Bytecode(hex::decode(object).unwrap().into())
+        } else {
+            BytecodeObject::Unlinked(object)
+        };
+
+        CompactContractBytecode {
+            abi: None,
+            bytecode: Some(CompactBytecode { object, source_map: None, link_references }),
+            deployed_bytecode: None,
+        }
+    }
+
+    fn linker(contracts: Vec<(&str, &str, CompactContractBytecode)>) -> Linker {
+        Linker::new(contracts.into_iter().map(|(source, name, contract)| {
+            let id = ArtifactId {
+                path: PathBuf::from(format!("out/{}.json", name)),
+                name: name.to_string(),
+                source: PathBuf::from(source),
+                version: Version::new(0, 8, 15),
+            };
+            (id, contract)
+        }))
+    }
+
+    #[test]
+    fn can_link_libraries_in_deploy_order() {
+        let linker = linker(vec![
+            ("src/Lib.sol", "LibA", contract(&[])),
+            ("src/Lib.sol", "LibB", contract(&["LibA"])),
+            ("src/Lib.sol", "LibC", contract(&[])),
+            ("src/Greeter.sol", "Greeter", contract(&["LibB", "LibA"])),
+        ]);
+
+        let order = linker.library_deploy_order("src/Greeter.sol:Greeter").unwrap();
+        assert_eq!(order, vec!["src/Lib.sol:LibA", "src/Lib.sol:LibB"]);
+
+        let sender = Address::repeat_byte(0x11);
+        let output = linker.link_with_nonce("src/Greeter.sol:Greeter", sender, 3u64).unwrap();
+        let lib_a = get_contract_address(sender, 3u64);
+        let lib_b = get_contract_address(sender, 4u64);
+        assert_eq!(
+            output.library_addresses(),
+            BTreeMap::from([
+                ("src/Lib.sol:LibA".to_string(), lib_a),
+                ("src/Lib.sol:LibB".to_string(), lib_b),
+            ])
+        );
+
+        let lib_b_code = &output.libraries[1].bytecode;
+        assert!(hex::encode(lib_b_code).contains(&hex::encode(lib_a)));
+
+        let code = output.contract.bytecode.unwrap().object.into_bytes().unwrap();
+        assert!(hex::encode(&code).contains(&hex::encode(lib_a)));
+        assert!(hex::encode(&code).contains(&hex::encode(lib_b)));
+    }
+
+    #[test]
+    fn can_link_with_create2() {
+        let linker = linker(vec![
+            ("src/Lib.sol", "LibA", contract(&[])),
+            ("src/Greeter.sol", "Greeter", contract(&["LibA"])),
+        ]);
+
+        let deployer = Address::repeat_byte(0x11);
+        let salt = Bytes::from([0u8; 32]);
+        let output =
+            linker.link_with_create2("src/Greeter.sol:Greeter", deployer, salt.clone()).unwrap();
+        let library = &output.libraries[0];
+        assert_eq!(library.address, get_create2_address(deployer, salt, library.bytecode.clone()));
+    }
+
+    #[test]
+    fn detects_cyclic_and_missing_libraries() {
+        let linker = linker(vec![
+            ("src/Lib.sol", "LibA", contract(&["LibB"])),
+            ("src/Lib.sol", "LibB", contract(&["LibA"])),
+            ("src/Greeter.sol", "Greeter", contract(&["LibA", "LibC"])),
+        ]);
+        assert!(linker.library_deploy_order("src/Lib.sol:LibA").is_err());
+        assert!(linker.library_deploy_order("src/Greeter.sol:Greeter").is_err());
+        assert!(linker.link_with_nonce("src/Greeter.sol:Missing", Address::zero(), 0u64).is_err());
+    }
+
+    #[test]
+    fn reports_ambiguous_artifacts_only_when_needed() {
+        let id = |source: &str, name: &str, version| ArtifactId {
+            path: PathBuf::from(format!("out/{}.json", name)),
+            name: name.to_string(),
+            source: PathBuf::from(source),
+            version,
+        };
+        let linker = Linker::new(vec![
+            (id("src/Lib.sol", "LibA", Version::new(0, 8, 15)), contract(&[])),
+            (id("src/Lib.sol", "LibB", Version::new(0, 8, 10)), contract(&[])),
+            (id("src/Lib.sol", "LibB", Version::new(0, 8, 15)), contract(&[])),
+            (id("src/Greeter.sol", "Greeter", Version::new(0, 8, 15)), contract(&["LibA"])),
+            (id("src/Other.sol", "Other", Version::new(0, 8, 15)), contract(&["LibB"])),
+        ]);
+
+        let output = linker.link_with_nonce("src/Greeter.sol:Greeter", Address::zero(), 0u64);
+        assert_eq!(output.unwrap().libraries.len(), 1);
+
+        let err = linker.link_with_nonce("src/Other.sol:Other", Address::zero(), 0u64).unwrap_err();
+        assert!(err.to_string().contains("0.8.10, 0.8.15"), "{}", err);
+        assert!(linker.get("src/Lib.sol:LibB").is_err());
+    }
+}
@@ -349,6 +349,18 @@ impl Graph {
 
             for import in node.data.imports.iter() {
                 let import_path = import.data().path();
+                if utils::is_vyper_file(&path) {
+                    if let Some(import) = paths.resolve_vyper_import(cwd, import_path) {
+                        add_node(&mut unresolved, &mut index, &mut resolved_imports, import)?;
+                    } else {
+                        tracing::trace!(
+                            "skipping untracked vyper import {:?} of {:?}",
+                            import_path,
+                            node.path
+                        )
+                    }
+                    continue
+                }
                 match paths.resolve_import(cwd, import_path) {
                     Ok(import) => {
                         add_node(&mut unresolved, &mut index, &mut resolved_imports, import)?;
@@ -398,6 +410,8 @@ impl Graph {
     ///
     /// First we determine the compatible version for each input file (from sources and test folder,
     /// see `Self::resolve`) and then we add all resolved library imports.
+    ///
+    /// Vyper files are not included in the solc version sets.
     pub fn into_sources_by_version(self, offline: bool) -> Result<(VersionedSources, GraphEdges)> {
         /// insert the imports of the given node into the sources map
         /// There can be following graph:
@@ -530,6 +544,10 @@ impl Graph {
         let mut all_candidates = Vec::with_capacity(self.edges.num_input_files);
         // walking through the node's dep tree and filtering the versions along the way
         for idx in 0..self.edges.num_input_files {
+            if utils::is_vyper_file(&self.node(idx).path) {
+                // vyper files are not compiled with solc
+                continue
+            }
             let mut candidates = all_versions.iter().collect::<Vec<_>>();
             // remove all incompatible versions from the candidates list by checking the node and
             // all its imports
@@ -911,6 +929,31 @@ src/Dapp.t.sol >=0.6.6
         );
     }
 
+    #[test]
+    fn can_resolve_vyper_imports() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = utils::canonicalize(tmp.path()).unwrap();
+        let paths = ProjectPathsConfig::dapptools(&root).unwrap();
+        std::fs::create_dir_all(&paths.sources).unwrap();
+        std::fs::create_dir_all(root.join("interfaces")).unwrap();
+        std::fs::write(
+            paths.sources.join("Vault.vy"),
+            "# @version ^0.3.7\nimport interfaces.IVault as IVault\nfrom . import Helper\n\
+             from vyper.interfaces import ERC20\nimport interfaces.IToken as IToken\n",
+        )
+        .unwrap();
+        std::fs::write(paths.sources.join("Helper.vy"), "# @version ^0.3.7\n").unwrap();
+        std::fs::write(root.join("interfaces/IVault.vy"), "# @version ^0.3.7\n").unwrap();
+        // json interfaces are not tracked
+        std::fs::write(root.join("interfaces/IToken.json"), "[]").unwrap();
+
+        let graph = Graph::resolve(&paths).unwrap();
+        assert_eq!(
+            graph.imports(paths.sources.join("Vault.vy")),
+            HashSet::from([&paths.sources.join("Helper.vy"), &root.join("interfaces/IVault.vy")])
+        );
+    }
+
     #[test]
     #[cfg(not(target_os = "windows"))]
     fn can_print_hardhat_sample_graph() {
@@ -1,4 +1,4 @@
-use crate::{utils, Solc};
+use crate::{utils, Solc, Vyper};
 use semver::VersionReq;
 use solang_parser::pt::{
     ContractPart, ContractTy, FunctionAttribute, FunctionDefinition, Import, Loc, SourceUnitPart,
@@ -38,7 +38,12 @@ impl SolData {
     ///
     /// This will attempt to parse the solidity AST and extract the imports and version pragma. If
     /// parsing fails, we'll fall back to extract that info via regex
+    ///
+    /// For vyper files only the `# @version` pragma is extracted, see [`Self::parse_vyper()`]
     pub fn parse(content: &str, file: &Path) -> Self {
+        if utils::is_vyper_file(file) {
+            return Self::parse_vyper(content)
+        }
         let mut version = None;
         let mut experimental = None;
         let mut imports = Vec::<SolDataUnit<SolImport>>::new();
@@ -124,6 +129,29 @@ impl SolData {
         Self { version_req, version, experimental, imports, license, libraries, contracts }
     }
 
+    /// Extracts the version pragma, `# @version ^0.3.7` or `#pragma version ^0.3.7`, and the
+    /// imports from a vyper source
+    ///
+    /// Imports are converted to the paths of the imported vyper files, `import a.b as c` to
+    /// `a/b.vy` and `from . import d` to `./d.vy`. Imports of builtin interfaces, e.g.
+    /// `from vyper.interfaces import ERC20`, are skipped.
+    pub fn parse_vyper(content: &str) -> Self {
+        let version = capture_outer_and_inner(content, &utils::RE_VYPER_VERSION, &["version"])
+            .first()
+            .map(|(cap, name)| SolDataUnit::new(name.as_str().to_owned(), cap.range()));
+        let version_req = version.as_ref().and_then(|v| Vyper::version_req(v.data()).ok());
+
+        Self {
+            version_req,
+            version,
+            experimental: None,
+            imports: capture_vyper_imports(content),
+            license: None,
+            libraries: Vec::new(),
+            contracts: Vec::new(),
+        }
+    }
+
     /// Returns `true` if the solidity file associated with this type contains a solidity library
     /// that won't be inlined
     pub fn has_link_references(&self) -> bool {
@@ -288,6 +316,58 @@ pub fn capture_imports(content: &str) -> Vec<SolDataUnit<SolImport>> {
     imports
 }
 
+/// Returns all imports of vyper files in the vyper source
+fn capture_vyper_imports(content: &str) -> Vec<SolDataUnit<SolImport>> {
+    let mut imports = Vec::new();
+    for cap in utils::RE_VYPER_IMPORT.captures_iter(content) {
+        let statement = cap.get(0).unwrap();
+        let modules = match (cap.name("path"), cap.name("module"), cap.name("names")) {
+            (Some(path), _, _) => vec![path.as_str().to_string()],
+            (None, Some(module), Some(names)) => names
+                .as_str()
+                .split(',')
+                .filter_map(|name| name.split_whitespace().next())
+                .map(|name| {
+                    let module = module.as_str();
+                    if module.ends_with('.') {
+                        format!("{}{}", module, name)
+                    } else {
+                        format!("{}.{}", module, name)
+                    }
+                })
+                .collect(),
+            _ => continue,
+        };
+        for module in modules {
+            if let Some(path) = vyper_module_path(&module) {
+                imports.push(SolDataUnit::new(SolImport::new(path), statement.range()));
+            }
+        }
+    }
+    imports
+}
+
+/// Converts a vyper module, `a.b` or `..a`, to the path of its source file, `a/b.vy` or `../a.vy`
+///
+/// Returns `None` for the builtin `vyper` and `ethereum` modules.
+fn vyper_module_path(module: &str) -> Option<PathBuf> {
+    let name = module.trim_start_matches('.');
+    let level = module.len() - name.len();
+    let mut path = match level {
+        0 => {
+            if matches!(name.split('.').next(), Some("vyper") | Some("ethereum")) {
+                return None
+            }
+            PathBuf::new()
+        }
+        1 => PathBuf::from("."),
+        _ => (1..level).map(|_| "..").collect(),
+    };
+    path.extend(name.split('.'));
+    path.set_extension("vy");
+    Some(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,4 +422,43 @@ import { FloatMath as Math, Math as FloatMath } from "./Math.sol";
             ]
         );
     }
+
+    #[test]
+    fn can_parse_vyper_version_pragma() {
+        let content = r#"# @version ^0.3.7
+from vyper.interfaces import ERC20
+
+implements: ERC20
+"#;
+        let data = SolData::parse(content, Path::new("contracts/Token.vy"));
+        assert_eq!(data.version.as_ref().map(|v| v.data().as_str()), Some("^0.3.7"));
+        assert_eq!(data.version.as_ref().map(|v| v.loc()), Some(0..17));
+        assert!(data.imports.is_empty());
+        assert!(data.version_req.unwrap().matches(&semver::Version::new(0, 3, 7)));
+    }
+
+    #[test]
+    fn can_parse_vyper_imports() {
+        let content = r#"# @version ^0.3.7
+from vyper.interfaces import ERC20
+import interfaces.IVault as IVault
+from . import Helper
+from .. import Token, Pool as P  # comment
+from .lib import math
+
+    # import nested.Ignored as Ignored
+"#;
+        let data = SolData::parse(content, Path::new("contracts/Vault.vy"));
+        let imports = data.imports.iter().map(|i| i.data().path().clone()).collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            vec![
+                PathBuf::from("interfaces/IVault.vy"),
+                PathBuf::from("./Helper.vy"),
+                PathBuf::from("../Token.vy"),
+                PathBuf::from("../Pool.vy"),
+                PathBuf::from("./lib/math.vy"),
+            ]
+        );
+    }
 }
@@ -1,4 +1,15 @@
-use std::{fmt, fmt::Write, iter::Peekable, str::CharIndices};
+use crate::{
+    artifacts::{contract::Contract, BytecodeObject, CompactBytecode, SourceFile, Sources},
+    error::{format_err, SolcError},
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fmt::Write,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::CharIndices,
+};
 
 type Spanned<Token, Loc, Error> = Result<(Token, Loc), Error>;
 
@@ -497,6 +508,213 @@ pub fn parse(input: &str) -> Result<SourceMap, SyntaxError> {
     Parser::new(input).collect()
 }
 
+/// Resolves program counters of a bytecode to the location in the solidity source that the
+/// instruction at that program counter was generated from.
+///
+/// The source map has one [`SourceElement`] per instruction, so the resolver first maps the program
+/// counter to the index of the instruction, skipping the immediate values of `PUSH` instructions.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_solc::{artifacts::Source, sourcemap::PcSourceResolver, Solc};
+/// # fn demo() -> ethers_solc::error::Result<()> {
+/// let sources = Source::read_all_from("./contracts")?;
+/// let output = Solc::default().compile_source("./contracts")?;
+/// let contract = &output.contracts["./contracts/Greeter.sol"]["Greeter"];
+/// let resolver = PcSourceResolver::from_contract(contract, &sources, &output.sources)?;
+/// if let Some(location) = resolver.resolve(0x2a) {
+///     println!("{}: {}", location, location.snippet);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PcSourceResolver {
+    /// program counter -> instruction index
+    pc_to_instruction: BTreeMap<usize, usize>,
+    source_map: SourceMap,
+    /// source index -> source file
+    sources: BTreeMap<u32, IndexedSource>,
+}
+
+impl PcSourceResolver {
+    /// Creates a new resolver for the given bytecode and its source map
+    ///
+    /// `sources` are the path and the content of every source file, keyed by the source index
+    /// assigned by the compiler, see [`SourceFile::id`].
+    pub fn new(
+        code: impl AsRef<[u8]>,
+        source_map: SourceMap,
+        sources: impl IntoIterator<Item = (u32, PathBuf, String)>,
+    ) -> Self {
+        let sources = sources
+            .into_iter()
+            .map(|(id, path, content)| (id, IndexedSource::new(path, content)))
+            .collect();
+        Self { pc_to_instruction: pc_to_instruction(code.as_ref()), source_map, sources }
+    }
+
+    /// Creates a new resolver for the bytecode
+    ///
+    /// The source index of every file is looked up in `source_files`, which are the
+    /// [`CompilerOutput::sources`](crate::CompilerOutput::sources) of the compiler run that
+    /// produced the bytecode. Source files that are missing in `sources` can't be resolved.
+    ///
+    /// The bytecode does not need to be linked.
+    pub fn from_bytecode(
+        bytecode: &CompactBytecode,
+        sources: &Sources,
+        source_files: &BTreeMap<String, SourceFile>,
+    ) -> Result<Self, SolcError> {
+        let source_map = bytecode
+            .source_map()
+            .ok_or_else(|| format_err!("Bytecode has no source map"))?
+            .map_err(|err| format_err!("Failed to parse source map: {}", err))?;
+        let code = bytecode_to_code(&bytecode.object);
+
+        let sources = source_files.iter().filter_map(|(name, file)| {
+            let path = PathBuf::from(name);
+            let content = sources.get(&path)?.content.clone();
+            Some((file.id, path, content))
+        });
+        Ok(Self::new(code, source_map, sources))
+    }
+
+    /// Creates a new resolver for the deployed bytecode of the contract
+    ///
+    /// See also [`Self::from_bytecode()`]
+    pub fn from_contract(
+        contract: &Contract,
+        sources: &Sources,
+        source_files: &BTreeMap<String, SourceFile>,
+    ) -> Result<Self, SolcError> {
+        let bytecode = contract
+            .evm
+            .as_ref()
+            .and_then(|evm| evm.deployed_bytecode.as_ref())
+            .and_then(|deployed| deployed.bytecode.clone())
+            .ok_or_else(|| format_err!("Contract has no deployed bytecode"))?;
+        Self::from_bytecode(&bytecode.into(), sources, source_files)
+    }
+
+    /// Returns the index of the instruction at the program counter
+    ///
+    /// Returns `None` if the program counter points to the immediate value of a `PUSH` instruction
+    /// or is out of bounds.
+    pub fn instruction_index(&self, pc: usize) -> Option<usize> {
+        self.pc_to_instruction.get(&pc).copied()
+    }
+
+    /// Returns the source map element of the instruction at the program counter
+    pub fn source_element(&self, pc: usize) -> Option<&SourceElement> {
+        self.source_map.get(self.instruction_index(pc)?)
+    }
+
+    /// Returns the source location of the instruction at the program counter
+    ///
+    /// Returns `None` if the instruction is not mapped to a source file, which is the case for
+    /// compiler generated code, or if the source file is unknown.
+    pub fn resolve(&self, pc: usize) -> Option<PcSourceLocation<'_>> {
+        let element = self.source_element(pc)?;
+        let source = self.sources.get(&element.index?)?;
+        let (line, column) = source.line_column(element.offset)?;
+        let snippet = source.content.get(element.offset..element.offset + element.length)?;
+        Some(PcSourceLocation { file: &source.path, line, column, snippet, element })
+    }
+}
+
+/// The location in the solidity source of an instruction, see [`PcSourceResolver::resolve()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcSourceLocation<'a> {
+    /// The source file
+    pub file: &'a Path,
+    /// The line of the start of the source range, starting at 1
+    pub line: usize,
+    /// The column of the start of the source range, starting at 1
+    pub column: usize,
+    /// The source code of the source range
+    pub snippet: &'a str,
+    /// The source map element of the instruction
+    pub element: &'a SourceElement,
+}
+
+impl<'a> fmt::Display for PcSourceLocation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+/// A source file with the byte offsets of its lines
+#[derive(Debug, Clone)]
+struct IndexedSource {
+    path: PathBuf,
+    content: String,
+    line_starts: Vec<usize>,
+}
+
+impl IndexedSource {
+    fn new(path: PathBuf, content: String) -> Self {
+        let line_starts =
+            std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Self { path, content, line_starts }
+    }
+
+    /// Returns the 1-based line and column of the byte offset
+    fn line_column(&self, offset: usize) -> Option<(usize, usize)> {
+        if offset > self.content.len() {
+            return None
+        }
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.content.get(line_start..offset)?.chars().count() + 1;
+        Some((line, column))
+    }
+}
+
+/// Returns the code of the bytecode object.
+///
+/// Library placeholders of unlinked bytecode are zeroed, they are the immediate value of a `PUSH20`
+/// so this does not change the instructions.
+fn bytecode_to_code(object: &BytecodeObject) -> Vec<u8> {
+    match object {
+        BytecodeObject::Bytecode(bytes) => bytes.to_vec(),
+        BytecodeObject::Unlinked(unlinked) => {
+            let unlinked = unlinked.strip_prefix("0x").unwrap_or(unlinked);
+            unlinked
+                .as_bytes()
+                .chunks(2)
+                .map(|byte| {
+                    std::str::from_utf8(byte)
+                        .ok()
+                        .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                        .unwrap_or_default()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Maps the program counter of every instruction in the code to the index of the instruction
+fn pc_to_instruction(code: &[u8]) -> BTreeMap<usize, usize> {
+    const PUSH1: u8 = 0x60;
+    const PUSH32: u8 = 0x7f;
+
+    let mut map = BTreeMap::new();
+    let mut pc = 0;
+    let mut instruction = 0;
+    while pc < code.len() {
+        map.insert(pc, instruction);
+        let op = code[pc];
+        if (PUSH1..=PUSH32).contains(&op) {
+            pc += (op - PUSH1 + 1) as usize;
+        }
+        pc += 1;
+        instruction += 1;
+    }
+    map
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,4 +743,64 @@ mod tests {
         let _map = parser.collect::<Result<SourceMap, _>>().unwrap();
         assert_eq!(out, s);
     }
+
+    #[test]
+    fn can_map_pc_to_instruction() {
+        // PUSH1 0x80 PUSH1 0x40 MSTORE PUSH32 <32 bytes> POP STOP
+        let mut code = vec![0x60, 0x80, 0x60, 0x40, 0x52, 0x7f];
+        code.extend([0xff; 32]);
+        code.extend([0x50, 0x00]);
+
+        let map = pc_to_instruction(&code);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.get(&2), Some(&1));
+        assert_eq!(map.get(&4), Some(&2));
+        assert_eq!(map.get(&5), Some(&3));
+        assert_eq!(map.get(&6), None);
+        assert_eq!(map.get(&38), Some(&4));
+        assert_eq!(map.get(&39), Some(&5));
+        assert_eq!(map.len(), 6);
+    }
+
+    #[test]
+    fn can_resolve_pc() {
+        let content = "contract A {\n    uint256 x;\n    function f() public { x = 1; }\n}\n";
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0x00];
+        let source_map = parse("0:63:0:-;17:10;54:5;54:5").unwrap();
+        let resolver = PcSourceResolver::new(
+            code,
+            source_map,
+            vec![(0, PathBuf::from("A.sol"), content.to_string())],
+        );
+
+        let location = resolver.resolve(0).unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+
+        let location = resolver.resolve(2).unwrap();
+        assert_eq!(location.file, Path::new("A.sol"));
+        assert_eq!((location.line, location.column), (2, 5));
+        assert_eq!(location.snippet, "uint256 x;");
+        assert_eq!(location.to_string(), "A.sol:2:5");
+
+        let location = resolver.resolve(4).unwrap();
+        assert_eq!((location.line, location.column), (3, 27));
+        assert_eq!(location.snippet, "x = 1");
+
+        // immediate value of `PUSH1`
+        assert!(resolver.resolve(1).is_none());
+        assert_eq!(resolver.instruction_index(5), Some(3));
+    }
+
+    #[test]
+    fn can_zero_library_placeholders() {
+        let object = BytecodeObject::Unlinked(
+            "0x73__$a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5$__6000".to_string(),
+        );
+        let code = bytecode_to_code(&object);
+        assert_eq!(code.len(), 23);
+        assert_eq!(code[0], 0x73);
+        assert_eq!(&code[21..], &[0x60, 0x00]);
+        assert_eq!(pc_to_instruction(&code).len(), 2);
+    }
 }
@@ -37,6 +37,24 @@ pub static RE_SOL_PRAGMA_VERSION: Lazy<Regex> =
 pub static RE_SOL_SDPX_LICENSE_IDENTIFIER: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"///?\s*SPDX-License-Identifier:\s*(?P<license>.+)").unwrap());
 
+/// A regex that matches the version part of a vyper version pragma
+/// as follows: `# @version ^0.3.7` or `#pragma version ^0.3.7` => `^0.3.7`
+pub static RE_VYPER_VERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^[ \t]*#[ \t]*(?:@version|pragma[ \t]+version)[ \t]+(?P<version>[^\r\n]+?)[ \t]*\r?$",
+    )
+    .unwrap()
+});
+
+/// A regex that matches a vyper import statement, `import a.b as c` or `from .a import b, c as d`,
+/// with the named groups "path" for plain imports and "module", "names" for `from` imports
+pub static RE_VYPER_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^[ \t]*(?:from[ \t]+(?P<module>\.*[\w.]*)[ \t]+import[ \t]+(?P<names>[\w \t,]+?)|import[ \t]+(?P<path>[\w.]+)(?:[ \t]+as[ \t]+\w+)?)[ \t]*(?:#[^\r\n]*)?\r?$",
+    )
+    .unwrap()
+});
+
 /// A regex used to remove extra lines in flatenned files
 pub static RE_THREE_OR_MORE_NEWLINES: Lazy<Regex> = Lazy::new(|| Regex::new("\n{3,}").unwrap());
 
@@ -72,8 +90,19 @@ pub fn find_version_pragma(contract: &str) -> Option<Match> {
     RE_SOL_PRAGMA_VERSION.captures(contract)?.name("version")
 }
 
-/// Returns an iterator that yields all solidity/yul files funder under the given root path or the
-/// `root` itself, if it is a sol/yul file
+/// Returns the vyper version pragma from the given input:
+/// `# @version ^0.3.7` => `^0.3.7`
+pub fn find_vyper_version_pragma(contract: &str) -> Option<Match<'_>> {
+    RE_VYPER_VERSION.captures(contract)?.name("version")
+}
+
+/// Returns `true` if the file is a vyper source file (`*.vy`)
+pub fn is_vyper_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().map(|ext| ext == "vy").unwrap_or_default()
+}
+
+/// Returns an iterator that yields all solidity/yul/vyper files funder under the given root path
+/// or the `root` itself, if it is a sol/yul/vy file
 ///
 /// This also follows symlinks.
 pub fn source_files_iter(root: impl AsRef<Path>) -> impl Iterator<Item = PathBuf> {
@@ -83,7 +112,10 @@ pub fn source_files_iter(root: impl AsRef<Path>) -> impl Iterator<Item = PathBuf
         .filter_map(Result::ok)
         .filter(|e| e.file_type().is_file())
         .filter(|e| {
-            e.path().extension().map(|ext| (ext == "sol") || (ext == "yul")).unwrap_or_default()
+            e.path()
+                .extension()
+                .map(|ext| (ext == "sol") || (ext == "yul") || (ext == "vy"))
+                .unwrap_or_default()
         })
         .map(|e| e.path().into())
 }
@@ -451,6 +483,23 @@ mod tests {
         assert_eq!(files, expected);
     }
 
+    #[test]
+    fn can_find_vyper_sources() {
+        let tmp_dir = tempdir("contracts").unwrap();
+
+        let file_a = tmp_dir.path().join("a.sol");
+        let file_b = tmp_dir.path().join("b.vy");
+        let file_c = tmp_dir.path().join("c.txt");
+        File::create(&file_a).unwrap();
+        File::create(&file_b).unwrap();
+        File::create(&file_c).unwrap();
+
+        let files: HashSet<_> = source_files(tmp_dir.path()).into_iter().collect();
+        let expected: HashSet<_> = [file_a, file_b.clone()].into();
+        assert_eq!(files, expected);
+        assert!(is_vyper_file(file_b));
+    }
+
     #[test]
     fn can_parse_curly_bracket_imports() {
         let s =
@@ -514,6 +563,17 @@ pragma solidity ^0.8.0;
         assert_eq!(Some("^0.8.0"), find_version_pragma(s).map(|s| s.as_str()));
     }
 
+    #[test]
+    fn can_find_vyper_version() {
+        let s = r#"# @version ^0.3.7
+# @license MIT
+"#;
+        assert_eq!(Some("^0.3.7"), find_vyper_version_pragma(s).map(|s| s.as_str()));
+
+        let s = "\"\"\"\n@title Token\n\"\"\"\n#pragma version >=0.3.10 <0.4.0\n";
+        assert_eq!(Some(">=0.3.10 <0.4.0"), find_vyper_version_pragma(s).map(|s| s.as_str()));
+    }
+
     #[test]
     fn can_find_ancestor() {
         let a = Path::new("/foo/bar/bar/test.txt");
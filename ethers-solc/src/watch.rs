@@ -0,0 +1,319 @@
+//! Watches the files of a project and recompiles them on change
+//!
+//! The [`ProjectWatcher`] observes all directories that contain files of the project, that are
+//! the sources, tests, scripts and libraries, as well as the paths of all remappings. Changes are
+//! debounced, so that saving several files at once results in a single recompilation, and the
+//! dependency [`Graph`] of the project determines which files are affected by the changes.
+//!
+//! The watcher owns the project, so that the compilations can run on tokio's blocking thread pool
+//! instead of blocking the async runtime.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ethers_solc::Project;
+//! use futures_util::StreamExt;
+//! # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+//! let project = Project::builder().build()?;
+//! let mut outputs = project.watch()?.into_stream().boxed();
+//! while let Some(output) = outputs.next().await {
+//!     match output {
+//!         Ok(output) => println!("{}", output),
+//!         Err(err) => eprintln!("{}", err),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{error::Result, ArtifactOutput, Graph, Project, ProjectCompileOutput};
+use futures_util::stream::{self, Stream};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+/// The default time to wait for further changes before recompiling
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the files of a [`Project`] and recompiles the files affected by changes, see
+/// [`Project::watch()`]
+pub struct ProjectWatcher<T: ArtifactOutput> {
+    project: Arc<Project<T>>,
+    /// the file system watcher, events are received until it's dropped
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<notify::Result<Event>>,
+    /// all watched directories
+    watched: Vec<PathBuf>,
+    /// how long to wait for further changes before recompiling
+    debounce: Duration,
+}
+
+impl<T> ProjectWatcher<T>
+where
+    T: ArtifactOutput + Send + Sync + 'static,
+    T::Artifact: Send,
+{
+    /// Starts watching all directories of the project that exist
+    pub fn new(project: impl Into<Arc<Project<T>>>) -> Result<Self> {
+        let project = project.into();
+        let (tx, events) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // the receiver is only dropped together with the watcher
+            let _ = tx.send(event);
+        })?;
+
+        let watched = watched_paths(&project);
+        for dir in watched.iter() {
+            tracing::trace!("watching \"{}\"", dir.display());
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self { project, _watcher: watcher, events, watched, debounce: DEFAULT_DEBOUNCE })
+    }
+
+    /// Sets the time to wait for further changes before recompiling, default is
+    /// [`DEFAULT_DEBOUNCE`]
+    #[must_use]
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns the watched project
+    pub fn project(&self) -> &Project<T> {
+        &self.project
+    }
+
+    /// Returns all watched directories
+    pub fn watched_paths(&self) -> &[PathBuf] {
+        &self.watched
+    }
+
+    /// Waits until source files of the project change and recompiles the files affected by the
+    /// changes.
+    ///
+    /// Changes to files that no input file imports, like unused library files, don't trigger a
+    /// recompilation. The compilation runs on tokio's blocking thread pool.
+    pub async fn next_output(&mut self) -> Option<Result<ProjectCompileOutput<T>>> {
+        loop {
+            let changed = match self.changed_files().await? {
+                Ok(changed) => changed,
+                Err(err) => return Some(Err(err)),
+            };
+            tracing::trace!("detected changed files {:?}", changed);
+
+            let affected = match Graph::resolve(&self.project.paths) {
+                Ok(graph) => affected_files(&graph, &changed),
+                Err(err) => return Some(Err(err)),
+            };
+            if affected.is_empty() {
+                tracing::trace!("no input files affected by the changes");
+                continue
+            }
+            tracing::trace!("recompiling affected files {:?}", affected);
+            return Some(self.recompile(affected).await)
+        }
+    }
+
+    /// Turns the watcher into a stream of the outputs of all recompilations, see
+    /// [`Self::next_output()`]
+    pub fn into_stream(self) -> impl Stream<Item = Result<ProjectCompileOutput<T>>> {
+        stream::unfold(self, |mut watcher| async move {
+            watcher.next_output().await.map(|output| (output, watcher))
+        })
+    }
+
+    /// Compiles the affected files
+    ///
+    /// If caching is enabled, the whole project is compiled, because compiling only a subset of
+    /// the files drops the cache entries of all other files. The cache then determines the same
+    /// dirty files, so only those are passed to solc.
+    async fn recompile(&self, affected: Vec<PathBuf>) -> Result<ProjectCompileOutput<T>> {
+        let project = self.project.clone();
+        let compile = tokio::task::spawn_blocking(move || {
+            if project.cached {
+                project.compile()
+            } else {
+                project.compile_files(affected)
+            }
+        });
+        match compile.await {
+            Ok(output) => output,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+
+    /// Waits for the next change to a source file and then collects all changes until no further
+    /// change happens within the debounce time
+    ///
+    /// Returns `None` if the file system watcher stopped
+    async fn changed_files(&mut self) -> Option<Result<BTreeSet<PathBuf>>> {
+        let mut changed = BTreeSet::new();
+        while changed.is_empty() {
+            match self.events.recv().await? {
+                Ok(event) => changed.extend(changed_source_files(event)),
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+        while let Ok(Some(event)) = tokio::time::timeout(self.debounce, self.events.recv()).await {
+            match event {
+                Ok(event) => changed.extend(changed_source_files(event)),
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+        Some(Ok(changed))
+    }
+}
+
+impl<T: ArtifactOutput> fmt::Debug for ProjectWatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProjectWatcher")
+            .field("root", self.project.root())
+            .field("watched", &self.watched)
+            .field("debounce", &self.debounce)
+            .finish()
+    }
+}
+
+/// Returns the sources, tests, scripts, libraries and remapped directories of the project that
+/// exist, without those that are nested in another watched directory
+fn watched_paths<T: ArtifactOutput>(project: &Project<T>) -> Vec<PathBuf> {
+    let paths = &project.paths;
+    let mut dirs = vec![paths.sources.clone(), paths.tests.clone(), paths.scripts.clone()];
+    dirs.extend(paths.libraries.iter().cloned());
+    dirs.extend(paths.remappings.iter().map(|remapping| paths.root.join(&remapping.path)));
+    dirs.retain(|dir| dir.exists());
+    dirs.sort();
+    dirs.dedup();
+
+    let all = dirs.clone();
+    dirs.retain(|dir| !all.iter().any(|other| other != dir && dir.starts_with(other)));
+    dirs
+}
+
+/// Returns the solidity, yul and vyper files that were modified, created or removed
+fn changed_source_files(event: Event) -> impl Iterator<Item = PathBuf> {
+    let paths = if matches!(event.kind, EventKind::Access(_)) { Vec::new() } else { event.paths };
+    paths.into_iter().filter(|path| is_source_file(path))
+}
+
+fn is_source_file(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "sol" || ext == "yul" || ext == "vy").unwrap_or_default()
+}
+
+/// Returns the input files of the graph that are affected by the changed files, which are the
+/// changed files themselves and all files that import them, directly or transitively
+///
+/// If a file was removed, all input files are affected, since any of them could have imported it.
+fn affected_files(graph: &Graph, changed: &BTreeSet<PathBuf>) -> Vec<PathBuf> {
+    let removed = changed.iter().any(|file| !file.exists());
+    graph
+        .input_nodes()
+        .enumerate()
+        .filter(|(idx, _)| {
+            removed || graph.nodes(*idx).any(|node| changed.contains(node.unpack().0))
+        })
+        .map(|(_, node)| node.unpack().0.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{remappings::Remapping, utils::tempdir, ConfigurableArtifacts, ProjectPathsConfig};
+    use std::fs;
+
+    #[test]
+    fn can_find_affected_files() {
+        let tmp_dir = tempdir("root").unwrap();
+        let root = tmp_dir.path();
+        let src = root.join("src");
+        let lib = root.join("lib");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&lib).unwrap();
+
+        fs::write(src.join("A.sol"), "pragma solidity ^0.8.10;\nimport \"./B.sol\";\n").unwrap();
+        fs::write(src.join("B.sol"), "pragma solidity ^0.8.10;\nimport \"../lib/C.sol\";\n")
+            .unwrap();
+        fs::write(src.join("D.sol"), "pragma solidity ^0.8.10;\n").unwrap();
+        fs::write(lib.join("C.sol"), "pragma solidity ^0.8.10;\n").unwrap();
+        fs::write(lib.join("E.sol"), "pragma solidity ^0.8.10;\n").unwrap();
+
+        let paths =
+            ProjectPathsConfig::builder().root(root).sources(&src).lib(&lib).build().unwrap();
+        let graph = Graph::resolve(&paths).unwrap();
+
+        let affected = |files: &[PathBuf]| {
+            let changed = files.iter().cloned().collect();
+            affected_files(&graph, &changed).into_iter().collect::<BTreeSet<_>>()
+        };
+        let src = paths.sources.clone();
+        let lib = &paths.libraries[0];
+
+        assert_eq!(
+            affected(&[lib.join("C.sol")]),
+            BTreeSet::from([src.join("A.sol"), src.join("B.sol")])
+        );
+        assert_eq!(affected(&[src.join("D.sol")]), BTreeSet::from([src.join("D.sol")]));
+        assert!(affected(&[lib.join("E.sol")]).is_empty());
+        assert_eq!(affected(&[src.join("Removed.sol")]).len(), 3);
+    }
+
+    #[test]
+    fn watches_existing_unnested_paths() {
+        let tmp_dir = tempdir("root").unwrap();
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("lib/ds-test/src")).unwrap();
+        fs::create_dir_all(root.join("remapped")).unwrap();
+
+        let paths = ProjectPathsConfig::builder()
+            .root(root)
+            .sources(root.join("src"))
+            .lib(root.join("lib"))
+            .remappings(vec![
+                "ds-test/=lib/ds-test/src/".parse::<Remapping>().unwrap(),
+                "remapped/=remapped/".parse::<Remapping>().unwrap(),
+            ])
+            .build()
+            .unwrap();
+        let project = Project::builder().paths(paths).build().unwrap();
+
+        let root = project.root();
+        assert_eq!(
+            watched_paths(&project),
+            vec![root.join("lib"), root.join("remapped/"), root.join("src")]
+        );
+    }
+
+    #[test]
+    fn outputs_can_be_sent_to_other_threads() {
+        fn assert_send<S: Send>(_: S) {}
+        let _ = |watcher: ProjectWatcher<ConfigurableArtifacts>| assert_send(watcher.into_stream());
+    }
+
+    #[tokio::test]
+    async fn debounces_changes() {
+        let tmp_dir = tempdir("root").unwrap();
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let paths =
+            ProjectPathsConfig::builder().root(root).sources(root.join("src")).build().unwrap();
+        let project = Project::builder().paths(paths).build().unwrap();
+        let src = project.sources_path().clone();
+
+        let mut watcher = project.watch().unwrap().debounce(Duration::from_millis(500));
+        fs::write(src.join("A.sol"), "pragma solidity ^0.8.10;\n").unwrap();
+        fs::write(src.join("README.md"), "# Readme\n").unwrap();
+        fs::write(src.join("B.sol"), "pragma solidity ^0.8.10;\n").unwrap();
+
+        let changed = watcher.changed_files().await.unwrap().unwrap();
+        assert_eq!(changed, BTreeSet::from([src.join("A.sol"), src.join("B.sol")]));
+    }
+}
@@ -0,0 +1,7 @@
+# @version ^0.3.7
+
+count: public(uint256)
+
+@external
+def increment():
+    self.count += 1
@@ -7,7 +7,7 @@ use std::{
     str::FromStr,
 };
 
-use ethers_core::types::Address;
+use ethers_core::{types::Address, utils::get_contract_address};
 use ethers_solc::{
     artifacts::{
         BytecodeHash, DevDoc, ErrorDoc, EventDoc, Libraries, MethodDoc, ModelCheckerEngine::CHC,
@@ -16,10 +16,11 @@ use ethers_solc::{
     buildinfo::BuildInfo,
     cache::{SolFilesCache, SOLIDITY_FILES_CACHE_FILENAME},
     info::ContractInfo,
+    linker::Linker,
     project_util::*,
     remappings::Remapping,
     CompilerInput, ConfigurableArtifacts, ExtraOutputValues, Graph, Project, ProjectCompileOutput,
-    ProjectPathsConfig, Solc, TestFileFilter,
+    ProjectPathsConfig, Solc, TestFileFilter, Vyper,
 };
 use pretty_assertions::assert_eq;
 use semver::Version;
@@ -130,6 +131,83 @@ fn can_compile_yul_sample() {
     assert_eq!(cache, updated_cache);
 }
 
+#[test]
+fn can_link_libraries_of_compiled_project() {
+    let project = TempProject::dapptools().unwrap();
+    project
+        .add_source(
+            "Math",
+            r#"
+pragma solidity ^0.8.10;
+library Add {
+    function add(uint256 a, uint256 b) external pure returns (uint256) { return a + b; }
+}
+library Math {
+    function double(uint256 a) external pure returns (uint256) { return Add.add(a, a); }
+}
+"#,
+        )
+        .unwrap();
+    project
+        .add_source(
+            "Greeter",
+            r#"
+pragma solidity ^0.8.10;
+import "./Math.sol";
+contract Greeter {
+    function double(uint256 a) public pure returns (uint256) { return Math.double(a); }
+}
+"#,
+        )
+        .unwrap();
+
+    let compiled = project.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+
+    let linker = Linker::from_output(compiled, project.root());
+    let sender = Address::repeat_byte(0x11);
+    let output = linker.link_with_nonce("src/Greeter.sol:Greeter", sender, 0u64).unwrap();
+    assert_eq!(
+        output.library_addresses(),
+        BTreeMap::from([
+            ("src/Math.sol:Add".to_string(), get_contract_address(sender, 0u64)),
+            ("src/Math.sol:Math".to_string(), get_contract_address(sender, 1u64)),
+        ])
+    );
+    assert!(output.contract.bytecode.unwrap().object.into_bytes().is_some());
+}
+
+#[test]
+fn can_compile_vyper_sample() {
+    // vyper is not installed by svm
+    if Vyper::default().version().is_err() {
+        eprintln!("skipping vyper sample, vyper is not installed");
+        return
+    }
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/vyper-sample");
+    let paths = ProjectPathsConfig::builder().sources(root);
+    let mut project = TempProject::<ConfigurableArtifacts>::new(paths).unwrap();
+    project.project_mut().build_info = true;
+
+    let compiled = project.compile().unwrap();
+    assert!(compiled.find_first("Dapp").is_some());
+    assert!(compiled.find_first("Counter").is_some());
+    assert!(!compiled.has_compiler_errors());
+
+    // one build info for each language
+    let languages = fs::read_dir(project.project().build_info_path())
+        .unwrap()
+        .map(|entry| BuildInfo::read(entry.unwrap().path()).unwrap().input.language)
+        .collect::<HashSet<_>>();
+    assert_eq!(languages, HashSet::from(["Solidity".to_string(), "Vyper".to_string()]));
+
+    // nothing to compile
+    let compiled = project.compile().unwrap();
+    assert!(compiled.find_first("Dapp").is_some());
+    assert!(compiled.find_first("Counter").is_some());
+    assert!(compiled.is_unchanged());
+}
+
 #[test]
 fn can_compile_configured() {
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/dapp-sample");